@@ -0,0 +1,15 @@
+#![no_main]
+
+use ai_agent::domain::chunk_content;
+use libfuzzer_sys::fuzz_target;
+use uuid::Uuid;
+
+fuzz_target!(|input: (u16, &str)| {
+    let (chunk_size, content) = input;
+    let chunks = chunk_content(Uuid::nil(), content, chunk_size as usize);
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        assert_eq!(chunk.chunk_index, i);
+        assert!(!chunk.content.is_empty());
+    }
+});
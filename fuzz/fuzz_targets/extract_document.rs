@@ -0,0 +1,31 @@
+#![no_main]
+
+use std::sync::OnceLock;
+
+use ai_agent::domain::DomainError;
+use ai_agent::infrastructure::ExtractorRegistry;
+use libfuzzer_sys::fuzz_target;
+use tokio::runtime::Runtime;
+
+const CONTENT_TYPES: &[&str] = &["application/pdf", "text/plain; charset=utf-8"];
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("tokio runtime")
+    })
+}
+
+// Uploaded bytes are untrusted; extractors must reject what they can't parse
+// with a validation error. An internal error means the blocking parse panicked.
+fuzz_target!(|data: &[u8]| {
+    let registry = ExtractorRegistry::default();
+    for content_type in CONTENT_TYPES {
+        let result = runtime().block_on(registry.extract(content_type, data));
+        if let Err(DomainError::Internal(e)) = result {
+            panic!("{content_type} extractor panicked: {e}");
+        }
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use ai_agent::infrastructure::{EmbedDocumentJob, IndexDocumentJob, JobResult, ProcessChatJob};
+use libfuzzer_sys::fuzz_target;
+
+// Queue payloads come straight from Redis; malformed input must be rejected, never panic.
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<ProcessChatJob>(data);
+    let _ = serde_json::from_slice::<EmbedDocumentJob>(data);
+    let _ = serde_json::from_slice::<IndexDocumentJob>(data);
+    let _ = serde_json::from_slice::<JobResult>(data);
+});
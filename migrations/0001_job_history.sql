@@ -0,0 +1,14 @@
+-- Finished-job records kept after the Redis status keys expire. IF NOT EXISTS
+-- lets databases set up before versioned migrations adopt this version.
+CREATE TABLE IF NOT EXISTS job_history (
+    job_id UUID PRIMARY KEY,
+    job_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    tenant_id TEXT,
+    tokens BIGINT,
+    duration_ms BIGINT,
+    error TEXT,
+    completed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS job_history_completed_at ON job_history (completed_at);
@@ -0,0 +1 @@
+ALTER TABLE job_history ADD COLUMN IF NOT EXISTS stored_bytes BIGINT;
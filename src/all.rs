@@ -0,0 +1,125 @@
+//! API server and job consumer in one process, sharing config, the Redis pool,
+//! the RAG service and the document store. Meant for demos and small installs;
+//! production runs the `api` and `worker` binaries separately so they scale
+//! independently.
+
+use ai_agent::api::{create_router, AppState};
+use ai_agent::application::{search_rag_services, DocumentService};
+use ai_agent::consumer::{create_pool, JobConsumer, WorkerState};
+use ai_agent::infrastructure::config::VectorStoreBackend;
+use ai_agent::infrastructure::{
+    migrate_vector_store, AppConfig, PostgresJobHistory, QdrantSnapshots, VectorStoreHealth,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::info;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "ai_agent_all=debug,ai_agent=debug,tower_http=debug".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    dotenvy::dotenv().ok();
+
+    let config = AppConfig::load().unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Failed to load config, using defaults");
+        AppConfig::default()
+    });
+
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".into());
+    let qdrant_url = std::env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".into());
+
+    let redis_pool = create_pool(&redis_url)?;
+    config.config.redis_tracing.install();
+    info!("Redis pool initialized");
+
+    let concurrency = std::env::var("WORKER_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(config.config.worker.concurrency);
+
+    if config.config.migrations.on_startup {
+        migrate_vector_store(&config.config, &qdrant_url, &redis_pool).await?;
+    }
+    let mut worker_state =
+        WorkerState::new(redis_pool.clone(), &qdrant_url, config.clone()).await?;
+    info!("Qdrant connected");
+    if worker_state.config.config.worker.history.postgres {
+        let database_url = std::env::var("DATABASE_URL")
+            .map_err(|_| anyhow::anyhow!("DATABASE_URL must be set for worker.history.postgres"))?;
+        let history = PostgresJobHistory::connect(&database_url).await?;
+        if worker_state.config.config.migrations.on_startup {
+            history.migrate().await?;
+        }
+        worker_state = worker_state.with_job_history(history);
+        info!("Postgres job history connected");
+    }
+
+    let mut state = AppState::new(redis_pool.clone(), config);
+    // Document search shares the worker's retrieval and its agent's tool.
+    let collection = &state.config.config.vector_store.collection;
+    let services = search_rag_services(
+        collection,
+        state
+            .config
+            .config
+            .tools
+            .knowledge_base
+            .collection(&state.config.config.vector_store),
+        |name| {
+            let rag = if &name == collection {
+                worker_state.rag.clone()
+            } else {
+                worker_state.agent.rag().clone()
+            };
+            async move { Ok(rag) }
+        },
+    )
+    .await?;
+    state = state.with_search_services(services);
+    // Share the worker's document store so both see the same in-memory records.
+    if let Some(store) = worker_state.document_store.clone() {
+        state = state.with_document_service(Arc::new(DocumentService::new(
+            store,
+            worker_state.chunker.clone(),
+        )));
+    }
+    if let Some(history) = worker_state.job_history.clone() {
+        state = state.with_job_history(history);
+    }
+    match state.config.config.vector_store.backend {
+        VectorStoreBackend::Qdrant => {
+            let rest_url =
+                std::env::var("QDRANT_HTTP_URL").unwrap_or_else(|_| "http://localhost:6333".into());
+            state = state
+                .with_vector_store_health(VectorStoreHealth::qdrant(&qdrant_url)?)
+                .with_snapshots(QdrantSnapshots::new(&qdrant_url, &rest_url)?);
+        }
+        VectorStoreBackend::Redis => {
+            state = state.with_vector_store_health(VectorStoreHealth::Redis(redis_pool.clone()));
+        }
+    }
+    let app = create_router(state);
+    let consumer = JobConsumer::new(worker_state, concurrency);
+
+    let host = std::env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".into());
+    let port: u16 = std::env::var("SERVER_PORT")
+        .unwrap_or_else(|_| "8080".into())
+        .parse()?;
+    let addr = SocketAddr::new(host.parse()?, port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    info!(%addr, concurrency, "API server and worker started");
+    tokio::select! {
+        served = axum::serve(listener, app) => served?,
+        consumed = consumer.start() => consumed?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use deadpool_redis::redis::{self, AsyncCommands};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::api::queue::{QueueError, RedisPool, Result};
+use crate::domain::Annotation;
+use crate::infrastructure::keys;
+
+/// Labels on conversation answers, kept in Redis without an expiry in a
+/// hash per conversation; the worker's retention sweep anonymizes or
+/// deletes them.
+#[derive(Clone)]
+pub struct AnnotationStore {
+    pool: RedisPool,
+}
+
+impl AnnotationStore {
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    async fn conn(&self) -> Result<deadpool_redis::Connection> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| QueueError::Pool(e.to_string()))
+    }
+
+    pub async fn save(&self, annotation: &Annotation) -> Result<()> {
+        let mut conn = self.conn().await?;
+        let id = annotation.id.to_string();
+        redis::pipe()
+            .atomic()
+            .hset(
+                keys::annotations(&annotation.conversation_id),
+                &id,
+                serde_json::to_string(annotation)?,
+            )
+            .ignore()
+            .hset(
+                keys::ANNOTATION_INDEX,
+                &id,
+                annotation.conversation_id.to_string(),
+            )
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Every annotation, oldest first.
+    pub async fn list(&self) -> Result<Vec<Annotation>> {
+        let mut conn = self.conn().await?;
+        let conversation_ids: HashSet<String> = conn
+            .hvals(keys::ANNOTATION_INDEX)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        let mut annotations = Vec::new();
+        for conversation_id in conversation_ids {
+            let Ok(conversation_id) = conversation_id.parse::<Uuid>() else {
+                continue;
+            };
+            annotations.extend(Self::read(&mut conn, &conversation_id).await?);
+        }
+        annotations.sort_by_key(|a| a.created_at);
+        Ok(annotations)
+    }
+
+    /// A conversation's annotations, oldest first.
+    pub async fn for_conversation(&self, conversation_id: &Uuid) -> Result<Vec<Annotation>> {
+        let mut conn = self.conn().await?;
+        let mut annotations = Self::read(&mut conn, conversation_id).await?;
+        annotations.sort_by_key(|a| a.created_at);
+        Ok(annotations)
+    }
+
+    async fn read(
+        conn: &mut deadpool_redis::Connection,
+        conversation_id: &Uuid,
+    ) -> Result<Vec<Annotation>> {
+        let values: Vec<String> = conn
+            .hvals(keys::annotations(conversation_id))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+        values
+            .iter()
+            .map(|json| serde_json::from_str(json).map_err(Into::into))
+            .collect()
+    }
+
+    /// Deletes an annotation, returning whether it existed.
+    pub async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let mut conn = self.conn().await?;
+        let id = id.to_string();
+        let conversation_id: Option<String> = conn
+            .hget(keys::ANNOTATION_INDEX, &id)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+        let Some(conversation_id) = conversation_id.and_then(|c| c.parse::<Uuid>().ok()) else {
+            return Ok(false);
+        };
+
+        let (removed, _): (usize, usize) = redis::pipe()
+            .atomic()
+            .hdel(keys::annotations(&conversation_id), &id)
+            .hdel(keys::ANNOTATION_INDEX, &id)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+        Ok(removed > 0)
+    }
+}
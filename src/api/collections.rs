@@ -0,0 +1,63 @@
+use deadpool_redis::redis::AsyncCommands;
+
+use crate::api::queue::{QueueError, RedisPool, Result};
+use crate::infrastructure::keys;
+
+/// Redis-backed pointer to the vector collection workers should use.
+#[derive(Clone)]
+pub struct CollectionRegistry {
+    pool: RedisPool,
+    default_collection: String,
+}
+
+impl CollectionRegistry {
+    pub fn new(pool: RedisPool, default_collection: impl Into<String>) -> Self {
+        Self {
+            pool,
+            default_collection: default_collection.into(),
+        }
+    }
+
+    async fn conn(&self) -> Result<deadpool_redis::Connection> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| QueueError::Pool(e.to_string()))
+    }
+
+    /// The active collection, or the configured one if none was ever activated.
+    pub async fn active(&self) -> Result<String> {
+        let mut conn = self.conn().await?;
+        let active: Option<String> = conn
+            .get(keys::ACTIVE_COLLECTION)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        Ok(active.unwrap_or_else(|| self.default_collection.clone()))
+    }
+
+    pub async fn is_validated(&self, collection: &str) -> Result<bool> {
+        let mut conn = self.conn().await?;
+        conn.exists(keys::collection_validated(collection))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Points the active collection at `collection`.
+    ///
+    /// Returns `false` without switching if the collection has not passed
+    /// re-embed validation. The configured default can always be activated.
+    pub async fn activate(&self, collection: &str) -> Result<bool> {
+        if collection != self.default_collection && !self.is_validated(collection).await? {
+            return Ok(false);
+        }
+
+        let mut conn = self.conn().await?;
+        conn.set::<_, _, ()>(keys::ACTIVE_COLLECTION, collection)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        tracing::info!(collection, "active collection switched");
+        Ok(true)
+    }
+}
@@ -0,0 +1,265 @@
+use deadpool_redis::redis::{cmd, pipe, AsyncCommands};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::api::queue::{QueueError, RedisPool, Result};
+use crate::application::RetentionReport;
+use crate::domain::Conversation;
+use crate::infrastructure::{keys, traced, update_conversation};
+
+/// Index entries read per round trip when listing.
+const LIST_BATCH: usize = 100;
+
+/// Access to the conversations the worker keeps in Redis.
+#[derive(Clone)]
+pub struct ConversationStore {
+    pool: RedisPool,
+}
+
+impl ConversationStore {
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    async fn conn(&self) -> Result<deadpool_redis::Connection> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| QueueError::Pool(e.to_string()))
+    }
+
+    /// Stores a conversation, expiring after `ttl_seconds` like the worker's.
+    pub async fn save(&self, conversation: &Conversation, ttl_seconds: u64) -> Result<()> {
+        let mut conn = self.conn().await?;
+        let json = serde_json::to_string(conversation)?;
+        let key = keys::conversation(&conversation.id);
+        traced(
+            "MULTI",
+            &key,
+            pipe()
+                .atomic()
+                .set_ex(&key, json, ttl_seconds)
+                .ignore()
+                .zadd(
+                    keys::CONVERSATIONS_BY_UPDATED,
+                    conversation.id.to_string(),
+                    conversation.updated_at.timestamp_millis(),
+                )
+                .ignore()
+                .query_async::<()>(&mut *conn),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Applies `apply` to the latest stored copy of conversation `id` and
+    /// saves the result, so concurrent updates (e.g. the worker appending an
+    /// answer) aren't lost. `apply` gets `None` when there is no such
+    /// conversation and may refuse with `Err`, saving nothing.
+    pub async fn update<T, E>(
+        &self,
+        id: &Uuid,
+        ttl_seconds: u64,
+        apply: impl FnMut(Option<Conversation>) -> std::result::Result<(Conversation, T), E>,
+    ) -> Result<std::result::Result<T, E>> {
+        let mut conn = self.conn().await?;
+        update_conversation(&mut conn, id, ttl_seconds, apply)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Records `document_id` as attached to a conversation, so the worker
+    /// deletes its vectors once the conversation is gone.
+    pub async fn attach(&self, conversation_id: &Uuid, document_id: &Uuid) -> Result<()> {
+        let mut conn = self.conn().await?;
+        traced(
+            "HSET",
+            keys::CONVERSATION_ATTACHMENTS,
+            conn.hset::<_, _, _, ()>(
+                keys::CONVERSATION_ATTACHMENTS,
+                document_id.to_string(),
+                conversation_id.to_string(),
+            ),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Attached document ids mapped to their conversation.
+    pub async fn attachments(&self) -> Result<HashMap<Uuid, Uuid>> {
+        let mut conn = self.conn().await?;
+        let attachments: HashMap<String, String> = traced(
+            "HGETALL",
+            keys::CONVERSATION_ATTACHMENTS,
+            conn.hgetall(keys::CONVERSATION_ATTACHMENTS),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))?;
+        Ok(attachments
+            .into_iter()
+            .filter_map(|(document, conversation)| {
+                Some((document.parse().ok()?, conversation.parse().ok()?))
+            })
+            .collect())
+    }
+
+    /// Returns up to `limit` conversations of `tenant_id` (those without a
+    /// tenant for `None`), most recently updated first. Expired
+    /// conversations met on the way are dropped from the index.
+    pub async fn list(&self, limit: usize, tenant_id: Option<&str>) -> Result<Vec<Conversation>> {
+        let mut conn = self.conn().await?;
+        let mut conversations = Vec::new();
+        let mut start = 0;
+
+        while conversations.len() < limit {
+            let stop = (start + LIST_BATCH - 1) as isize;
+            let ids: Vec<String> = traced(
+                "ZREVRANGE",
+                keys::CONVERSATIONS_BY_UPDATED,
+                conn.zrevrange(keys::CONVERSATIONS_BY_UPDATED, start as isize, stop),
+            )
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+            if ids.is_empty() {
+                break;
+            }
+
+            let keys: Vec<String> = ids
+                .iter()
+                .map(|id| format!("{}{}", keys::CONVERSATION_PREFIX, id))
+                .collect();
+            // Explicit MGET: a single missing key must still answer a list.
+            let values: Vec<Option<String>> = traced(
+                "MGET",
+                &keys[0],
+                cmd("MGET").arg(&keys).query_async(&mut *conn),
+            )
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+            let mut expired = Vec::new();
+            for (id, value) in ids.iter().zip(values) {
+                let Some(json) = value else {
+                    expired.push(id);
+                    continue;
+                };
+                let conversation: Conversation = serde_json::from_str(&json)?;
+                if conversation.tenant_id.as_deref() == tenant_id && conversations.len() < limit {
+                    conversations.push(conversation);
+                }
+            }
+            if !expired.is_empty() {
+                traced(
+                    "ZREM",
+                    keys::CONVERSATIONS_BY_UPDATED,
+                    conn.zrem::<_, _, ()>(keys::CONVERSATIONS_BY_UPDATED, &expired),
+                )
+                .await
+                .map_err(|e| QueueError::Redis(e.to_string()))?;
+            }
+            start += ids.len() - expired.len();
+        }
+
+        Ok(conversations)
+    }
+
+    /// Gets a conversation; ids of conversations merged into another one
+    /// resolve to that one.
+    pub async fn get(&self, id: &Uuid) -> Result<Option<Conversation>> {
+        let mut conn = self.conn().await?;
+        let mut current = *id;
+        for _ in 0..=keys::CONVERSATION_ALIAS_HOPS {
+            let key = keys::conversation(&current);
+            let result: Option<String> = traced("GET", &key, conn.get(&key))
+                .await
+                .map_err(|e| QueueError::Redis(e.to_string()))?;
+            if let Some(json) = result {
+                return Ok(Some(serde_json::from_str(&json)?));
+            }
+            let key = keys::conversation_alias(&current);
+            let alias: Option<String> = traced("GET", &key, conn.get(&key))
+                .await
+                .map_err(|e| QueueError::Redis(e.to_string()))?;
+            match alias.and_then(|target| target.parse().ok()) {
+                Some(target) => current = target,
+                None => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Replaces `source`, already merged into `target`: its id resolves to
+    /// `target` for `ttl_seconds` and its attached documents move there.
+    pub async fn retire_merged(
+        &self,
+        source: &Uuid,
+        target: &Uuid,
+        ttl_seconds: u64,
+    ) -> Result<()> {
+        let moved: Vec<Uuid> = self
+            .attachments()
+            .await?
+            .into_iter()
+            .filter(|(_, conversation)| conversation == source)
+            .map(|(document, _)| document)
+            .collect();
+
+        let mut conn = self.conn().await?;
+        let mut pipe = pipe();
+        pipe.set_ex(
+            keys::conversation_alias(source),
+            target.to_string(),
+            ttl_seconds,
+        )
+        .ignore()
+        .del(keys::conversation(source))
+        .ignore()
+        .zrem(keys::CONVERSATIONS_BY_UPDATED, source.to_string())
+        .ignore();
+        for document in moved {
+            pipe.hset(
+                keys::CONVERSATION_ATTACHMENTS,
+                document.to_string(),
+                target.to_string(),
+            )
+            .ignore();
+        }
+        let key = keys::conversation(source);
+        traced("PIPELINE", &key, pipe.query_async::<()>(&mut *conn))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// The report of the worker's most recent retention sweep.
+    pub async fn retention_report(&self) -> Result<Option<RetentionReport>> {
+        let mut conn = self.conn().await?;
+        let result: Option<String> = conn
+            .get(keys::RETENTION_REPORT)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        result
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Deletes a conversation, returning whether it existed.
+    pub async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let mut conn = self.conn().await?;
+        let key = keys::conversation(id);
+        let (removed,): (usize,) = traced(
+            "MULTI",
+            &key,
+            pipe()
+                .atomic()
+                .del(&key)
+                .zrem(keys::CONVERSATIONS_BY_UPDATED, id.to_string())
+                .ignore()
+                .query_async(&mut *conn),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        Ok(removed > 0)
+    }
+}
@@ -1,2 +1,4 @@
-// Middleware module - currently using tower_http::trace::TraceLayer for request logging.
-// Custom middleware can be added here as needed.
+// Request logging uses tower_http::trace::TraceLayer; custom middleware lives here.
+mod read_only;
+
+pub use read_only::reject_writes_when_read_only;
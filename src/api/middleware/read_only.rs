@@ -0,0 +1,138 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::state::AppState;
+
+/// Routes that stay writable in read-only mode: chatting, searching and
+/// switching the mode back off.
+const ALLOWED_WRITES: [(Method, &str); 6] = [
+    (Method::POST, "/api/v1/chat"),
+    (Method::DELETE, "/api/v1/chat/jobs/{job_id}"),
+    (Method::POST, "/api/v1/conversations"),
+    (Method::POST, "/api/v1/documents/search"),
+    (Method::POST, "/api/v1/debug/retrieval-diff"),
+    (Method::PUT, "/api/v1/admin/read-only"),
+];
+
+/// Answers 503 to requests that change data while the API is read-only, or
+/// while the switch can't be read: an outage is when writes most need
+/// holding back.
+pub async fn reject_writes_when_read_only(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+    if is_allowed(request.method(), route) {
+        return next.run(request).await;
+    }
+
+    match state.read_only.is_enabled().await {
+        Ok(false) => next.run(request).await,
+        Ok(true) => unavailable("The API is in read-only mode; chat and search remain available."),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to read read-only mode, rejecting write");
+            unavailable("Read-only mode can't be checked; writes are paused until it can.")
+        }
+    }
+}
+
+fn unavailable(message: &'static str) -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, "60")],
+        message,
+    )
+        .into_response()
+}
+
+/// Whether `method` on the route template `route` may run in read-only mode.
+/// Requests no route matched have no template and are treated as writes.
+fn is_allowed(method: &Method, route: Option<&str>) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+    route.is_some_and(|route| {
+        ALLOWED_WRITES
+            .iter()
+            .any(|(allowed, pattern)| allowed == method && *pattern == route)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_chat_search_and_the_switch_are_writable() {
+        assert!(is_allowed(&Method::GET, Some("/api/v1/documents")));
+        assert!(is_allowed(&Method::POST, Some("/api/v1/chat")));
+        assert!(is_allowed(
+            &Method::DELETE,
+            Some("/api/v1/chat/jobs/{job_id}")
+        ));
+        assert!(is_allowed(&Method::POST, Some("/api/v1/documents/search")));
+        assert!(is_allowed(&Method::PUT, Some("/api/v1/admin/read-only")));
+
+        assert!(!is_allowed(&Method::POST, Some("/api/v1/documents")));
+        assert!(!is_allowed(&Method::DELETE, Some("/api/v1/documents/{id}")));
+        assert!(!is_allowed(
+            &Method::DELETE,
+            Some("/api/v1/chat/jobs/{job_id}/stream")
+        ));
+        assert!(!is_allowed(
+            &Method::POST,
+            Some("/api/v1/admin/collections/reembed")
+        ));
+        assert!(!is_allowed(
+            &Method::DELETE,
+            Some("/api/v1/conversations/{id}")
+        ));
+        assert!(!is_allowed(&Method::POST, None));
+    }
+
+    #[tokio::test]
+    async fn test_outer_layer_sees_the_nested_route_template() {
+        use axum::{body::Body, middleware, routing::delete, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .nest(
+                "/api/v1",
+                Router::new().route("/chat/jobs/{job_id}", delete(|| async { StatusCode::OK })),
+            )
+            .layer(middleware::from_fn(
+                |request: Request, next: Next| async move {
+                    let route = request
+                        .extensions()
+                        .get::<MatchedPath>()
+                        .map(MatchedPath::as_str);
+                    if is_allowed(request.method(), route) {
+                        next.run(request).await
+                    } else {
+                        StatusCode::SERVICE_UNAVAILABLE.into_response()
+                    }
+                },
+            ));
+
+        let request = |uri: &str| Request::delete(uri).body(Body::empty()).unwrap();
+        let allowed = app
+            .clone()
+            .oneshot(request("/api/v1/chat/jobs/42"))
+            .await
+            .unwrap();
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let unmatched = app
+            .oneshot(request("/api/v1/chat/jobs/42/x"))
+            .await
+            .unwrap();
+        assert_eq!(unmatched.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -1,8 +1,18 @@
+pub mod annotations;
+pub mod collections;
+pub mod conversations;
 pub mod middleware;
+pub mod models;
 pub mod queue;
+pub mod read_only_switch;
 pub mod routes;
 pub mod state;
 
+pub use annotations::AnnotationStore;
+pub use collections::CollectionRegistry;
+pub use conversations::ConversationStore;
+pub use models::ModelReleaseStore;
 pub use queue::JobProducer;
+pub use read_only_switch::ReadOnlySwitch;
 pub use routes::create_router;
 pub use state::AppState;
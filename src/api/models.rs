@@ -0,0 +1,68 @@
+use deadpool_redis::redis::{self, AsyncCommands};
+
+use crate::api::queue::{QueueError, RedisPool, Result};
+use crate::infrastructure::{keys, ModelRollout};
+
+/// Live release pointers of the model registry and their rollout history.
+#[derive(Clone)]
+pub struct ModelReleaseStore {
+    pool: RedisPool,
+}
+
+impl ModelReleaseStore {
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    async fn conn(&self) -> Result<deadpool_redis::Connection> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| QueueError::Pool(e.to_string()))
+    }
+
+    /// The version rolled out for `model`, if any was.
+    pub async fn active(&self, model: &str) -> Result<Option<u32>> {
+        let mut conn = self.conn().await?;
+        conn.get(keys::model_active_release(model))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Makes the rollout's version live and records it.
+    pub async fn roll_out(&self, rollout: &ModelRollout) -> Result<()> {
+        let mut conn = self.conn().await?;
+        redis::pipe()
+            .atomic()
+            .set(keys::model_active_release(&rollout.model), rollout.version)
+            .lpush(
+                keys::model_rollouts(&rollout.model),
+                serde_json::to_string(rollout)?,
+            )
+            .query_async::<()>(&mut *conn)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        tracing::info!(
+            model = %rollout.model,
+            version = rollout.version,
+            previous_version = ?rollout.previous_version,
+            "model release rolled out"
+        );
+        Ok(())
+    }
+
+    /// Rollouts of `model`, newest first.
+    pub async fn rollouts(&self, model: &str) -> Result<Vec<ModelRollout>> {
+        let mut conn = self.conn().await?;
+        let values: Vec<String> = conn
+            .lrange(keys::model_rollouts(model), 0, -1)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        values
+            .iter()
+            .map(|json| serde_json::from_str(json).map_err(Into::into))
+            .collect()
+    }
+}
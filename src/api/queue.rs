@@ -1,12 +1,23 @@
-use deadpool_redis::{redis::AsyncCommands, Config, Pool, Runtime};
+use deadpool_redis::{
+    redis::{self, AsyncCommands},
+    Config, Pool, Runtime,
+};
+use std::time::Duration;
+use tokio::time::Instant;
 use uuid::Uuid;
 
+use crate::application::FreshnessReport;
+use crate::infrastructure::config::{QueueFullAction, WorkerConfig};
 use crate::infrastructure::{
-    keys, queues, EmbedDocumentJob, IndexDocumentJob, JobResult, ProcessChatJob,
+    keys, queues, traced, ChatSnapshot, CrawlSiteJob, EmbedDocumentJob, ExportCollectionJob,
+    ImportCollectionJob, IndexDocumentJob, JobResult, JobSummary, ProcessChatJob,
+    QuarantinedDocument, ReembedCollectionJob, S3SyncJob, SyncGitRepoJob,
 };
 
 pub type RedisPool = Pool;
 
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 #[derive(Debug, thiserror::Error)]
 pub enum QueueError {
     #[error("Redis pool error: {0}")]
@@ -15,6 +26,11 @@ pub enum QueueError {
     Redis(String),
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("Chat queue is full ({depth} jobs waiting)")]
+    Full {
+        depth: usize,
+        estimated_wait_seconds: u64,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, QueueError>;
@@ -25,15 +41,24 @@ pub fn create_pool(redis_url: &str) -> Result<RedisPool> {
         .map_err(|e| QueueError::Pool(e.to_string()))
 }
 
+/// A queued chat job and how long it is expected to wait for a worker.
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedChatJob {
+    pub job_id: Uuid,
+    /// Chat jobs that were already waiting.
+    pub queue_depth: usize,
+    pub estimated_wait_seconds: u64,
+}
+
 #[derive(Clone)]
 pub struct JobProducer {
     pool: RedisPool,
-    result_ttl: u64,
+    worker: WorkerConfig,
 }
 
 impl JobProducer {
-    pub fn new(pool: RedisPool, result_ttl: u64) -> Self {
-        Self { pool, result_ttl }
+    pub fn new(pool: RedisPool, worker: WorkerConfig) -> Self {
+        Self { pool, worker }
     }
 
     async fn conn(&self) -> Result<deadpool_redis::Connection> {
@@ -46,22 +71,75 @@ impl JobProducer {
     async fn push_job(&self, queue: &str, job_id: Uuid, payload: &str) -> Result<Uuid> {
         let mut conn = self.conn().await?;
 
-        conn.lpush::<_, _, ()>(queue, payload)
+        traced("LPUSH", queue, conn.lpush::<_, _, ()>(queue, payload))
             .await
             .map_err(|e| QueueError::Redis(e.to_string()))?;
 
         let status = serde_json::to_string(&JobResult::pending(job_id))?;
-        conn.set_ex::<_, _, ()>(keys::job_status(&job_id), &status, self.result_ttl)
-            .await
-            .map_err(|e| QueueError::Redis(e.to_string()))?;
+        let key = keys::job_status(&job_id);
+        traced(
+            "SETEX",
+            &key,
+            conn.set_ex::<_, _, ()>(&key, &status, self.worker.result_ttl(queue)),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))?;
 
         tracing::info!(job_id = %job_id, queue, "job queued");
         Ok(job_id)
     }
 
-    pub async fn push_chat_job(&self, job: &ProcessChatJob) -> Result<Uuid> {
-        self.push_job(queues::CHAT_QUEUE, job.job_id, &serde_json::to_string(job)?)
-            .await
+    /// Queues a chat job with an estimate of its wait, or applies
+    /// `worker.backpressure.when_full` once the chat queues hold
+    /// `max_queue_depth` jobs.
+    pub async fn push_chat_job(&self, job: &ProcessChatJob) -> Result<QueuedChatJob> {
+        let backpressure = &self.worker.backpressure;
+        let (depth, estimated_wait_seconds) = self.chat_queue_wait().await?;
+        let mut queue = queues::CHAT_QUEUE;
+        if backpressure.max_queue_depth > 0 && depth >= backpressure.max_queue_depth {
+            match backpressure.when_full {
+                QueueFullAction::Reject => {
+                    return Err(QueueError::Full {
+                        depth,
+                        estimated_wait_seconds,
+                    });
+                }
+                QueueFullAction::LowPriority => queue = queues::CHAT_LOW_QUEUE,
+            }
+        }
+        let job_id = self
+            .push_job(queue, job.job_id, &serde_json::to_string(job)?)
+            .await?;
+        Ok(QueuedChatJob {
+            job_id,
+            queue_depth: depth,
+            estimated_wait_seconds,
+        })
+    }
+
+    /// Chat jobs waiting in the normal and low-priority queues, and the
+    /// estimated wait of a job queued behind them.
+    pub async fn chat_queue_wait(&self) -> Result<(usize, u64)> {
+        let mut conn = self.conn().await?;
+        let (normal, low, durations_ms): (usize, usize, Vec<u64>) = traced(
+            "PIPELINE",
+            queues::CHAT_QUEUE,
+            redis::pipe()
+                .llen(queues::CHAT_QUEUE)
+                .llen(queues::CHAT_LOW_QUEUE)
+                .lrange(keys::CHAT_DURATIONS, 0, -1)
+                .query_async(&mut conn),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))?;
+        let depth = normal + low;
+        let wait = estimated_wait_seconds(
+            depth,
+            &durations_ms,
+            self.worker.backpressure.estimated_job_seconds,
+            self.worker.concurrency,
+        );
+        Ok((depth, wait))
     }
 
     pub async fn push_embed_job(&self, job: &EmbedDocumentJob) -> Result<Uuid> {
@@ -82,10 +160,163 @@ impl JobProducer {
         .await
     }
 
+    pub async fn push_reembed_job(&self, job: &ReembedCollectionJob) -> Result<Uuid> {
+        self.push_job(
+            queues::REEMBED_QUEUE,
+            job.job_id,
+            &serde_json::to_string(job)?,
+        )
+        .await
+    }
+
+    pub async fn push_export_job(&self, job: &ExportCollectionJob) -> Result<Uuid> {
+        self.push_job(
+            queues::EXPORT_QUEUE,
+            job.job_id,
+            &serde_json::to_string(job)?,
+        )
+        .await
+    }
+
+    pub async fn push_import_job(&self, job: &ImportCollectionJob) -> Result<Uuid> {
+        self.push_job(
+            queues::IMPORT_QUEUE,
+            job.job_id,
+            &serde_json::to_string(job)?,
+        )
+        .await
+    }
+
+    pub async fn push_git_sync_job(&self, job: &SyncGitRepoJob) -> Result<Uuid> {
+        self.push_job(
+            queues::GIT_SYNC_QUEUE,
+            job.job_id,
+            &serde_json::to_string(job)?,
+        )
+        .await
+    }
+
+    pub async fn push_crawl_job(&self, job: &CrawlSiteJob) -> Result<Uuid> {
+        self.push_job(
+            queues::CRAWL_QUEUE,
+            job.job_id,
+            &serde_json::to_string(job)?,
+        )
+        .await
+    }
+
+    pub async fn push_s3_sync_job(&self, job: &S3SyncJob) -> Result<Uuid> {
+        self.push_job(
+            queues::S3_SYNC_QUEUE,
+            job.job_id,
+            &serde_json::to_string(job)?,
+        )
+        .await
+    }
+
     pub async fn get_job_status(&self, job_id: &Uuid) -> Result<Option<JobResult>> {
+        let mut conn = self.conn().await?;
+        let key = keys::job_status(job_id);
+        let result: Option<String> = traced("GET", &key, conn.get(&key))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        result
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// What a finished chat job saw, while `worker.replay` keeps it.
+    pub async fn chat_snapshot(&self, job_id: &Uuid) -> Result<Option<ChatSnapshot>> {
+        let mut conn = self.conn().await?;
+        let key = keys::chat_snapshot(job_id);
+        let snapshot: Option<String> = traced("GET", &key, conn.get(&key))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        snapshot
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Makes an approved draft the result of the chat job that wrote it. Jobs
+    /// whose status has already expired are left alone.
+    pub async fn release_draft(&self, job_id: &Uuid, result: serde_json::Value) -> Result<()> {
+        let Some(mut status) = self.get_job_status(job_id).await? else {
+            return Ok(());
+        };
+        status.result = Some(result);
+
+        let mut conn = self.conn().await?;
+        let key = keys::job_status(job_id);
+        traced(
+            "SETEX",
+            &key,
+            conn.set_ex::<_, _, ()>(
+                &key,
+                serde_json::to_string(&status)?,
+                self.worker.result_ttl(queues::CHAT_QUEUE),
+            ),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Polls a job's status until it reaches a terminal state or `timeout` elapses.
+    ///
+    /// Returns the last status seen, or `None` if the job is unknown.
+    pub async fn wait_for_job(
+        &self,
+        job_id: &Uuid,
+        timeout: Duration,
+    ) -> Result<Option<JobResult>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let status = self.get_job_status(job_id).await?;
+            match &status {
+                Some(result) if !result.status.is_terminal() && Instant::now() < deadline => {
+                    tokio::time::sleep(STATUS_POLL_INTERVAL.min(deadline - Instant::now())).await;
+                }
+                _ => return Ok(status),
+            }
+        }
+    }
+
+    /// Answer chunks a chat job has streamed, starting at index `from`.
+    pub async fn stream_chunks(&self, job_id: &Uuid, from: usize) -> Result<Vec<String>> {
+        let mut conn = self.conn().await?;
+        let key = keys::job_stream(job_id);
+        traced("LRANGE", &key, conn.lrange(&key, from as isize, -1))
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))
+    }
+
+    /// Returns up to `limit` finished-job summaries, newest first.
+    pub async fn history(&self, limit: usize) -> Result<Vec<JobSummary>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.conn().await?;
+        let entries: Vec<String> = traced(
+            "LRANGE",
+            keys::JOB_HISTORY,
+            conn.lrange(keys::JOB_HISTORY, 0, limit as isize - 1),
+        )
+        .await
+        .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        entries
+            .iter()
+            .map(|json| serde_json::from_str(json).map_err(Into::into))
+            .collect()
+    }
+
+    /// The worker's latest report of documents past their review or expiry date.
+    pub async fn freshness_report(&self) -> Result<Option<FreshnessReport>> {
         let mut conn = self.conn().await?;
         let result: Option<String> = conn
-            .get(keys::job_status(job_id))
+            .get(keys::FRESHNESS_REPORT)
             .await
             .map_err(|e| QueueError::Redis(e.to_string()))?;
 
@@ -93,4 +324,151 @@ impl JobProducer {
             .map(|json| serde_json::from_str(&json).map_err(Into::into))
             .transpose()
     }
+
+    /// Documents the safety filter is holding for review, oldest first.
+    pub async fn quarantined(&self) -> Result<Vec<QuarantinedDocument>> {
+        let mut conn = self.conn().await?;
+        let entries: Vec<String> = conn
+            .hvals(keys::QUARANTINE)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        let mut documents = entries
+            .iter()
+            .map(|json| serde_json::from_str(json))
+            .collect::<std::result::Result<Vec<QuarantinedDocument>, _>>()?;
+        documents.sort_by_key(|d| d.quarantined_at);
+        Ok(documents)
+    }
+
+    /// Removes a document from quarantine, returning it if it was there.
+    pub async fn release_quarantined(
+        &self,
+        document_id: &Uuid,
+    ) -> Result<Option<QuarantinedDocument>> {
+        let mut conn = self.conn().await?;
+        let field = document_id.to_string();
+        let (entry, _): (Option<String>, usize) = deadpool_redis::redis::pipe()
+            .atomic()
+            .hget(keys::QUARANTINE, &field)
+            .hdel(keys::QUARANTINE, &field)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        entry
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Flags a job as cancelled so the worker skips or abandons it.
+    ///
+    /// Returns the job's status afterwards, or `None` if the job is unknown.
+    /// Jobs that already finished are left untouched: the status is watched
+    /// (Redis `WATCH`), so a job finishing meanwhile is checked again. The
+    /// cancelled status keeps the TTL the job's queue gave its status
+    /// (`worker.result_ttl_overrides`), and the flag expires with it.
+    pub async fn cancel_job(&self, job_id: &Uuid) -> Result<Option<JobResult>> {
+        let mut conn = self.conn().await?;
+        let status_key = keys::job_status(job_id);
+        let cancelled_key = keys::job_cancelled(job_id);
+        let cancelled = JobResult::cancelled(*job_id);
+        let json = serde_json::to_string(&cancelled)?;
+
+        loop {
+            traced(
+                "WATCH",
+                &status_key,
+                redis::cmd("WATCH")
+                    .arg(&status_key)
+                    .query_async::<()>(&mut *conn),
+            )
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+            let (current, ttl_ms): (Option<String>, i64) = traced(
+                "GET",
+                &status_key,
+                redis::pipe()
+                    .get(&status_key)
+                    .pttl(&status_key)
+                    .query_async(&mut *conn),
+            )
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+            let current = current
+                .map(|json| serde_json::from_str::<JobResult>(&json))
+                .transpose()?;
+
+            match current {
+                Some(current) if !current.status.is_terminal() => {}
+                finished => {
+                    traced(
+                        "UNWATCH",
+                        &status_key,
+                        redis::cmd("UNWATCH").query_async::<()>(&mut *conn),
+                    )
+                    .await
+                    .map_err(|e| QueueError::Redis(e.to_string()))?;
+                    return Ok(finished);
+                }
+            }
+
+            let ttl_ms = u64::try_from(ttl_ms)
+                .ok()
+                .filter(|ms| *ms > 0)
+                .unwrap_or(self.worker.result_ttl_seconds.saturating_mul(1000));
+            // EXEC answers nil when the status changed since WATCH.
+            let applied: Option<()> = traced(
+                "MULTI",
+                &status_key,
+                redis::pipe()
+                    .atomic()
+                    .pset_ex(&cancelled_key, 1, ttl_ms)
+                    .ignore()
+                    .cmd("SET")
+                    .arg(&status_key)
+                    .arg(&json)
+                    .arg("KEEPTTL")
+                    .ignore()
+                    .query_async(&mut *conn),
+            )
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+            if applied.is_some() {
+                break;
+            }
+        }
+
+        tracing::info!(job_id = %job_id, "job cancelled");
+        Ok(Some(cancelled))
+    }
+}
+
+/// Rough time until a job queued behind `depth` others starts: the mean of
+/// recent chat job durations, or `fallback_seconds` before any are recorded,
+/// spread over the worker's concurrency.
+fn estimated_wait_seconds(
+    depth: usize,
+    durations_ms: &[u64],
+    fallback_seconds: f64,
+    concurrency: usize,
+) -> u64 {
+    let job_seconds = if durations_ms.is_empty() {
+        fallback_seconds
+    } else {
+        durations_ms.iter().sum::<u64>() as f64 / durations_ms.len() as f64 / 1000.0
+    };
+    (depth as f64 * job_seconds / concurrency.max(1) as f64).ceil() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_wait_uses_recent_durations() {
+        assert_eq!(estimated_wait_seconds(0, &[4000], 10.0, 2), 0);
+        assert_eq!(estimated_wait_seconds(3, &[], 10.0, 2), 15);
+        assert_eq!(estimated_wait_seconds(3, &[2000, 4000], 10.0, 2), 5);
+    }
 }
@@ -0,0 +1,46 @@
+use deadpool_redis::redis::AsyncCommands;
+
+use crate::api::queue::{QueueError, RedisPool, Result};
+use crate::infrastructure::keys;
+
+/// Redis-backed switch that disables mutating endpoints on every API replica,
+/// e.g. during migrations or incident response.
+#[derive(Clone)]
+pub struct ReadOnlySwitch {
+    pool: RedisPool,
+    /// `api.read_only`, in effect until the switch is first set.
+    configured: bool,
+}
+
+impl ReadOnlySwitch {
+    pub fn new(pool: RedisPool, configured: bool) -> Self {
+        Self { pool, configured }
+    }
+
+    async fn conn(&self) -> Result<deadpool_redis::Connection> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| QueueError::Pool(e.to_string()))
+    }
+
+    pub async fn is_enabled(&self) -> Result<bool> {
+        let mut conn = self.conn().await?;
+        let value: Option<String> = conn
+            .get(keys::READ_ONLY)
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        Ok(value.map_or(self.configured, |value| value == "1"))
+    }
+
+    pub async fn set(&self, enabled: bool) -> Result<()> {
+        let mut conn = self.conn().await?;
+        conn.set::<_, _, ()>(keys::READ_ONLY, if enabled { "1" } else { "0" })
+            .await
+            .map_err(|e| QueueError::Redis(e.to_string()))?;
+
+        tracing::warn!(enabled, "API read-only mode switched");
+        Ok(())
+    }
+}
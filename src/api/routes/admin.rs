@@ -0,0 +1,552 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::queue::QueueError;
+use crate::api::routes::error_status;
+use crate::api::state::AppState;
+use crate::application::{
+    is_valid_dump_file, month_bounds, FreshnessReport, Invoice, PayloadRepairReport,
+    RetentionReport,
+};
+use crate::infrastructure::{
+    ExportCollectionJob, ImportCollectionJob, QuarantinedDocument, ReembedCollectionJob,
+    SnapshotInfo,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReembedRequest {
+    pub target_collection: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReembedResponse {
+    pub job_id: Uuid,
+    pub source_collection: String,
+    pub target_collection: String,
+    pub status: String,
+}
+
+/// Export or import of a collection dump; `collection` defaults to the active one.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DumpRequest {
+    pub collection: Option<String>,
+    /// File name in the worker's `vector_store.dump_dir`; exports default to
+    /// `<collection>-<timestamp>.jsonl`.
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpResponse {
+    pub job_id: Uuid,
+    pub collection: String,
+    pub file: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApproveQuarantinedResponse {
+    pub document_id: Uuid,
+    pub index_job_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActivateCollectionRequest {
+    pub collection: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActiveCollectionResponse {
+    pub collection: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReadOnlyMode {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplayRequest {
+    /// Answer from the chunks the original run retrieved instead of searching again.
+    #[serde(default)]
+    pub pin_retrieval: bool,
+    /// Replay as this agent (model, routing, glossary); the original one by default.
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    pub job_id: Uuid,
+    pub replay_of: Uuid,
+    pub agent_id: Option<String>,
+    pub pinned_retrieval: bool,
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BillingQuery {
+    /// `YYYY-MM`, in UTC.
+    pub month: String,
+    #[serde(default)]
+    pub format: BillingFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+pub async fn get_active_collection(
+    State(state): State<AppState>,
+) -> Result<Json<ActiveCollectionResponse>, StatusCode> {
+    let collection = state.collection_registry.active().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read active collection");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ActiveCollectionResponse { collection }))
+}
+
+/// Flips the active collection; 409 if the target has not been validated yet.
+pub async fn set_active_collection(
+    State(state): State<AppState>,
+    Json(request): Json<ActivateCollectionRequest>,
+) -> Result<Json<ActiveCollectionResponse>, StatusCode> {
+    let switched = state
+        .collection_registry
+        .activate(&request.collection)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to switch active collection");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !switched {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    Ok(Json(ActiveCollectionResponse {
+        collection: request.collection,
+    }))
+}
+
+pub async fn get_read_only(
+    State(state): State<AppState>,
+) -> Result<Json<ReadOnlyMode>, StatusCode> {
+    let enabled = state.read_only.is_enabled().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read read-only mode");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ReadOnlyMode { enabled }))
+}
+
+/// Turns read-only mode on or off for every API replica; while on, document
+/// writes, deletes and admin actions answer 503 but chat and search work.
+pub async fn set_read_only(
+    State(state): State<AppState>,
+    Json(request): Json<ReadOnlyMode>,
+) -> Result<Json<ReadOnlyMode>, StatusCode> {
+    state.read_only.set(request.enabled).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to switch read-only mode");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(request))
+}
+
+/// Queues a background re-embed of the active collection into a new one.
+pub async fn reembed_collection(
+    State(state): State<AppState>,
+    Json(request): Json<ReembedRequest>,
+) -> Result<Json<ReembedResponse>, StatusCode> {
+    let source_collection = state.collection_registry.active().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read active collection");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if request.target_collection.trim().is_empty() || request.target_collection == source_collection
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let job = ReembedCollectionJob::new(&request.target_collection);
+    let job_id = state
+        .job_producer
+        .push_reembed_job(&job)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue re-embed job");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ReembedResponse {
+        job_id,
+        source_collection,
+        target_collection: request.target_collection,
+        status: "queued".to_string(),
+    }))
+}
+
+/// Re-runs a past chat job on its recorded conversation, leaving the
+/// conversation untouched; the result carries `original_response` to compare.
+/// 404 once the job's snapshot has expired or when `worker.replay` is off.
+pub async fn replay_chat_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    request: Option<Json<ReplayRequest>>,
+) -> Result<Json<ReplayResponse>, StatusCode> {
+    let Json(request) = request.unwrap_or_default();
+    let snapshot = state
+        .job_producer
+        .chat_snapshot(&job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to read chat snapshot");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let job = snapshot.replay_job(request.pin_retrieval, request.agent_id);
+    match state.job_producer.push_chat_job(&job).await {
+        Ok(_) => {}
+        Err(QueueError::Full { .. }) => return Err(StatusCode::SERVICE_UNAVAILABLE),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to queue chat replay");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    tracing::info!(job_id = %job.job_id, replay_of = %job_id, "chat replay queued");
+
+    Ok(Json(ReplayResponse {
+        job_id: job.job_id,
+        replay_of: job_id,
+        agent_id: job.agent_id,
+        pinned_retrieval: request.pin_retrieval,
+        status: "queued".to_string(),
+    }))
+}
+
+/// The latest conversation retention report (a dry run lists what would change).
+pub async fn get_retention_report(
+    State(state): State<AppState>,
+) -> Result<Json<RetentionReport>, StatusCode> {
+    let report = state
+        .conversation_store
+        .retention_report()
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to read retention report");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    report.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// The latest report of documents past their review or expiry date.
+pub async fn get_freshness_report(
+    State(state): State<AppState>,
+) -> Result<Json<FreshnessReport>, StatusCode> {
+    let report = state.job_producer.freshness_report().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read freshness report");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    report.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Documents the safety filter quarantined, awaiting review.
+pub async fn list_quarantined(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<QuarantinedDocument>>, StatusCode> {
+    let documents = state.job_producer.quarantined().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to list quarantined documents");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(documents))
+}
+
+/// Releases a quarantined document and queues it for indexing.
+pub async fn approve_quarantined(
+    State(state): State<AppState>,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<ApproveQuarantinedResponse>, StatusCode> {
+    let released = state
+        .job_producer
+        .release_quarantined(&document_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to release quarantined document");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut job = released.job;
+    job.job_id = Uuid::new_v4();
+    job.safety_reviewed = true;
+    let index_job_id = state.job_producer.push_embed_job(&job).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to queue approved document");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ApproveQuarantinedResponse {
+        document_id,
+        index_job_id,
+    }))
+}
+
+/// Discards a quarantined document without indexing it.
+pub async fn reject_quarantined(
+    State(state): State<AppState>,
+    Path(document_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    state
+        .job_producer
+        .release_quarantined(&document_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to release quarantined document");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Some(doc_service) = &state.document_service {
+        doc_service.delete(document_id).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to delete rejected document");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Queues a dump of a collection's points, payloads and vectors to a JSON
+/// lines file on the worker.
+pub async fn export_collection(
+    State(state): State<AppState>,
+    Json(request): Json<DumpRequest>,
+) -> Result<Json<DumpResponse>, StatusCode> {
+    let collection = dump_collection(&state, request.collection).await?;
+    let file = request.file.unwrap_or_else(|| {
+        format!(
+            "{collection}-{}.jsonl",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S")
+        )
+    });
+    if !is_valid_dump_file(&file) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let job = ExportCollectionJob::new(Some(collection.clone()), &file);
+    let job_id = state
+        .job_producer
+        .push_export_job(&job)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue export job");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(DumpResponse {
+        job_id,
+        collection,
+        file,
+        status: "queued".to_string(),
+    }))
+}
+
+/// Queues a restore of a dump written by [`export_collection`] into a
+/// collection, keeping chunk ids so re-running it is harmless.
+pub async fn import_collection(
+    State(state): State<AppState>,
+    Json(request): Json<DumpRequest>,
+) -> Result<Json<DumpResponse>, StatusCode> {
+    let Some(file) = request.file.filter(|f| is_valid_dump_file(f)) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let collection = dump_collection(&state, request.collection).await?;
+
+    let job = ImportCollectionJob::new(Some(collection.clone()), &file);
+    let job_id = state
+        .job_producer
+        .push_import_job(&job)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue import job");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(DumpResponse {
+        job_id,
+        collection,
+        file,
+        status: "queued".to_string(),
+    }))
+}
+
+/// The requested collection, or the active one.
+async fn dump_collection(
+    state: &AppState,
+    collection: Option<String>,
+) -> Result<String, StatusCode> {
+    match collection {
+        Some(collection) if collection.trim().is_empty() => Err(StatusCode::BAD_REQUEST),
+        Some(collection) => Ok(collection),
+        None => state.collection_registry.active().await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to read active collection");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }),
+    }
+}
+
+/// Collection for the snapshot endpoints; defaults to the active one.
+#[derive(Debug, Deserialize)]
+pub struct SnapshotQuery {
+    pub collection: Option<String>,
+}
+
+/// Takes a Qdrant snapshot of a collection. 404 unless the vector store is Qdrant.
+pub async fn create_snapshot(
+    State(state): State<AppState>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Json<SnapshotInfo>, StatusCode> {
+    let snapshots = state.snapshots.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let collection = dump_collection(&state, query.collection).await?;
+
+    let snapshot = snapshots.create(&collection).await.map_err(|e| {
+        tracing::error!(error = %e, %collection, "Failed to create snapshot");
+        error_status(&e)
+    })?;
+    tracing::info!(%collection, snapshot = %snapshot.name, "Snapshot created");
+
+    Ok(Json(snapshot))
+}
+
+/// Snapshots of a collection, newest first.
+pub async fn list_snapshots(
+    State(state): State<AppState>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Json<Vec<SnapshotInfo>>, StatusCode> {
+    let snapshots = state.snapshots.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let collection = dump_collection(&state, query.collection).await?;
+
+    let list = snapshots.list(&collection).await.map_err(|e| {
+        tracing::error!(error = %e, %collection, "Failed to list snapshots");
+        error_status(&e)
+    })?;
+
+    Ok(Json(list))
+}
+
+/// Streams a snapshot file from Qdrant to the caller.
+pub async fn download_snapshot(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Response, StatusCode> {
+    let snapshots = state.snapshots.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let collection = dump_collection(&state, query.collection).await?;
+
+    let response = snapshots.download(&collection, &name).await.map_err(|e| {
+        tracing::error!(error = %e, %collection, snapshot = %name, "Failed to download snapshot");
+        error_status(&e)
+    })?;
+
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{name}\""),
+        );
+    if let Some(length) = response.content_length() {
+        builder = builder.header(header::CONTENT_LENGTH, length);
+    }
+    // Relay chunk by chunk; an upstream error ends the body early.
+    let body = futures::stream::unfold(Some(response), |response| async move {
+        let mut response = response?;
+        match response.chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), Some(response))),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(error = %e, "Snapshot download interrupted");
+                Some((Err(e), None))
+            }
+        }
+    });
+
+    builder
+        .body(Body::from_stream(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Rewrites vector payloads that searches skip as malformed, re-deriving them
+/// from the document store. 404 without a document store or vector store.
+pub async fn repair_payloads(
+    State(state): State<AppState>,
+) -> Result<Json<PayloadRepairReport>, StatusCode> {
+    let (Some(doc_service), Some(rag)) = (&state.document_service, state.rag_service_for(None))
+    else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let attachments = state.conversation_store.attachments().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read conversation attachments");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let report = rag
+        .repair_payloads(doc_service, &attachments)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to repair payloads");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(report))
+}
+
+/// Per-tenant token, message and storage charges for a month, priced with
+/// `billing` from the config; 404 without the Postgres job history.
+pub async fn billing_export(
+    State(state): State<AppState>,
+    Query(query): Query<BillingQuery>,
+) -> Result<Response, StatusCode> {
+    let history = state.job_history.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let (from, to) = month_bounds(&query.month).ok_or(StatusCode::BAD_REQUEST)?;
+    let month = from.format("%Y-%m").to_string();
+
+    let usage = history.usage_by_tenant(from, to).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to aggregate tenant usage");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let invoice = Invoice::new(&month, usage, &state.config.config.billing);
+
+    Ok(match query.format {
+        BillingFormat::Json => Json(invoice).into_response(),
+        BillingFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"billing-{month}.csv\""),
+                ),
+            ],
+            invoice.to_csv(),
+        )
+            .into_response(),
+    })
+}
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::domain::{Annotation, AnswerRating, SourceLabel};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnnotateRequest {
+    /// Assistant message to label; the latest one when omitted.
+    pub message_index: Option<usize>,
+    pub rating: Option<AnswerRating>,
+    #[serde(default)]
+    pub sources: Vec<SourceLabel>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+    pub author: Option<String>,
+}
+
+/// Filters for the JSONL export; all must match.
+#[derive(Debug, Deserialize)]
+pub struct ExportAnnotationsQuery {
+    pub tag: Option<String>,
+    pub rating: Option<AnswerRating>,
+    pub tenant_id: Option<String>,
+}
+
+/// Labels an answer of a conversation: a good/bad rating, verdicts on the
+/// retrieved chunks, tags and a note. 400 when nothing is labeled or the
+/// message is not an answer.
+pub async fn create_annotation(
+    State(state): State<AppState>,
+    Path(conversation_id): Path<Uuid>,
+    Json(request): Json<AnnotateRequest>,
+) -> Result<Json<Annotation>, StatusCode> {
+    let tags: Vec<String> = request
+        .tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let note = request.note.filter(|n| !n.trim().is_empty());
+    if request.rating.is_none() && request.sources.is_empty() && tags.is_empty() && note.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let conversation = match state.conversation_store.get(&conversation_id).await {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get conversation");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let (index, question, answer) = conversation
+        .answer_turn(request.message_index)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    let mut annotation =
+        Annotation::new(conversation_id, index, question.unwrap_or_default(), answer);
+    annotation.rating = request.rating;
+    annotation.sources = request.sources;
+    annotation.tags = tags;
+    annotation.note = note;
+    annotation.author = request.author;
+    annotation.tenant_id = conversation.tenant_id.clone();
+
+    state
+        .annotation_store
+        .save(&annotation)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to save annotation");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(annotation))
+}
+
+pub async fn list_annotations(
+    State(state): State<AppState>,
+    Path(conversation_id): Path<Uuid>,
+) -> Result<Json<Vec<Annotation>>, StatusCode> {
+    let annotations = state
+        .annotation_store
+        .for_conversation(&conversation_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list annotations");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(annotations))
+}
+
+pub async fn delete_annotation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    match state.annotation_store.delete(&id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to delete annotation");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Labeled answers as JSON lines, oldest first, for fine-tuning and eval sets.
+pub async fn export_annotations(
+    State(state): State<AppState>,
+    Query(query): Query<ExportAnnotationsQuery>,
+) -> Result<Response, StatusCode> {
+    let annotations = state.annotation_store.list().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to list annotations");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut body = String::new();
+    for annotation in annotations.iter().filter(|a| {
+        query.tag.as_deref().map_or(true, |tag| a.has_tag(tag))
+            && query.rating.map_or(true, |rating| a.rating == Some(rating))
+            && query
+                .tenant_id
+                .as_ref()
+                .map_or(true, |tenant| a.tenant_id.as_ref() == Some(tenant))
+    }) {
+        let line = serde_json::to_string(annotation).map_err(|e| {
+            tracing::error!(error = %e, "Failed to encode annotation");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        body.push_str(&line);
+        body.push('\n');
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"annotations.jsonl\"",
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
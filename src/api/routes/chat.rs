@@ -1,25 +1,87 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
+use futures::{stream, Stream};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::api::queue::{JobProducer, QueueError};
 use crate::api::state::AppState;
-use crate::infrastructure::ProcessChatJob;
+use crate::domain::validate_tenant_id;
+use crate::infrastructure::{
+    keys, AnswerFormat, Channel, GenerationParams, JobResult, JobSummary, ProcessChatJob,
+    QueueJobStatus,
+};
+
+const MAX_WAIT_SECONDS: u64 = 30;
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+const MAX_HISTORY_LIMIT: usize = 1000;
+/// How often an open answer stream checks for new text.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ChatRequest {
     pub message: String,
     pub conversation_id: Option<Uuid>,
     pub agent_id: Option<String>,
+    /// Tenant and end-user the conversation belongs to, used for retention rules.
+    pub tenant_id: Option<String>,
+    pub user_id: Option<String>,
+    /// Client surface the request came from; defaults to `api`.
+    pub channel: Option<Channel>,
+    /// Append permalinks to the documentation sources used in the answer.
+    #[serde(default)]
+    pub include_links: bool,
+    /// Upper bound on the answer's length in tokens.
+    pub max_answer_tokens: Option<usize>,
+    /// `markdown` (default), `plain` or `html`.
+    pub format: Option<AnswerFormat>,
+    /// Sampling settings replacing `llm`'s for this request.
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+    /// Upper bound on the tokens the model generates; unlike
+    /// `max_answer_tokens`, the model knows about it.
+    pub max_tokens: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ChatResponse {
     pub job_id: Uuid,
     pub status: String,
+    /// Expected seconds before a worker picks the job up.
+    pub estimated_wait_seconds: u64,
+}
+
+/// Body of the 503 returned while the chat queue is over its depth limit.
+#[derive(Debug, Serialize)]
+pub struct QueueFullResponse {
+    pub error: String,
+    pub queue_depth: usize,
+    pub estimated_wait_seconds: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobStatusQuery {
+    /// Block up to this many seconds (capped) for the job to finish.
+    pub wait_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobHistoryQuery {
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -28,39 +90,152 @@ pub struct JobStatusResponse {
     pub status: String,
     pub result: Option<serde_json::Value>,
     pub error: Option<String>,
+    /// Answer so far while the job is processing; poll again for more.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_result: Option<String>,
+}
+
+impl From<JobResult> for JobStatusResponse {
+    fn from(job_result: JobResult) -> Self {
+        Self {
+            job_id: job_result.job_id,
+            status: format!("{:?}", job_result.status).to_lowercase(),
+            result: job_result.result,
+            error: job_result.error,
+            partial_result: job_result.partial_result,
+        }
+    }
 }
 
 pub async fn chat_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(request): Json<ChatRequest>,
-) -> Result<Json<ChatResponse>, StatusCode> {
+) -> Result<Response, StatusCode> {
+    if let Some(tenant_id) = &request.tenant_id {
+        validate_tenant_id(tenant_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+    // A chat may only continue a conversation its tenant owns; one without a
+    // tenant may only continue conversations nobody owns.
+    if let Some(conv_id) = request.conversation_id {
+        let conversation = state.conversation_store.get(&conv_id).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to load conversation");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if conversation.is_some_and(|c| !c.is_accessible_by(request.tenant_id.as_deref())) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
     let mut job = ProcessChatJob::new(&request.message);
 
+    if let Some(api_key_id) = api_key_id(&headers) {
+        job = job.with_api_key_id(api_key_id);
+    }
     if let Some(conv_id) = request.conversation_id {
         job = job.with_conversation(conv_id);
     }
     if let Some(agent_id) = request.agent_id {
         job = job.with_agent(agent_id);
     }
+    if let Some(tenant_id) = request.tenant_id {
+        job = job.with_tenant(tenant_id);
+    }
+    if let Some(user_id) = request.user_id {
+        job = job.with_user(user_id);
+    }
+    if let Some(channel) = request.channel {
+        job = job.with_channel(channel);
+    }
+    if request.include_links {
+        job = job.with_links();
+    }
+    if let Some(max) = request.max_answer_tokens {
+        if max == 0 {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        job = job.with_max_answer_tokens(max);
+    }
+    if let Some(format) = request.format {
+        job = job.with_format(format);
+    }
+    let generation = GenerationParams {
+        temperature: request.temperature,
+        top_p: request.top_p,
+        stop_sequences: request.stop_sequences,
+        max_tokens: request.max_tokens,
+    };
+    if generation.validate().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !generation.is_empty() {
+        job = job.with_generation(generation);
+    }
 
-    let job_id = state.job_producer.push_chat_job(&job).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to queue chat job");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let queued = match state.job_producer.push_chat_job(&job).await {
+        Ok(queued) => queued,
+        Err(QueueError::Full {
+            depth,
+            estimated_wait_seconds,
+        }) => {
+            tracing::warn!(depth, "chat queue full, refusing job");
+            let body = QueueFullResponse {
+                error: "chat queue is full, try again later".to_string(),
+                queue_depth: depth,
+                estimated_wait_seconds,
+            };
+            return Ok((
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(
+                    header::RETRY_AFTER,
+                    estimated_wait_seconds.max(1).to_string(),
+                )],
+                Json(body),
+            )
+                .into_response());
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to queue chat job");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
 
     Ok(Json(ChatResponse {
-        job_id,
+        job_id: queued.job_id,
         status: "queued".to_string(),
-    }))
+        estimated_wait_seconds: queued.estimated_wait_seconds,
+    })
+    .into_response())
+}
+
+/// Identifies the API key in `X-API-Key` or a bearer `Authorization`
+/// header by the first 16 hex digits of its SHA-256, so usage can be counted
+/// per key without storing the key.
+fn api_key_id(headers: &HeaderMap) -> Option<String> {
+    let key = headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .filter(|key| !key.is_empty())?;
+    let digest = format!("{:x}", Sha256::digest(key.as_bytes()));
+    Some(digest[..16].to_string())
 }
 
 pub async fn get_job_status(
     State(state): State<AppState>,
     Path(job_id): Path<Uuid>,
+    Query(query): Query<JobStatusQuery>,
 ) -> Result<Json<JobStatusResponse>, StatusCode> {
+    let wait = Duration::from_secs(query.wait_seconds.unwrap_or(0).min(MAX_WAIT_SECONDS));
     let result = state
         .job_producer
-        .get_job_status(&job_id)
+        .wait_for_job(&job_id, wait)
         .await
         .map_err(|e| {
             tracing::error!(error = %e, "Failed to get job status");
@@ -68,12 +243,163 @@ pub async fn get_job_status(
         })?;
 
     match result {
-        Some(job_result) => Ok(Json(JobStatusResponse {
-            job_id: job_result.job_id,
-            status: format!("{:?}", job_result.status).to_lowercase(),
-            result: job_result.result,
-            error: job_result.error,
-        })),
+        Some(job_result) => Ok(Json(JobStatusResponse::from(job_result))),
         None => Err(StatusCode::NOT_FOUND),
     }
 }
+
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<JobStatusResponse>, StatusCode> {
+    let result = state.job_producer.cancel_job(&job_id).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to cancel job");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    match result {
+        Some(job_result) if job_result.status == QueueJobStatus::Cancelled => {
+            Ok(Json(JobStatusResponse::from(job_result)))
+        }
+        Some(_) => Err(StatusCode::CONFLICT),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Streams a chat job's answer as server-sent events: a `delta` event per
+/// chunk of new text (`{"text": ...}`, ids counting chunks from 1), then a
+/// `done` event with the final job status. A `reset` event means the answer
+/// restarted (a retry or provider fallback) and the text so far is void.
+///
+/// A client reconnecting with `Last-Event-ID` resumes after that chunk,
+/// for as long as `streaming.buffer_ttl_seconds` keeps the chunks.
+pub async fn stream_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let exists = state
+        .job_producer
+        .get_job_status(&job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to get job status");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .is_some();
+    if !exists {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let resume_after = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    let cursor = StreamCursor {
+        producer: state.job_producer.clone(),
+        job_id,
+        next: resume_after,
+        pending: VecDeque::new(),
+        finished: false,
+    };
+
+    let events = stream::unfold(cursor, |mut cursor| async move {
+        loop {
+            if let Some(event) = cursor.pending.pop_front() {
+                return Some((Ok(event), cursor));
+            }
+            if cursor.finished {
+                return None;
+            }
+            cursor.poll().await;
+        }
+    });
+
+    let keep_alive = state.config.config.streaming.keep_alive_seconds;
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(Duration::from_secs(keep_alive))))
+}
+
+/// Read position of an answer stream and the events due to be sent.
+struct StreamCursor {
+    producer: JobProducer,
+    job_id: Uuid,
+    /// Index of the next chunk to send, which is also the last sent event id.
+    next: usize,
+    pending: VecDeque<Event>,
+    finished: bool,
+}
+
+impl StreamCursor {
+    /// Queues new chunks and, once the job is over, the `done` event; waits a
+    /// poll interval first when nothing is new.
+    async fn poll(&mut self) {
+        // Status before chunks, so every chunk of a finished job is sent before `done`.
+        let status = self.producer.get_job_status(&self.job_id).await;
+        let chunks = self.producer.stream_chunks(&self.job_id, self.next).await;
+        let (status, chunks) = match (status, chunks) {
+            (Ok(status), Ok(chunks)) => (status, chunks),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!(error = %e, job_id = %self.job_id, "answer stream failed");
+                self.pending
+                    .push_back(Event::default().event("error").data("stream unavailable"));
+                self.finished = true;
+                return;
+            }
+        };
+
+        for chunk in chunks {
+            self.next += 1;
+            let event = if chunk == keys::STREAM_RESET {
+                Event::default()
+                    .id(self.next.to_string())
+                    .event("reset")
+                    .data("{}")
+            } else {
+                Event::default()
+                    .id(self.next.to_string())
+                    .event("delta")
+                    .data(serde_json::json!({ "text": chunk }).to_string())
+            };
+            self.pending.push_back(event);
+        }
+
+        match status {
+            Some(result) if result.status.is_terminal() => {
+                let status =
+                    serde_json::to_string(&JobStatusResponse::from(result)).unwrap_or_default();
+                self.pending
+                    .push_back(Event::default().event("done").data(status));
+                self.finished = true;
+            }
+            Some(_) => {
+                if self.pending.is_empty() {
+                    tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+                }
+            }
+            // The status expired; there is nothing more to send.
+            None => self.finished = true,
+        }
+    }
+}
+
+/// Lists summaries of finished jobs of every type, newest first.
+pub async fn list_job_history(
+    State(state): State<AppState>,
+    Query(query): Query<JobHistoryQuery>,
+) -> Result<Json<Vec<JobSummary>>, StatusCode> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .min(MAX_HISTORY_LIMIT);
+
+    state
+        .job_producer
+        .history(limit)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to read job history");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
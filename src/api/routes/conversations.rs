@@ -0,0 +1,341 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::domain::{
+    validate_tenant_id, ContextDocument, Conversation, Draft, DraftStatus, Message,
+};
+
+const DEFAULT_LIST_LIMIT: usize = 50;
+const MAX_LIST_LIMIT: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListConversationsQuery {
+    pub limit: Option<usize>,
+    /// Lists this tenant's conversations; without it, untenanted ones.
+    pub tenant_id: Option<String>,
+}
+
+/// Tenant a request acts for. Conversations a tenant owns are 404 to
+/// requests for any other tenant or for none.
+#[derive(Debug, Deserialize)]
+pub struct TenantQuery {
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateConversationRequest {
+    pub tenant_id: Option<String>,
+    pub user_id: Option<String>,
+    /// Added to the agent's system prompt for this conversation.
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Documents the agent sees with every message, without indexing them.
+    #[serde(default)]
+    pub context: Vec<ContextDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergeConversationRequest {
+    /// Conversation merged into the one in the path and then retired.
+    pub conversation_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConversationSummaryResponse {
+    pub id: Uuid,
+    pub message_count: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Conversation> for ConversationSummaryResponse {
+    fn from(conv: Conversation) -> Self {
+        Self {
+            id: conv.id,
+            message_count: conv.messages.len(),
+            created_at: conv.created_at,
+            updated_at: conv.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConversationResponse {
+    pub id: Uuid,
+    pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<ContextDocument>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A drafted answer as reviewers see it. Only served on admin routes, since
+/// pending content hasn't been approved for the conversation's users.
+#[derive(Debug, Serialize)]
+pub struct DraftResponse {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub status: DraftStatus,
+    pub content: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<&Draft> for DraftResponse {
+    fn from(draft: &Draft) -> Self {
+        Self {
+            id: draft.id,
+            job_id: draft.job_id,
+            status: draft.status,
+            content: draft.content.clone(),
+            created_at: draft.created_at,
+            approved_at: draft.approved_at,
+        }
+    }
+}
+
+impl From<Conversation> for ConversationResponse {
+    fn from(conv: Conversation) -> Self {
+        Self {
+            id: conv.id,
+            messages: conv.messages,
+            system_prompt: conv.system_prompt,
+            metadata: conv.metadata,
+            context: conv.context,
+            merged: conv.merged,
+            created_at: conv.created_at,
+            updated_at: conv.updated_at,
+        }
+    }
+}
+
+/// Creates an empty conversation so clients know its id before the first chat.
+pub async fn create_conversation(
+    State(state): State<AppState>,
+    Json(request): Json<CreateConversationRequest>,
+) -> Result<Json<ConversationResponse>, StatusCode> {
+    if request
+        .context
+        .iter()
+        .any(|doc| doc.content.trim().is_empty())
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Some(tenant_id) = &request.tenant_id {
+        validate_tenant_id(tenant_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+
+    let mut conversation = Conversation::new();
+    conversation.tenant_id = request.tenant_id;
+    conversation.user_id = request.user_id;
+    conversation.system_prompt = request.system_prompt.filter(|p| !p.trim().is_empty());
+    conversation.metadata = request.metadata;
+    conversation.context = request.context;
+
+    let ttl = state.config.config.worker.conversation_ttl_seconds;
+    state
+        .conversation_store
+        .save(&conversation, ttl)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to create conversation");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ConversationResponse::from(conversation)))
+}
+
+pub async fn list_conversations(
+    State(state): State<AppState>,
+    Query(query): Query<ListConversationsQuery>,
+) -> Result<Json<Vec<ConversationSummaryResponse>>, StatusCode> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .min(MAX_LIST_LIMIT);
+
+    let conversations = state
+        .conversation_store
+        .list(limit, query.tenant_id.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list conversations");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(conversations.into_iter().map(Into::into).collect()))
+}
+
+pub async fn get_conversation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(tenant): Query<TenantQuery>,
+) -> Result<Json<ConversationResponse>, StatusCode> {
+    let conversation = load_conversation(&state, &id, &tenant).await?;
+    Ok(Json(ConversationResponse::from(conversation)))
+}
+
+pub async fn delete_conversation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(tenant): Query<TenantQuery>,
+) -> Result<StatusCode, StatusCode> {
+    load_conversation(&state, &id, &tenant).await?;
+    match state.conversation_store.delete(&id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to delete conversation");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Hands a conversation over to another one, e.g. when a user who started
+/// in the widget continues on Slack: the request's conversation is merged
+/// into the path's, its messages keeping their channel and origin, and its
+/// id resolves to the merged conversation from then on. 409 when the two
+/// belong to different tenants or users.
+pub async fn merge_conversation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(tenant): Query<TenantQuery>,
+    Json(request): Json<MergeConversationRequest>,
+) -> Result<Json<ConversationResponse>, StatusCode> {
+    let mut conversation = load_conversation(&state, &id, &tenant).await?;
+    let source = load_conversation(&state, &request.conversation_id, &tenant).await?;
+    // Either id may already resolve to the other through an earlier merge.
+    if source.id == conversation.id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let source_id = source.id;
+    conversation.merge(source).map_err(|e| {
+        tracing::warn!(error = %e, conversation_id = %id, "Refused to merge conversations");
+        StatusCode::CONFLICT
+    })?;
+
+    let ttl = state.config.config.worker.conversation_ttl_seconds;
+    let store = &state.conversation_store;
+    store.save(&conversation, ttl).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to save conversation");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    store
+        .retire_merged(&source_id, &conversation.id, ttl)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to retire merged conversation");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!(conversation_id = %conversation.id, merged = %source_id, "conversations merged");
+    Ok(Json(ConversationResponse::from(conversation)))
+}
+
+/// The conversation `id` resolves to, if `tenant` may access it.
+async fn load_conversation(
+    state: &AppState,
+    id: &Uuid,
+    tenant: &TenantQuery,
+) -> Result<Conversation, StatusCode> {
+    match state.conversation_store.get(id).await {
+        Ok(Some(conversation)) if conversation.is_accessible_by(tenant.tenant_id.as_deref()) => {
+            Ok(conversation)
+        }
+        Ok(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get conversation");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// A conversation's drafted answers, pending and approved, for reviewers.
+pub async fn list_drafts(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<DraftResponse>>, StatusCode> {
+    match state.conversation_store.get(&id).await {
+        Ok(Some(conversation)) => Ok(Json(
+            conversation
+                .drafts
+                .iter()
+                .map(DraftResponse::from)
+                .collect(),
+        )),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get conversation");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Releases a drafted answer: it joins the conversation history and becomes
+/// the result of the chat job that wrote it. 409 if it was already approved.
+pub async fn approve_draft(
+    State(state): State<AppState>,
+    Path((id, draft_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<DraftResponse>, StatusCode> {
+    // Resolves ids of merged conversations.
+    let id = match state.conversation_store.get(&id).await {
+        Ok(Some(conversation)) => conversation.id,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get conversation");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let ttl = state.config.config.worker.conversation_ttl_seconds;
+    let draft = state
+        .conversation_store
+        .update(&id, ttl, |conversation| {
+            let mut conversation = conversation.ok_or(StatusCode::NOT_FOUND)?;
+            match conversation.drafts.iter().find(|d| d.id == draft_id) {
+                None => return Err(StatusCode::NOT_FOUND),
+                Some(draft) if draft.status != DraftStatus::Pending => {
+                    return Err(StatusCode::CONFLICT)
+                }
+                Some(_) => {}
+            }
+            let draft = conversation
+                .approve_draft(draft_id)
+                .cloned()
+                .ok_or(StatusCode::NOT_FOUND)?;
+            Ok((conversation, draft))
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to save conversation");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })??;
+    state
+        .job_producer
+        .release_draft(&draft.job_id, draft.result.clone())
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, job_id = %draft.job_id, "Failed to release draft");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!(conversation_id = %id, draft_id = %draft.id, "draft approved");
+    Ok(Json(DraftResponse::from(&draft)))
+}
@@ -0,0 +1,123 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::api::routes::documents::SearchResultResponse;
+use crate::api::state::AppState;
+use crate::application::{diff_results, RetrievalDiff, RetrievalOptions, RetrievalStrategy};
+use crate::domain::{SearchFilter, SearchResult};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetrievalDiffRequest {
+    pub query: String,
+    /// Applied to both runs.
+    #[serde(default)]
+    pub filter: SearchFilter,
+    pub a: RetrievalVariant,
+    pub b: RetrievalVariant,
+}
+
+/// One side of a diff; unset fields keep the knowledge base tool's settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetrievalVariant {
+    pub top_k: Option<usize>,
+    pub min_score: Option<f32>,
+    pub strategy: Option<RetrievalStrategy>,
+    pub collection: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetrievalRun {
+    pub top_k: usize,
+    pub min_score: f32,
+    pub strategy: RetrievalStrategy,
+    pub collection: String,
+    pub latency_ms: u64,
+    pub results: Vec<SearchResultResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetrievalDiffResponse {
+    pub query: String,
+    pub a: RetrievalRun,
+    pub b: RetrievalRun,
+    pub diff: RetrievalDiff,
+}
+
+/// Runs one query under two retrieval configurations and diffs the results
+/// by chunk, to compare tuning changes. 404 for an unknown collection.
+pub async fn retrieval_diff(
+    State(state): State<AppState>,
+    Json(request): Json<RetrievalDiffRequest>,
+) -> Result<Json<RetrievalDiffResponse>, StatusCode> {
+    let ((a, results_a), (b, results_b)) = tokio::try_join!(
+        run_variant(&state, &request.query, &request.filter, request.a),
+        run_variant(&state, &request.query, &request.filter, request.b),
+    )?;
+
+    let diff = diff_results(&results_a, &results_b);
+    let now = chrono::Utc::now();
+    let into_responses = |results: Vec<_>| {
+        results
+            .into_iter()
+            .map(|r| SearchResultResponse::new(r, now))
+            .collect()
+    };
+    Ok(Json(RetrievalDiffResponse {
+        query: request.query,
+        a: RetrievalRun {
+            results: into_responses(results_a),
+            ..a
+        },
+        b: RetrievalRun {
+            results: into_responses(results_b),
+            ..b
+        },
+        diff,
+    }))
+}
+
+/// Retrieves for `query` under `variant`, returning the resolved settings
+/// (results left empty) and the raw results.
+async fn run_variant(
+    state: &AppState,
+    query: &str,
+    filter: &SearchFilter,
+    variant: RetrievalVariant,
+) -> Result<(RetrievalRun, Vec<SearchResult>), StatusCode> {
+    let config = &state.config.config;
+    let tool = &config.tools.knowledge_base;
+    let mut options: RetrievalOptions = tool.retrieval_options(&config.rag);
+    options.top_k = variant.top_k.unwrap_or(options.top_k);
+    options.min_score = variant.min_score.unwrap_or(options.min_score);
+    options.strategy = variant.strategy.unwrap_or(options.strategy);
+    options.filter = filter.clone().with_default_tags(&options.filter.tags);
+
+    let collection = variant
+        .collection
+        .unwrap_or_else(|| tool.collection(&config.vector_store).to_string());
+    let rag_service = state
+        .rag_service_for(Some(&collection))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let started = Instant::now();
+    let results = rag_service
+        .retrieve_with(query, &options)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, collection, "Retrieval diff search failed");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let run = RetrievalRun {
+        top_k: options.top_k,
+        min_score: options.min_score,
+        strategy: options.strategy,
+        collection,
+        latency_ms: started.elapsed().as_millis() as u64,
+        results: Vec::new(),
+    };
+    Ok((run, results))
+}
@@ -1,20 +1,53 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::api::routes::error_status;
 use crate::api::state::AppState;
-use crate::domain::Document;
+use crate::application::{RetrievalOptions, RetrievalStrategy};
+use crate::domain::{
+    validate_tenant_id, ChunkMetadata, Document, DomainError, ExtractedPage, Freshness,
+    SearchFilter, SearchResult,
+};
+use crate::infrastructure::{EmbedDocumentJob, IndexDocumentJob};
+
+const INDEX_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Request body limit for file uploads.
+pub const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CreateDocumentRequest {
     pub name: String,
     pub content: String,
     #[allow(dead_code)]
     pub content_type: Option<String>,
+    /// Block until the embed job finishes so the document is immediately searchable.
+    #[serde(default)]
+    pub wait_for_index: bool,
+    /// Skip chunking in the request and return `202 Accepted` with the job id
+    /// as soon as the embed job is queued; for large documents.
+    #[serde(default, rename = "async")]
+    pub async_ingest: bool,
+    /// When the content should be reviewed; it is reported as stale afterwards.
+    pub review_by: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the content stops being valid; retrieval then applies `rag.freshness`.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Labels for tag-scoped search, e.g. `["billing", "emea"]`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Attach the document to this conversation only, instead of the shared
+    /// knowledge base; its vectors are deleted when the conversation expires.
+    pub conversation_id: Option<Uuid>,
+    /// Tenant owning the document: only its chats and searches see it, and
+    /// its storage is billed to it.
+    pub tenant_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -22,6 +55,12 @@ pub struct DocumentResponse {
     pub id: Uuid,
     pub name: String,
     pub content_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_by: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -32,12 +71,57 @@ impl From<Document> for DocumentResponse {
             id: doc.id,
             name: doc.name,
             content_type: doc.content_type,
+            review_by: doc.review_by,
+            expires_at: doc.expires_at,
+            tags: doc.tags,
             created_at: doc.created_at,
             updated_at: doc.updated_at,
         }
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct CreateDocumentResponse {
+    #[serde(flatten)]
+    pub document: DocumentResponse,
+    /// Absent when the content duplicated an existing document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_job_id: Option<Uuid>,
+    /// `queued` unless `wait_for_index` was set, in which case this is the
+    /// embed job's status when the wait ended; `duplicate` when an identical
+    /// document already existed and is returned instead.
+    pub index_status: String,
+}
+
+impl CreateDocumentResponse {
+    fn duplicate(document: Document) -> Self {
+        Self {
+            document: DocumentResponse::from(document),
+            index_job_id: None,
+            index_status: "duplicate".to_string(),
+        }
+    }
+}
+
+/// Returned for `async` ingestion; poll `/chat/jobs/{job_id}` for progress.
+#[derive(Debug, Serialize)]
+pub struct AcceptedDocumentResponse {
+    pub document_id: Uuid,
+    /// Absent when the content duplicated an existing document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentChunkResponse {
+    pub id: Uuid,
+    pub chunk_index: usize,
+    pub content: String,
+    pub metadata: ChunkMetadata,
+    /// Whether the vector store holds this chunk with the same content.
+    pub embedded: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListDocumentsQuery {
     #[allow(dead_code)]
@@ -47,9 +131,17 @@ pub struct ListDocumentsQuery {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SearchDocumentsRequest {
     pub query: String,
     pub limit: Option<usize>,
+    /// Name of an agent tool whose collection and strategy should be used.
+    pub tool: Option<String>,
+    pub collection: Option<String>,
+    pub strategy: Option<RetrievalStrategy>,
+    /// Only search chunks matching these document ids, tags or content types.
+    #[serde(default)]
+    pub filter: SearchFilter,
 }
 
 #[derive(Debug, Serialize)]
@@ -58,25 +150,357 @@ pub struct SearchResultResponse {
     pub document_id: Uuid,
     pub content: String,
     pub score: f32,
+    /// Short summary of the chunk, when chunk titles are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Source reference for chunks from connectors, e.g. `docs/a.md:3-9@1a2b3c4`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Set when the chunk's document is past its review or expiry date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<Freshness>,
+}
+
+impl SearchResultResponse {
+    pub fn new(result: SearchResult, now: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            source: result.chunk.metadata.citation(),
+            title: result.chunk.metadata.title.clone(),
+            freshness: Some(result.chunk.metadata.freshness(now))
+                .filter(|f| *f != Freshness::Current),
+            chunk_id: result.chunk.id,
+            document_id: result.chunk.document_id,
+            content: result.chunk.content,
+            score: result.score,
+        }
+    }
 }
 
 pub async fn create_document(
     State(state): State<AppState>,
     Json(request): Json<CreateDocumentRequest>,
-) -> Result<Json<DocumentResponse>, StatusCode> {
+) -> Result<Response, StatusCode> {
+    if let Some(tenant_id) = &request.tenant_id {
+        validate_tenant_id(tenant_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+    if request.async_ingest {
+        if request.wait_for_index {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        return create_document_async(state, request)
+            .await
+            .map(|accepted| (StatusCode::ACCEPTED, Json(accepted)).into_response());
+    }
+
+    let dedup = request.conversation_id.is_none();
+    let doc = match &state.document_service {
+        Some(doc_service) => {
+            let ingested = doc_service
+                .ingest(
+                    &request.name,
+                    &request.content,
+                    request.tenant_id.as_deref(),
+                    dedup,
+                )
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = %e, "Failed to create document");
+                    error_status(&e)
+                })?;
+            if ingested.duplicate {
+                return Ok(
+                    Json(CreateDocumentResponse::duplicate(ingested.document)).into_response()
+                );
+            }
+            ingested.document
+        }
+        None => Document::new(&request.name),
+    }
+    .with_freshness(request.review_by, request.expires_at)
+    .with_tags(request.tags)
+    .with_tenant(request.tenant_id.clone());
+    update_document(&state, &doc).await?;
+
+    let mut job = EmbedDocumentJob::new(doc.id, &request.content)
+        .with_content_type(&doc.content_type)
+        .with_freshness(doc.review_by, doc.expires_at)
+        .with_tags(doc.tags.clone());
+    if let Some(conversation_id) = request.conversation_id {
+        attach_to_conversation(&state, conversation_id, doc.id).await?;
+        job = job.with_conversation(conversation_id);
+    }
+    if let Some(tenant_id) = request.tenant_id {
+        job = job.with_tenant(tenant_id);
+    }
+    let (index_job_id, index_status) = queue_embed(&state, &job, request.wait_for_index).await?;
+
+    Ok(Json(CreateDocumentResponse {
+        document: DocumentResponse::from(doc),
+        index_job_id: Some(index_job_id),
+        index_status,
+    })
+    .into_response())
+}
+
+/// Records the document and queues its embed job, leaving all chunking to the worker.
+async fn create_document_async(
+    state: AppState,
+    request: CreateDocumentRequest,
+) -> Result<AcceptedDocumentResponse, StatusCode> {
+    let doc = Document::new(&request.name)
+        .with_freshness(request.review_by, request.expires_at)
+        .with_tags(request.tags)
+        .with_tenant(request.tenant_id.clone());
+    if let Some(doc_service) = &state.document_service {
+        let dedup = request.conversation_id.is_none();
+        let ingested = doc_service
+            .register(doc.clone(), &request.content, dedup)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Failed to create document");
+                error_status(&e)
+            })?;
+        if ingested.duplicate {
+            return Ok(AcceptedDocumentResponse {
+                document_id: ingested.document.id,
+                job_id: None,
+            });
+        }
+    }
+
+    let mut job = EmbedDocumentJob::new(doc.id, request.content)
+        .with_content_type(&doc.content_type)
+        .with_freshness(doc.review_by, doc.expires_at)
+        .with_tags(doc.tags);
+    if let Some(conversation_id) = request.conversation_id {
+        attach_to_conversation(&state, conversation_id, doc.id).await?;
+        job = job.with_conversation(conversation_id);
+    }
+    if let Some(tenant_id) = request.tenant_id {
+        job = job.with_tenant(tenant_id);
+    }
+    let (job_id, _) = queue_embed(&state, &job, false).await?;
+
+    Ok(AcceptedDocumentResponse {
+        document_id: doc.id,
+        job_id: Some(job_id),
+    })
+}
+
+/// Ingests an uploaded file (multipart field `file`), extracting text by content type.
+///
+/// Optional text fields: `name` (defaults to the file name), `wait_for_index`,
+/// RFC 3339 `review_by` / `expires_at` dates, comma-separated `tags`, a
+/// `conversation_id` to attach to and the `tenant_id` billed for storage.
+pub async fn upload_document(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<CreateDocumentResponse>, StatusCode> {
+    let mut file = None;
+    let mut name = None;
+    let mut wait_for_index = false;
+    let mut review_by = None;
+    let mut expires_at = None;
+    let mut conversation_id = None;
+    let mut tenant_id = None;
+    let mut tags = Vec::new();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+    {
+        match field.name() {
+            Some("file") => {
+                let file_name = field.file_name().map(str::to_string);
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                file = Some((file_name, content_type, bytes));
+            }
+            Some("name") => name = Some(field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?),
+            Some("wait_for_index") => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                wait_for_index = value.trim().parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+            }
+            Some("tags") => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                tags = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            Some("conversation_id") => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                conversation_id = Some(value.trim().parse().map_err(|_| StatusCode::BAD_REQUEST)?);
+            }
+            Some("tenant_id") => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                tenant_id = Some(value.trim().to_string()).filter(|t| !t.is_empty());
+                if let Some(tenant_id) = &tenant_id {
+                    validate_tenant_id(tenant_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+                }
+            }
+            Some(date @ ("review_by" | "expires_at")) => {
+                let date = date.to_string();
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                let parsed = Some(value.trim().parse().map_err(|_| StatusCode::BAD_REQUEST)?);
+                if date == "review_by" {
+                    review_by = parsed;
+                } else {
+                    expires_at = parsed;
+                }
+            }
+            _ => return Err(StatusCode::BAD_REQUEST),
+        }
+    }
+
+    let Some((file_name, content_type, bytes)) = file else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let name = name.or(file_name).ok_or(StatusCode::BAD_REQUEST)?;
+
+    let pages = state
+        .extractors
+        .extract(&content_type, &bytes)
+        .await
+        .map_err(|e| match e {
+            DomainError::Validation(msg) => {
+                tracing::warn!(error = %msg, "Rejected upload");
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+            e => {
+                tracing::error!(error = %e, "Failed to extract upload");
+                error_status(&e)
+            }
+        })?;
+
+    let doc = match &state.document_service {
+        Some(doc_service) => {
+            let ingested = doc_service
+                .ingest_pages(
+                    &name,
+                    &content_type,
+                    &pages,
+                    tenant_id.as_deref(),
+                    conversation_id.is_none(),
+                )
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = %e, "Failed to create document");
+                    error_status(&e)
+                })?;
+            if ingested.duplicate {
+                return Ok(Json(CreateDocumentResponse::duplicate(ingested.document)));
+            }
+            ingested.document
+        }
+        None => Document::new(&name).with_content_type(&content_type),
+    }
+    .with_freshness(review_by, expires_at)
+    .with_tags(tags)
+    .with_tenant(tenant_id.clone());
+    update_document(&state, &doc).await?;
+
+    let content = join_pages(&pages);
+    let mut job = EmbedDocumentJob::new(doc.id, content)
+        .with_content_type(&doc.content_type)
+        .with_freshness(doc.review_by, doc.expires_at)
+        .with_tags(doc.tags.clone())
+        .with_pages(pages);
+    if let Some(conversation_id) = conversation_id {
+        attach_to_conversation(&state, conversation_id, doc.id).await?;
+        job = job.with_conversation(conversation_id);
+    }
+    if let Some(tenant_id) = tenant_id {
+        job = job.with_tenant(tenant_id);
+    }
+    let (index_job_id, index_status) = queue_embed(&state, &job, wait_for_index).await?;
+
+    Ok(Json(CreateDocumentResponse {
+        document: DocumentResponse::from(doc),
+        index_job_id: Some(index_job_id),
+        index_status,
+    }))
+}
+
+/// Saves tags and dates set after ingestion, so re-indexing keeps them.
+async fn update_document(state: &AppState, doc: &Document) -> Result<(), StatusCode> {
     let Some(doc_service) = &state.document_service else {
-        let doc = Document::new(&request.name);
-        return Ok(Json(DocumentResponse::from(doc)));
+        return Ok(());
     };
+    doc_service.update(doc).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to update document");
+        error_status(&e)
+    })
+}
 
-    doc_service
-        .ingest(&request.name, &request.content)
+/// Records an attachment, failing with 404 if the conversation doesn't exist.
+async fn attach_to_conversation(
+    state: &AppState,
+    conversation_id: Uuid,
+    document_id: Uuid,
+) -> Result<(), StatusCode> {
+    let store = &state.conversation_store;
+    let internal = |e| {
+        tracing::error!(error = %e, "Failed to attach document to conversation");
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    if store
+        .get(&conversation_id)
+        .await
+        .map_err(internal)?
+        .is_none()
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    store
+        .attach(&conversation_id, &document_id)
+        .await
+        .map_err(internal)
+}
+
+fn join_pages(pages: &[ExtractedPage]) -> String {
+    pages
+        .iter()
+        .map(|p| p.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Queues an embed job, optionally waiting for it; returns its id and status.
+async fn queue_embed(
+    state: &AppState,
+    job: &EmbedDocumentJob,
+    wait: bool,
+) -> Result<(Uuid, String), StatusCode> {
+    let job_id = state.job_producer.push_embed_job(job).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to queue embed job");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if !wait {
+        return Ok((job_id, "queued".to_string()));
+    }
+
+    let result = state
+        .job_producer
+        .wait_for_job(&job_id, INDEX_WAIT_TIMEOUT)
         .await
-        .map(|(doc, _)| Json(DocumentResponse::from(doc)))
         .map_err(|e| {
-            tracing::error!(error = %e, "Failed to create document");
+            tracing::error!(error = %e, "Failed to wait for embed job");
             StatusCode::INTERNAL_SERVER_ERROR
-        })
+        })?;
+
+    let status = result
+        .map(|r| format!("{:?}", r.status).to_lowercase())
+        .unwrap_or_else(|| "queued".to_string());
+    Ok((job_id, status))
 }
 
 pub async fn get_document(
@@ -92,11 +516,91 @@ pub async fn get_document(
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!(error = %e, "Failed to get document");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(error_status(&e))
         }
     }
 }
 
+/// The stored chunks of a document, for debugging retrieval.
+pub async fn get_document_chunks(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<DocumentChunkResponse>>, StatusCode> {
+    let Some(doc_service) = &state.document_service else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let (_, chunks) = doc_service
+        .get_with_chunks(id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to get document chunks");
+            error_status(&e)
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // The worker chunks documents itself, so chunks are matched by position and content.
+    let indexed = match state.rag_service_for(None) {
+        Some(rag) => rag.document_chunks(id).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to get indexed chunks");
+            error_status(&e)
+        })?,
+        None => Vec::new(),
+    };
+
+    Ok(Json(
+        chunks
+            .into_iter()
+            .map(|chunk| DocumentChunkResponse {
+                embedded: indexed
+                    .iter()
+                    .any(|c| c.chunk_index == chunk.chunk_index && c.content == chunk.content),
+                id: chunk.id,
+                chunk_index: chunk.chunk_index,
+                content: chunk.content,
+                metadata: chunk.metadata,
+            })
+            .collect(),
+    ))
+}
+
+/// Queues a re-chunk and re-embed of a stored document with the current
+/// chunking and embedding settings.
+pub async fn reindex_document(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, Json<AcceptedDocumentResponse>), StatusCode> {
+    let Some(doc_service) = &state.document_service else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    doc_service
+        .get(id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to get document");
+            error_status(&e)
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let job_id = state
+        .job_producer
+        .push_index_job(&IndexDocumentJob::new(id))
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue index job");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(AcceptedDocumentResponse {
+            document_id: id,
+            job_id: Some(job_id),
+        }),
+    ))
+}
+
 pub async fn list_documents(
     State(_state): State<AppState>,
     Query(_query): Query<ListDocumentsQuery>,
@@ -115,7 +619,7 @@ pub async fn delete_document(
 
     doc_service.delete(id).await.map_err(|e| {
         tracing::error!(error = %e, "Failed to delete document");
-        StatusCode::INTERNAL_SERVER_ERROR
+        error_status(&e)
     })?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -125,24 +629,45 @@ pub async fn search_documents(
     State(state): State<AppState>,
     Json(request): Json<SearchDocumentsRequest>,
 ) -> Result<Json<Vec<SearchResultResponse>>, StatusCode> {
-    let Some(rag_service) = &state.rag_service else {
+    let config = &state.config.config;
+    let mut options = RetrievalOptions::similarity(5);
+    let mut collection = None;
+
+    if let Some(tool) = &request.tool {
+        let tool_config = &config.tools.knowledge_base;
+        if *tool != tool_config.name {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        options = tool_config.retrieval_options(&config.rag);
+        collection = tool_config.collection.clone();
+    }
+
+    if let Some(limit) = request.limit {
+        options.top_k = limit;
+    }
+    if let Some(strategy) = request.strategy {
+        options.strategy = strategy;
+        options.min_score = config.rag.min_score;
+    }
+    options.filter = request.filter.with_default_tags(&options.filter.tags);
+    let collection = request.collection.or(collection);
+
+    let Some(rag_service) = state.rag_service_for(collection.as_deref()) else {
+        if collection.is_some_and(|c| c != config.vector_store.collection) {
+            return Err(StatusCode::NOT_FOUND);
+        }
         return Ok(Json(vec![]));
     };
 
-    let top_k = request.limit.unwrap_or(5);
+    let now = chrono::Utc::now();
     rag_service
-        .retrieve_top_k(&request.query, top_k)
+        .retrieve_with(&request.query, &options)
         .await
         .map(|results| {
             Json(
                 results
                     .into_iter()
-                    .map(|r| SearchResultResponse {
-                        chunk_id: r.chunk.id,
-                        document_id: r.chunk.document_id,
-                        content: r.chunk.content,
-                        score: r.score,
-                    })
+                    .map(|r| SearchResultResponse::new(r, now))
                     .collect(),
             )
         })
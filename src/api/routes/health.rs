@@ -10,10 +10,14 @@ pub struct HealthResponse {
     pub version: String,
 }
 
+/// Overall readiness and the status of each dependency.
 #[derive(Serialize)]
 pub struct ReadinessResponse {
     pub status: String,
     pub redis: String,
+    /// Absent when the API has no vector store check configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_store: Option<String>,
 }
 
 pub async fn health_check() -> Json<HealthResponse> {
@@ -23,9 +27,11 @@ pub async fn health_check() -> Json<HealthResponse> {
     })
 }
 
+/// Ready once Redis answers and the active collection is reachable on the
+/// vector store; otherwise 503 with the failing dependency.
 pub async fn readiness_check(
     State(state): State<AppState>,
-) -> Result<Json<ReadinessResponse>, StatusCode> {
+) -> (StatusCode, Json<ReadinessResponse>) {
     let redis_status = match state.redis_pool.get().await {
         Ok(mut conn) => {
             let ping: Result<String, _> = cmd("PING").query_async(&mut *conn).await;
@@ -38,16 +44,36 @@ pub async fn readiness_check(
         Err(_) => "disconnected",
     };
 
-    let is_healthy = redis_status == "connected";
+    let vector_store_status = match &state.vector_store_health {
+        Some(health) => Some(match state.collection_registry.active().await {
+            Ok(collection) => match health.check(&collection).await {
+                Ok(()) => "connected",
+                Err(e) => {
+                    tracing::warn!(error = %e, %collection, "vector store not ready");
+                    "disconnected"
+                }
+            },
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to read active collection");
+                "disconnected"
+            }
+        }),
+        None => None,
+    };
+
+    let is_healthy =
+        redis_status == "connected" && matches!(vector_store_status, None | Some("connected"));
 
     let response = ReadinessResponse {
         status: if is_healthy { "ready" } else { "not_ready" }.into(),
         redis: redis_status.into(),
+        vector_store: vector_store_status.map(Into::into),
     };
 
-    if is_healthy {
-        Ok(Json(response))
+    let status = if is_healthy {
+        StatusCode::OK
     } else {
-        Err(StatusCode::SERVICE_UNAVAILABLE)
-    }
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
 }
@@ -1,14 +1,23 @@
+pub mod admin;
+pub mod annotations;
 pub mod chat;
+pub mod conversations;
+pub mod debug;
 pub mod documents;
 pub mod health;
+pub mod models;
+pub mod sources;
 
-use axum::http::{header, Method};
-use axum::{routing::get, routing::post, Router};
+use axum::extract::DefaultBodyLimit;
+use axum::http::{header, Method, StatusCode};
+use axum::{middleware, routing::get, routing::post, Router};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::warn;
 
+use crate::api::middleware::reject_writes_when_read_only;
 use crate::api::state::AppState;
+use crate::domain::{DomainError, ErrorKind};
 
 pub fn create_router(state: AppState) -> Router {
     let cors = build_cors(&state);
@@ -17,11 +26,27 @@ pub fn create_router(state: AppState) -> Router {
         .route("/health", get(health::health_check))
         .route("/ready", get(health::readiness_check))
         .nest("/api/v1", api_v1_routes())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            reject_writes_when_read_only,
+        ))
         .layer(TraceLayer::new_for_http())
         .layer(cors)
         .with_state(state)
 }
 
+/// HTTP status for a failed domain call: 4xx when the request is at fault,
+/// 429/503 when the caller may retry.
+pub(crate) fn error_status(e: &DomainError) -> StatusCode {
+    match e.kind() {
+        ErrorKind::InvalidInput if matches!(e, DomainError::NotFound(_)) => StatusCode::NOT_FOUND,
+        ErrorKind::InvalidInput => StatusCode::BAD_REQUEST,
+        ErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        ErrorKind::Transient => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorKind::Permanent => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 fn build_cors(state: &AppState) -> CorsLayer {
     let cors_config = &state.config.config.cors;
 
@@ -45,13 +70,331 @@ fn build_cors(state: &AppState) -> CorsLayer {
 fn api_v1_routes() -> Router<AppState> {
     Router::new()
         .route("/chat", post(chat::chat_handler))
-        .route("/chat/jobs/{job_id}", get(chat::get_job_status))
+        .route(
+            "/chat/jobs/{job_id}",
+            get(chat::get_job_status).delete(chat::cancel_job),
+        )
+        .route("/chat/jobs/{job_id}/stream", get(chat::stream_job))
+        .route("/jobs/history", get(chat::list_job_history))
+        .route(
+            "/conversations",
+            get(conversations::list_conversations).post(conversations::create_conversation),
+        )
+        .route(
+            "/conversations/{id}",
+            get(conversations::get_conversation).delete(conversations::delete_conversation),
+        )
+        .route(
+            "/conversations/{id}/annotations",
+            get(annotations::list_annotations).post(annotations::create_annotation),
+        )
+        .route(
+            "/annotations/{id}",
+            axum::routing::delete(annotations::delete_annotation),
+        )
+        .route(
+            "/conversations/{id}/merge",
+            post(conversations::merge_conversation),
+        )
+        .route(
+            "/conversations/{id}/drafts/{draft_id}/approve",
+            post(conversations::approve_draft),
+        )
         .route("/documents", post(documents::create_document))
         .route("/documents", get(documents::list_documents))
+        .route(
+            "/documents/upload",
+            post(documents::upload_document)
+                .layer(DefaultBodyLimit::max(documents::MAX_UPLOAD_BYTES)),
+        )
         .route("/documents/{id}", get(documents::get_document))
+        .route(
+            "/documents/{id}/chunks",
+            get(documents::get_document_chunks),
+        )
+        .route("/documents/{id}/reindex", post(documents::reindex_document))
         .route(
             "/documents/{id}",
             axum::routing::delete(documents::delete_document),
         )
         .route("/documents/search", post(documents::search_documents))
+        .route("/debug/retrieval-diff", post(debug::retrieval_diff))
+        .route("/sources/git", post(sources::sync_git_repo))
+        .route("/sources/crawl", post(sources::crawl_site))
+        .route("/sources/s3/sync", post(sources::sync_s3_bucket))
+        .route(
+            "/admin/collections/active",
+            get(admin::get_active_collection).put(admin::set_active_collection),
+        )
+        .route(
+            "/admin/read-only",
+            get(admin::get_read_only).put(admin::set_read_only),
+        )
+        .route("/admin/retention/report", get(admin::get_retention_report))
+        .route("/admin/freshness/report", get(admin::get_freshness_report))
+        .route("/admin/quarantine", get(admin::list_quarantined))
+        .route(
+            "/admin/quarantine/{document_id}",
+            axum::routing::delete(admin::reject_quarantined),
+        )
+        .route(
+            "/admin/quarantine/{document_id}/approve",
+            post(admin::approve_quarantined),
+        )
+        .route(
+            "/admin/collections/reembed",
+            post(admin::reembed_collection),
+        )
+        .route("/admin/vector-store/repair", post(admin::repair_payloads))
+        .route("/admin/vector-store/export", post(admin::export_collection))
+        .route("/admin/vector-store/import", post(admin::import_collection))
+        .route(
+            "/admin/knowledge-base/snapshot",
+            post(admin::create_snapshot),
+        )
+        .route(
+            "/admin/knowledge-base/snapshots",
+            get(admin::list_snapshots),
+        )
+        .route(
+            "/admin/knowledge-base/snapshots/{name}",
+            get(admin::download_snapshot),
+        )
+        .route("/admin/billing", get(admin::billing_export))
+        .route("/admin/jobs/{job_id}/replay", post(admin::replay_chat_job))
+        .route("/admin/models", get(models::list_models))
+        .route("/admin/models/{name}/rollout", post(models::roll_out_model))
+        .route(
+            "/admin/models/{name}/rollouts",
+            get(models::list_model_rollouts),
+        )
+        .route(
+            "/admin/conversations/{id}/drafts",
+            get(conversations::list_drafts),
+        )
+        .route(
+            "/admin/annotations/export",
+            get(annotations::export_annotations),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use uuid::Uuid;
+
+    use crate::domain::{
+        ports::EmbeddingService, Conversation, Draft, Embedding, Message, MessageRole,
+    };
+
+    struct ConstantEmbedding;
+
+    #[async_trait::async_trait]
+    impl EmbeddingService for ConstantEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Embedding, DomainError> {
+            Ok(Embedding::new(vec![1.0, 0.0]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            Ok(texts
+                .iter()
+                .map(|_| Embedding::new(vec![1.0, 0.0]))
+                .collect())
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    fn fixed_time() -> DateTime<Utc> {
+        "2024-01-01T00:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_chat_responses_wire_format() {
+        insta::assert_json_snapshot!(
+            "chat_response",
+            chat::ChatResponse {
+                job_id: Uuid::from_u128(1),
+                status: "queued".to_string(),
+                estimated_wait_seconds: 12,
+            }
+        );
+        insta::assert_json_snapshot!(
+            "job_status_response",
+            chat::JobStatusResponse {
+                job_id: Uuid::from_u128(1),
+                status: "completed".to_string(),
+                result: Some(serde_json::json!({ "response": "Hi" })),
+                error: None,
+                partial_result: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_document_responses_wire_format() {
+        insta::assert_json_snapshot!(
+            "document_response",
+            documents::DocumentResponse {
+                id: Uuid::from_u128(1),
+                name: "Doc".to_string(),
+                content_type: "text/plain".to_string(),
+                review_by: None,
+                expires_at: None,
+                tags: Vec::new(),
+                created_at: fixed_time(),
+                updated_at: fixed_time(),
+            }
+        );
+        insta::assert_json_snapshot!(
+            "create_document_response",
+            documents::CreateDocumentResponse {
+                document: documents::DocumentResponse {
+                    id: Uuid::from_u128(1),
+                    name: "Doc".to_string(),
+                    content_type: "text/plain".to_string(),
+                    review_by: None,
+                    expires_at: None,
+                    tags: Vec::new(),
+                    created_at: fixed_time(),
+                    updated_at: fixed_time(),
+                },
+                index_job_id: Some(Uuid::from_u128(2)),
+                index_status: "completed".to_string(),
+            }
+        );
+        insta::assert_json_snapshot!(
+            "search_result_response",
+            documents::SearchResultResponse {
+                chunk_id: Uuid::from_u128(1),
+                document_id: Uuid::from_u128(2),
+                content: "chunk".to_string(),
+                score: 0.5,
+                title: None,
+                source: None,
+                freshness: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_conversation_responses_wire_format() {
+        let response = conversations::ConversationResponse {
+            id: Uuid::from_u128(1),
+            messages: vec![
+                Message::new(MessageRole::User, "Hello"),
+                Message::new(MessageRole::Assistant, "Hi"),
+            ],
+            system_prompt: None,
+            metadata: Default::default(),
+            context: Vec::new(),
+            merged: Vec::new(),
+            created_at: fixed_time(),
+            updated_at: fixed_time(),
+        };
+        insta::assert_json_snapshot!("conversation_response", response);
+    }
+
+    #[test]
+    fn test_conversation_response_hides_drafts() {
+        let mut conversation = Conversation::new();
+        conversation.drafts.push(Draft::new(
+            Uuid::new_v4(),
+            "Unreviewed answer",
+            serde_json::json!({}),
+        ));
+
+        let response = conversations::ConversationResponse::from(conversation);
+        let body = serde_json::to_string(&response).unwrap();
+        assert!(!body.contains("Unreviewed answer"));
+    }
+
+    #[test]
+    fn test_requests_reject_unknown_fields() {
+        let chat = serde_json::from_value::<chat::ChatRequest>(serde_json::json!({
+            "message": "Hello",
+            "conversation": "typo",
+        }));
+        let document = serde_json::from_value::<documents::CreateDocumentRequest>(
+            serde_json::json!({ "title": "Doc", "name": "Doc", "content": "..." }),
+        );
+        let search = serde_json::from_value::<documents::SearchDocumentsRequest>(
+            serde_json::json!({ "query": "term", "top_k": 5 }),
+        );
+        let conversation = serde_json::from_value::<conversations::CreateConversationRequest>(
+            serde_json::json!({ "system": "Be brief" }),
+        );
+
+        assert!(chat.is_err());
+        assert!(document.is_err());
+        assert!(search.is_err());
+        assert!(conversation.is_err());
+    }
+
+    #[test]
+    fn test_requests_accept_minimal_bodies() {
+        let chat: chat::ChatRequest =
+            serde_json::from_value(serde_json::json!({ "message": "Hello" })).unwrap();
+        let search: documents::SearchDocumentsRequest =
+            serde_json::from_value(serde_json::json!({ "query": "term" })).unwrap();
+
+        assert_eq!(chat.message, "Hello");
+        assert!(chat.conversation_id.is_none());
+        assert_eq!(search.query, "term");
+        assert!(search.limit.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_reads_the_requested_collection() {
+        use axum::extract::State;
+        use axum::Json;
+        use std::sync::Arc;
+
+        use crate::application::RagService;
+        use crate::domain::DocumentChunk;
+        use crate::infrastructure::{AppConfig, InMemoryVectorStore};
+
+        let rag = |content: &'static str| async move {
+            let rag = RagService::new(
+                Arc::new(ConstantEmbedding),
+                Arc::new(InMemoryVectorStore::new()),
+                5,
+            );
+            rag.index_chunks(&[DocumentChunk::new(Uuid::new_v4(), content, 0)])
+                .await
+                .unwrap();
+            Arc::new(rag)
+        };
+        let pool = crate::api::queue::create_pool("redis://localhost:6379").unwrap();
+        let state = AppState::new(pool, AppConfig::default())
+            .with_rag_service(rag("Default collection chunk.").await)
+            .with_collection_rag_service("archive", rag("Archived chunk.").await);
+        let search = |collection: Option<&str>| {
+            serde_json::from_value::<documents::SearchDocumentsRequest>(serde_json::json!({
+                "query": "chunk",
+                "collection": collection,
+            }))
+            .unwrap()
+        };
+
+        let Json(archived) =
+            documents::search_documents(State(state.clone()), Json(search(Some("archive"))))
+                .await
+                .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].content, "Archived chunk.");
+        let Json(default) = documents::search_documents(State(state.clone()), Json(search(None)))
+            .await
+            .unwrap();
+        assert_eq!(default[0].content, "Default collection chunk.");
+        assert_eq!(
+            documents::search_documents(State(state), Json(search(Some("missing"))))
+                .await
+                .err(),
+            Some(StatusCode::NOT_FOUND)
+        );
+    }
 }
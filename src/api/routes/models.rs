@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::state::AppState;
+use crate::infrastructure::{ModelRelease, ModelRollout};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RollOutRequest {
+    pub version: u32,
+    pub author: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegisteredModel {
+    pub name: String,
+    /// The release serving chats now.
+    pub active_version: Option<u32>,
+    pub releases: Vec<ModelRelease>,
+    /// Agents answering with this model.
+    pub agents: Vec<String>,
+}
+
+/// Every logical model in `model_registry` with its releases and the one live.
+pub async fn list_models(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RegisteredModel>>, StatusCode> {
+    let registry = &state.config.config.model_registry;
+    let mut models = Vec::with_capacity(registry.models.len());
+    for (name, releases) in &registry.models {
+        let active = state.model_releases.active(name).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to read active model release");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let mut releases = releases.clone();
+        releases.sort_by_key(|r| r.version);
+        let mut agents: Vec<String> = registry
+            .agents
+            .iter()
+            .filter(|(_, model)| *model == name)
+            .map(|(agent, _)| agent.clone())
+            .collect();
+        agents.sort();
+
+        models.push(RegisteredModel {
+            name: name.clone(),
+            active_version: registry.resolve(name, active).map(|r| r.version),
+            releases,
+            agents,
+        });
+    }
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(models))
+}
+
+/// Makes a release of `name` live, forward or back. 404 for an unknown
+/// model or version; 400 when the release pins a prompt version missing
+/// from prompts.yaml.
+pub async fn roll_out_model(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(request): Json<RollOutRequest>,
+) -> Result<Json<ModelRollout>, StatusCode> {
+    let registry = &state.config.config.model_registry;
+    let release = registry
+        .release(&name, request.version)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if release.system_prompt(&state.config.prompts).is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let active = state.model_releases.active(&name).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read active model release");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let rollout = ModelRollout {
+        model: name.clone(),
+        version: release.version,
+        previous_version: registry.resolve(&name, active).map(|r| r.version),
+        author: request.author,
+        note: request.note.filter(|n| !n.trim().is_empty()),
+        at: chrono::Utc::now(),
+    };
+
+    state.model_releases.roll_out(&rollout).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to roll out model release");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(rollout))
+}
+
+/// Rollouts of `name`, newest first.
+pub async fn list_model_rollouts(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<ModelRollout>>, StatusCode> {
+    if !state
+        .config
+        .config
+        .model_registry
+        .models
+        .contains_key(&name)
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let rollouts = state.model_releases.rollouts(&name).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read model rollouts");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(rollouts))
+}
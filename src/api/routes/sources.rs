@@ -0,0 +1,158 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::infrastructure::{CrawlSiteJob, GitConnector, S3SyncJob, SyncGitRepoJob};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncGitRepoRequest {
+    pub url: String,
+    pub branch: Option<String>,
+    /// Files to index, e.g. `docs/**/*.md`; defaults to `git.default_globs`.
+    #[serde(default)]
+    pub globs: Vec<String>,
+    /// Re-index every matching file instead of only those changed since the last sync.
+    #[serde(default)]
+    pub full: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncGitRepoResponse {
+    pub job_id: Uuid,
+    pub status: String,
+}
+
+/// Queues a (re)sync of a git repository into the knowledge base.
+pub async fn sync_git_repo(
+    State(state): State<AppState>,
+    Json(request): Json<SyncGitRepoRequest>,
+) -> Result<Json<SyncGitRepoResponse>, StatusCode> {
+    if GitConnector::validate_url(&request.url).is_err()
+        || request
+            .branch
+            .as_deref()
+            .is_some_and(|b| b.starts_with('-'))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut job = SyncGitRepoJob::new(&request.url).with_globs(request.globs);
+    if let Some(branch) = request.branch {
+        job = job.with_branch(branch);
+    }
+    if request.full {
+        job = job.full();
+    }
+
+    let job_id = state
+        .job_producer
+        .push_git_sync_job(&job)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue git sync job");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(SyncGitRepoResponse {
+        job_id,
+        status: "queued".to_string(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrawlSiteRequest {
+    /// A page or a sitemap; only links on the same host are followed.
+    pub url: String,
+    /// Defaults to, and is capped at, `crawl.max_depth`.
+    pub max_depth: Option<usize>,
+    /// Defaults to, and is capped at, `crawl.max_pages`.
+    pub max_pages: Option<usize>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrawlSiteResponse {
+    pub job_id: Uuid,
+    pub status: String,
+}
+
+/// Queues a crawl of a website into the knowledge base; progress is
+/// reported in the job status while it runs.
+pub async fn crawl_site(
+    State(state): State<AppState>,
+    Json(request): Json<CrawlSiteRequest>,
+) -> Result<Json<CrawlSiteResponse>, StatusCode> {
+    if request.url.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let limits = &state.config.config.crawl;
+    let job = CrawlSiteJob::new(&request.url)
+        .with_limits(
+            request.max_depth.map(|d| d.min(limits.max_depth)),
+            request.max_pages.map(|p| p.min(limits.max_pages)),
+        )
+        .with_tags(request.tags);
+
+    let job_id = state.job_producer.push_crawl_job(&job).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to queue crawl job");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(CrawlSiteResponse {
+        job_id,
+        status: "queued".to_string(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncS3Request {
+    pub bucket: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Re-embed every object instead of only those changed since the last sync.
+    #[serde(default)]
+    pub full: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncS3Response {
+    pub job_id: Uuid,
+    pub status: String,
+}
+
+/// Queues a sync of an S3 bucket prefix; supported objects are queued as embed jobs.
+pub async fn sync_s3_bucket(
+    State(state): State<AppState>,
+    Json(request): Json<SyncS3Request>,
+) -> Result<Json<SyncS3Response>, StatusCode> {
+    if request.bucket.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut job = S3SyncJob::new(&request.bucket, request.prefix).with_tags(request.tags);
+    if request.full {
+        job = job.full();
+    }
+
+    let job_id = state
+        .job_producer
+        .push_s3_sync_job(&job)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to queue S3 sync job");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(SyncS3Response {
+        job_id,
+        status: "queued".to_string(),
+    }))
+}
@@ -1,28 +1,72 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::api::annotations::AnnotationStore;
+use crate::api::collections::CollectionRegistry;
+use crate::api::conversations::ConversationStore;
+use crate::api::models::ModelReleaseStore;
 use crate::api::queue::{JobProducer, RedisPool};
+use crate::api::read_only_switch::ReadOnlySwitch;
 use crate::application::{DocumentService, RagService};
-use crate::infrastructure::AppConfig;
+use crate::infrastructure::{
+    chunker_from_config, document_store_from_config, AppConfig, ExtractorRegistry,
+    PostgresJobHistory, QdrantSnapshots, VectorStoreHealth,
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub redis_pool: RedisPool,
     pub job_producer: JobProducer,
+    pub conversation_store: ConversationStore,
+    pub annotation_store: AnnotationStore,
+    pub model_releases: ModelReleaseStore,
+    pub collection_registry: CollectionRegistry,
+    pub read_only: ReadOnlySwitch,
     pub document_service: Option<Arc<DocumentService>>,
     pub rag_service: Option<Arc<RagService>>,
+    pub collection_rag_services: HashMap<String, Arc<RagService>>,
+    pub extractors: ExtractorRegistry,
+    /// Finished-job records in Postgres, read by the billing export.
+    pub job_history: Option<PostgresJobHistory>,
+    /// Qdrant snapshot access for the admin endpoints; unset on other backends.
+    pub snapshots: Option<QdrantSnapshots>,
+    /// Vector store check for `/ready`; skipped when unset.
+    pub vector_store_health: Option<VectorStoreHealth>,
     pub config: Arc<AppConfig>,
 }
 
 impl AppState {
     pub fn new(redis_pool: RedisPool, config: AppConfig) -> Self {
         let config = Arc::new(config);
-        let job_producer =
-            JobProducer::new(redis_pool.clone(), config.config.worker.result_ttl_seconds);
+        let job_producer = JobProducer::new(redis_pool.clone(), config.config.worker.clone());
+        let conversation_store = ConversationStore::new(redis_pool.clone());
+        let annotation_store = AnnotationStore::new(redis_pool.clone());
+        let model_releases = ModelReleaseStore::new(redis_pool.clone());
+        let collection_registry =
+            CollectionRegistry::new(redis_pool.clone(), &config.config.vector_store.collection);
+        let read_only = ReadOnlySwitch::new(redis_pool.clone(), config.config.api.read_only);
+        let document_service =
+            document_store_from_config(&config.config.document_store).map(|store| {
+                Arc::new(DocumentService::new(
+                    store,
+                    chunker_from_config(&config.config.rag),
+                ))
+            });
         Self {
             redis_pool,
             job_producer,
-            document_service: None,
+            conversation_store,
+            annotation_store,
+            model_releases,
+            collection_registry,
+            read_only,
+            document_service,
             rag_service: None,
+            collection_rag_services: HashMap::new(),
+            extractors: ExtractorRegistry::default(),
+            job_history: None,
+            snapshots: None,
+            vector_store_health: None,
             config,
         }
     }
@@ -32,8 +76,57 @@ impl AppState {
         self
     }
 
+    pub fn with_job_history(mut self, history: PostgresJobHistory) -> Self {
+        self.job_history = Some(history);
+        self
+    }
+
+    pub fn with_vector_store_health(mut self, health: VectorStoreHealth) -> Self {
+        self.vector_store_health = Some(health);
+        self
+    }
+
+    pub fn with_snapshots(mut self, snapshots: QdrantSnapshots) -> Self {
+        self.snapshots = Some(snapshots);
+        self
+    }
+
     pub fn with_rag_service(mut self, service: Arc<RagService>) -> Self {
         self.rag_service = Some(service);
         self
     }
+
+    /// Registers a RAG service for a collection other than the default one.
+    pub fn with_collection_rag_service(
+        mut self,
+        collection: impl Into<String>,
+        service: Arc<RagService>,
+    ) -> Self {
+        self.collection_rag_services
+            .insert(collection.into(), service);
+        self
+    }
+
+    /// Registers document search per collection, the configured
+    /// `vector_store.collection` as the default one.
+    pub fn with_search_services(mut self, services: HashMap<String, Arc<RagService>>) -> Self {
+        for (collection, service) in services {
+            self = if collection == self.config.config.vector_store.collection {
+                self.with_rag_service(service)
+            } else {
+                self.with_collection_rag_service(collection, service)
+            };
+        }
+        self
+    }
+
+    /// Resolves the RAG service for a collection, `None` meaning the default one.
+    pub fn rag_service_for(&self, collection: Option<&str>) -> Option<Arc<RagService>> {
+        match collection {
+            Some(name) if name != self.config.config.vector_store.collection => {
+                self.collection_rag_services.get(name).cloned()
+            }
+            _ => self.rag_service.clone(),
+        }
+    }
 }
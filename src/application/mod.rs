@@ -6,4 +6,14 @@
 
 pub mod services;
 
-pub use services::{DocumentService, RagService};
+pub use services::{
+    apply_stale_policy, collapse_by_document, content_hash, diff_results, export_points,
+    import_points, is_valid_dump_file, maximal_marginal_relevance, migrate_points, month_bounds,
+    normalize_scores, reciprocal_rank_fusion, score_fusion, scrub_pii, search_rag_services,
+    warm_query_cache, BillingRates, Confidence, ConfidenceLevel, ConfidenceScorer,
+    ConfidenceWeights, DocumentService, FreshnessReport, Ingested, Invoice, InvoiceLine,
+    MigrationReport, PayloadRepairReport, QueryEmbeddingCache, QueryTransform, RagService,
+    RankChange, RetentionAction, RetentionDecision, RetentionPolicy, RetentionReport,
+    RetentionRule, RetrievalDiff, RetrievalOptions, RetrievalPath, RetrievalStrategy,
+    RetrievalTimings, ScoreNormalization, StaleDocument, StalePolicy, TenantUsage,
+};
@@ -0,0 +1,183 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+
+/// Unit prices applied to usage in billing exports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BillingRates {
+    pub currency: String,
+    pub per_1k_tokens: f64,
+    pub per_message: f64,
+    /// Per GB (10^9 bytes) of document content ingested in the month.
+    pub per_gb_stored: f64,
+}
+
+impl Default for BillingRates {
+    fn default() -> Self {
+        Self {
+            currency: "USD".to_string(),
+            per_1k_tokens: 0.0,
+            per_message: 0.0,
+            per_gb_stored: 0.0,
+        }
+    }
+}
+
+/// A tenant's usage over a billing period; `tenant_id` is `None` for jobs
+/// that were not attributed to a tenant.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TenantUsage {
+    pub tenant_id: Option<String>,
+    pub tokens: u64,
+    /// Completed chat jobs.
+    pub messages: u64,
+    pub stored_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvoiceLine {
+    #[serde(flatten)]
+    pub usage: TenantUsage,
+    pub token_cost: f64,
+    pub message_cost: f64,
+    pub storage_cost: f64,
+    pub total: f64,
+}
+
+/// Per-tenant charges for one month, amounts rounded to cents.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Invoice {
+    /// `YYYY-MM`.
+    pub month: String,
+    pub currency: String,
+    pub lines: Vec<InvoiceLine>,
+    pub total: f64,
+}
+
+impl Invoice {
+    pub fn new(month: impl Into<String>, usage: Vec<TenantUsage>, rates: &BillingRates) -> Self {
+        let mut lines: Vec<InvoiceLine> = usage
+            .into_iter()
+            .map(|usage| {
+                let token_cost = cents(usage.tokens as f64 / 1000.0 * rates.per_1k_tokens);
+                let message_cost = cents(usage.messages as f64 * rates.per_message);
+                let storage_cost =
+                    cents(usage.stored_bytes as f64 / BYTES_PER_GB * rates.per_gb_stored);
+                InvoiceLine {
+                    usage,
+                    token_cost,
+                    message_cost,
+                    storage_cost,
+                    total: cents(token_cost + message_cost + storage_cost),
+                }
+            })
+            .collect();
+        lines.sort_by(|a, b| a.usage.tenant_id.cmp(&b.usage.tenant_id));
+
+        let total = cents(lines.iter().map(|line| line.total).sum());
+        Self {
+            month: month.into(),
+            currency: rates.currency.clone(),
+            lines,
+            total,
+        }
+    }
+
+    /// One row per tenant, with a header row; unattributed usage has an
+    /// empty `tenant_id`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "month,tenant_id,tokens,messages,stored_bytes,token_cost,message_cost,storage_cost,total,currency\n",
+        );
+        for line in &self.lines {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.2},{:.2},{:.2},{:.2},{}\n",
+                self.month,
+                csv_field(line.usage.tenant_id.as_deref().unwrap_or_default()),
+                line.usage.tokens,
+                line.usage.messages,
+                line.usage.stored_bytes,
+                line.token_cost,
+                line.message_cost,
+                line.storage_cost,
+                line.total,
+                csv_field(&self.currency),
+            ));
+        }
+        csv
+    }
+}
+
+/// Start (inclusive) and end (exclusive) of a `YYYY-MM` month in UTC.
+pub fn month_bounds(month: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let (year, month) = month.split_once('-')?;
+    let start = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)?;
+    let end = match start.month() {
+        12 => NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)?,
+        m => NaiveDate::from_ymd_opt(start.year(), m + 1, 1)?,
+    };
+    Some((
+        start.and_hms_opt(0, 0, 0)?.and_utc(),
+        end.and_hms_opt(0, 0, 0)?.and_utc(),
+    ))
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invoice_prices_usage_per_tenant() {
+        let rates = BillingRates {
+            currency: "EUR".to_string(),
+            per_1k_tokens: 0.002,
+            per_message: 0.01,
+            per_gb_stored: 0.5,
+        };
+        let usage = vec![
+            TenantUsage {
+                tenant_id: Some("globex, inc".to_string()),
+                tokens: 0,
+                messages: 0,
+                stored_bytes: 3_000_000_000,
+            },
+            TenantUsage {
+                tenant_id: Some("acme".to_string()),
+                tokens: 250_000,
+                messages: 120,
+                stored_bytes: 0,
+            },
+        ];
+
+        let invoice = Invoice::new("2026-09", usage, &rates);
+        assert_eq!(invoice.lines[0].usage.tenant_id.as_deref(), Some("acme"));
+        assert_eq!(invoice.lines[0].total, 1.7);
+        assert_eq!(invoice.lines[1].storage_cost, 1.5);
+        assert_eq!(invoice.total, 3.2);
+
+        let csv = invoice.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], "2026-09,acme,250000,120,0,0.50,1.20,0.00,1.70,EUR");
+        assert!(rows[2].starts_with("2026-09,\"globex, inc\",0,0,3000000000,"));
+
+        let (start, end) = month_bounds("2026-12").unwrap();
+        assert_eq!(start.to_rfc3339(), "2026-12-01T00:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2027-01-01T00:00:00+00:00");
+        assert!(month_bounds("2026-13").is_none());
+    }
+}
@@ -0,0 +1,102 @@
+use futures::future::join_all;
+
+use crate::domain::{ports::LlmService, DocumentChunk};
+
+const TITLE_SYSTEM_PROMPT: &str = "Write a short, descriptive title for the passage, \
+at most 10 words. Reply with only the title.";
+
+/// Titles requested from the LLM at once.
+const TITLE_CONCURRENCY: usize = 4;
+
+/// Sets a short LLM-written title on each chunk that has none, cut to
+/// `max_chars` characters.
+///
+/// Titling is best effort: chunks whose title request fails or comes back
+/// empty are left untitled. Returns the number of chunks titled.
+pub async fn title_chunks(
+    llm: &dyn LlmService,
+    chunks: &mut [DocumentChunk],
+    max_chars: usize,
+) -> usize {
+    let untitled: Vec<usize> = (0..chunks.len())
+        .filter(|&i| chunks[i].metadata.title.is_none())
+        .collect();
+
+    let mut titled = 0;
+    for batch in untitled.chunks(TITLE_CONCURRENCY) {
+        let requests = batch
+            .iter()
+            .map(|&i| llm.complete_with_system(TITLE_SYSTEM_PROMPT, &chunks[i].content));
+        let replies = join_all(requests).await;
+
+        for (&i, reply) in batch.iter().zip(replies) {
+            match reply {
+                Ok(reply) => {
+                    chunks[i].metadata.title = clean_title(&reply, max_chars);
+                    titled += usize::from(chunks[i].metadata.title.is_some());
+                }
+                Err(e) => {
+                    tracing::warn!(chunk_id = %chunks[i].id, error = %e, "chunk title failed")
+                }
+            }
+        }
+    }
+    titled
+}
+
+/// First line of the reply without quotes or a trailing period.
+fn clean_title(reply: &str, max_chars: usize) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .trim_start_matches(['#', '*'])
+        .trim_matches(['"', '\'', '*', '`'])
+        .trim_end_matches('.')
+        .trim();
+    let title: String = line.chars().take(max_chars).collect();
+    let title = title.trim_end().to_string();
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::DomainError;
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    struct EchoLlm;
+
+    #[async_trait]
+    impl LlmService for EchoLlm {
+        async fn complete(&self, prompt: &str) -> Result<String, DomainError> {
+            Ok(prompt.to_string())
+        }
+
+        async fn complete_with_system(
+            &self,
+            _system: &str,
+            prompt: &str,
+        ) -> Result<String, DomainError> {
+            if prompt.is_empty() {
+                return Err(DomainError::external("empty prompt"));
+            }
+            Ok(format!("\"{prompt}.\"\nextra"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_title_chunks_is_best_effort_and_keeps_existing_titles() {
+        let document_id = Uuid::new_v4();
+        let mut chunks = vec![
+            DocumentChunk::new(document_id, "Installing the agent on Linux", 0),
+            DocumentChunk::new(document_id, "", 1),
+            DocumentChunk::new(document_id, "Configuration", 2),
+        ];
+        chunks[2].metadata.title = Some("Existing".to_string());
+
+        assert_eq!(title_chunks(&EchoLlm, &mut chunks, 14).await, 1);
+        assert_eq!(chunks[0].metadata.title.as_deref(), Some("Installing the"));
+        assert_eq!(chunks[1].metadata.title, None);
+        assert_eq!(chunks[2].metadata.title.as_deref(), Some("Existing"));
+    }
+}
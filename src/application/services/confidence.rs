@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::domain::{ports::LlmService, SearchResult};
+
+const JUDGE_SYSTEM_PROMPT: &str = "You review answers written by a support assistant. \
+Given the question, the retrieved passages and the answer, rate from 0 to 10 how well \
+every claim in the answer is supported by the passages (\"grounded\") and how confident \
+you are that the answer is correct and complete (\"confidence\"). Reply with only a JSON \
+object like {\"grounded\": 7, \"confidence\": 8}.";
+
+/// Passages shown to the judge, to bound the prompt size.
+const JUDGE_MAX_PASSAGES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfidenceLevel {
+    /// Clients may show a "verified" badge.
+    High,
+    Medium,
+    /// Candidates for human review.
+    Low,
+}
+
+/// Relative weight of each confidence signal; missing signals are left out
+/// and the remaining weights rescaled.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceWeights {
+    pub retrieval: f32,
+    pub groundedness: f32,
+    pub self_rating: f32,
+}
+
+impl Default for ConfidenceWeights {
+    fn default() -> Self {
+        Self {
+            retrieval: 0.4,
+            groundedness: 0.4,
+            self_rating: 0.2,
+        }
+    }
+}
+
+/// Estimated reliability of an answer, each signal scaled to 0..=1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Confidence {
+    pub score: f32,
+    pub level: ConfidenceLevel,
+    /// Best score among the chunks the answer was based on.
+    pub retrieval: Option<f32>,
+    /// How well the answer is supported by those chunks, judged by the LLM.
+    pub groundedness: Option<f32>,
+    /// The LLM's own rating of the answer.
+    pub self_rating: Option<f32>,
+}
+
+pub struct ConfidenceScorer {
+    llm: Option<Arc<dyn LlmService>>,
+    weights: ConfidenceWeights,
+    high_threshold: f32,
+    low_threshold: f32,
+}
+
+impl ConfidenceScorer {
+    /// Scores answers from retrieval alone until [`with_judge`](Self::with_judge) is set.
+    pub fn new(weights: ConfidenceWeights, high_threshold: f32, low_threshold: f32) -> Self {
+        Self {
+            llm: None,
+            weights,
+            high_threshold,
+            low_threshold,
+        }
+    }
+
+    /// Has `llm` rate groundedness and its confidence in each answer.
+    pub fn with_judge(mut self, llm: Arc<dyn LlmService>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    pub async fn score(
+        &self,
+        question: &str,
+        answer: &str,
+        retrieved: &[SearchResult],
+    ) -> Confidence {
+        let retrieval = retrieved
+            .iter()
+            .map(|r| r.score.clamp(0.0, 1.0))
+            .reduce(f32::max);
+        let (groundedness, self_rating) = match &self.llm {
+            Some(llm) => judge(llm.as_ref(), question, answer, retrieved).await,
+            None => (None, None),
+        };
+
+        self.combine(retrieval, groundedness, self_rating)
+    }
+
+    /// Weighted mean of the available signals; no signals at all is zero confidence.
+    pub fn combine(
+        &self,
+        retrieval: Option<f32>,
+        groundedness: Option<f32>,
+        self_rating: Option<f32>,
+    ) -> Confidence {
+        let signals = [
+            (retrieval, self.weights.retrieval),
+            (groundedness, self.weights.groundedness),
+            (self_rating, self.weights.self_rating),
+        ];
+        let (total, weight) = signals
+            .iter()
+            .filter_map(|(value, weight)| value.map(|v| (v * weight, *weight)))
+            .fold((0.0, 0.0), |(total, sum), (v, w)| (total + v, sum + w));
+        let score = if weight > 0.0 { total / weight } else { 0.0 };
+
+        let level = if score >= self.high_threshold {
+            ConfidenceLevel::High
+        } else if score < self.low_threshold {
+            ConfidenceLevel::Low
+        } else {
+            ConfidenceLevel::Medium
+        };
+
+        Confidence {
+            score,
+            level,
+            retrieval,
+            groundedness,
+            self_rating,
+        }
+    }
+}
+
+/// Groundedness and self-rating from one LLM call; both `None` if it fails.
+async fn judge(
+    llm: &dyn LlmService,
+    question: &str,
+    answer: &str,
+    retrieved: &[SearchResult],
+) -> (Option<f32>, Option<f32>) {
+    let passages: Vec<String> = retrieved
+        .iter()
+        .take(JUDGE_MAX_PASSAGES)
+        .enumerate()
+        .map(|(i, r)| format!("[{}] {}", i + 1, r.chunk.content))
+        .collect();
+    let passages = if passages.is_empty() {
+        "(none)".to_string()
+    } else {
+        passages.join("\n\n")
+    };
+    let prompt = format!("Question: {question}\n\nPassages:\n{passages}\n\nAnswer:\n{answer}");
+
+    match llm.complete_with_system(JUDGE_SYSTEM_PROMPT, &prompt).await {
+        Ok(reply) => parse_judgement(&reply).unwrap_or_else(|| {
+            tracing::warn!(reply = %reply, "unparseable confidence judgement");
+            (None, None)
+        }),
+        Err(e) => {
+            tracing::warn!(error = %e, "confidence judgement failed");
+            (None, None)
+        }
+    }
+}
+
+/// Reads `{"grounded": n, "confidence": n}` (0-10) from a reply, scaled to 0..=1.
+fn parse_judgement(reply: &str) -> Option<(Option<f32>, Option<f32>)> {
+    #[derive(Deserialize)]
+    struct Judgement {
+        grounded: Option<f32>,
+        confidence: Option<f32>,
+    }
+
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    let judgement: Judgement = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let scale = |v: f32| v.clamp(0.0, 10.0) / 10.0;
+    Some((
+        judgement.grounded.map(scale),
+        judgement.confidence.map(scale),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_rescales_missing_signals() {
+        let scorer = ConfidenceScorer::new(ConfidenceWeights::default(), 0.75, 0.4);
+
+        let full = scorer.combine(Some(0.9), Some(0.8), Some(1.0));
+        assert!((full.score - 0.88).abs() < 1e-6);
+        assert_eq!(full.level, ConfidenceLevel::High);
+
+        let retrieval_only = scorer.combine(Some(0.5), None, None);
+        assert_eq!(retrieval_only.score, 0.5);
+        assert_eq!(retrieval_only.level, ConfidenceLevel::Medium);
+
+        assert_eq!(scorer.combine(None, None, None).level, ConfidenceLevel::Low);
+    }
+
+    #[test]
+    fn test_parse_judgement() {
+        assert_eq!(
+            parse_judgement("```json\n{\"grounded\": 7, \"confidence\": 12}\n```"),
+            Some((Some(0.7), Some(1.0)))
+        );
+        assert_eq!(parse_judgement("no idea"), None);
+    }
+}
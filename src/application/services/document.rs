@@ -1,24 +1,41 @@
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::domain::{chunk_content, ports::DocumentStore, Document, DocumentChunk, DomainError};
+use crate::domain::{
+    ports::{ChunkingStrategy, DocumentStore},
+    Document, DocumentChunk, DomainError, ExtractedPage,
+};
+
+/// Outcome of storing a document.
+#[derive(Debug, Clone)]
+pub struct Ingested {
+    pub document: Document,
+    pub chunks: Vec<DocumentChunk>,
+    /// Identical content was already stored; `document` is that earlier
+    /// document and nothing new was saved.
+    pub duplicate: bool,
+}
+
+/// Hex SHA-256 of a document's text, pages joined by blank lines.
+pub fn content_hash(pages: &[ExtractedPage]) -> String {
+    let text = pages
+        .iter()
+        .map(|p| p.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
 
 pub struct DocumentService {
     store: Arc<dyn DocumentStore>,
-    chunk_size: usize,
+    chunker: Arc<dyn ChunkingStrategy>,
 }
 
 impl DocumentService {
-    pub fn new(store: Arc<dyn DocumentStore>) -> Self {
-        Self {
-            store,
-            chunk_size: 1000,
-        }
-    }
-
-    pub fn with_chunk_size(store: Arc<dyn DocumentStore>, chunk_size: usize) -> Self {
-        Self { store, chunk_size }
+    pub fn new(store: Arc<dyn DocumentStore>, chunker: Arc<dyn ChunkingStrategy>) -> Self {
+        Self { store, chunker }
     }
 
     #[instrument(skip(self, content), fields(name))]
@@ -26,16 +43,101 @@ impl DocumentService {
         &self,
         name: &str,
         content: &str,
-    ) -> Result<(Document, Vec<DocumentChunk>), DomainError> {
-        let doc = Document::new(name);
+        tenant_id: Option<&str>,
+        dedup: bool,
+    ) -> Result<Ingested, DomainError> {
+        self.ingest_pages(
+            name,
+            "text/plain",
+            &[ExtractedPage::new(None, content)],
+            tenant_id,
+            dedup,
+        )
+        .await
+    }
+
+    /// Ingests content already run through a `ContentExtractor`, keeping page numbers.
+    ///
+    /// With `dedup`, content identical to a stored document of the same
+    /// tenant is not stored again. Documents stored without it are neither matched nor hashed, for
+    /// copies that must stay separate such as conversation attachments.
+    #[instrument(skip(self, pages), fields(name))]
+    pub async fn ingest_pages(
+        &self,
+        name: &str,
+        content_type: &str,
+        pages: &[ExtractedPage],
+        tenant_id: Option<&str>,
+        dedup: bool,
+    ) -> Result<Ingested, DomainError> {
+        let mut doc = Document::new(name)
+            .with_content_type(content_type)
+            .with_tenant(tenant_id.map(str::to_string));
+        if dedup {
+            let hash = content_hash(pages);
+            if let Some(existing) = self.find_duplicate(&hash, tenant_id).await? {
+                return Ok(existing);
+            }
+            doc.content_hash = Some(hash);
+        }
         self.store.save_document(&doc).await?;
+        self.store.save_content(doc.id, pages).await?;
 
-        let chunks = chunk_content(doc.id, content, self.chunk_size);
+        let chunks = self.chunker.chunk_pages(doc.id, pages);
         if !chunks.is_empty() {
             self.store.save_chunks(&chunks).await?;
         }
 
-        Ok((doc, chunks))
+        Ok(Ingested {
+            document: doc,
+            chunks,
+            duplicate: false,
+        })
+    }
+
+    /// Stores a document record without chunking it, for content the worker
+    /// chunks and embeds in the background. `dedup` works as in
+    /// [`ingest_pages`](Self::ingest_pages).
+    #[instrument(skip(self, doc, content), fields(id = %doc.id))]
+    pub async fn register(
+        &self,
+        mut doc: Document,
+        content: &str,
+        dedup: bool,
+    ) -> Result<Ingested, DomainError> {
+        if dedup {
+            let hash = content_hash(&[ExtractedPage::new(None, content)]);
+            if let Some(existing) = self.find_duplicate(&hash, doc.tenant_id.as_deref()).await? {
+                return Ok(existing);
+            }
+            doc.content_hash = Some(hash);
+        }
+        self.store.save_document(&doc).await?;
+        self.store
+            .save_content(doc.id, &[ExtractedPage::new(None, content)])
+            .await?;
+        Ok(Ingested {
+            document: doc,
+            chunks: Vec::new(),
+            duplicate: false,
+        })
+    }
+
+    async fn find_duplicate(
+        &self,
+        hash: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<Ingested>, DomainError> {
+        let Some(document) = self.store.find_by_content_hash(hash, tenant_id).await? else {
+            return Ok(None);
+        };
+        tracing::info!(document_id = %document.id, "skipping duplicate document");
+        let chunks = self.store.get_chunks(document.id).await?;
+        Ok(Some(Ingested {
+            document,
+            chunks,
+            duplicate: true,
+        }))
     }
 
     #[instrument(skip(self))]
@@ -57,8 +159,103 @@ impl DocumentService {
         }
     }
 
+    /// Saves changes to a document's record, such as tags set after ingestion.
+    #[instrument(skip(self, doc), fields(id = %doc.id))]
+    pub async fn update(&self, doc: &Document) -> Result<(), DomainError> {
+        self.store.save_document(doc).await
+    }
+
+    /// Re-chunks a document's stored content with the current chunker,
+    /// replacing its stored chunks; `None` if the document or its content
+    /// isn't stored.
+    ///
+    /// Chunks carry the document's content type, tags, freshness dates and tenant.
+    #[instrument(skip(self))]
+    pub async fn rechunk(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<(Document, Vec<DocumentChunk>)>, DomainError> {
+        let Some(doc) = self.store.get_document(id).await? else {
+            return Ok(None);
+        };
+        let Some(pages) = self.store.get_content(id).await? else {
+            return Ok(None);
+        };
+
+        let mut chunks = self.chunker.chunk_pages(id, &pages);
+        for chunk in &mut chunks {
+            chunk.metadata.content_type = Some(doc.content_type.clone());
+            chunk.metadata.tags = doc.tags.clone();
+            chunk.metadata.review_by = doc.review_by;
+            chunk.metadata.expires_at = doc.expires_at;
+            chunk.metadata.tenant_id = doc.tenant_id.clone();
+        }
+        self.store.replace_chunks(id, &chunks).await?;
+        Ok(Some((doc, chunks)))
+    }
+
     #[instrument(skip(self))]
     pub async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
         self.store.delete_document(id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::{InMemoryDocumentStore, ParagraphChunker};
+
+    #[tokio::test]
+    async fn test_ingest_get_and_delete() {
+        let service = DocumentService::new(
+            Arc::new(InMemoryDocumentStore::new()),
+            Arc::new(ParagraphChunker::new(20)),
+        );
+
+        let content = "First paragraph.\n\nSecond paragraph.";
+        let Ingested {
+            document: doc,
+            chunks,
+            ..
+        } = service.ingest("Guide", content, None, true).await.unwrap();
+        assert_eq!(chunks.len(), 2);
+
+        let (stored, stored_chunks) = service.get_with_chunks(doc.id).await.unwrap().unwrap();
+        assert_eq!(stored.name, "Guide");
+        assert_eq!(stored_chunks[1].content, chunks[1].content);
+
+        let again = service
+            .ingest("Guide (copy)", content, None, true)
+            .await
+            .unwrap();
+        assert!(again.duplicate);
+        assert_eq!(again.document.id, doc.id);
+        assert_eq!(again.chunks.len(), 2);
+
+        let copy = service
+            .ingest("Guide (copy)", content, None, false)
+            .await
+            .unwrap();
+        assert!(!copy.duplicate);
+        assert_ne!(copy.document.id, doc.id);
+
+        let tenant_copy = service
+            .ingest("Guide", content, Some("acme"), true)
+            .await
+            .unwrap();
+        assert!(!tenant_copy.duplicate);
+        assert_eq!(tenant_copy.document.tenant_id.as_deref(), Some("acme"));
+
+        let (_, rechunked) =
+            DocumentService::new(service.store.clone(), Arc::new(ParagraphChunker::new(1000)))
+                .rechunk(doc.id)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(rechunked.len(), 1);
+        assert_eq!(service.store.get_chunks(doc.id).await.unwrap().len(), 1);
+
+        service.delete(doc.id).await.unwrap();
+        assert!(service.get_with_chunks(doc.id).await.unwrap().is_none());
+    }
+}
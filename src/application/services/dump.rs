@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::domain::{ports::VectorStore, DocumentChunk, DomainError, Embedding};
+
+/// One line of a knowledge-base dump: a chunk with its payload and vector.
+#[derive(Debug, Serialize, Deserialize)]
+struct DumpedPoint {
+    chunk: DocumentChunk,
+    vector: Vec<f32>,
+}
+
+/// Writes every point of `store` to `writer` as JSON lines, scrolling in
+/// pages of `page_size`. Returns the number of points written.
+pub async fn export_points<W>(
+    store: &dyn VectorStore,
+    writer: &mut W,
+    page_size: usize,
+) -> Result<usize, DomainError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut exported = 0;
+    let mut offset = None;
+
+    loop {
+        let page = store.scroll_points(offset, page_size).await?;
+        for (chunk, embedding) in page.points {
+            let point = DumpedPoint {
+                chunk,
+                vector: embedding.into_inner(),
+            };
+            let mut line = serde_json::to_vec(&point)
+                .map_err(|e| DomainError::internal(format!("Failed to encode point: {e}")))?;
+            line.push(b'\n');
+            writer.write_all(&line).await.map_err(write_error)?;
+            exported += 1;
+        }
+        tracing::debug!(exported, "exported page");
+
+        match page.next {
+            Some(next) => offset = Some(next),
+            None => break,
+        }
+    }
+
+    writer.flush().await.map_err(write_error)?;
+    Ok(exported)
+}
+
+/// Upserts every point of a dump written by [`export_points`] into `store`,
+/// keeping chunk ids, so re-importing the same dump is idempotent. Blank
+/// lines are skipped; a malformed line fails the import with its line number.
+pub async fn import_points<R>(store: &dyn VectorStore, reader: R) -> Result<usize, DomainError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut lines = reader.lines();
+    let mut imported = 0;
+    let mut line_number = 0;
+
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| DomainError::external(format!("Failed to read dump: {e}")))?
+    {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let point: DumpedPoint = serde_json::from_str(&line).map_err(|e| {
+            DomainError::validation(format!("Malformed point on line {line_number}: {e}"))
+        })?;
+        store
+            .upsert(&point.chunk, &Embedding::new(point.vector))
+            .await?;
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+/// Whether `name` can name a dump file: a plain file name, so requests can't
+/// reach outside the dump directory.
+pub fn is_valid_dump_file(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn write_error(e: std::io::Error) -> DomainError {
+    DomainError::external(format!("Failed to write dump: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::InMemoryVectorStore;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_export_then_import_restores_points() {
+        let source = InMemoryVectorStore::new();
+        let document_id = Uuid::new_v4();
+        for i in 0..3 {
+            let chunk = DocumentChunk::new(document_id, format!("chunk {i}"), i);
+            source
+                .upsert(&chunk, &Embedding::new(vec![i as f32, 1.0]))
+                .await
+                .unwrap();
+        }
+
+        let mut dump = Vec::new();
+        assert_eq!(export_points(&source, &mut dump, 2).await.unwrap(), 3);
+
+        let target = InMemoryVectorStore::new();
+        assert_eq!(import_points(&target, &dump[..]).await.unwrap(), 3);
+        let mut chunks = target.list_chunks().await.unwrap();
+        chunks.sort_by_key(|c| c.chunk_index);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].content, "chunk 2");
+
+        let err = import_points(&target, &b"\n{\"chunk\": 1}\n"[..])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+
+        assert!(is_valid_dump_file("kb-2026-10-16.jsonl"));
+        assert!(!is_valid_dump_file("../etc/passwd"));
+    }
+}
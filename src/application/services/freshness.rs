@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::domain::{DocumentChunk, Freshness, SearchResult};
+
+/// Score multiplier for expired chunks under [`StalePolicy::Demote`].
+const DEMOTED_SCORE_FACTOR: f32 = 0.5;
+
+/// What retrieval does with chunks whose document has expired.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StalePolicy {
+    /// Keep them in place; the context sent to the model flags them.
+    #[default]
+    Annotate,
+    /// Halve their score so current content ranks first.
+    Demote,
+    /// Drop them from results.
+    Exclude,
+}
+
+/// Applies `policy` to expired results, keeping them sorted by score.
+pub fn apply_stale_policy(
+    results: Vec<SearchResult>,
+    policy: StalePolicy,
+    now: DateTime<Utc>,
+) -> Vec<SearchResult> {
+    let expired = |r: &SearchResult| r.chunk.metadata.freshness(now) == Freshness::Expired;
+
+    match policy {
+        StalePolicy::Annotate => results,
+        StalePolicy::Exclude => results.into_iter().filter(|r| !expired(r)).collect(),
+        StalePolicy::Demote => {
+            let mut results: Vec<SearchResult> = results
+                .into_iter()
+                .map(|mut r| {
+                    if expired(&r) {
+                        r.score *= DEMOTED_SCORE_FACTOR;
+                    }
+                    r
+                })
+                .collect();
+            results.sort_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            results
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleDocument {
+    pub document_id: Uuid,
+    pub freshness: Freshness,
+    pub review_by: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Citation of one of the document's chunks, where it has one.
+    pub source: Option<String>,
+}
+
+/// Documents past their review or expiry date, most stale first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreshnessReport {
+    pub ran_at: DateTime<Utc>,
+    pub scanned_chunks: usize,
+    pub documents: Vec<StaleDocument>,
+}
+
+impl FreshnessReport {
+    pub fn from_chunks(chunks: &[DocumentChunk], ran_at: DateTime<Utc>) -> Self {
+        let mut stale: BTreeMap<Uuid, StaleDocument> = BTreeMap::new();
+
+        for chunk in chunks {
+            let metadata = &chunk.metadata;
+            let freshness = metadata.freshness(ran_at);
+            if freshness == Freshness::Current {
+                continue;
+            }
+            let doc = stale
+                .entry(chunk.document_id)
+                .or_insert_with(|| StaleDocument {
+                    document_id: chunk.document_id,
+                    freshness,
+                    review_by: metadata.review_by,
+                    expires_at: metadata.expires_at,
+                    source: None,
+                });
+            if doc.source.is_none() {
+                doc.source = metadata.citation();
+            }
+        }
+
+        let mut documents: Vec<StaleDocument> = stale.into_values().collect();
+        documents.sort_by_key(|doc| std::cmp::Reverse(doc.freshness));
+
+        Self {
+            ran_at,
+            scanned_chunks: chunks.len(),
+            documents,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ChunkMetadata;
+    use chrono::Duration;
+
+    fn chunk(review_in: Option<i64>, expires_in: Option<i64>, now: DateTime<Utc>) -> DocumentChunk {
+        DocumentChunk::new(Uuid::new_v4(), "text", 0).with_metadata(ChunkMetadata {
+            review_by: review_in.map(|d| now + Duration::days(d)),
+            expires_at: expires_in.map(|d| now + Duration::days(d)),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_report_lists_stale_documents() {
+        let now = Utc::now();
+        let chunks = vec![
+            chunk(None, None, now),
+            chunk(Some(-1), Some(30), now),
+            chunk(Some(-10), Some(-1), now),
+            chunk(Some(5), None, now),
+        ];
+
+        let report = FreshnessReport::from_chunks(&chunks, now);
+        let found: Vec<_> = report.documents.iter().map(|d| d.freshness).collect();
+
+        assert_eq!(report.scanned_chunks, 4);
+        assert_eq!(found, vec![Freshness::Expired, Freshness::ReviewDue]);
+    }
+
+    #[test]
+    fn test_demote_and_exclude_expired() {
+        let now = Utc::now();
+        let results = vec![
+            SearchResult {
+                chunk: chunk(None, Some(-1), now),
+                score: 0.9,
+            },
+            SearchResult {
+                chunk: chunk(None, None, now),
+                score: 0.6,
+            },
+        ];
+
+        let demoted = apply_stale_policy(results.clone(), StalePolicy::Demote, now);
+        assert_eq!(demoted[0].score, 0.6);
+        assert_eq!(demoted[1].score, 0.45);
+
+        let excluded = apply_stale_policy(results, StalePolicy::Exclude, now);
+        assert_eq!(excluded.len(), 1);
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::domain::{ports::VectorStore, DomainError};
+
+/// Outcome of [`migrate_points`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Points copied from the source store.
+    pub migrated: usize,
+    /// Copied points that could not be found in the target afterwards.
+    pub missing: usize,
+}
+
+/// Streams every point of `source` into `target` in pages of `page_size`,
+/// keeping chunk ids, payloads and embeddings as they are.
+///
+/// The copy is idempotent, so an interrupted migration can simply be re-run.
+pub async fn migrate_points(
+    source: &dyn VectorStore,
+    target: &dyn VectorStore,
+    page_size: usize,
+) -> Result<MigrationReport, DomainError> {
+    let mut copied: Vec<Uuid> = Vec::new();
+    let mut offset = None;
+
+    loop {
+        let page = source.scroll_points(offset, page_size).await?;
+        for (chunk, embedding) in &page.points {
+            target.upsert(chunk, embedding).await?;
+            copied.push(chunk.id);
+        }
+        tracing::debug!(migrated = copied.len(), "migrated page");
+
+        match page.next {
+            Some(next) => offset = Some(next),
+            None => break,
+        }
+    }
+
+    let present: HashSet<Uuid> = target
+        .list_chunks()
+        .await?
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    let missing = copied.iter().filter(|id| !present.contains(id)).count();
+
+    Ok(MigrationReport {
+        migrated: copied.len(),
+        missing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{DocumentChunk, Embedding, SearchFilter};
+    use crate::infrastructure::InMemoryVectorStore;
+
+    #[tokio::test]
+    async fn test_migrate_preserves_points() {
+        let source = InMemoryVectorStore::new();
+        let document_id = Uuid::new_v4();
+        for i in 0..5 {
+            let chunk = DocumentChunk::new(document_id, format!("chunk {i}"), i);
+            source
+                .upsert(&chunk, &Embedding::new(vec![i as f32, 1.0]))
+                .await
+                .unwrap();
+        }
+        let target = InMemoryVectorStore::new();
+
+        let report = migrate_points(&source, &target, 2).await.unwrap();
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 5,
+                missing: 0
+            }
+        );
+
+        let hits = target
+            .search(&Embedding::new(vec![4.0, 1.0]), 1, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(hits[0].chunk.content, "chunk 4");
+    }
+}
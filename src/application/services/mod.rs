@@ -1,5 +1,33 @@
+mod billing;
+mod chunk_titles;
+mod confidence;
 mod document;
+mod dump;
+mod freshness;
+mod migration;
+mod query_cache;
 mod rag;
+mod repair;
+mod retention;
+mod retrieval_diff;
+mod search;
 
-pub use document::DocumentService;
-pub use rag::RagService;
+pub use billing::{month_bounds, BillingRates, Invoice, InvoiceLine, TenantUsage};
+pub use chunk_titles::title_chunks;
+pub use confidence::{Confidence, ConfidenceLevel, ConfidenceScorer, ConfidenceWeights};
+pub use document::{content_hash, DocumentService, Ingested};
+pub use dump::{export_points, import_points, is_valid_dump_file};
+pub use freshness::{apply_stale_policy, FreshnessReport, StaleDocument, StalePolicy};
+pub use migration::{migrate_points, MigrationReport};
+pub use query_cache::{warm_query_cache, QueryEmbeddingCache};
+pub use rag::{
+    collapse_by_document, maximal_marginal_relevance, normalize_scores, reciprocal_rank_fusion,
+    score_fusion, QueryTransform, RagService, RetrievalOptions, RetrievalPath, RetrievalStrategy,
+    RetrievalTimings, ScoreNormalization,
+};
+pub use repair::{repair_payloads, PayloadRepairReport, UnrepairedPoint};
+pub use retention::{
+    scrub_pii, RetentionAction, RetentionDecision, RetentionPolicy, RetentionReport, RetentionRule,
+};
+pub use retrieval_diff::{diff_results, RankChange, RetrievalDiff};
+pub use search::search_rag_services;
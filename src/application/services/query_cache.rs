@@ -0,0 +1,155 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::domain::{
+    ports::{EmbeddingPurpose, EmbeddingService},
+    DomainError, Embedding, EmbeddingPrecision, QuantizedEmbedding,
+};
+
+/// Bounded map of query text to its embedding; the least recently used
+/// entry is evicted first. Keys ignore case and surrounding whitespace.
+pub struct QueryEmbeddingCache {
+    capacity: usize,
+    precision: EmbeddingPrecision,
+    entries: Mutex<(HashMap<String, QuantizedEmbedding>, VecDeque<String>)>,
+}
+
+impl QueryEmbeddingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            precision: EmbeddingPrecision::default(),
+            entries: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    /// Holds embeddings at `precision`, returning them dequantized.
+    pub fn with_precision(mut self, precision: EmbeddingPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn key(query: &str) -> String {
+        query.trim().to_lowercase()
+    }
+
+    pub fn get(&self, query: &str) -> Option<Embedding> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (map, order) = &mut *entries;
+        let key = Self::key(query);
+        let embedding = map.get(&key)?.dequantize();
+        touch(order, key);
+        Some(embedding)
+    }
+
+    pub fn insert(&self, query: &str, embedding: Embedding) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (map, order) = &mut *entries;
+        let key = Self::key(query);
+        map.insert(
+            key.clone(),
+            QuantizedEmbedding::new(embedding, self.precision),
+        );
+        touch(order, key);
+        while order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                map.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .0
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn touch(order: &mut VecDeque<String>, key: String) {
+    if let Some(position) = order.iter().position(|k| *k == key) {
+        order.remove(position);
+    }
+    order.push_back(key);
+}
+
+/// Embeds the `queries` missing from `cache` in one batch and marks the
+/// rest as recently used, so frequent queries survive eviction. Returns how
+/// many were embedded.
+pub async fn warm_query_cache(
+    embedding: &dyn EmbeddingService,
+    cache: &QueryEmbeddingCache,
+    queries: &[String],
+) -> Result<usize, DomainError> {
+    let missing: Vec<&str> = queries
+        .iter()
+        .map(String::as_str)
+        .filter(|query| cache.get(query).is_none())
+        .collect();
+    if missing.is_empty() {
+        return Ok(0);
+    }
+
+    let embeddings = embedding
+        .embed_batch_for(&missing, EmbeddingPurpose::Query)
+        .await?;
+    for (query, embedding) in missing.iter().zip(embeddings) {
+        cache.insert(query, embedding);
+    }
+    Ok(missing.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct LengthEmbedding;
+
+    #[async_trait]
+    impl EmbeddingService for LengthEmbedding {
+        async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+            Ok(Embedding::new(vec![text.len() as f32]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            Ok(texts
+                .iter()
+                .map(|t| Embedding::new(vec![t.len() as f32]))
+                .collect())
+        }
+
+        fn dimension(&self) -> usize {
+            1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warming_embeds_missing_queries_and_keeps_them() {
+        let cache = QueryEmbeddingCache::new(2);
+        cache.insert("reset password", Embedding::new(vec![1.0]));
+
+        let queries = vec!["Reset password ".to_string(), "vpn setup".to_string()];
+        let embedded = warm_query_cache(&LengthEmbedding, &cache, &queries)
+            .await
+            .unwrap();
+        assert_eq!(embedded, 1);
+        assert_eq!(cache.get("VPN setup").unwrap().as_slice(), &[9.0]);
+
+        // Warmed entries are the most recently used, so a new query evicts
+        // whichever of them was touched least recently.
+        cache.get("reset password");
+        cache.insert("billing", Embedding::new(vec![7.0]));
+        assert!(cache.get("vpn setup").is_none());
+        assert!(cache.get("reset password").is_some());
+        assert_eq!(cache.len(), 2);
+    }
+}
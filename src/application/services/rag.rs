@@ -1,15 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, HashMap};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::instrument;
 
+use super::chunk_titles::title_chunks;
+use super::freshness::{apply_stale_policy, StalePolicy};
+use super::query_cache::QueryEmbeddingCache;
+use super::repair::{repair_payloads, PayloadRepairReport};
+use super::DocumentService;
 use crate::domain::{
-    ports::{EmbeddingService, VectorStore},
-    DocumentChunk, DomainError, SearchResult,
+    ports::{DocumentStore, EmbeddingPurpose, EmbeddingService, LlmService, QueryLog, VectorStore},
+    DocumentChunk, DomainError, Embedding, EmbeddingPrecision, SearchFilter, SearchResult,
 };
 
+/// How retrieved chunks are selected once the vector search has run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetrievalStrategy {
+    /// Return the `top_k` nearest chunks regardless of score.
+    #[default]
+    Similarity,
+    /// Return the `top_k` nearest chunks, dropping any below `min_score`.
+    Threshold,
+    /// Fuse vector and keyword (BM25) results by reciprocal rank; scores are
+    /// fused ranks, not similarities. With a [`ScoreNormalization`], results
+    /// are fused by their mean normalized score, and vector results below
+    /// `min_score` (by raw similarity) are dropped before fusing.
+    Hybrid,
+}
+
+/// How each retrieval source's scores are rescaled to `0..=1` before hybrid
+/// search fuses them, so cosine and BM25 scores line up. Other strategies
+/// keep raw scores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreNormalization {
+    /// Keep raw scores.
+    #[default]
+    None,
+    /// `(score - min) / (max - min)` over the source's results.
+    MinMax,
+    /// The standard score `(score - mean) / stddev` through a logistic curve.
+    ZScore,
+}
+
+/// How the user's query is rewritten before it is embedded for vector search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryTransform {
+    /// Embed the query as written.
+    #[default]
+    None,
+    /// Embed an LLM-drafted hypothetical answer (HyDE), which tends to sit
+    /// closer to answer passages than a short question does.
+    Hyde,
+    /// Search with the query plus LLM-written rephrasings and merge the hits.
+    MultiQuery,
+}
+
+/// How query results were found, recorded as `retrieval_path` on the
+/// retrieval span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetrievalPath {
+    /// The query was embedded (or found in the query cache) and searched as configured.
+    Vector,
+    /// Embedding failed; an earlier embedding of the same query was reused.
+    CachedEmbedding,
+    /// Embedding failed with nothing cached; keyword (BM25) search only.
+    Keyword,
+}
+
+impl RetrievalPath {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Vector => "vector",
+            Self::CachedEmbedding => "cached_embedding",
+            Self::Keyword => "keyword",
+        }
+    }
+}
+
+const HYDE_SYSTEM_PROMPT: &str = "Write a short passage that plausibly answers the question, \
+as it might appear in a reference document. Do not mention that it is hypothetical.";
+
+/// Rephrasings searched alongside the original query in [`QueryTransform::MultiQuery`].
+const MULTI_QUERY_VARIANTS: usize = 3;
+
+const MULTI_QUERY_SYSTEM_PROMPT: &str = "Rewrite the search query in 3 different ways \
+that could match relevant documents. Reply with one rewrite per line and nothing else.";
+
+const RERANK_SYSTEM_PROMPT: &str = "You judge how relevant passages are to a search query. \
+Rate each passage from 0 (irrelevant) to 10 (directly answers the query). \
+Reply with only a JSON array of numbers, one per passage, in order.";
+
+/// Rank constant for reciprocal rank fusion; damps the weight of top ranks.
+const RRF_K: f32 = 60.0;
+
+/// Merges ranked result lists, scoring each chunk `sum(1 / (RRF_K + rank))`
+/// over the lists it appears in, and keeps the best `top_k`.
+pub fn reciprocal_rank_fusion(lists: &[Vec<SearchResult>], top_k: usize) -> Vec<SearchResult> {
+    let mut fused: Vec<SearchResult> = Vec::new();
+    let mut positions: HashMap<uuid::Uuid, usize> = HashMap::new();
+
+    for list in lists {
+        for (rank, result) in list.iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f32 + 1.0);
+            match positions.get(&result.chunk.id) {
+                Some(&i) => fused[i].score += score,
+                None => {
+                    positions.insert(result.chunk.id, fused.len());
+                    fused.push(SearchResult {
+                        chunk: result.chunk.clone(),
+                        score,
+                    });
+                }
+            }
+        }
+    }
+
+    fused.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    fused.truncate(top_k);
+    fused
+}
+
+/// Rescales the scores of one source's results in place.
+///
+/// When every score is equal there is nothing to tell results apart, so all
+/// get 1.0.
+pub fn normalize_scores(results: &mut [SearchResult], method: ScoreNormalization) {
+    if method == ScoreNormalization::None || results.is_empty() {
+        return;
+    }
+    let n = results.len() as f32;
+    let (min, max) = results
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), r| {
+            (min.min(r.score), max.max(r.score))
+        });
+    if max - min <= f32::EPSILON {
+        results.iter_mut().for_each(|r| r.score = 1.0);
+        return;
+    }
+
+    match method {
+        ScoreNormalization::None => {}
+        ScoreNormalization::MinMax => {
+            for result in results.iter_mut() {
+                result.score = (result.score - min) / (max - min);
+            }
+        }
+        ScoreNormalization::ZScore => {
+            let mean = results.iter().map(|r| r.score).sum::<f32>() / n;
+            let variance = results
+                .iter()
+                .map(|r| (r.score - mean).powi(2))
+                .sum::<f32>()
+                / n;
+            let stddev = variance.sqrt();
+            for result in results.iter_mut() {
+                let z = (result.score - mean) / stddev;
+                result.score = 1.0 / (1.0 + (-z).exp());
+            }
+        }
+    }
+}
+
+/// Merges normalized result lists, scoring each chunk by its mean score over
+/// all lists (0 where it is missing), and keeps the best `top_k`.
+pub fn score_fusion(lists: &[Vec<SearchResult>], top_k: usize) -> Vec<SearchResult> {
+    let mut fused: Vec<SearchResult> = Vec::new();
+    let mut positions: HashMap<uuid::Uuid, usize> = HashMap::new();
+    let weight = 1.0 / lists.len().max(1) as f32;
+
+    for list in lists {
+        for result in list {
+            let score = result.score * weight;
+            match positions.get(&result.chunk.id) {
+                Some(&i) => fused[i].score += score,
+                None => {
+                    positions.insert(result.chunk.id, fused.len());
+                    fused.push(SearchResult {
+                        chunk: result.chunk.clone(),
+                        score,
+                    });
+                }
+            }
+        }
+    }
+
+    fused.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    fused.truncate(top_k);
+    fused
+}
+
+#[derive(Debug, Clone)]
+pub struct RetrievalOptions {
+    pub top_k: usize,
+    pub strategy: RetrievalStrategy,
+    pub min_score: f32,
+    pub filter: SearchFilter,
+}
+
+impl RetrievalOptions {
+    pub fn similarity(top_k: usize) -> Self {
+        Self {
+            top_k,
+            strategy: RetrievalStrategy::Similarity,
+            min_score: 0.0,
+            filter: SearchFilter::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: SearchFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// Time spent in each retrieval stage.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetrievalTimings {
+    pub embedding: Duration,
+    pub search: Duration,
+}
+
 pub struct RagService {
     embedding: Arc<dyn EmbeddingService>,
     vector_store: Arc<dyn VectorStore>,
     default_top_k: usize,
+    reranker: Option<Arc<dyn LlmService>>,
+    rerank_candidates: usize,
+    mmr_lambda: Option<f32>,
+    mmr_candidates: usize,
+    /// Candidates fetched when hits are collapsed per document; `None` keeps every hit.
+    collapse_candidates: Option<usize>,
+    /// Source of the neighbouring chunks added around each result.
+    neighbors: Option<Arc<dyn DocumentStore>>,
+    query_transform: QueryTransform,
+    transform_llm: Option<Arc<dyn LlmService>>,
+    stale_policy: StalePolicy,
+    normalization: ScoreNormalization,
+    /// Recent query embeddings, reused on embedding failures and, when
+    /// shared through [`with_query_cache`](Self::with_query_cache), before
+    /// calling the provider at all.
+    query_cache: Option<Arc<QueryEmbeddingCache>>,
+    cache_first: bool,
+    embedding_fallback: bool,
+    query_log: Option<Arc<dyn QueryLog>>,
+    /// Titles untitled chunks before they are indexed, up to this many characters.
+    chunk_titles: Option<(Arc<dyn LlmService>, usize)>,
 }
 
 impl RagService {
@@ -22,9 +270,117 @@ impl RagService {
             embedding,
             vector_store,
             default_top_k,
+            reranker: None,
+            rerank_candidates: 0,
+            mmr_lambda: None,
+            mmr_candidates: 0,
+            collapse_candidates: None,
+            neighbors: None,
+            query_transform: QueryTransform::None,
+            transform_llm: None,
+            stale_policy: StalePolicy::default(),
+            normalization: ScoreNormalization::None,
+            query_cache: None,
+            cache_first: false,
+            embedding_fallback: false,
+            query_log: None,
+            chunk_titles: None,
         }
     }
 
+    /// Keeps retrieval working when the embedding provider fails: the last
+    /// `cache_size` query embeddings are reused for repeated queries, and
+    /// other queries are answered by keyword search alone. The cache holds
+    /// them at `precision`.
+    pub fn with_embedding_fallback(
+        mut self,
+        cache_size: usize,
+        precision: EmbeddingPrecision,
+    ) -> Self {
+        self.query_cache.get_or_insert_with(|| {
+            Arc::new(QueryEmbeddingCache::new(cache_size).with_precision(precision))
+        });
+        self.embedding_fallback = true;
+        self
+    }
+
+    /// Answers repeated queries from `cache` without calling the embedding
+    /// provider; the cache can be shared and warmed with [`warm_query_cache`].
+    /// Skipped while a [`QueryTransform`] is configured.
+    ///
+    /// [`warm_query_cache`]: super::warm_query_cache
+    pub fn with_query_cache(mut self, cache: Arc<QueryEmbeddingCache>) -> Self {
+        self.query_cache = Some(cache);
+        self.cache_first = true;
+        self
+    }
+
+    /// Records every retrieved query in `log`.
+    pub fn with_query_log(mut self, log: Arc<dyn QueryLog>) -> Self {
+        self.query_log = Some(log);
+        self
+    }
+
+    /// Has `llm` write a short title for each chunk as it is indexed; see
+    /// [`title_chunks`](super::title_chunks).
+    pub fn with_chunk_titles(mut self, llm: Arc<dyn LlmService>, max_chars: usize) -> Self {
+        self.chunk_titles = Some((llm, max_chars));
+        self
+    }
+
+    /// Normalizes each retrieval source's scores before hybrid fusion.
+    pub fn with_score_normalization(mut self, normalization: ScoreNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Sets how chunks of expired documents are treated in results.
+    pub fn with_stale_policy(mut self, policy: StalePolicy) -> Self {
+        self.stale_policy = policy;
+        self
+    }
+
+    /// Rewrites queries with `llm` according to `transform` before searching.
+    pub fn with_query_transform(
+        mut self,
+        transform: QueryTransform,
+        llm: Arc<dyn LlmService>,
+    ) -> Self {
+        self.query_transform = transform;
+        self.transform_llm = Some(llm);
+        self
+    }
+
+    /// Picks the final top_k from `candidates` results by maximal marginal
+    /// relevance, trading relevance (`lambda` = 1) against diversity (0).
+    pub fn with_mmr(mut self, lambda: f32, candidates: usize) -> Self {
+        self.mmr_lambda = Some(lambda.clamp(0.0, 1.0));
+        self.mmr_candidates = candidates;
+        self
+    }
+
+    /// Collapses hits from the same document into one result, over-fetching
+    /// `candidates` so the top_k still span several documents.
+    pub fn with_document_collapse(mut self, candidates: usize) -> Self {
+        self.collapse_candidates = Some(candidates);
+        self
+    }
+
+    /// Adds the chunks just before and after each result, read from `store`,
+    /// to its content so answers aren't cut off at chunk boundaries.
+    pub fn with_neighbor_expansion(mut self, store: Arc<dyn DocumentStore>) -> Self {
+        self.neighbors = Some(store);
+        self
+    }
+
+    /// Over-fetches `candidates` results and has `llm` reorder them by
+    /// relevance before the top_k are returned.
+    pub fn with_reranker(mut self, llm: Arc<dyn LlmService>, candidates: usize) -> Self {
+        self.reranker = Some(llm);
+        self.rerank_candidates = candidates;
+        self
+    }
+
     #[instrument(skip(self), fields(top_k))]
     pub async fn retrieve(&self, query: &str) -> Result<Vec<SearchResult>, DomainError> {
         self.retrieve_top_k(query, self.default_top_k).await
@@ -36,13 +392,314 @@ impl RagService {
         query: &str,
         top_k: usize,
     ) -> Result<Vec<SearchResult>, DomainError> {
-        let embedding = self.embedding.embed(query).await?;
-        self.vector_store.search(&embedding, top_k).await
+        let embedding = self
+            .embedding
+            .embed_for(query, EmbeddingPurpose::Query)
+            .await?;
+        self.vector_store
+            .search(&embedding, top_k, &SearchFilter::default())
+            .await
+    }
+
+    /// Similarity score of the best match for `query`, without reranking or
+    /// logging the query; `None` when nothing matches.
+    #[instrument(skip(self, filter))]
+    pub async fn top_score(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+    ) -> Result<Option<f32>, DomainError> {
+        let embedding = self
+            .embedding
+            .embed_for(query, EmbeddingPurpose::Query)
+            .await?;
+        let results = self.vector_store.search(&embedding, 1, filter).await?;
+        Ok(results.first().map(|r| r.score))
+    }
+
+    /// Ids of the `top_k` nearest chunks for `query`, without reranking or
+    /// logging the query.
+    #[instrument(skip(self, filter))]
+    pub async fn nearest_chunk_ids(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<uuid::Uuid>, DomainError> {
+        let embedding = self
+            .embedding
+            .embed_for(query, EmbeddingPurpose::Query)
+            .await?;
+        let results = self.vector_store.search(&embedding, top_k, filter).await?;
+        Ok(results.into_iter().map(|r| r.chunk.id).collect())
+    }
+
+    #[instrument(skip(self, options), fields(top_k = options.top_k, strategy = ?options.strategy))]
+    pub async fn retrieve_with(
+        &self,
+        query: &str,
+        options: &RetrievalOptions,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.retrieve_timed(query, options)
+            .await
+            .map(|(results, _)| results)
+    }
+
+    /// Retrieves with [`RetrievalStrategy::Hybrid`], whatever `options.strategy` says.
+    #[instrument(skip(self, options), fields(top_k = options.top_k))]
+    pub async fn retrieve_hybrid(
+        &self,
+        query: &str,
+        options: &RetrievalOptions,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let options = RetrievalOptions {
+            strategy: RetrievalStrategy::Hybrid,
+            ..options.clone()
+        };
+        self.retrieve_with(query, &options).await
+    }
+
+    /// Like [`retrieve_with`](Self::retrieve_with), also reporting per-stage timings.
+    #[instrument(skip(self, options), fields(top_k = options.top_k, retrieval_path))]
+    pub async fn retrieve_timed(
+        &self,
+        query: &str,
+        options: &RetrievalOptions,
+    ) -> Result<(Vec<SearchResult>, RetrievalTimings), DomainError> {
+        let mut fetch_k = options.top_k;
+        if self.reranker.is_some() {
+            fetch_k = fetch_k.max(self.rerank_candidates);
+        }
+        if self.mmr_lambda.is_some() {
+            fetch_k = fetch_k.max(self.mmr_candidates);
+        }
+        if let Some(candidates) = self.collapse_candidates {
+            fetch_k = fetch_k.max(candidates);
+        }
+
+        if let Some(log) = &self.query_log {
+            let (log, query) = (log.clone(), query.to_string());
+            tokio::spawn(async move {
+                if let Err(e) = log.record(&query).await {
+                    tracing::warn!(error = %e, "failed to record query");
+                }
+            });
+        }
+
+        let started = Instant::now();
+        let (embeddings, path) = self.query_embeddings(query).await?;
+        tracing::Span::current().record("retrieval_path", path.as_str());
+        let mut embeddings = embeddings.into_iter();
+        let embedding = embeddings.next();
+        let embedded = Instant::now();
+        let results = match &embedding {
+            Some(embedding) => {
+                let results = self
+                    .vector_store
+                    .search(embedding, fetch_k, &options.filter)
+                    .await?;
+                let mut variant_results = vec![results];
+                for variant in embeddings {
+                    variant_results.push(
+                        self.vector_store
+                            .search(&variant, fetch_k, &options.filter)
+                            .await?,
+                    );
+                }
+                let mut results = merge_by_best_score(variant_results, fetch_k);
+                // min_score is a similarity, so it applies before any rescaling:
+                // min-max would lift the best hit of an unrelated query to 1.0.
+                let thresholded = match options.strategy {
+                    RetrievalStrategy::Similarity => false,
+                    RetrievalStrategy::Threshold => true,
+                    RetrievalStrategy::Hybrid => self.normalization != ScoreNormalization::None,
+                };
+                if thresholded {
+                    results.retain(|r| r.score >= options.min_score);
+                }
+                match options.strategy {
+                    RetrievalStrategy::Hybrid => {
+                        let mut keyword = self
+                            .vector_store
+                            .keyword_search(query, fetch_k, &options.filter)
+                            .await?;
+                        if self.normalization == ScoreNormalization::None {
+                            reciprocal_rank_fusion(&[results, keyword], fetch_k)
+                        } else {
+                            normalize_scores(&mut results, self.normalization);
+                            normalize_scores(&mut keyword, self.normalization);
+                            score_fusion(&[results, keyword], fetch_k)
+                        }
+                    }
+                    _ => results,
+                }
+            }
+            // Raw BM25 scores can't be held against min_score.
+            None => {
+                self.vector_store
+                    .keyword_search(query, fetch_k, &options.filter)
+                    .await?
+            }
+        };
+
+        let timings = RetrievalTimings {
+            embedding: embedded - started,
+            search: embedded.elapsed(),
+        };
+
+        let results = apply_stale_policy(results, self.stale_policy, chrono::Utc::now());
+        let results = match &self.reranker {
+            Some(llm) => rerank(llm.as_ref(), query, results).await,
+            None => results,
+        };
+        let results = match self.collapse_candidates {
+            Some(_) => collapse_by_document(results),
+            None => results,
+        };
+        let mut results = match (self.mmr_lambda, &embedding) {
+            (Some(lambda), Some(embedding)) if results.len() > options.top_k => {
+                let candidates = self.with_vectors(results).await?;
+                maximal_marginal_relevance(embedding, candidates, options.top_k, lambda)
+            }
+            _ => results,
+        };
+        results.truncate(options.top_k);
+        let results = match &self.neighbors {
+            Some(store) => expand_neighbors(store.as_ref(), results).await,
+            None => results,
+        };
+
+        Ok((results, timings))
+    }
+
+    /// Pairs results with their stored vectors for MMR, embedding afresh
+    /// only those the store can't return.
+    async fn with_vectors(
+        &self,
+        results: Vec<SearchResult>,
+    ) -> Result<Vec<(SearchResult, Embedding)>, DomainError> {
+        let ids: Vec<uuid::Uuid> = results.iter().map(|r| r.chunk.id).collect();
+        let stored = self.vector_store.vectors(&ids).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "failed to fetch candidate vectors, embedding them");
+            HashMap::new()
+        });
+
+        let missing: Vec<&str> = results
+            .iter()
+            .filter(|r| !stored.contains_key(&r.chunk.id))
+            .map(|r| r.chunk.content.as_str())
+            .collect();
+        let mut embedded = if missing.is_empty() {
+            Vec::new()
+        } else {
+            self.embedding
+                .embed_batch_for(&missing, EmbeddingPurpose::Document)
+                .await?
+        }
+        .into_iter();
+
+        Ok(results
+            .into_iter()
+            .filter_map(|result| {
+                let vector = match stored.get(&result.chunk.id) {
+                    Some(vector) => vector.clone(),
+                    None => embedded.next()?,
+                };
+                Some((result, vector))
+            })
+            .collect())
+    }
+
+    /// Search vectors for `query` and how they were obtained; empty when
+    /// only keyword search is possible. Errors only without a fallback.
+    async fn query_embeddings(
+        &self,
+        query: &str,
+    ) -> Result<(Vec<Embedding>, RetrievalPath), DomainError> {
+        if let Some(cache) = self.query_cache.as_ref().filter(|_| self.cache_first) {
+            if let Some(embedding) = cache.get(query).filter(|_| self.transform_llm.is_none()) {
+                return Ok((vec![embedding], RetrievalPath::Vector));
+            }
+        }
+
+        let error = match self.embed_query(query).await {
+            Ok(embeddings) => {
+                if let (Some(cache), Some(primary)) = (&self.query_cache, embeddings.first()) {
+                    cache.insert(query, primary.clone());
+                }
+                return Ok((embeddings, RetrievalPath::Vector));
+            }
+            Err(e) => e,
+        };
+        let Some(cache) = self
+            .query_cache
+            .as_ref()
+            .filter(|_| self.embedding_fallback)
+        else {
+            return Err(error);
+        };
+
+        match cache.get(query) {
+            Some(embedding) => {
+                tracing::warn!(error = %error, "query embedding failed, reusing cached embedding");
+                Ok((vec![embedding], RetrievalPath::CachedEmbedding))
+            }
+            None => {
+                tracing::warn!(error = %error, "query embedding failed, falling back to keyword search");
+                Ok((Vec::new(), RetrievalPath::Keyword))
+            }
+        }
+    }
+
+    /// Search vectors for `query` under the configured [`QueryTransform`],
+    /// the primary one first.
+    ///
+    /// Transforms are best effort: if the LLM fails the query is embedded as is.
+    async fn embed_query(&self, query: &str) -> Result<Vec<Embedding>, DomainError> {
+        let Some(llm) = &self.transform_llm else {
+            return Ok(vec![
+                self.embedding
+                    .embed_for(query, EmbeddingPurpose::Query)
+                    .await?,
+            ]);
+        };
+
+        let texts: Vec<String> = match self.query_transform {
+            QueryTransform::None => vec![query.to_string()],
+            QueryTransform::Hyde => match llm.complete_with_system(HYDE_SYSTEM_PROMPT, query).await
+            {
+                Ok(draft) if !draft.trim().is_empty() => vec![draft],
+                Ok(_) => vec![query.to_string()],
+                Err(e) => {
+                    tracing::warn!(error = %e, "HyDE draft failed, embedding query");
+                    vec![query.to_string()]
+                }
+            },
+            QueryTransform::MultiQuery => {
+                let mut texts = vec![query.to_string()];
+                match llm
+                    .complete_with_system(MULTI_QUERY_SYSTEM_PROMPT, query)
+                    .await
+                {
+                    Ok(reply) => texts.extend(parse_query_variants(&reply)),
+                    Err(e) => tracing::warn!(error = %e, "query rewrite failed, embedding query"),
+                }
+                texts
+            }
+        };
+
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        self.embedding
+            .embed_batch_for(&texts, EmbeddingPurpose::Query)
+            .await
     }
 
     #[instrument(skip(self, chunk), fields(chunk_id = %chunk.id))]
     pub async fn index_chunk(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
-        let embedding = self.embedding.embed(&chunk.content).await?;
+        let embedding = self
+            .embedding
+            .embed_for(&chunk.content, EmbeddingPurpose::Document)
+            .await?;
         self.vector_store.upsert(chunk, &embedding).await
     }
 
@@ -52,8 +709,22 @@ impl RagService {
             return Ok(());
         }
 
+        let titled;
+        let chunks = match &self.chunk_titles {
+            Some((llm, max_chars)) if chunks.iter().any(|c| c.metadata.title.is_none()) => {
+                let mut copies = chunks.to_vec();
+                title_chunks(llm.as_ref(), &mut copies, *max_chars).await;
+                titled = copies;
+                &titled[..]
+            }
+            _ => chunks,
+        };
+
         let texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
-        let embeddings = self.embedding.embed_batch(&texts).await?;
+        let embeddings = self
+            .embedding
+            .embed_batch_for(&texts, EmbeddingPurpose::Document)
+            .await?;
 
         for (chunk, embedding) in chunks.iter().zip(embeddings.iter()) {
             self.vector_store.upsert(chunk, embedding).await?;
@@ -62,8 +733,558 @@ impl RagService {
         Ok(())
     }
 
+    /// The indexed chunks of a document, in chunk order.
+    pub async fn document_chunks(
+        &self,
+        document_id: uuid::Uuid,
+    ) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.vector_store.document_chunks(document_id).await
+    }
+
+    /// Rewrites unreadable point payloads from `documents`; see [`repair_payloads`].
+    pub async fn repair_payloads(
+        &self,
+        documents: &DocumentService,
+        attachments: &HashMap<uuid::Uuid, uuid::Uuid>,
+    ) -> Result<PayloadRepairReport, DomainError> {
+        repair_payloads(self.vector_store.as_ref(), documents, attachments).await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete_document(&self, document_id: uuid::Uuid) -> Result<(), DomainError> {
         self.vector_store.delete_by_document(document_id).await
     }
 }
+
+/// Merges result lists, keeping each chunk's best score, into the `top_k` best.
+fn merge_by_best_score(lists: Vec<Vec<SearchResult>>, top_k: usize) -> Vec<SearchResult> {
+    let mut lists = lists.into_iter();
+    let mut merged = lists.next().unwrap_or_default();
+    for list in lists {
+        for result in list {
+            match merged.iter_mut().find(|r| r.chunk.id == result.chunk.id) {
+                Some(existing) => existing.score = existing.score.max(result.score),
+                None => merged.push(result),
+            }
+        }
+        merged.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+    merged.truncate(top_k);
+    merged
+}
+
+/// Keeps one result per document, in score order: its best hit, with any
+/// hits on directly adjacent chunks merged into it in document order.
+/// Other hits from the same document are dropped.
+pub fn collapse_by_document(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut groups: Vec<Vec<SearchResult>> = Vec::new();
+    for result in results {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].chunk.document_id == result.chunk.document_id)
+        {
+            Some(group) => group.push(result),
+            None => groups.push(vec![result]),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|mut group| {
+            let best = group.remove(0);
+            let mut run = vec![best.chunk.clone()];
+            // Grow the run outwards while the next chunk on either side was also a hit.
+            loop {
+                let first = run[0].chunk_index;
+                let last = run[run.len() - 1].chunk_index;
+                if let Some(i) = group
+                    .iter()
+                    .position(|r| first > 0 && r.chunk.chunk_index == first - 1)
+                {
+                    run.insert(0, group.swap_remove(i).chunk);
+                } else if let Some(i) = group.iter().position(|r| r.chunk.chunk_index == last + 1) {
+                    run.push(group.swap_remove(i).chunk);
+                } else {
+                    break;
+                }
+            }
+            if run.len() == 1 {
+                return best;
+            }
+
+            let mut chunk = best.chunk;
+            chunk.content = run
+                .iter()
+                .map(|c| c.content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            chunk.chunk_index = run[0].chunk_index;
+            chunk.metadata.line_start = run[0].metadata.line_start;
+            chunk.metadata.line_end = run[run.len() - 1].metadata.line_end;
+            SearchResult {
+                chunk,
+                score: best.score,
+            }
+        })
+        .collect()
+}
+
+/// Widens each result with its document's adjacent chunks from `store`.
+///
+/// Best effort: results whose document can't be read are kept as they are.
+async fn expand_neighbors(
+    store: &dyn DocumentStore,
+    results: Vec<SearchResult>,
+) -> Vec<SearchResult> {
+    let mut documents: HashMap<uuid::Uuid, Vec<DocumentChunk>> = HashMap::new();
+    let mut expanded = Vec::with_capacity(results.len());
+
+    for result in results {
+        let document_id = result.chunk.document_id;
+        let chunks = match documents.entry(document_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let chunks = store.get_chunks(document_id).await.unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, %document_id, "neighbour lookup failed");
+                    Vec::new()
+                });
+                entry.insert(chunks)
+            }
+        };
+        expanded.push(with_neighbors(result, chunks));
+    }
+
+    expanded
+}
+
+/// Prepends and appends the chunks at `chunk_index` ∓ 1 among `chunks`,
+/// unless the result already contains them.
+fn with_neighbors(mut result: SearchResult, chunks: &[DocumentChunk]) -> SearchResult {
+    let index = result.chunk.chunk_index;
+    let neighbor = |target: Option<usize>| {
+        chunks
+            .iter()
+            .find(|c| Some(c.chunk_index) == target)
+            .filter(|c| !result.chunk.content.contains(c.content.as_str()))
+            .cloned()
+    };
+    let before = neighbor(index.checked_sub(1));
+    // Collapsed results may span several chunks; look past the last one included.
+    let last = chunks
+        .iter()
+        .filter(|c| c.chunk_index >= index && result.chunk.content.contains(c.content.as_str()))
+        .map(|c| c.chunk_index)
+        .max()
+        .unwrap_or(index);
+    let after = neighbor(Some(last + 1));
+
+    let chunk = &mut result.chunk;
+    if let Some(before) = before {
+        chunk.content = format!("{}\n\n{}", before.content, chunk.content);
+        chunk.chunk_index = before.chunk_index;
+        chunk.metadata.line_start = before.metadata.line_start.or(chunk.metadata.line_start);
+    }
+    if let Some(after) = after {
+        chunk.content = format!("{}\n\n{}", chunk.content, after.content);
+        chunk.metadata.line_end = after.metadata.line_end.or(chunk.metadata.line_end);
+    }
+    result
+}
+
+/// Distinct non-empty lines of a rewrite reply, stripped of list markers.
+fn parse_query_variants(reply: &str) -> Vec<String> {
+    let mut variants: Vec<String> = Vec::new();
+    for line in reply.lines() {
+        let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+        let line = match line.split_once(['.', ')']) {
+            Some((n, rest)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                rest.trim()
+            }
+            _ => line,
+        };
+        if !line.is_empty() && !variants.iter().any(|v| v == line) {
+            variants.push(line.to_string());
+        }
+    }
+    variants.truncate(MULTI_QUERY_VARIANTS);
+    variants
+}
+
+/// Reorders `results` by LLM-judged relevance to `query`, with scores set to
+/// the judged relevance scaled to 0..=1.
+///
+/// Reranking is best effort: if the LLM fails or its reply cannot be parsed,
+/// the original order is kept.
+async fn rerank(
+    llm: &dyn LlmService,
+    query: &str,
+    mut results: Vec<SearchResult>,
+) -> Vec<SearchResult> {
+    if results.len() > 1 {
+        let passages: Vec<String> = results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| format!("[{}] {}", i + 1, r.chunk.content))
+            .collect();
+        let prompt = format!("Query: {query}\n\nPassages:\n{}", passages.join("\n\n"));
+
+        match llm
+            .complete_with_system(RERANK_SYSTEM_PROMPT, &prompt)
+            .await
+        {
+            Ok(reply) => match parse_relevance(&reply, results.len()) {
+                Some(scores) => {
+                    for (result, score) in results.iter_mut().zip(scores) {
+                        result.score = score.clamp(0.0, 10.0) / 10.0;
+                    }
+                    results.sort_by(|a, b| {
+                        b.score
+                            .partial_cmp(&a.score)
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                }
+                None => tracing::warn!(reply = %reply, "unparseable rerank reply, keeping order"),
+            },
+            Err(e) => tracing::warn!(error = %e, "rerank failed, keeping order"),
+        }
+    }
+
+    results
+}
+
+/// Greedily selects `top_k` candidates, each maximising
+/// `lambda * sim(query, c) - (1 - lambda) * max sim(c, already selected)`,
+/// so near-duplicates of earlier picks lose out to different content.
+pub fn maximal_marginal_relevance(
+    query: &Embedding,
+    mut candidates: Vec<(SearchResult, Embedding)>,
+    top_k: usize,
+    lambda: f32,
+) -> Vec<SearchResult> {
+    let mut selected: Vec<(SearchResult, Embedding)> = Vec::with_capacity(top_k);
+
+    while selected.len() < top_k && !candidates.is_empty() {
+        let marginal = |(_, vector): &(SearchResult, Embedding)| {
+            let redundancy = selected
+                .iter()
+                .map(|(_, chosen)| vector.cosine_similarity(chosen))
+                .fold(0.0_f32, f32::max);
+            lambda * query.cosine_similarity(vector) - (1.0 - lambda) * redundancy
+        };
+        let best = candidates
+            .iter()
+            .map(marginal)
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        selected.push(candidates.remove(best));
+    }
+
+    selected.into_iter().map(|(result, _)| result).collect()
+}
+
+/// The JSON array of `expected` scores in an LLM reply, ignoring any prose
+/// or code fences around it.
+fn parse_relevance(reply: &str, expected: usize) -> Option<Vec<f32>> {
+    let start = reply.find('[')?;
+    let end = reply.rfind(']')?;
+    let scores: Vec<f32> = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    (scores.len() == expected).then_some(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::InMemoryVectorStore;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use uuid::Uuid;
+
+    /// Embeds every text to the same vector until switched off.
+    struct FlakyEmbedding {
+        down: AtomicBool,
+    }
+
+    #[async_trait]
+    impl EmbeddingService for FlakyEmbedding {
+        async fn embed(&self, _: &str) -> Result<Embedding, DomainError> {
+            if self.down.load(Ordering::Relaxed) {
+                return Err(DomainError::external("embedding API unavailable"));
+            }
+            Ok(Embedding::new(vec![1.0, 0.0]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embedding_failure_falls_back_to_cache_then_keywords() {
+        let embedding = Arc::new(FlakyEmbedding {
+            down: AtomicBool::new(false),
+        });
+        let store = Arc::new(InMemoryVectorStore::new());
+        let rag = RagService::new(embedding.clone(), store.clone(), 1)
+            .with_embedding_fallback(8, EmbeddingPrecision::F32);
+        let document = Uuid::new_v4();
+        rag.index_chunks(&[
+            DocumentChunk::new(document, "Invoices are sent monthly.", 0),
+            DocumentChunk::new(document, "Refunds take five days.", 1),
+        ])
+        .await
+        .unwrap();
+        let options = RetrievalOptions::similarity(1);
+        let cached = rag
+            .retrieve_with("When are invoices sent?", &options)
+            .await
+            .unwrap();
+
+        embedding.down.store(true, Ordering::Relaxed);
+        let reused = rag
+            .retrieve_with("when are invoices sent? ", &options)
+            .await
+            .unwrap();
+        assert_eq!(reused[0].chunk.id, cached[0].chunk.id);
+
+        let keyword = rag.retrieve_with("refunds", &options).await.unwrap();
+        assert_eq!(keyword[0].chunk.chunk_index, 1);
+
+        let strict = RagService::new(embedding, store, 1);
+        assert!(strict.retrieve_with("refunds", &options).await.is_err());
+    }
+
+    /// Embeds "invoices" and "refunds" texts apart and anything else (the
+    /// query) orthogonally to invoices.
+    struct TopicEmbedding;
+
+    #[async_trait]
+    impl EmbeddingService for TopicEmbedding {
+        async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+            Ok(Embedding::new(if text.contains("Invoices") {
+                vec![1.0, 0.0]
+            } else if text.contains("Refunds") {
+                vec![0.8, 0.6]
+            } else {
+                vec![0.0, 1.0]
+            }))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    #[tokio::test]
+    async fn test_min_score_applies_to_raw_similarity_despite_normalization() {
+        let rag = RagService::new(
+            Arc::new(TopicEmbedding),
+            Arc::new(InMemoryVectorStore::new()),
+            2,
+        )
+        .with_score_normalization(ScoreNormalization::MinMax);
+        let document = Uuid::new_v4();
+        rag.index_chunks(&[
+            DocumentChunk::new(document, "Invoices are sent monthly.", 0),
+            DocumentChunk::new(document, "Refunds take five days.", 1),
+        ])
+        .await
+        .unwrap();
+
+        let similar = rag
+            .retrieve_with("weather", &RetrievalOptions::similarity(2))
+            .await
+            .unwrap();
+        assert!((similar[0].score - 0.6).abs() < 1e-4);
+
+        let threshold = RetrievalOptions {
+            strategy: RetrievalStrategy::Threshold,
+            min_score: 0.7,
+            ..RetrievalOptions::similarity(2)
+        };
+        assert!(rag
+            .retrieve_with("weather", &threshold)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mmr_uses_stored_vectors() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let chunks = [
+            ("Invoices a", vec![1.0, 0.1]),
+            ("Refunds b", vec![1.0, 0.11]),
+            ("Invoices c", vec![0.7, -0.7]),
+        ];
+        for (i, (text, vector)) in chunks.into_iter().enumerate() {
+            store
+                .upsert(
+                    &DocumentChunk::new(Uuid::nil(), text, i),
+                    &Embedding::new(vector),
+                )
+                .await
+                .unwrap();
+        }
+        // Re-embedding the texts would make chunks 0 and 2 duplicates.
+        let rag = RagService::new(Arc::new(TopicEmbedding), store, 2).with_mmr(0.5, 3);
+
+        let picked = rag
+            .retrieve_with("Invoices?", &RetrievalOptions::similarity(2))
+            .await
+            .unwrap();
+        let order: Vec<_> = picked.iter().map(|r| r.chunk.chunk_index).collect();
+        assert_eq!(order, vec![0, 2]);
+    }
+
+    fn result(chunk: &DocumentChunk) -> SearchResult {
+        SearchResult {
+            chunk: chunk.clone(),
+            score: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion_rewards_agreement() {
+        let [a, b, c] = [0, 1, 2].map(|i| DocumentChunk::new(Uuid::nil(), "text", i));
+        let dense = vec![result(&a), result(&b)];
+        let keyword = vec![result(&c), result(&b)];
+
+        let fused = reciprocal_rank_fusion(&[dense, keyword], 2);
+        let order: Vec<_> = fused.iter().map(|r| r.chunk.chunk_index).collect();
+
+        assert_eq!(order, vec![1, 0]);
+        assert!((fused[0].score - 2.0 / 62.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalized_scores_fuse_on_one_scale() {
+        let [a, b, c] = [0, 1, 2].map(|i| DocumentChunk::new(Uuid::nil(), "text", i));
+        let scored = |chunk, score| SearchResult {
+            score,
+            ..result(chunk)
+        };
+        let mut dense = vec![scored(&a, 0.82), scored(&b, 0.80), scored(&c, 0.78)];
+        let mut keyword = vec![scored(&b, 14.0), scored(&c, 2.0)];
+
+        normalize_scores(&mut dense, ScoreNormalization::MinMax);
+        normalize_scores(&mut keyword, ScoreNormalization::MinMax);
+        let scores: Vec<_> = dense.iter().map(|r| (r.score * 100.0).round()).collect();
+        assert_eq!(scores, vec![100.0, 50.0, 0.0]);
+
+        let fused = score_fusion(&[dense, keyword], 3);
+        let order: Vec<_> = fused.iter().map(|r| r.chunk.chunk_index).collect();
+        assert_eq!(order, vec![1, 0, 2]);
+        assert!((fused[0].score - 0.75).abs() < 1e-6);
+
+        let mut tied = vec![scored(&a, 3.0), scored(&b, 3.0)];
+        normalize_scores(&mut tied, ScoreNormalization::ZScore);
+        assert!(tied.iter().all(|r| r.score == 1.0));
+    }
+
+    #[test]
+    fn test_mmr_skips_near_duplicates() {
+        let [a, b, c] = [0, 1, 2].map(|i| DocumentChunk::new(Uuid::nil(), "text", i));
+        let query = Embedding::new(vec![1.0, 0.0]);
+        let candidates = vec![
+            (result(&a), Embedding::new(vec![1.0, 0.1])),
+            (result(&b), Embedding::new(vec![1.0, 0.11])),
+            (result(&c), Embedding::new(vec![0.7, -0.7])),
+        ];
+
+        let picked = maximal_marginal_relevance(&query, candidates.clone(), 2, 0.5);
+        let order: Vec<_> = picked.iter().map(|r| r.chunk.chunk_index).collect();
+        assert_eq!(order, vec![0, 2]);
+
+        let relevance_only = maximal_marginal_relevance(&query, candidates, 2, 1.0);
+        let order: Vec<_> = relevance_only.iter().map(|r| r.chunk.chunk_index).collect();
+        assert_eq!(order, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_collapse_merges_adjacent_hits_per_document() {
+        let doc = Uuid::new_v4();
+        let hit = |document_id, index, score| SearchResult {
+            chunk: DocumentChunk::new(document_id, format!("c{index}"), index),
+            score,
+        };
+        let results = vec![
+            hit(doc, 3, 0.9),
+            hit(doc, 2, 0.8),
+            hit(Uuid::nil(), 0, 0.7),
+            hit(doc, 7, 0.6),
+            hit(doc, 4, 0.5),
+        ];
+
+        let collapsed = collapse_by_document(results);
+        assert_eq!(collapsed.len(), 2);
+        assert_eq!(collapsed[0].chunk.content, "c2\n\nc3\n\nc4");
+        assert_eq!(collapsed[0].chunk.chunk_index, 2);
+        assert_eq!(collapsed[0].score, 0.9);
+        assert_eq!(collapsed[1].chunk.document_id, Uuid::nil());
+    }
+
+    #[test]
+    fn test_with_neighbors_adds_adjacent_chunks_once() {
+        let doc = Uuid::new_v4();
+        let chunks: Vec<_> = (0..4)
+            .map(|i| DocumentChunk::new(doc, format!("c{i}"), i))
+            .collect();
+
+        let expanded = with_neighbors(result(&chunks[1]), &chunks);
+        assert_eq!(expanded.chunk.content, "c0\n\nc1\n\nc2");
+        assert_eq!(expanded.chunk.chunk_index, 0);
+
+        let first = with_neighbors(result(&chunks[0]), &chunks);
+        assert_eq!(first.chunk.content, "c0\n\nc1");
+
+        let mut collapsed = result(&chunks[1]);
+        collapsed.chunk.content = "c1\n\nc2".to_string();
+        let expanded = with_neighbors(collapsed, &chunks);
+        assert_eq!(expanded.chunk.content, "c0\n\nc1\n\nc2\n\nc3");
+    }
+
+    #[test]
+    fn test_parse_query_variants() {
+        let reply = "1. reset password\n2) recover account login\n\n- reset password\n* change credentials\nextra";
+        assert_eq!(
+            parse_query_variants(reply),
+            vec![
+                "reset password",
+                "recover account login",
+                "change credentials"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_relevance() {
+        assert_eq!(
+            parse_relevance("```json\n[7, 2.5, 0]\n```", 3),
+            Some(vec![7.0, 2.5, 0.0])
+        );
+        assert_eq!(parse_relevance("[7, 2]", 3), None);
+        assert_eq!(parse_relevance("no idea", 3), None);
+    }
+}
@@ -0,0 +1,169 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::DocumentService;
+use crate::domain::{
+    ports::{MalformedPoint, VectorStore},
+    DocumentChunk, DomainError,
+};
+
+/// Outcome of [`repair_payloads`], by point id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PayloadRepairReport {
+    pub repaired: Vec<String>,
+    /// Points that could not be matched to a stored chunk, with the reason.
+    pub unrepaired: Vec<UnrepairedPoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnrepairedPoint {
+    pub id: String,
+    pub reason: String,
+}
+
+/// Rewrites the payload of every malformed point in `store` from the
+/// document store, keeping the point's vector.
+///
+/// Points are matched to stored chunks by the document id and chunk index
+/// still readable from their payload. Document-level metadata (content type,
+/// tags, freshness dates) is taken from the stored document and the
+/// conversation from `attachments` (document id to conversation id);
+/// per-chunk source details such as pages or line ranges are not recovered.
+pub async fn repair_payloads(
+    store: &dyn VectorStore,
+    documents: &DocumentService,
+    attachments: &HashMap<Uuid, Uuid>,
+) -> Result<PayloadRepairReport, DomainError> {
+    let mut report = PayloadRepairReport::default();
+
+    for point in store.malformed_points().await? {
+        match rederive(&point, documents, attachments).await? {
+            Ok(chunk) => {
+                store.rewrite_payload(&chunk).await?;
+                tracing::info!(point_id = %point.id, "repaired point payload");
+                report.repaired.push(point.id);
+            }
+            Err(reason) => {
+                tracing::warn!(point_id = %point.id, reason, error = %point.error, "cannot repair point payload");
+                report.unrepaired.push(UnrepairedPoint {
+                    id: point.id,
+                    reason: reason.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// The chunk a malformed point should hold, or why it can't be found.
+async fn rederive(
+    point: &MalformedPoint,
+    documents: &DocumentService,
+    attachments: &HashMap<Uuid, Uuid>,
+) -> Result<Result<DocumentChunk, &'static str>, DomainError> {
+    let Ok(id) = point.id.parse::<Uuid>() else {
+        return Ok(Err("point id is not a chunk id"));
+    };
+    let (Some(document_id), Some(chunk_index)) = (point.document_id, point.chunk_index) else {
+        return Ok(Err("payload has no readable document_id and chunk_index"));
+    };
+    let Some((document, chunks)) = documents.get_with_chunks(document_id).await? else {
+        return Ok(Err("document not in the document store"));
+    };
+    let Some(chunk) = chunks.into_iter().find(|c| c.chunk_index == chunk_index) else {
+        return Ok(Err("chunk not in the document store"));
+    };
+
+    let mut chunk = DocumentChunk { id, ..chunk };
+    chunk.metadata.content_type = Some(document.content_type);
+    chunk.metadata.tags = document.tags;
+    chunk.metadata.review_by = document.review_by;
+    chunk.metadata.expires_at = document.expires_at;
+    chunk.metadata.conversation_id = attachments.get(&document_id).copied();
+    Ok(Ok(chunk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ports::DocumentStore, Embedding, SearchFilter, SearchResult};
+    use crate::infrastructure::{InMemoryDocumentStore, ParagraphChunker};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Holds one malformed point and records rewritten payloads.
+    struct CorruptStore {
+        point: MalformedPoint,
+        rewritten: Mutex<Vec<DocumentChunk>>,
+    }
+
+    #[async_trait]
+    impl VectorStore for CorruptStore {
+        async fn upsert(&self, _: &DocumentChunk, _: &Embedding) -> Result<(), DomainError> {
+            Ok(())
+        }
+
+        async fn search(
+            &self,
+            _: &Embedding,
+            _: usize,
+            _: &SearchFilter,
+        ) -> Result<Vec<SearchResult>, DomainError> {
+            Ok(Vec::new())
+        }
+
+        async fn delete_by_document(&self, _: Uuid) -> Result<(), DomainError> {
+            Ok(())
+        }
+
+        async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+            Ok(Vec::new())
+        }
+
+        async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+            Ok(vec![self.point.clone()])
+        }
+
+        async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+            self.rewritten.lock().unwrap().push(chunk.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repairs_payload_from_document_store() {
+        let document_store = Arc::new(InMemoryDocumentStore::new());
+        let documents =
+            DocumentService::new(document_store.clone(), Arc::new(ParagraphChunker::new(10)));
+        let mut document = documents
+            .ingest("Guide", "First.\n\nSecond.", None, true)
+            .await
+            .unwrap()
+            .document;
+        document.tags = vec!["emea".to_string()];
+        document_store.save_document(&document).await.unwrap();
+
+        let point_id = Uuid::new_v4();
+        let store = CorruptStore {
+            point: MalformedPoint {
+                id: point_id.to_string(),
+                document_id: Some(document.id),
+                chunk_index: Some(1),
+                error: "missing field `content`".to_string(),
+            },
+            rewritten: Mutex::new(Vec::new()),
+        };
+
+        let report = repair_payloads(&store, &documents, &HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(report.repaired, vec![point_id.to_string()]);
+
+        let rewritten = store.rewritten.lock().unwrap();
+        assert_eq!(rewritten[0].id, point_id);
+        assert_eq!(rewritten[0].content, "Second.");
+        assert_eq!(rewritten[0].metadata.tags, vec!["emea".to_string()]);
+    }
+}
@@ -0,0 +1,320 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::{Annotation, Conversation};
+
+/// Decisions listed individually in a report; counts cover every conversation.
+const MAX_REPORTED_DECISIONS: usize = 1000;
+
+/// What happens to a conversation once it ages past its rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Hash the user id and scrub PII from messages, keeping the conversation.
+    Anonymize,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRule {
+    /// Days since the conversation was last updated.
+    pub after_days: u32,
+    pub action: RetentionAction,
+}
+
+/// Retention rules: a default plus per-tenant overrides.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    default_rule: Option<RetentionRule>,
+    tenant_rules: HashMap<String, RetentionRule>,
+    hash_salt: String,
+}
+
+impl RetentionPolicy {
+    pub fn new(default_rule: Option<RetentionRule>) -> Self {
+        Self {
+            default_rule,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_tenant_rules(mut self, rules: HashMap<String, RetentionRule>) -> Self {
+        self.tenant_rules = rules;
+        self
+    }
+
+    /// Salt mixed into user id hashes so they can't be reversed by lookup.
+    pub fn with_hash_salt(mut self, salt: impl Into<String>) -> Self {
+        self.hash_salt = salt.into();
+        self
+    }
+
+    fn rule_for(&self, tenant_id: Option<&String>) -> Option<&RetentionRule> {
+        tenant_id
+            .and_then(|tenant| self.tenant_rules.get(tenant))
+            .or(self.default_rule.as_ref())
+    }
+
+    /// The action due for `conversation` at `now`, if any.
+    ///
+    /// Already-anonymized conversations are only ever deleted.
+    pub fn evaluate(
+        &self,
+        conversation: &Conversation,
+        now: DateTime<Utc>,
+    ) -> Option<RetentionAction> {
+        let rule = self.rule_for(conversation.tenant_id.as_ref())?;
+        if now - conversation.updated_at < Duration::days(rule.after_days.into()) {
+            return None;
+        }
+
+        match rule.action {
+            RetentionAction::Anonymize if conversation.anonymized_at.is_some() => None,
+            action => Some(action),
+        }
+    }
+
+    /// Hashes the user id and scrubs PII from every message.
+    pub fn anonymize(&self, conversation: &mut Conversation, now: DateTime<Utc>) {
+        if let Some(user_id) = &conversation.user_id {
+            conversation.user_id = Some(self.hash_user_id(user_id));
+        }
+        for message in &mut conversation.messages {
+            message.content = scrub_pii(&message.content);
+        }
+        for value in conversation.metadata.values_mut() {
+            *value = scrub_pii(value);
+        }
+        // Unreviewed answers of an expired conversation are never released.
+        conversation.drafts.clear();
+        conversation.anonymized_at = Some(now);
+    }
+
+    /// The action due for `annotation` at `now`: the one just taken on its
+    /// conversation, if any, else its tenant's rule aged from when it was
+    /// created, so annotations of expired conversations are swept too.
+    pub fn evaluate_annotation(
+        &self,
+        annotation: &Annotation,
+        conversation_action: Option<RetentionAction>,
+        now: DateTime<Utc>,
+    ) -> Option<RetentionAction> {
+        let action = conversation_action.or_else(|| {
+            let rule = self.rule_for(annotation.tenant_id.as_ref())?;
+            (now - annotation.created_at >= Duration::days(rule.after_days.into()))
+                .then_some(rule.action)
+        })?;
+
+        match action {
+            RetentionAction::Anonymize if annotation.anonymized_at.is_some() => None,
+            action => Some(action),
+        }
+    }
+
+    /// Scrubs PII from the question, answer and note copied into an annotation.
+    pub fn anonymize_annotation(&self, annotation: &mut Annotation, now: DateTime<Utc>) {
+        annotation.question = scrub_pii(&annotation.question);
+        annotation.answer = scrub_pii(&annotation.answer);
+        if let Some(note) = &annotation.note {
+            annotation.note = Some(scrub_pii(note));
+        }
+        annotation.anonymized_at = Some(now);
+    }
+
+    fn hash_user_id(&self, user_id: &str) -> String {
+        let salted = format!("{}:{}", self.hash_salt, user_id);
+        format!(
+            "anon-{}",
+            Uuid::new_v5(&Uuid::NAMESPACE_OID, salted.as_bytes()).simple()
+        )
+    }
+}
+
+/// One conversation the retention run acted on (or would have, in a dry run).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionDecision {
+    pub conversation_id: Uuid,
+    pub tenant_id: Option<String>,
+    pub action: RetentionAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub ran_at: DateTime<Utc>,
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub anonymized: usize,
+    pub deleted: usize,
+    #[serde(default)]
+    pub annotations_anonymized: usize,
+    #[serde(default)]
+    pub annotations_deleted: usize,
+    pub decisions: Vec<RetentionDecision>,
+}
+
+impl RetentionReport {
+    pub fn new(ran_at: DateTime<Utc>, dry_run: bool) -> Self {
+        Self {
+            ran_at,
+            dry_run,
+            scanned: 0,
+            anonymized: 0,
+            deleted: 0,
+            annotations_anonymized: 0,
+            annotations_deleted: 0,
+            decisions: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, conversation: &Conversation, action: RetentionAction) {
+        match action {
+            RetentionAction::Anonymize => self.anonymized += 1,
+            RetentionAction::Delete => self.deleted += 1,
+        }
+        if self.decisions.len() < MAX_REPORTED_DECISIONS {
+            self.decisions.push(RetentionDecision {
+                conversation_id: conversation.id,
+                tenant_id: conversation.tenant_id.clone(),
+                action,
+            });
+        }
+    }
+
+    pub fn record_annotation(&mut self, action: RetentionAction) {
+        match action {
+            RetentionAction::Anonymize => self.annotations_anonymized += 1,
+            RetentionAction::Delete => self.annotations_deleted += 1,
+        }
+    }
+}
+
+/// Replaces email addresses, phone numbers and long digit runs with placeholders.
+pub fn scrub_pii(text: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(|piece| {
+            let token = piece.trim_end();
+            let word =
+                token.trim_matches(|c: char| !c.is_alphanumeric() && !matches!(c, '+' | '@' | '('));
+            let placeholder = if is_email(word) {
+                Some("[EMAIL]")
+            } else if is_phone_or_number(word) {
+                Some("[NUMBER]")
+            } else {
+                None
+            };
+
+            match placeholder {
+                Some(placeholder) => piece.replacen(word, placeholder, 1),
+                None => piece.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn is_email(word: &str) -> bool {
+    match word.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && domain.contains('.') && !domain.starts_with('.')
+        }
+        None => false,
+    }
+}
+
+fn is_phone_or_number(word: &str) -> bool {
+    let digits = word.chars().filter(char::is_ascii_digit).count();
+    digits >= 7
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '(' | ')' | '.' | ' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::MessageRole;
+
+    fn conversation(tenant: Option<&str>, age_days: i64) -> Conversation {
+        let mut conv = Conversation::new();
+        conv.tenant_id = tenant.map(str::to_string);
+        conv.user_id = Some("alice".to_string());
+        conv.add_message(
+            MessageRole::User,
+            "Mail me at alice@example.com or +1-555-123-4567.",
+        );
+        conv.updated_at = Utc::now() - Duration::days(age_days);
+        conv
+    }
+
+    #[test]
+    fn test_evaluate_uses_tenant_rule_over_default() {
+        let policy = RetentionPolicy::new(Some(RetentionRule {
+            after_days: 30,
+            action: RetentionAction::Anonymize,
+        }))
+        .with_tenant_rules(HashMap::from([(
+            "acme".to_string(),
+            RetentionRule {
+                after_days: 7,
+                action: RetentionAction::Delete,
+            },
+        )]));
+        let now = Utc::now();
+
+        assert_eq!(policy.evaluate(&conversation(None, 10), now), None);
+        assert_eq!(
+            policy.evaluate(&conversation(None, 31), now),
+            Some(RetentionAction::Anonymize)
+        );
+        assert_eq!(
+            policy.evaluate(&conversation(Some("acme"), 10), now),
+            Some(RetentionAction::Delete)
+        );
+    }
+
+    #[test]
+    fn test_anonymize_hashes_user_and_scrubs_messages() {
+        let policy = RetentionPolicy::new(None).with_hash_salt("salt");
+        let mut conv = conversation(None, 0);
+        policy.anonymize(&mut conv, Utc::now());
+
+        let user_id = conv.user_id.clone().unwrap();
+        assert!(user_id.starts_with("anon-"));
+        assert_ne!(user_id, "alice");
+        assert_eq!(conv.messages[0].content, "Mail me at [EMAIL] or [NUMBER].");
+        assert!(conv.anonymized_at.is_some());
+        assert_eq!(policy.evaluate(&conv, Utc::now()), None);
+    }
+
+    #[test]
+    fn test_annotations_follow_their_conversation_or_age_out() {
+        let policy = RetentionPolicy::new(Some(RetentionRule {
+            after_days: 30,
+            action: RetentionAction::Anonymize,
+        }));
+        let now = Utc::now();
+        let mut annotation = Annotation::new(
+            Uuid::new_v4(),
+            1,
+            "My email is alice@example.com",
+            "Thanks, alice@example.com is noted.",
+        );
+
+        assert_eq!(policy.evaluate_annotation(&annotation, None, now), None);
+        assert_eq!(
+            policy.evaluate_annotation(&annotation, Some(RetentionAction::Delete), now),
+            Some(RetentionAction::Delete)
+        );
+
+        annotation.created_at = now - Duration::days(31);
+        assert_eq!(
+            policy.evaluate_annotation(&annotation, None, now),
+            Some(RetentionAction::Anonymize)
+        );
+        policy.anonymize_annotation(&mut annotation, now);
+        assert_eq!(annotation.question, "My email is [EMAIL]");
+        assert_eq!(annotation.answer, "Thanks, [EMAIL] is noted.");
+        assert_eq!(policy.evaluate_annotation(&annotation, None, now), None);
+    }
+}
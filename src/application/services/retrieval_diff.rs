@@ -0,0 +1,96 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::SearchResult;
+
+/// How the results of two retrieval runs for one query differ, by chunk.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievalDiff {
+    /// Chunks only the first run returned, in its order.
+    pub only_a: Vec<Uuid>,
+    /// Chunks only the second run returned, in its order.
+    pub only_b: Vec<Uuid>,
+    /// Chunks both returned, in the first run's order.
+    pub common: Vec<RankChange>,
+    /// Shared chunks over all distinct chunks returned; 1.0 when both are empty.
+    pub overlap: f32,
+}
+
+/// Where a chunk both runs returned ranked and scored in each (ranks from 1).
+#[derive(Debug, Clone, Serialize)]
+pub struct RankChange {
+    pub chunk_id: Uuid,
+    pub rank_a: usize,
+    pub rank_b: usize,
+    pub score_a: f32,
+    pub score_b: f32,
+}
+
+pub fn diff_results(a: &[SearchResult], b: &[SearchResult]) -> RetrievalDiff {
+    let ranks_b: HashMap<Uuid, (usize, f32)> = b
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.chunk.id, (i + 1, r.score)))
+        .collect();
+
+    let mut only_a = Vec::new();
+    let mut common = Vec::new();
+    for (i, result) in a.iter().enumerate() {
+        match ranks_b.get(&result.chunk.id) {
+            Some(&(rank_b, score_b)) => common.push(RankChange {
+                chunk_id: result.chunk.id,
+                rank_a: i + 1,
+                rank_b,
+                score_a: result.score,
+                score_b,
+            }),
+            None => only_a.push(result.chunk.id),
+        }
+    }
+    let only_b: Vec<Uuid> = b
+        .iter()
+        .map(|r| r.chunk.id)
+        .filter(|id| !common.iter().any(|c| c.chunk_id == *id))
+        .collect();
+
+    let total = only_a.len() + only_b.len() + common.len();
+    let overlap = if total == 0 {
+        1.0
+    } else {
+        common.len() as f32 / total as f32
+    };
+
+    RetrievalDiff {
+        only_a,
+        only_b,
+        common,
+        overlap,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::DocumentChunk;
+
+    fn result(id: u128, score: f32) -> SearchResult {
+        let mut chunk = DocumentChunk::new(Uuid::nil(), "text", 0);
+        chunk.id = Uuid::from_u128(id);
+        SearchResult { chunk, score }
+    }
+
+    #[test]
+    fn test_diff_reports_moved_and_exclusive_chunks() {
+        let a = vec![result(1, 0.9), result(2, 0.8), result(3, 0.7)];
+        let b = vec![result(2, 0.85), result(4, 0.6)];
+
+        let diff = diff_results(&a, &b);
+        assert_eq!(diff.only_a, vec![Uuid::from_u128(1), Uuid::from_u128(3)]);
+        assert_eq!(diff.only_b, vec![Uuid::from_u128(4)]);
+        assert_eq!(diff.common.len(), 1);
+        assert_eq!((diff.common[0].rank_a, diff.common[0].rank_b), (2, 1));
+        assert_eq!(diff.overlap, 0.25);
+        assert_eq!(diff_results(&[], &[]).overlap, 1.0);
+    }
+}
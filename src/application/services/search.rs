@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::application::RagService;
+use crate::domain::DomainError;
+
+/// Retrieval for document search keyed by collection: the ingestion
+/// `collection` and, when it differs, the knowledge_base tool's
+/// `tool_collection`, so searches naming the tool see what the agent
+/// retrieves. `build` creates the retrieval for each.
+pub async fn search_rag_services<F, Fut>(
+    collection: &str,
+    tool_collection: &str,
+    mut build: F,
+) -> Result<HashMap<String, Arc<RagService>>, DomainError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<Arc<RagService>, DomainError>>,
+{
+    let mut collections = vec![collection];
+    if tool_collection != collection {
+        collections.push(tool_collection);
+    }
+
+    let mut services = HashMap::new();
+    for collection in collections {
+        let rag = build(collection.to_string()).await?;
+        services.insert(collection.to_string(), rag);
+    }
+    Ok(services)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::EmbeddingService;
+    use crate::domain::Embedding;
+    use crate::infrastructure::InMemoryVectorStore;
+    use async_trait::async_trait;
+
+    struct ZeroEmbedding;
+
+    #[async_trait]
+    impl EmbeddingService for ZeroEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Embedding, DomainError> {
+            Ok(Embedding::new(vec![0.0, 0.0]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            Ok(texts
+                .iter()
+                .map(|_| Embedding::new(vec![0.0, 0.0]))
+                .collect())
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    async fn build(_collection: String) -> Result<Arc<RagService>, DomainError> {
+        Ok(Arc::new(RagService::new(
+            Arc::new(ZeroEmbedding),
+            Arc::new(InMemoryVectorStore::new()),
+            1,
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_tool_collection_is_registered_when_it_differs() {
+        let services = search_rag_services("documents", "documents", build)
+            .await
+            .unwrap();
+        assert_eq!(services.len(), 1);
+
+        let services = search_rag_services("documents", "handbook", build)
+            .await
+            .unwrap();
+        assert!(services.contains_key("documents") && services.contains_key("handbook"));
+    }
+}
@@ -0,0 +1,213 @@
+//! Load generator for capacity planning.
+//!
+//! Pushes synthetic chat and ingestion jobs onto the Redis queues of a running stack,
+//! waits for the workers to finish them and reports throughput and latency percentiles.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+
+use ai_agent::api::{queue, JobProducer};
+use ai_agent::infrastructure::config::WorkerConfig;
+use ai_agent::infrastructure::{Config, EmbedDocumentJob, ProcessChatJob, QueueJobStatus};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy)]
+enum JobKind {
+    Chat,
+    Embed,
+}
+
+impl JobKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::Embed => "embed",
+        }
+    }
+}
+
+struct BenchConfig {
+    chat_jobs: usize,
+    embed_jobs: usize,
+    concurrency: usize,
+    job_timeout: Duration,
+}
+
+impl BenchConfig {
+    fn from_env() -> Self {
+        Self {
+            chat_jobs: env_or("BENCH_CHAT_JOBS", 50),
+            embed_jobs: env_or("BENCH_EMBED_JOBS", 20),
+            concurrency: env_or("BENCH_CONCURRENCY", 8),
+            job_timeout: Duration::from_secs(env_or("BENCH_JOB_TIMEOUT_SECONDS", 300)),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+struct Sample {
+    latency: Duration,
+    ok: bool,
+}
+
+async fn run_job(producer: &JobProducer, kind: JobKind, seq: usize, timeout: Duration) -> Sample {
+    let started = Instant::now();
+
+    let pushed = match kind {
+        JobKind::Chat => {
+            let job = ProcessChatJob::new(format!("Benchmark question #{seq}: what is RAG?"));
+            producer
+                .push_chat_job(&job)
+                .await
+                .map(|queued| queued.job_id)
+        }
+        JobKind::Embed => {
+            let content = (0..20)
+                .map(|p| format!("Synthetic paragraph {p} of benchmark document {seq}."))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let job = EmbedDocumentJob::new(Uuid::new_v4(), content);
+            producer.push_embed_job(&job).await
+        }
+    };
+
+    let job_id = match pushed {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::error!(error = %e, kind = kind.as_str(), "failed to push job");
+            return Sample {
+                latency: started.elapsed(),
+                ok: false,
+            };
+        }
+    };
+
+    while started.elapsed() < timeout {
+        match producer.get_job_status(&job_id).await {
+            Ok(Some(result)) if result.status == QueueJobStatus::Completed => {
+                return Sample {
+                    latency: started.elapsed(),
+                    ok: true,
+                };
+            }
+            Ok(Some(result)) if result.status == QueueJobStatus::Failed => break,
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, %job_id, "failed to poll job status"),
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    Sample {
+        latency: started.elapsed(),
+        ok: false,
+    }
+}
+
+async fn run_load(
+    producer: &JobProducer,
+    kind: JobKind,
+    jobs: usize,
+    config: &BenchConfig,
+) -> (Vec<Sample>, Duration) {
+    let semaphore = Arc::new(Semaphore::new(config.concurrency));
+    let started = Instant::now();
+
+    let handles: Vec<_> = (0..jobs)
+        .map(|seq| {
+            let producer = producer.clone();
+            let semaphore = semaphore.clone();
+            let timeout = config.job_timeout;
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                Some(run_job(&producer, kind, seq, timeout).await)
+            })
+        })
+        .collect();
+
+    let mut samples = Vec::with_capacity(jobs);
+    for handle in handles {
+        if let Ok(Some(sample)) = handle.await {
+            samples.push(sample);
+        }
+    }
+
+    (samples, started.elapsed())
+}
+
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((pct / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn report(kind: JobKind, samples: &[Sample], wall: Duration) {
+    let mut latencies: Vec<Duration> = samples.iter().filter(|s| s.ok).map(|s| s.latency).collect();
+    latencies.sort();
+    let failed = samples.len() - latencies.len();
+    let throughput = latencies.len() as f64 / wall.as_secs_f64().max(f64::EPSILON);
+
+    println!(
+        "{:<6} jobs={:<5} ok={:<5} failed={:<5} wall={:>8.2}s throughput={:>7.2}/s p50={:>8.0}ms p90={:>8.0}ms p99={:>8.0}ms max={:>8.0}ms",
+        kind.as_str(),
+        samples.len(),
+        latencies.len(),
+        failed,
+        wall.as_secs_f64(),
+        throughput,
+        percentile(&latencies, 50.0).as_secs_f64() * 1000.0,
+        percentile(&latencies, 90.0).as_secs_f64() * 1000.0,
+        percentile(&latencies, 99.0).as_secs_f64() * 1000.0,
+        latencies.last().copied().unwrap_or_default().as_secs_f64() * 1000.0,
+    );
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "bench=info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    dotenvy::dotenv().ok();
+
+    let config = BenchConfig::from_env();
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".into());
+    let worker = WorkerConfig {
+        result_ttl_seconds: 3600,
+        ..Config::default().worker
+    };
+    let producer = JobProducer::new(queue::create_pool(&redis_url)?, worker);
+
+    println!(
+        "Benchmarking against {redis_url} (concurrency={})",
+        config.concurrency
+    );
+
+    for (kind, jobs) in [
+        (JobKind::Embed, config.embed_jobs),
+        (JobKind::Chat, config.chat_jobs),
+    ] {
+        if jobs == 0 {
+            continue;
+        }
+        let (samples, wall) = run_load(&producer, kind, jobs, &config).await;
+        report(kind, &samples, wall);
+    }
+
+    Ok(())
+}
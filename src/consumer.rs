@@ -0,0 +1,2678 @@
+//! Redis job consumer, run by the `worker` binary or alongside the API in `ai-agent-all`.
+
+use deadpool_redis::redis::{self, AsyncCommands};
+use deadpool_redis::{Config as RedisConfig, Connection, Pool, Runtime};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::application::{
+    export_points, import_points, warm_query_cache, ConfidenceScorer, DocumentService,
+    FreshnessReport, QueryEmbeddingCache, QueryTransform, RagService, RetentionAction,
+    RetentionPolicy, RetentionReport,
+};
+use crate::domain::ports::{
+    ChunkingStrategy, DocumentStore, EmbeddingService, LlmService, QueryLog, TokenUsage,
+    VectorStore,
+};
+use crate::domain::{
+    annotate_line_ranges, annotate_markdown_sections, Annotation, Conversation, Document,
+    DocumentChunk, DomainError, Draft, Message, MessageRole, SearchFilter,
+};
+#[cfg(feature = "chaos")]
+use crate::infrastructure::chaos::{FaultInjector, FaultTarget, FaultyEmbedding, FaultyLlm};
+use crate::infrastructure::config::{DimensionMismatch, EmbeddingConfig, WorkerConfig};
+use crate::infrastructure::{
+    append_source_links, check_store_dimension, chunker_from_config, configure_retrieval,
+    content_type_for_key, count_tokens, document_store_from_config, embedding_from_config, keys,
+    llm_from_config, object_url, probe_dimension, queues, source_links, traced,
+    update_conversation, vector_store_from_config, AgentReply, AppConfig, ChatAgent, ChatSnapshot,
+    CircuitBreaker, CrawlLimits, CrawlSiteJob, DimensionProbe, EmbedDocumentJob,
+    ExportCollectionJob, ExtractorRegistry, GitChanges, GitConnector, ImportCollectionJob,
+    IndexDocumentJob, JobResult, JobSummary, LatencyBreakdown, ModelRelease, ModelRoute,
+    ModelTiers, PartialResponse, PostgresJobHistory, ProcessChatJob, QuarantinedDocument,
+    QueueJobStatus, RedisQueryLog, RedisResponseCache, ReembedCollectionJob, ResponseKey,
+    S3Connector, S3ObjectVersion, S3SyncJob, SafetyAction, StreamUpdate, SwitchableVectorStore,
+    SyncGitRepoJob, VersionCompatibility, WebCrawler, WebPage, PRODUCER_VERSION,
+};
+
+pub type RedisPool = Pool;
+
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const PARTIAL_PUBLISH_INTERVAL: Duration = Duration::from_millis(500);
+const REEMBED_BATCH_SIZE: usize = 64;
+const EXPORT_PAGE_SIZE: usize = 256;
+const RETENTION_SCAN_BATCH: usize = 100;
+const HISTORY_EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+const HISTORY_EXPORT_BATCH: usize = 100;
+/// Completed chat jobs averaged for the queue wait estimate.
+const CHAT_DURATION_SAMPLES: isize = 50;
+const DEGRADED_COUNTER: &str = "chat_degraded_responses_total";
+const FALLBACK_COUNTER: &str = "chat_provider_fallbacks_total";
+const RESPONSE_CACHE_COUNTER: &str = "chat_response_cache_lookups_total";
+const RETRY_COUNTER: &str = "worker_job_retries_total";
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkerError {
+    #[error("Redis pool error: {0}")]
+    Pool(String),
+    #[error("Redis error: {0}")]
+    Redis(String),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, WorkerError>;
+
+pub fn create_pool(redis_url: &str) -> Result<RedisPool> {
+    let cfg = RedisConfig::from_url(redis_url);
+    cfg.create_pool(Some(Runtime::Tokio1))
+        .map_err(|e| WorkerError::Pool(e.to_string()))
+}
+
+pub struct WorkerState {
+    pub redis_pool: RedisPool,
+    pub agent: Arc<ChatAgent>,
+    pub rag: Arc<RagService>,
+    pub embedding: Arc<dyn EmbeddingService>,
+    pub vector_store: Arc<SwitchableVectorStore>,
+    pub config: Arc<AppConfig>,
+    pub chunker: Arc<dyn ChunkingStrategy>,
+    /// Document records from `document_store`, shared with the API when both
+    /// run in one process.
+    pub document_store: Option<Arc<dyn DocumentStore>>,
+    pub git: GitConnector,
+    pub crawler: WebCrawler,
+    /// Receives finished-job records when `worker.history.postgres` is set.
+    pub job_history: Option<PostgresJobHistory>,
+    /// Scores chat answers when `confidence.enabled` is set.
+    pub confidence: Option<ConfidenceScorer>,
+    /// Answers to first messages when `response_cache.enabled` is set.
+    response_cache: Option<RedisResponseCache>,
+    /// Query embeddings shared by every collection's retrieval when
+    /// `rag.warm_queries` is enabled, and the query counts that warm them.
+    query_cache: Option<Arc<QueryEmbeddingCache>>,
+    query_log: Option<Arc<RedisQueryLog>>,
+    qdrant_url: String,
+    /// Shared by every collection when `degraded_mode` is enabled.
+    vector_store_breaker: Option<Arc<CircuitBreaker>>,
+    #[cfg(feature = "chaos")]
+    pub faults: Arc<FaultInjector>,
+}
+
+/// Probes the embedding provider and, per `embedding.probe.on_mismatch`,
+/// adopts the dimension it returns (reporting `true`) or refuses to start.
+async fn check_embedding_dimension(
+    embedding: &dyn EmbeddingService,
+    config: &mut EmbeddingConfig,
+) -> anyhow::Result<bool> {
+    let configured = config.dimension;
+    match probe_dimension(embedding, &config.probe.canary, configured).await {
+        DimensionProbe::Matches(dimension) => {
+            tracing::info!(model = %config.model, dimension, "embedding dimension verified");
+        }
+        DimensionProbe::Mismatch { actual, .. }
+            if config.probe.on_mismatch == DimensionMismatch::Fail =>
+        {
+            anyhow::bail!(
+                "embedding.dimension is {configured} but {} returns {actual}-dimensional vectors",
+                config.model
+            );
+        }
+        DimensionProbe::Mismatch { actual, .. } => {
+            tracing::warn!(
+                model = %config.model,
+                configured,
+                actual,
+                "embedding.dimension does not match the provider; using the provider's dimension"
+            );
+            config.dimension = actual;
+            return Ok(true);
+        }
+        DimensionProbe::Unavailable(e) => {
+            tracing::warn!(
+                error = %e,
+                dimension = configured,
+                "could not probe the embedding provider; trusting embedding.dimension"
+            );
+        }
+    }
+    Ok(false)
+}
+
+impl WorkerState {
+    pub async fn new(
+        redis_pool: RedisPool,
+        qdrant_url: &str,
+        mut config: AppConfig,
+    ) -> anyhow::Result<Self> {
+        let mut embedding = embedding_from_config(&config.config.embedding)?;
+        let embedding_config = &mut config.config.embedding;
+        if embedding_config.probe.enabled
+            && check_embedding_dimension(embedding.as_ref(), embedding_config).await?
+        {
+            // Rebuilt so the service reports the dimension it actually returns.
+            embedding = embedding_from_config(embedding_config)?;
+        }
+        let config = Arc::new(config);
+        #[cfg(feature = "chaos")]
+        let faults = Arc::new(FaultInjector::new(config.config.chaos.clone()));
+
+        #[cfg(feature = "chaos")]
+        let embedding: Arc<dyn EmbeddingService> =
+            Arc::new(FaultyEmbedding::new(embedding, faults.clone()));
+
+        let degraded_mode = &config.config.degraded_mode;
+        let breaker = degraded_mode.enabled.then(|| {
+            Arc::new(CircuitBreaker::new(
+                "vector_store",
+                degraded_mode.failure_threshold,
+                Duration::from_secs(degraded_mode.open_seconds),
+            ))
+        });
+
+        let collection = &config.config.vector_store.collection;
+        let vector_store = Arc::new(SwitchableVectorStore::new(
+            collection.as_str(),
+            vector_store_from_config(
+                qdrant_url,
+                &redis_pool,
+                collection,
+                &config.config,
+                breaker.as_ref(),
+                #[cfg(feature = "chaos")]
+                &faults,
+            )
+            .await?,
+        ));
+        check_store_dimension(embedding.as_ref(), vector_store.as_ref(), collection)?;
+
+        let rerank = &config.config.rag.rerank;
+        let query_transform = config.config.rag.query_transform;
+        let confidence = &config.config.confidence;
+        let needs_llm = rerank.enabled
+            || query_transform != QueryTransform::None
+            || (confidence.enabled && confidence.judge);
+        let rag_llm: Option<Arc<dyn LlmService>> = needs_llm
+            .then(|| llm_from_config(&config.config.llm, &config.config.llm.model))
+            .transpose()?;
+        #[cfg(feature = "chaos")]
+        let rag_llm =
+            rag_llm.map(|llm| Arc::new(FaultyLlm::new(llm, faults.clone())) as Arc<dyn LlmService>);
+        let chunk_titles = &config.config.rag.chunk_titles;
+        let title_llm: Option<Arc<dyn LlmService>> = chunk_titles
+            .enabled
+            .then(|| {
+                let model = chunk_titles
+                    .model
+                    .as_deref()
+                    .unwrap_or(&config.config.llm.model);
+                llm_from_config(&config.config.llm, model)
+            })
+            .transpose()?;
+        #[cfg(feature = "chaos")]
+        let title_llm = title_llm
+            .map(|llm| Arc::new(FaultyLlm::new(llm, faults.clone())) as Arc<dyn LlmService>);
+        let document_store = document_store_from_config(&config.config.document_store);
+        let neighbors = match (&document_store, config.config.rag.expand_neighbors) {
+            (Some(store), true) => Some(store.clone()),
+            (None, true) => {
+                tracing::warn!("rag.expand_neighbors needs a document_store backend; ignoring it");
+                None
+            }
+            _ => None,
+        };
+        let warm_queries = &config.config.rag.warm_queries;
+        let query_cache = warm_queries.enabled.then(|| {
+            Arc::new(
+                QueryEmbeddingCache::new(warm_queries.cache_size)
+                    .with_precision(warm_queries.precision),
+            )
+        });
+        let query_log = warm_queries.enabled.then(|| {
+            Arc::new(RedisQueryLog::new(
+                redis_pool.clone(),
+                warm_queries.window_days,
+            ))
+        });
+        let configure_rag = |rag: RagService| {
+            let rag = configure_retrieval(rag, &config.config, rag_llm.as_ref());
+            let rag = match (&query_cache, &query_log) {
+                (Some(cache), Some(log)) => rag
+                    .with_query_cache(cache.clone())
+                    .with_query_log(log.clone()),
+                _ => rag,
+            };
+            let rag = match &neighbors {
+                Some(store) => rag.with_neighbor_expansion(store.clone()),
+                None => rag,
+            };
+            match &title_llm {
+                Some(llm) => rag.with_chunk_titles(llm.clone(), chunk_titles.max_chars),
+                None => rag,
+            }
+        };
+
+        let rag = Arc::new(configure_rag(RagService::new(
+            embedding.clone(),
+            vector_store.clone(),
+            config.config.rag.top_k,
+        )));
+
+        // The agent's knowledge_base tool may search a different collection than ingestion.
+        let tool_collection = config
+            .config
+            .tools
+            .knowledge_base
+            .collection(&config.config.vector_store);
+        let agent_rag = if tool_collection == config.config.vector_store.collection {
+            rag.clone()
+        } else {
+            let tool_store = vector_store_from_config(
+                qdrant_url,
+                &redis_pool,
+                tool_collection,
+                &config.config,
+                breaker.as_ref(),
+                #[cfg(feature = "chaos")]
+                &faults,
+            )
+            .await?;
+            check_store_dimension(embedding.as_ref(), tool_store.as_ref(), tool_collection)?;
+            Arc::new(configure_rag(RagService::new(
+                embedding.clone(),
+                tool_store,
+                config.config.rag.top_k,
+            )))
+        };
+        let agent = ChatAgent::new(agent_rag, &config)?;
+        let agent = Arc::new(match &breaker {
+            Some(breaker) => agent.with_degraded_mode(breaker.clone()),
+            None => agent,
+        });
+
+        let confidence = confidence.enabled.then(|| {
+            let scorer = ConfidenceScorer::new(
+                confidence.weights,
+                confidence.high_threshold,
+                confidence.low_threshold,
+            );
+            match &rag_llm {
+                Some(llm) if confidence.judge => scorer.with_judge(llm.clone()),
+                _ => scorer,
+            }
+        });
+
+        let response_cache = config.config.response_cache.enabled.then(|| {
+            RedisResponseCache::new(redis_pool.clone(), config.config.response_cache.ttl_seconds)
+        });
+
+        Ok(Self {
+            redis_pool,
+            agent,
+            rag,
+            embedding,
+            vector_store,
+            chunker: chunker_from_config(&config.config.rag),
+            document_store,
+            git: GitConnector::new(&config.config.git.checkout_dir),
+            crawler: WebCrawler::new(
+                &config.config.crawl.user_agent,
+                Duration::from_secs(config.config.crawl.timeout_seconds),
+            )?,
+            job_history: None,
+            confidence,
+            response_cache,
+            query_cache,
+            query_log,
+            config,
+            qdrant_url: qdrant_url.to_string(),
+            vector_store_breaker: breaker,
+            #[cfg(feature = "chaos")]
+            faults,
+        })
+    }
+
+    /// Writes finished-job records to Postgres in the background.
+    pub fn with_job_history(mut self, history: PostgresJobHistory) -> Self {
+        self.job_history = Some(history);
+        self
+    }
+
+    async fn open_collection(
+        &self,
+        collection: &str,
+    ) -> std::result::Result<Arc<dyn VectorStore>, DomainError> {
+        vector_store_from_config(
+            &self.qdrant_url,
+            &self.redis_pool,
+            collection,
+            &self.config.config,
+            self.vector_store_breaker.as_ref(),
+            #[cfg(feature = "chaos")]
+            &self.faults,
+        )
+        .await
+    }
+
+    /// Follows the Redis active-collection pointer flipped by the admin API.
+    async fn sync_active_collection(&self, conn: &mut Connection) -> Result<()> {
+        let active: Option<String> = conn
+            .get(keys::ACTIVE_COLLECTION)
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+        let Some(active) = active else {
+            return Ok(());
+        };
+        if active == self.vector_store.active_collection() {
+            return Ok(());
+        }
+
+        match self.open_collection(&active).await {
+            Ok(store) => self.vector_store.switch(active, store),
+            Err(e) => {
+                tracing::error!(error = %e, collection = %active, "failed to switch collection")
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_connection(&self) -> Result<Connection> {
+        #[cfg(feature = "chaos")]
+        self.faults
+            .inject(FaultTarget::Redis)
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+        self.redis_pool
+            .get()
+            .await
+            .map_err(|e| WorkerError::Pool(e.to_string()))
+    }
+}
+
+pub struct JobConsumer {
+    state: Arc<WorkerState>,
+    concurrency: usize,
+}
+
+impl JobConsumer {
+    pub fn new(state: WorkerState, concurrency: usize) -> Self {
+        Self {
+            state: Arc::new(state),
+            concurrency,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        tracing::info!(concurrency = self.concurrency, "consumer started");
+
+        if self.state.config.config.retention.enabled {
+            tokio::spawn(retention_loop(self.state.clone()));
+        }
+        if self.state.config.config.rag.freshness.report_enabled {
+            tokio::spawn(freshness_loop(self.state.clone()));
+        }
+        tokio::spawn(attachment_sweep_loop(self.state.clone()));
+        if let Some(history) = &self.state.job_history {
+            tokio::spawn(history_export_loop(self.state.clone(), history.clone()));
+        }
+        if let (Some(cache), Some(log)) = (&self.state.query_cache, &self.state.query_log) {
+            tokio::spawn(query_warm_loop(
+                self.state.clone(),
+                cache.clone(),
+                log.clone(),
+            ));
+        }
+
+        loop {
+            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            let state = self.state.clone();
+
+            tokio::spawn(async move {
+                let _permit = permit;
+                if let Err(e) = process_next_job(&state).await {
+                    tracing::error!(error = %e, "job failed");
+                }
+            });
+
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+    }
+}
+
+/// Stores a job's status with its queue's result TTL.
+///
+/// Terminal statuses are also summarised into the job history list.
+async fn set_job_status(
+    conn: &mut Connection,
+    worker: &WorkerConfig,
+    queue: &str,
+    status: &JobResult,
+) -> Result<()> {
+    let json = serde_json::to_string(status)?;
+    let key = keys::job_status(&status.job_id);
+    traced(
+        "SETEX",
+        &key,
+        conn.set_ex::<_, _, ()>(&key, &json, worker.result_ttl(queue)),
+    )
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+    if status.status == QueueJobStatus::Processing {
+        // Only the first processing update marks the start.
+        let key = keys::job_started(&status.job_id);
+        traced(
+            "SET",
+            &key,
+            redis::cmd("SET")
+                .arg(&key)
+                .arg(chrono::Utc::now().timestamp_millis())
+                .arg("NX")
+                .arg("EX")
+                .arg(worker.result_ttl(queue))
+                .query_async::<()>(conn),
+        )
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    }
+    if status.status.is_terminal() {
+        record_history(conn, worker, queue, status).await?;
+    }
+    Ok(())
+}
+
+async fn record_history(
+    conn: &mut Connection,
+    worker: &WorkerConfig,
+    queue: &str,
+    status: &JobResult,
+) -> Result<()> {
+    let started_key = keys::job_started(&status.job_id);
+    let (started_at,): (Option<i64>,) = traced(
+        "MULTI",
+        &started_key,
+        redis::pipe()
+            .atomic()
+            .get(&started_key)
+            .del(&started_key)
+            .ignore()
+            .query_async(conn),
+    )
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    let duration_ms = started_at
+        .zip(status.completed_at)
+        .map(|(started, completed)| (completed.timestamp_millis() - started).max(0) as u64);
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    // Only completed chats feed the wait estimate; failures end early.
+    let is_chat = queue == queues::CHAT_QUEUE || queue == queues::CHAT_LOW_QUEUE;
+    if let Some(duration_ms) =
+        duration_ms.filter(|_| is_chat && status.status == QueueJobStatus::Completed)
+    {
+        pipe.lpush(keys::CHAT_DURATIONS, duration_ms)
+            .ignore()
+            .ltrim(keys::CHAT_DURATIONS, 0, CHAT_DURATION_SAMPLES - 1)
+            .ignore();
+    }
+
+    let max_entries = worker.history.max_entries;
+    let mut summary = JobSummary::new(queue, status);
+    summary.duration_ms = duration_ms;
+    let summary = serde_json::to_string(&summary)?;
+    if max_entries > 0 {
+        pipe.lpush(keys::JOB_HISTORY, &summary)
+            .ignore()
+            .ltrim(keys::JOB_HISTORY, 0, max_entries as isize - 1)
+            .ignore();
+    }
+    if worker.history.postgres {
+        pipe.lpush(keys::JOB_HISTORY_OUTBOX, &summary).ignore();
+    }
+    traced("MULTI", keys::JOB_HISTORY, pipe.query_async::<()>(conn))
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))
+}
+
+/// Drains the job history outbox into Postgres every `HISTORY_EXPORT_INTERVAL`.
+async fn history_export_loop(state: Arc<WorkerState>, history: PostgresJobHistory) {
+    loop {
+        if let Err(e) = export_job_history(&state, &history).await {
+            tracing::error!(error = %e, "job history export failed");
+        }
+        tokio::time::sleep(HISTORY_EXPORT_INTERVAL).await;
+    }
+}
+
+/// Moves batches of outbox records to Postgres until the outbox is empty.
+///
+/// A batch that fails to insert goes back to the outbox to be retried.
+async fn export_job_history(state: &WorkerState, history: &PostgresJobHistory) -> Result<()> {
+    let mut conn = state.get_connection().await?;
+    loop {
+        let batch: Vec<String> = traced(
+            "RPOP",
+            keys::JOB_HISTORY_OUTBOX,
+            conn.rpop(
+                keys::JOB_HISTORY_OUTBOX,
+                std::num::NonZeroUsize::new(HISTORY_EXPORT_BATCH),
+            ),
+        )
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let records: Vec<JobSummary> = batch
+            .iter()
+            .filter_map(|entry| match serde_json::from_str(entry) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    tracing::warn!(error = %e, "dropping malformed job history record");
+                    None
+                }
+            })
+            .collect();
+        if let Err(e) = history.insert(&records).await {
+            traced(
+                "RPUSH",
+                keys::JOB_HISTORY_OUTBOX,
+                conn.rpush::<_, _, ()>(keys::JOB_HISTORY_OUTBOX, &batch),
+            )
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            tracing::error!(error = %e, records = records.len(), "job history insert failed, will retry");
+            return Ok(());
+        }
+        tracing::debug!(records = records.len(), "exported job history");
+    }
+}
+
+/// Archives a job cancelled through the API, which already stored its status.
+async fn archive_cancelled(
+    conn: &mut Connection,
+    worker: &WorkerConfig,
+    queue: &str,
+    job_id: Uuid,
+) -> Result<()> {
+    record_history(conn, worker, queue, &JobResult::cancelled(job_id)).await
+}
+
+/// Marks a job processing unless it was cancelled, returning whether it
+/// was started. The cancellation flag is watched (Redis `WATCH`), so a
+/// cancel landing between the check and the update is seen.
+async fn start_job(
+    conn: &mut Connection,
+    worker: &WorkerConfig,
+    queue: &str,
+    job_id: Uuid,
+) -> Result<bool> {
+    let cancelled_key = keys::job_cancelled(&job_id);
+    let status_key = keys::job_status(&job_id);
+    let started_key = keys::job_started(&job_id);
+    let json = serde_json::to_string(&JobResult::processing(job_id))?;
+    let ttl = worker.result_ttl(queue);
+
+    loop {
+        traced(
+            "WATCH",
+            &cancelled_key,
+            redis::cmd("WATCH")
+                .arg(&cancelled_key)
+                .query_async::<()>(&mut *conn),
+        )
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        if is_cancelled(conn, job_id).await? {
+            traced(
+                "UNWATCH",
+                &cancelled_key,
+                redis::cmd("UNWATCH").query_async::<()>(&mut *conn),
+            )
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            return Ok(false);
+        }
+
+        // EXEC answers nil when the job was cancelled since WATCH. Only the
+        // first processing update marks the start.
+        let started: Option<()> = traced(
+            "MULTI",
+            &status_key,
+            redis::pipe()
+                .atomic()
+                .set_ex(&status_key, &json, ttl)
+                .ignore()
+                .cmd("SET")
+                .arg(&started_key)
+                .arg(chrono::Utc::now().timestamp_millis())
+                .arg("NX")
+                .arg("EX")
+                .arg(ttl)
+                .ignore()
+                .query_async(&mut *conn),
+        )
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        if started.is_some() {
+            return Ok(true);
+        }
+    }
+}
+
+async fn is_cancelled(conn: &mut Connection, job_id: Uuid) -> Result<bool> {
+    let key = keys::job_cancelled(&job_id);
+    traced("EXISTS", &key, conn.exists(&key))
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))
+}
+
+/// Resolves once the job's cancellation flag is set.
+async fn wait_for_cancellation(state: &WorkerState, job_id: Uuid) -> Result<()> {
+    loop {
+        tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+        let mut conn = state.get_connection().await?;
+        if is_cancelled(&mut conn, job_id).await? {
+            return Ok(());
+        }
+    }
+}
+
+async fn process_next_job(state: &WorkerState) -> Result<()> {
+    let mut conn = state.get_connection().await?;
+
+    let result: Option<(String, String)> = traced(
+        "BRPOP",
+        queues::CHAT_QUEUE,
+        conn.brpop(
+            &[
+                queues::CHAT_QUEUE,
+                queues::EMBED_QUEUE,
+                queues::INDEX_QUEUE,
+                queues::REEMBED_QUEUE,
+                queues::GIT_SYNC_QUEUE,
+                queues::CRAWL_QUEUE,
+                queues::S3_SYNC_QUEUE,
+                queues::EXPORT_QUEUE,
+                queues::IMPORT_QUEUE,
+                queues::CHAT_LOW_QUEUE,
+            ],
+            1.0,
+        ),
+    )
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+    if let Some((queue, job_json)) = result {
+        state.sync_active_collection(&mut conn).await?;
+
+        match queue.as_str() {
+            queues::CHAT_QUEUE | queues::CHAT_LOW_QUEUE => {
+                process_chat_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::EMBED_QUEUE => {
+                process_embed_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::INDEX_QUEUE => {
+                process_index_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::REEMBED_QUEUE => {
+                process_reembed_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::GIT_SYNC_QUEUE => {
+                process_git_sync_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::CRAWL_QUEUE => {
+                process_crawl_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::S3_SYNC_QUEUE => {
+                process_s3_sync_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::EXPORT_QUEUE => {
+                process_export_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            queues::IMPORT_QUEUE => {
+                process_import_job(
+                    state,
+                    decode_job(&mut conn, state, &queue, &job_json).await?,
+                )
+                .await?;
+            }
+            _ => tracing::warn!(queue, "unknown queue"),
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a job payload, flagging producer version skew from rolling deploys.
+///
+/// Payloads that fail to decode are marked failed (when their job id can be
+/// recovered) instead of leaving the job pending forever.
+async fn decode_job<T: DeserializeOwned>(
+    conn: &mut Connection,
+    state: &WorkerState,
+    queue: &str,
+    job_json: &str,
+) -> Result<T> {
+    let payload: serde_json::Value = serde_json::from_str(job_json)?;
+    let producer_version = payload
+        .get("producer_version")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    match VersionCompatibility::of(producer_version.as_deref()) {
+        VersionCompatibility::Same => {}
+        compatibility => tracing::warn!(
+            queue,
+            ?compatibility,
+            producer_version = producer_version.as_deref().unwrap_or("unknown"),
+            worker_version = PRODUCER_VERSION,
+            "job produced by a different version"
+        ),
+    }
+
+    let job_id = payload
+        .get("job_id")
+        .and_then(|v| v.as_str())
+        .and_then(|id| id.parse::<Uuid>().ok());
+
+    match serde_json::from_value(payload) {
+        Ok(job) => Ok(job),
+        Err(e) => {
+            tracing::error!(
+                queue,
+                error = %e,
+                producer_version = producer_version.as_deref().unwrap_or("unknown"),
+                worker_version = PRODUCER_VERSION,
+                "incompatible job payload"
+            );
+            if let Some(job_id) = job_id {
+                let message = format!(
+                    "Incompatible job payload from producer {}: {e}",
+                    producer_version.as_deref().unwrap_or("unknown")
+                );
+                set_job_status(
+                    conn,
+                    &state.config.config.worker,
+                    queue,
+                    &JobResult::failed(job_id, message),
+                )
+                .await?;
+            }
+            Err(e.into())
+        }
+    }
+}
+
+async fn process_chat_job(state: &WorkerState, job: ProcessChatJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, conversation_id = ?job.conversation_id, "processing chat");
+    let queue_wait = job
+        .enqueued_at
+        .and_then(|at| (chrono::Utc::now() - at).to_std().ok())
+        .unwrap_or_default();
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+    let conv_ttl = worker.conversation_ttl_seconds;
+
+    if !start_job(&mut conn, worker, queues::CHAT_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "chat cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::CHAT_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let mut conversation = match &job.replay {
+        // The recorded conversation already ends with the message.
+        Some(replay) => replay.conversation.clone(),
+        None => {
+            let conversation_id = job.conversation_id.unwrap_or_else(Uuid::new_v4);
+            let mut conversation = load_conversation(&mut conn, &conversation_id).await?;
+            if !conversation.is_accessible_by(job.tenant_id.as_deref()) {
+                // Otherwise the chat would search and extend another tenant's data.
+                tracing::warn!(job_id = %job.job_id, %conversation_id, "chat tenant does not own the conversation");
+                set_job_status(
+                    &mut conn,
+                    worker,
+                    queues::CHAT_QUEUE,
+                    &JobResult::failed(job.job_id, "conversation belongs to another tenant"),
+                )
+                .await?;
+                return Ok(());
+            }
+            if conversation.tenant_id.is_none() {
+                conversation.tenant_id = job.tenant_id.clone();
+            }
+            if conversation.user_id.is_none() {
+                conversation.user_id = job.user_id.clone();
+            }
+            conversation.push_message(
+                Message::new(MessageRole::User, &job.message).with_channel(job.channel.as_str()),
+            );
+            conversation
+        }
+    };
+    let conversation_id = conversation.id;
+
+    // Get history excluding the message we just added
+    let history: Vec<Message> = conversation
+        .messages
+        .iter()
+        .take(conversation.messages.len().saturating_sub(1))
+        .cloned()
+        .collect();
+
+    // Drafts are released only once approved, so nothing is streamed meanwhile.
+    let drafted = job.replay.is_none()
+        && state
+            .config
+            .config
+            .drafts
+            .requires_approval(job.agent_id.as_deref(), job.channel);
+    let release = pinned_release(state, &mut conn, job.agent_id.as_deref()).await?;
+    let partial = PartialResponse::new();
+    let publish = async {
+        if drafted {
+            std::future::pending().await
+        } else {
+            publish_partial(state, job.job_id, &partial).await
+        }
+    };
+    let response = tokio::select! {
+        response = run_agent(state, &job, &conversation, &history, &partial, release) => response,
+        Err(e) = publish => return Err(e),
+        cancelled = wait_for_cancellation(state, job.job_id) => {
+            cancelled?;
+            tracing::info!(job_id = %job.job_id, "chat cancelled during processing");
+            archive_cancelled(&mut conn, worker, queues::CHAT_QUEUE, job.job_id).await?;
+            return Ok(());
+        }
+    };
+
+    // The client may have given up while the agent was finishing.
+    if is_cancelled(&mut conn, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "chat cancelled, discarding response");
+        archive_cancelled(&mut conn, worker, queues::CHAT_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    match response {
+        Ok(reply) => {
+            let post_processing = Instant::now();
+            let links = if job.include_links {
+                let metadata: Vec<_> = reply
+                    .sources
+                    .iter()
+                    .map(|r| r.chunk.metadata.clone())
+                    .collect();
+                source_links(&metadata)
+            } else {
+                Vec::new()
+            };
+            let answer = job.answer_options();
+            let truncated = answer.truncate(&reply.response);
+            let confidence = match &state.confidence {
+                Some(scorer) => Some(scorer.score(&job.message, &truncated, &reply.sources).await),
+                None => None,
+            };
+            // Link titles and URLs come from chunk metadata, so the list is cleaned
+            // with the answer. Renderers escape text and banners are operator config.
+            let result = state
+                .config
+                .config
+                .sanitize
+                .sanitize(&append_source_links(&truncated, &links));
+            let content = result.clone();
+
+            // Rendering, notices and banners are for the reader only; history stays Markdown.
+            // A channel with its own markup (Slack, Telegram, SMS) overrides `format`.
+            let formatter = state.config.config.channels.formatter(job.channel);
+            let mut rendered = match &formatter {
+                Some(formatter) => formatter.format(&result),
+                None => answer.render(&result),
+            };
+            if reply.fallback {
+                ::metrics::counter!(FALLBACK_COUNTER, "provider" => reply.provider.as_str())
+                    .increment(1);
+            }
+            if reply.degraded {
+                ::metrics::counter!(DEGRADED_COUNTER).increment(1);
+                rendered = format!("{rendered}\n\n{}", state.config.config.degraded_mode.notice);
+            }
+            let result = state.config.config.banners.decorate(
+                &rendered,
+                job.agent_id.as_deref(),
+                job.channel,
+            );
+
+            let latency =
+                LatencyBreakdown::new(queue_wait, reply.timings, post_processing.elapsed());
+            latency.record();
+            let estimated = reply.usage.is_empty() && !reply.cached;
+            let usage = if estimated {
+                estimate_chat_tokens(&job.message, &history, &reply)
+            } else {
+                reply.usage
+            };
+            let cost = state
+                .config
+                .config
+                .llm
+                .pricing
+                .get(&reply.model)
+                .map(|price| price.cost(&usage));
+
+            let messages = formatter.map(|formatter| formatter.split(&result));
+
+            let mut result = serde_json::json!({
+                "response": result,
+                "sources": links,
+                "conversation_id": conversation_id,
+                "latency": latency,
+                "confidence": confidence,
+                "degraded": reply.degraded,
+                "served_by": {
+                    "provider": reply.provider.as_str(),
+                    "model": reply.model,
+                    "fallback": reply.fallback,
+                },
+                "cached": reply.cached,
+                "usage": {
+                    "prompt_tokens": usage.prompt_tokens,
+                    "completion_tokens": usage.completion_tokens,
+                    "total_tokens": usage.total(),
+                    "estimated": estimated,
+                    "cost": cost,
+                },
+            });
+            if let Some(messages) = messages {
+                result["messages"] = serde_json::json!(messages);
+            }
+            if let Some((name, release)) = release {
+                result["model_release"] =
+                    serde_json::json!({ "name": name, "version": release.version });
+            }
+            match &job.replay {
+                Some(replay) => {
+                    result["replay_of"] = serde_json::json!(replay.of_job_id);
+                    result["original_response"] = serde_json::json!(replay.original_response);
+                }
+                None if worker.replay.enabled => {
+                    let snapshot = ChatSnapshot {
+                        job: job.clone(),
+                        conversation: conversation.clone(),
+                        sources: reply.sources.clone(),
+                        response: content.clone(),
+                    };
+                    save_chat_snapshot(&mut conn, &snapshot, worker.replay.ttl_seconds).await?;
+                }
+                None => {}
+            }
+
+            // Replays are for debugging: the conversation and usage stay untouched.
+            let replayed = job.replay.is_some();
+            if drafted {
+                let draft = Draft::new(job.job_id, content, result);
+                result = serde_json::json!({
+                    "draft_id": draft.id,
+                    "status": "pending_approval",
+                    "conversation_id": conversation_id,
+                });
+                tracing::info!(job_id = %job.job_id, draft_id = %draft.id, "chat answer held for approval");
+                conversation.drafts.push(draft);
+            } else if !replayed {
+                conversation.push_message(
+                    Message::new(MessageRole::Assistant, content)
+                        .with_channel(job.channel.as_str()),
+                );
+            }
+            if !replayed {
+                save_chat_turn(&mut conn, &conversation, history.len(), drafted, conv_ttl).await?;
+                record_chat_usage(&mut conn, &job, &conversation_id, &usage, cost, conv_ttl)
+                    .await?;
+            }
+
+            let completed = JobResult::completed(job.job_id, result);
+            let completed = if replayed {
+                completed
+            } else {
+                completed.with_usage(conversation.tenant_id.clone(), Some(usage.total()))
+            };
+
+            set_job_status(&mut conn, worker, queues::CHAT_QUEUE, &completed).await?;
+        }
+        Err(e) => {
+            let retry = job.next_attempt();
+            if retry_later(
+                state,
+                &mut conn,
+                queues::CHAT_QUEUE,
+                job.job_id,
+                &e,
+                job.attempt,
+                &retry,
+            )
+            .await?
+            {
+                return Ok(());
+            }
+            set_job_status(
+                &mut conn,
+                worker,
+                queues::CHAT_QUEUE,
+                &JobResult::failed(job.job_id, e.to_string())
+                    .with_usage(conversation.tenant_id.clone(), None),
+            )
+            .await?;
+        }
+    }
+
+    tracing::info!(job_id = %job.job_id, "chat completed");
+    Ok(())
+}
+
+/// Requeues `retry` (the failed job with its attempt bumped) after a backoff
+/// when `error` is retryable and `worker.retry.max_attempts` allows it.
+/// Returns whether the job was requeued; its status goes back to pending.
+async fn retry_later<J: Serialize>(
+    state: &WorkerState,
+    conn: &mut Connection,
+    queue: &'static str,
+    job_id: Uuid,
+    error: &DomainError,
+    attempt: u32,
+    retry: &J,
+) -> Result<bool> {
+    let worker = &state.config.config.worker;
+    if !error.is_retryable() || attempt + 1 >= worker.retry.max_attempts {
+        return Ok(false);
+    }
+
+    let payload = serde_json::to_string(retry)?;
+    let delay = worker.retry.delay(attempt, error.kind());
+    tracing::warn!(
+        %job_id,
+        queue,
+        attempt = attempt + 1,
+        delay_ms = delay.as_millis() as u64,
+        kind = ?error.kind(),
+        error = %error,
+        "job failed, retrying"
+    );
+    ::metrics::counter!(RETRY_COUNTER, "queue" => queue).increment(1);
+    set_job_status(conn, worker, queue, &JobResult::pending(job_id)).await?;
+
+    let pool = state.redis_pool.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let pushed = match pool.get().await {
+            Ok(mut conn) => traced("LPUSH", queue, conn.lpush::<_, _, ()>(queue, payload))
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = pushed {
+            tracing::error!(%job_id, queue, error = %e, "failed to requeue job");
+        }
+    });
+    Ok(true)
+}
+
+/// Rough LLM tokens of a chat turn: history, message and retrieved context in,
+/// answer out, counted with the cl100k tokenizer. Used when the provider
+/// reports no usage.
+fn estimate_chat_tokens(message: &str, history: &[Message], reply: &AgentReply) -> TokenUsage {
+    let prompt = history
+        .iter()
+        .map(|m| count_tokens(&m.content))
+        .chain(reply.sources.iter().map(|r| count_tokens(&r.chunk.content)))
+        .sum::<usize>()
+        + count_tokens(message);
+    TokenUsage::new(prompt as u64, count_tokens(&reply.response) as u64)
+}
+
+/// Adds a chat's usage to its conversation's counters, which expire with
+/// the conversation, and to its API key's.
+async fn record_chat_usage(
+    conn: &mut Connection,
+    job: &ProcessChatJob,
+    conversation_id: &Uuid,
+    usage: &TokenUsage,
+    cost: Option<f64>,
+    ttl: u64,
+) -> Result<()> {
+    let mut counters = vec![(keys::conversation_usage(conversation_id), Some(ttl))];
+    if let Some(api_key_id) = &job.api_key_id {
+        counters.push((keys::api_key_usage(api_key_id), None));
+    }
+
+    let mut pipe = redis::pipe();
+    for (key, ttl) in &counters {
+        pipe.hincr(key, "prompt_tokens", usage.prompt_tokens)
+            .ignore()
+            .hincr(key, "completion_tokens", usage.completion_tokens)
+            .ignore()
+            .hincr(key, "chats", 1)
+            .ignore();
+        if let Some(cost) = cost {
+            pipe.cmd("HINCRBYFLOAT")
+                .arg(key)
+                .arg("cost")
+                .arg(cost)
+                .ignore();
+        }
+        if let Some(ttl) = ttl {
+            pipe.expire(key, *ttl as i64).ignore();
+        }
+    }
+    traced("PIPELINE", &counters[0].0, pipe.query_async::<()>(conn))
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))
+}
+
+async fn run_agent(
+    state: &WorkerState,
+    job: &ProcessChatJob,
+    conversation: &Conversation,
+    history: &[Message],
+    partial: &PartialResponse,
+    release: Option<(&str, &ModelRelease)>,
+) -> std::result::Result<AgentReply, DomainError> {
+    #[cfg(feature = "chaos")]
+    state.faults.inject(FaultTarget::Llm).await?;
+
+    let glossary = state
+        .config
+        .config
+        .glossary
+        .instructions(&job.message, job.agent_id.as_deref());
+    let instructions: Vec<String> = conversation
+        .instructions()
+        .into_iter()
+        .chain(job.answer_options().instructions())
+        .chain(glossary)
+        .collect();
+    let instructions = (!instructions.is_empty()).then(|| instructions.join("\n\n"));
+    let filter = SearchFilter {
+        conversation_id: Some(conversation.id),
+        tenant_id: conversation.tenant_id.clone(),
+        ..Default::default()
+    };
+
+    let pinned = release.map(|(name, release)| {
+        tracing::info!(
+            job_id = %job.job_id,
+            model_name = name,
+            version = release.version,
+            model = %release.model,
+            "chat model release pinned"
+        );
+        let agent = state
+            .agent
+            .as_ref()
+            .clone()
+            .with_model(release.model.clone());
+        match release.system_prompt(&state.config.prompts) {
+            Some(prompt) => agent.with_system_prompt(prompt),
+            None => {
+                tracing::warn!(
+                    model_name = name,
+                    prompt_version = ?release.prompt_version,
+                    "pinned prompt version missing from prompts.yaml, keeping agent.system"
+                );
+                agent
+            }
+        }
+    });
+    let base = pinned.as_ref().unwrap_or(state.agent.as_ref());
+
+    let routed;
+    let agent = match state
+        .config
+        .config
+        .model_routing
+        .for_agent(job.agent_id.as_deref())
+    {
+        Some(tiers) => {
+            let route = route_model(state, tiers, &job.message, &filter, base.model()).await;
+            tracing::info!(
+                job_id = %job.job_id,
+                agent_id = job.agent_id.as_deref().unwrap_or("default"),
+                model = %route.model,
+                tier = ?route.tier,
+                reason = route.reason,
+                "chat model routed"
+            );
+            routed = base.clone().with_model(route.model);
+            &routed
+        }
+        None => base,
+    };
+
+    let pinned_sources;
+    let agent = match job.replay.as_ref().and_then(|r| r.pinned_sources.clone()) {
+        Some(sources) => {
+            pinned_sources = agent.clone().with_pinned_sources(sources);
+            &pinned_sources
+        }
+        None => agent,
+    };
+
+    let tuned;
+    let agent = if job.generation.is_empty() {
+        agent
+    } else {
+        tuned = agent.clone().with_generation(&job.generation);
+        &tuned
+    };
+
+    // Only first messages are cached: later answers depend on the history. The
+    // key's retrieval is an extra embedding call and vector search per first
+    // message, so it only runs with response_cache enabled.
+    let cache = state
+        .response_cache
+        .as_ref()
+        .filter(|_| history.is_empty() && job.replay.is_none() && job.generation.is_empty());
+    let cache_key = match cache {
+        Some(_) => match agent.knowledge_base_ids(&job.message, filter.clone()).await {
+            Ok(chunk_ids) => Some(
+                ResponseKey {
+                    agent_id: job.agent_id.as_deref(),
+                    tenant_id: conversation.tenant_id.as_deref(),
+                    model: agent.model(),
+                    system_prompt: agent.system_prompt(),
+                    instructions: instructions.as_deref(),
+                    message: &job.message,
+                }
+                .with_chunks(&chunk_ids),
+            ),
+            Err(e) => {
+                tracing::warn!(job_id = %job.job_id, error = %e, "retrieval for response cache failed");
+                None
+            }
+        },
+        None => None,
+    };
+    if let Some((cache, key)) = cache.zip(cache_key.as_ref()) {
+        match cache.get(key).await {
+            Ok(Some(reply)) => {
+                ::metrics::counter!(RESPONSE_CACHE_COUNTER, "result" => "hit").increment(1);
+                tracing::info!(job_id = %job.job_id, "chat answered from response cache");
+                return Ok(reply);
+            }
+            Ok(None) => {
+                ::metrics::counter!(RESPONSE_CACHE_COUNTER, "result" => "miss").increment(1)
+            }
+            Err(e) => {
+                tracing::warn!(job_id = %job.job_id, error = %e, "response cache lookup failed")
+            }
+        }
+    }
+
+    let reply = agent
+        .chat_streaming(&job.message, history, instructions, filter, partial)
+        .await?;
+    // Degraded answers lack the knowledge base; fallbacks may be weaker.
+    if let Some((cache, key)) = cache.zip(cache_key) {
+        if !reply.degraded && !reply.fallback {
+            if let Err(e) = cache.put(&key, &reply).await {
+                tracing::warn!(job_id = %job.job_id, error = %e, "failed to cache response");
+            }
+        }
+    }
+    Ok(reply)
+}
+
+/// Picks the fast or premium model for `message`, looking up its best
+/// retrieval score when the tiers require one. A failed lookup counts as low
+/// confidence; `base_model` is the premium model when the tiers name none.
+async fn route_model(
+    state: &WorkerState,
+    tiers: &ModelTiers,
+    message: &str,
+    filter: &SearchFilter,
+    base_model: &str,
+) -> ModelRoute {
+    let top_score = if tiers.needs_retrieval_check(message) {
+        state
+            .rag
+            .top_score(message, filter)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "retrieval check for model routing failed");
+                None
+            })
+    } else {
+        None
+    };
+    tiers.route(message, top_score, base_model)
+}
+
+/// The model registry release the agent answers with: the one rolled out
+/// through the admin API, else the latest. `None` when the agent has no
+/// logical model.
+async fn pinned_release<'a>(
+    state: &'a WorkerState,
+    conn: &mut Connection,
+    agent_id: Option<&str>,
+) -> Result<Option<(&'a str, &'a ModelRelease)>> {
+    let registry = &state.config.config.model_registry;
+    let Some(name) = registry.model_for_agent(agent_id) else {
+        return Ok(None);
+    };
+    let active: Option<u32> = conn
+        .get(keys::model_active_release(name))
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+    let release = registry.resolve(name, active);
+    if release.is_none() {
+        tracing::warn!(
+            model_name = name,
+            "model registry has no releases for model"
+        );
+    }
+    Ok(release.map(|release| (name, release)))
+}
+
+/// Writes the streamed answer, sanitized like the final one, into the job
+/// status whenever it changes, so status polling shows progress, and appends
+/// the new text to the job's stream buffer for SSE clients. When the text
+/// stops extending what was sent (a retry or fallback cleared the partial
+/// answer, or sanitizing removed markup) the buffer gets a
+/// [`keys::STREAM_RESET`] entry followed by the new text. Only returns on error.
+async fn publish_partial(
+    state: &WorkerState,
+    job_id: Uuid,
+    partial: &PartialResponse,
+) -> Result<Infallible> {
+    let worker = &state.config.config.worker;
+    let buffer_ttl = state.config.config.streaming.buffer_ttl_seconds;
+    let sanitize = &state.config.config.sanitize;
+    let mut published = String::new();
+    loop {
+        tokio::time::sleep(PARTIAL_PUBLISH_INTERVAL).await;
+        let text = sanitize.sanitize_partial(&partial.snapshot());
+        let chunks = match StreamUpdate::between(&published, &text) {
+            StreamUpdate::Unchanged => continue,
+            StreamUpdate::Append(suffix) => vec![suffix],
+            StreamUpdate::Restart(text) => {
+                let mut chunks = vec![keys::STREAM_RESET];
+                if !text.is_empty() {
+                    chunks.push(text);
+                }
+                chunks
+            }
+        };
+
+        let mut conn = state.get_connection().await?;
+        set_job_status(
+            &mut conn,
+            worker,
+            queues::CHAT_QUEUE,
+            &JobResult::streaming(job_id, &text),
+        )
+        .await?;
+        let key = keys::job_stream(&job_id);
+        traced(
+            "PIPELINE",
+            &key,
+            redis::pipe()
+                .rpush(&key, chunks)
+                .ignore()
+                .expire(&key, buffer_ttl as i64)
+                .ignore()
+                .query_async::<()>(&mut conn),
+        )
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        published = text;
+    }
+}
+
+/// Loads a conversation, following the ids of conversations merged into
+/// another one.
+async fn load_conversation(conn: &mut Connection, id: &Uuid) -> Result<Conversation> {
+    let mut current = *id;
+    for _ in 0..=keys::CONVERSATION_ALIAS_HOPS {
+        let key = keys::conversation(&current);
+        let data: Option<String> = traced("GET", &key, conn.get(&key))
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        if let Some(json) = data {
+            return serde_json::from_str(&json).map_err(WorkerError::from);
+        }
+        let key = keys::conversation_alias(&current);
+        let alias: Option<String> = traced("GET", &key, conn.get(&key))
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        match alias.and_then(|target| target.parse().ok()) {
+            Some(target) => current = target,
+            None => break,
+        }
+    }
+    Ok(Conversation::with_id(*id))
+}
+
+async fn save_chat_snapshot(
+    conn: &mut Connection,
+    snapshot: &ChatSnapshot,
+    ttl: u64,
+) -> Result<()> {
+    let json = serde_json::to_string(snapshot)?;
+    let key = keys::chat_snapshot(&snapshot.job.job_id);
+    traced("SETEX", &key, conn.set_ex::<_, _, ()>(&key, json, ttl))
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))
+}
+
+/// Saves a chat's turn: the messages `conversation` gained after its first
+/// `known_messages` and, when `drafted`, its newest draft. They are added to
+/// the stored copy, so changes made while the agent answered (an approved
+/// draft, another chat) are kept.
+async fn save_chat_turn(
+    conn: &mut Connection,
+    conversation: &Conversation,
+    known_messages: usize,
+    drafted: bool,
+    ttl: u64,
+) -> Result<()> {
+    let turn = &conversation.messages[known_messages.min(conversation.messages.len())..];
+    let draft = conversation.drafts.last().filter(|_| drafted);
+    let saved = update_conversation(conn, &conversation.id, ttl, |stored| {
+        let Some(mut stored) = stored else {
+            return Ok::<_, Infallible>((conversation.clone(), ()));
+        };
+        if stored.tenant_id.is_none() {
+            stored.tenant_id = conversation.tenant_id.clone();
+        }
+        if stored.user_id.is_none() {
+            stored.user_id = conversation.user_id.clone();
+        }
+        for message in turn {
+            stored.push_message(message.clone());
+        }
+        stored.drafts.extend(draft.cloned());
+        Ok((stored, ()))
+    })
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    let Ok(()) = saved;
+    Ok(())
+}
+
+async fn process_embed_job(state: &WorkerState, job: EmbedDocumentJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, document_id = %job.document_id, "processing embed");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::EMBED_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "embed cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::EMBED_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    if !job.safety_reviewed {
+        let categories = state.config.config.safety.flagged_categories(&job.content);
+        if !categories.is_empty() {
+            let result = screen_out(&mut conn, state, &job, categories).await?;
+            set_job_status(&mut conn, worker, queues::EMBED_QUEUE, &result).await?;
+            return Ok(());
+        }
+    }
+
+    let mut chunks = if job.pages.is_empty() {
+        state.chunker.chunk(job.document_id, &job.content)
+    } else {
+        state.chunker.chunk_pages(job.document_id, &job.pages)
+    };
+    for chunk in &mut chunks {
+        chunk.metadata.content_type = job.content_type.clone();
+        chunk.metadata.review_by = job.review_by;
+        chunk.metadata.expires_at = job.expires_at;
+        chunk.metadata.tags = job.tags.clone();
+        chunk.metadata.conversation_id = job.conversation_id;
+        chunk.metadata.tenant_id = job.tenant_id.clone();
+    }
+
+    let result = match index_job_chunks(state, &job, &chunks).await {
+        Ok(()) => {
+            if let Some(object) = &job.s3_object {
+                record_s3_etag(&mut conn, object).await?;
+            }
+            if chunks.is_empty() {
+                JobResult::completed(
+                    job.job_id,
+                    serde_json::json!({ "document_id": job.document_id, "chunks_created": 0 }),
+                )
+            } else {
+                JobResult::completed(
+                    job.job_id,
+                    serde_json::json!({
+                        "document_id": job.document_id,
+                        "chunks_created": chunks.len()
+                    }),
+                )
+                .with_usage(job.tenant_id.clone(), None)
+                .with_stored_bytes(job.content.len() as u64)
+            }
+        }
+        Err(e) => {
+            let retry = job.next_attempt();
+            if retry_later(
+                state,
+                &mut conn,
+                queues::EMBED_QUEUE,
+                job.job_id,
+                &e,
+                job.attempt,
+                &retry,
+            )
+            .await?
+            {
+                return Ok(());
+            }
+            JobResult::failed(job.job_id, e.to_string())
+        }
+    };
+
+    set_job_status(&mut conn, worker, queues::EMBED_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, chunks = chunks.len(), "embed completed");
+    Ok(())
+}
+
+/// Indexes an embed job's chunks, first dropping the document's existing
+/// ones when the job replaces them.
+async fn index_job_chunks(
+    state: &WorkerState,
+    job: &EmbedDocumentJob,
+    chunks: &[DocumentChunk],
+) -> std::result::Result<(), DomainError> {
+    if job.replace {
+        state.rag.delete_document(job.document_id).await?;
+    }
+    state.rag.index_chunks(chunks).await
+}
+
+/// Marks an S3 object as embedded at its ETag, so incremental syncs skip it
+/// until it changes. Objects whose embed failed keep their previous ETag
+/// and are queued again by the next sync.
+async fn record_s3_etag(conn: &mut Connection, object: &S3ObjectVersion) -> Result<()> {
+    let Some(etag) = &object.etag else {
+        return Ok(());
+    };
+    let etags_key = keys::s3_etags(&object.bucket);
+    traced(
+        "HSET",
+        &etags_key,
+        conn.hset::<_, _, _, ()>(&etags_key, &object.key, etag),
+    )
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))
+}
+
+/// Rejects or quarantines a document the safety filter flagged, per `safety.action`.
+async fn screen_out(
+    conn: &mut Connection,
+    state: &WorkerState,
+    job: &EmbedDocumentJob,
+    categories: Vec<String>,
+) -> Result<JobResult> {
+    tracing::warn!(
+        job_id = %job.job_id,
+        document_id = %job.document_id,
+        categories = ?categories,
+        "document flagged by safety filter"
+    );
+
+    match state.config.config.safety.action {
+        SafetyAction::Reject => Ok(JobResult::failed(
+            job.job_id,
+            format!("Rejected by safety filter: {}", categories.join(", ")),
+        )),
+        SafetyAction::Quarantine => {
+            let quarantined = QuarantinedDocument {
+                document_id: job.document_id,
+                categories: categories.clone(),
+                quarantined_at: chrono::Utc::now(),
+                job: job.clone(),
+            };
+            conn.hset::<_, _, _, ()>(
+                keys::QUARANTINE,
+                job.document_id.to_string(),
+                serde_json::to_string(&quarantined)?,
+            )
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+            Ok(JobResult::completed(
+                job.job_id,
+                serde_json::json!({
+                    "document_id": job.document_id,
+                    "chunks_created": 0,
+                    "quarantined": true,
+                    "categories": categories,
+                }),
+            ))
+        }
+    }
+}
+
+async fn process_index_job(state: &WorkerState, job: IndexDocumentJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, document_id = %job.document_id, "processing index");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::INDEX_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "index cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::INDEX_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let conversation_id: Option<String> = conn
+        .hget(keys::CONVERSATION_ATTACHMENTS, job.document_id.to_string())
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    let conversation_id = conversation_id.and_then(|id| id.parse().ok());
+
+    let result = match reindex_document(state, job.document_id, conversation_id).await {
+        Ok(chunks) => JobResult::completed(
+            job.job_id,
+            serde_json::json!({
+                "document_id": job.document_id,
+                "indexed": true,
+                "chunks_created": chunks,
+            }),
+        ),
+        Err(e) => JobResult::failed(job.job_id, e.to_string()),
+    };
+
+    set_job_status(&mut conn, worker, queues::INDEX_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, "index completed");
+    Ok(())
+}
+
+/// Re-chunks a stored document with the current settings and replaces its
+/// vectors; returns the number of chunks indexed.
+async fn reindex_document(
+    state: &WorkerState,
+    document_id: Uuid,
+    conversation_id: Option<Uuid>,
+) -> std::result::Result<usize, DomainError> {
+    let Some(store) = &state.document_store else {
+        return Err(DomainError::validation(
+            "re-indexing needs a document_store backend",
+        ));
+    };
+    let documents = DocumentService::new(store.clone(), state.chunker.clone());
+    let (_, mut chunks) = documents.rechunk(document_id).await?.ok_or_else(|| {
+        DomainError::not_found(format!("document {document_id} has no stored content"))
+    })?;
+    for chunk in &mut chunks {
+        chunk.metadata.conversation_id = conversation_id;
+    }
+
+    state.rag.delete_document(document_id).await?;
+    if !chunks.is_empty() {
+        state.rag.index_chunks(&chunks).await?;
+    }
+    Ok(chunks.len())
+}
+
+async fn process_reembed_job(state: &WorkerState, job: ReembedCollectionJob) -> Result<()> {
+    let source = state.vector_store.active_collection();
+    tracing::info!(job_id = %job.job_id, %source, target = %job.target_collection, "processing re-embed");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::REEMBED_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "re-embed cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::REEMBED_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let result = match reembed_into(state, &job.target_collection).await {
+        Ok((chunks, missing)) => {
+            let validated = missing == 0;
+            if validated {
+                conn.set::<_, _, ()>(keys::collection_validated(&job.target_collection), &source)
+                    .await
+                    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            }
+            JobResult::completed(
+                job.job_id,
+                serde_json::json!({
+                    "source_collection": source,
+                    "target_collection": job.target_collection,
+                    "chunks": chunks,
+                    "missing_chunks": missing,
+                    "validated": validated,
+                }),
+            )
+        }
+        Err(e) => JobResult::failed(job.job_id, e.to_string()),
+    };
+
+    set_job_status(&mut conn, worker, queues::REEMBED_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, "re-embed completed");
+    Ok(())
+}
+
+/// Copies every chunk of the active collection into `target`, embedding it afresh.
+///
+/// Returns the number of source chunks and how many of them are missing from
+/// the target afterwards.
+async fn reembed_into(
+    state: &WorkerState,
+    target: &str,
+) -> std::result::Result<(usize, usize), DomainError> {
+    let chunks = state.vector_store.list_chunks().await?;
+    let target_store = state.open_collection(target).await?;
+    let target_rag = RagService::new(
+        state.embedding.clone(),
+        target_store.clone(),
+        state.config.config.rag.top_k,
+    );
+
+    for batch in chunks.chunks(REEMBED_BATCH_SIZE) {
+        target_rag.index_chunks(batch).await?;
+    }
+
+    let indexed: HashSet<Uuid> = target_store
+        .list_chunks()
+        .await?
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    let missing = chunks.iter().filter(|c| !indexed.contains(&c.id)).count();
+
+    Ok((chunks.len(), missing))
+}
+
+async fn process_export_job(state: &WorkerState, job: ExportCollectionJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, file = %job.file, "processing export");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::EXPORT_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "export cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::EXPORT_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let collection = job
+        .collection
+        .clone()
+        .unwrap_or_else(|| state.vector_store.active_collection());
+    let result = match export_collection(state, &collection, &job.file).await {
+        Ok(points) => JobResult::completed(
+            job.job_id,
+            serde_json::json!({
+                "collection": collection,
+                "file": job.file,
+                "points": points,
+            }),
+        ),
+        Err(e) => JobResult::failed(job.job_id, e.to_string()),
+    };
+
+    set_job_status(&mut conn, worker, queues::EXPORT_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, "export completed");
+    Ok(())
+}
+
+/// Writes `collection` to `file` in the dump directory, via a temporary file
+/// so a failed export never leaves a truncated dump under the final name.
+async fn export_collection(
+    state: &WorkerState,
+    collection: &str,
+    file: &str,
+) -> std::result::Result<usize, DomainError> {
+    let store = state.open_collection(collection).await?;
+    let dir = std::path::Path::new(&state.config.config.vector_store.dump_dir);
+    let io_error = |e: std::io::Error| DomainError::external(format!("Failed to write dump: {e}"));
+    tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+
+    let partial = dir.join(format!("{file}.partial"));
+    let mut writer =
+        tokio::io::BufWriter::new(tokio::fs::File::create(&partial).await.map_err(io_error)?);
+    let points = export_points(store.as_ref(), &mut writer, EXPORT_PAGE_SIZE).await?;
+    tokio::fs::rename(&partial, dir.join(file))
+        .await
+        .map_err(io_error)?;
+    Ok(points)
+}
+
+async fn process_import_job(state: &WorkerState, job: ImportCollectionJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, file = %job.file, "processing import");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::IMPORT_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "import cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::IMPORT_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let collection = job
+        .collection
+        .clone()
+        .unwrap_or_else(|| state.vector_store.active_collection());
+    let result = match import_collection(state, &collection, &job.file).await {
+        Ok(points) => JobResult::completed(
+            job.job_id,
+            serde_json::json!({
+                "collection": collection,
+                "file": job.file,
+                "points": points,
+            }),
+        ),
+        Err(e) => JobResult::failed(job.job_id, e.to_string()),
+    };
+
+    set_job_status(&mut conn, worker, queues::IMPORT_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, "import completed");
+    Ok(())
+}
+
+async fn import_collection(
+    state: &WorkerState,
+    collection: &str,
+    file: &str,
+) -> std::result::Result<usize, DomainError> {
+    let path = std::path::Path::new(&state.config.config.vector_store.dump_dir).join(file);
+    let dump = tokio::fs::File::open(&path)
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => DomainError::not_found(format!("dump {file}")),
+            _ => DomainError::external(format!("Failed to read dump: {e}")),
+        })?;
+    let store = state.open_collection(collection).await?;
+    import_points(store.as_ref(), tokio::io::BufReader::new(dump)).await
+}
+
+async fn process_git_sync_job(state: &WorkerState, job: SyncGitRepoJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, url = %job.url, "processing git sync");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::GIT_SYNC_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "git sync cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::GIT_SYNC_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let previous: Option<String> = if job.full {
+        None
+    } else {
+        conn.get(keys::git_head(&job.url))
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?
+    };
+
+    let result = match sync_git_repo(state, &job, previous.as_deref()).await {
+        Ok((changes, chunks)) => {
+            conn.set::<_, _, ()>(keys::git_head(&job.url), &changes.head)
+                .await
+                .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            JobResult::completed(
+                job.job_id,
+                serde_json::json!({
+                    "url": job.url,
+                    "previous_commit": previous,
+                    "commit": changes.head,
+                    "files_indexed": changes.changed.len(),
+                    "files_deleted": changes.deleted.len(),
+                    "chunks_created": chunks,
+                }),
+            )
+        }
+        Err(e) => JobResult::failed(job.job_id, e.to_string()),
+    };
+
+    set_job_status(&mut conn, worker, queues::GIT_SYNC_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, "git sync completed");
+    Ok(())
+}
+
+/// Re-indexes files changed since `since`, tagging chunks with path, commit and lines.
+///
+/// Returns the changes applied and the number of chunks created.
+async fn sync_git_repo(
+    state: &WorkerState,
+    job: &SyncGitRepoJob,
+    since: Option<&str>,
+) -> std::result::Result<(GitChanges, usize), DomainError> {
+    let globs = if job.globs.is_empty() {
+        &state.config.config.git.default_globs
+    } else {
+        &job.globs
+    };
+    let changes = state
+        .git
+        .sync(&job.url, job.branch.as_deref(), globs, since)
+        .await?;
+
+    for path in &changes.deleted {
+        let document_id = GitConnector::document_id(&job.url, path);
+        state.rag.delete_document(document_id).await?;
+    }
+
+    let mut created = 0;
+    for file in &changes.changed {
+        let document_id = GitConnector::document_id(&job.url, &file.path);
+        state.rag.delete_document(document_id).await?;
+
+        // Repository files can't be held for review, so flagged ones are skipped.
+        let categories = state.config.config.safety.flagged_categories(&file.content);
+        if !categories.is_empty() {
+            tracing::warn!(path = %file.path, categories = ?categories, "skipping file flagged by safety filter");
+            continue;
+        }
+
+        let mut chunks = state.chunker.chunk(document_id, &file.content);
+        annotate_line_ranges(&file.content, &mut chunks);
+        if file.path.ends_with(".md") || file.path.ends_with(".markdown") {
+            annotate_markdown_sections(&mut chunks);
+        }
+        for chunk in &mut chunks {
+            chunk.metadata.source_url = Some(job.url.clone());
+            chunk.metadata.source_path = Some(file.path.clone());
+            chunk.metadata.commit = Some(changes.head.clone());
+        }
+
+        if !chunks.is_empty() {
+            state.rag.index_chunks(&chunks).await?;
+            created += chunks.len();
+        }
+    }
+
+    Ok((changes, created))
+}
+
+async fn process_crawl_job(state: &WorkerState, job: CrawlSiteJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, url = %job.url, "processing crawl");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::CRAWL_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "crawl cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::CRAWL_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let defaults = &state.config.config.crawl;
+    let limits = CrawlLimits {
+        max_depth: job
+            .max_depth
+            .map_or(defaults.max_depth, |d| d.min(defaults.max_depth)),
+        max_pages: job
+            .max_pages
+            .map_or(defaults.max_pages, |p| p.min(defaults.max_pages)),
+    };
+    let mut crawl = match state.crawler.crawl(&job.url, limits) {
+        Ok(crawl) => crawl,
+        Err(e) => {
+            let result = JobResult::failed(job.job_id, e.to_string());
+            set_job_status(&mut conn, worker, queues::CRAWL_QUEUE, &result).await?;
+            return Ok(());
+        }
+    };
+
+    let (mut indexed, mut created) = (0, 0);
+    let mut failure = None;
+    while let Some(page) = crawl.next_page().await {
+        if is_cancelled(&mut conn, job.job_id).await? {
+            tracing::info!(job_id = %job.job_id, indexed, "crawl cancelled");
+            archive_cancelled(&mut conn, worker, queues::CRAWL_QUEUE, job.job_id).await?;
+            return Ok(());
+        }
+
+        match index_web_page(state, &job, &page).await {
+            Ok(0) => {}
+            Ok(chunks) => {
+                indexed += 1;
+                created += chunks;
+            }
+            Err(e) => {
+                failure = Some(format!("{}: {e}", page.url));
+                break;
+            }
+        }
+
+        let progress = JobResult::progress(
+            job.job_id,
+            serde_json::json!({
+                "pages_fetched": crawl.fetched(),
+                "pages_queued": crawl.queued(),
+                "pages_indexed": indexed,
+                "chunks_created": created,
+            }),
+        );
+        set_job_status(&mut conn, worker, queues::CRAWL_QUEUE, &progress).await?;
+    }
+
+    let result = match failure {
+        Some(error) => JobResult::failed(job.job_id, error),
+        None => JobResult::completed(
+            job.job_id,
+            serde_json::json!({
+                "url": job.url,
+                "pages_fetched": crawl.fetched(),
+                "pages_indexed": indexed,
+                "chunks_created": created,
+            }),
+        ),
+    };
+    set_job_status(&mut conn, worker, queues::CRAWL_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, pages = indexed, "crawl completed");
+    Ok(())
+}
+
+/// Replaces a crawled page's chunks, returning how many were indexed.
+async fn index_web_page(
+    state: &WorkerState,
+    job: &CrawlSiteJob,
+    page: &WebPage,
+) -> std::result::Result<usize, DomainError> {
+    let document_id = WebCrawler::document_id(&page.url);
+    state.rag.delete_document(document_id).await?;
+    if let Some(store) = &state.document_store {
+        store.delete_document(document_id).await?;
+    }
+
+    // Like repository files, crawled pages can't be held for review.
+    let categories = state.config.config.safety.flagged_categories(&page.text);
+    if !categories.is_empty() {
+        tracing::warn!(url = %page.url, categories = ?categories, "skipping page flagged by safety filter");
+        return Ok(0);
+    }
+
+    let mut chunks = state.chunker.chunk(document_id, &page.text);
+    for chunk in &mut chunks {
+        chunk.metadata.source_url = Some(page.url.clone());
+        chunk.metadata.content_type = Some("text/html".to_string());
+        chunk.metadata.tags = job.tags.clone();
+    }
+    if chunks.is_empty() {
+        return Ok(0);
+    }
+
+    if let Some(store) = &state.document_store {
+        let name = page.title.clone().unwrap_or_else(|| page.url.clone());
+        let document = Document {
+            id: document_id,
+            ..Document::new(name)
+                .with_content_type("text/html")
+                .with_metadata(serde_json::json!({ "source_url": page.url }))
+                .with_tags(job.tags.clone())
+        };
+        store.save_document(&document).await?;
+        store.save_chunks(&chunks).await?;
+    }
+
+    state.rag.index_chunks(&chunks).await?;
+    Ok(chunks.len())
+}
+
+/// Progress of a sync of a bucket prefix.
+#[derive(Default)]
+struct S3Changes {
+    /// Embed jobs queued for changed objects.
+    embed_job_ids: Vec<Uuid>,
+    deleted: usize,
+    unchanged: usize,
+    skipped: usize,
+}
+
+async fn process_s3_sync_job(state: &WorkerState, job: S3SyncJob) -> Result<()> {
+    tracing::info!(job_id = %job.job_id, bucket = %job.bucket, prefix = %job.prefix, "processing S3 sync");
+    let mut conn = state.get_connection().await?;
+    let worker = &state.config.config.worker;
+
+    if !start_job(&mut conn, worker, queues::S3_SYNC_QUEUE, job.job_id).await? {
+        tracing::info!(job_id = %job.job_id, "S3 sync cancelled before processing");
+        archive_cancelled(&mut conn, worker, queues::S3_SYNC_QUEUE, job.job_id).await?;
+        return Ok(());
+    }
+
+    let etags: HashMap<String, String> = conn
+        .hgetall(keys::s3_etags(&job.bucket))
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+    let mut changes = S3Changes::default();
+    if let Err(e) = sync_s3_objects(state, &mut conn, &job, &etags, &mut changes).await? {
+        // Jobs already queued still run and record their objects' ETags.
+        tracing::warn!(job_id = %job.job_id, queued = changes.embed_job_ids.len(), error = %e, "S3 sync failed");
+        let result = JobResult::failed(job.job_id, e.to_string());
+        set_job_status(&mut conn, worker, queues::S3_SYNC_QUEUE, &result).await?;
+        return Ok(());
+    }
+
+    let result = JobResult::completed(
+        job.job_id,
+        serde_json::json!({
+            "bucket": job.bucket,
+            "prefix": job.prefix,
+            "objects_queued": changes.embed_job_ids.len(),
+            "objects_unchanged": changes.unchanged,
+            "objects_skipped": changes.skipped,
+            "objects_deleted": changes.deleted,
+            "embed_job_ids": changes.embed_job_ids,
+        }),
+    );
+    set_job_status(&mut conn, worker, queues::S3_SYNC_QUEUE, &result).await?;
+    tracing::info!(job_id = %job.job_id, queued = changes.embed_job_ids.len(), "S3 sync completed");
+    Ok(())
+}
+
+/// Drops the vectors of objects no longer in the bucket, then downloads,
+/// extracts and queues an embed job for each object whose ETag changed
+/// since it was last embedded, one object at a time.
+///
+/// Objects without a supported extension, too large, or failing extraction
+/// are skipped. The embed jobs replace the objects' previous chunks and
+/// record their ETags once they succeed, so an object is queued again by
+/// the next sync until its embed goes through.
+async fn sync_s3_objects(
+    state: &WorkerState,
+    conn: &mut Connection,
+    job: &S3SyncJob,
+    etags: &HashMap<String, String>,
+    changes: &mut S3Changes,
+) -> Result<std::result::Result<(), DomainError>> {
+    let config = &state.config.config.s3;
+    let worker = &state.config.config.worker;
+    let s3 = S3Connector::new(config.region.as_deref(), config.endpoint_url.as_deref()).await;
+    let extractors = ExtractorRegistry::default();
+    let objects = match s3.list(&job.bucket, &job.prefix).await {
+        Ok(objects) => objects,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    let etags_key = keys::s3_etags(&job.bucket);
+    let listed: HashSet<&str> = objects.iter().map(|o| o.key.as_str()).collect();
+    for key in etags.keys() {
+        if key.starts_with(&job.prefix) && !listed.contains(key.as_str()) {
+            let document_id = S3Connector::document_id(&job.bucket, key);
+            if let Err(e) = state.rag.delete_document(document_id).await {
+                return Ok(Err(e));
+            }
+            conn.hdel::<_, _, ()>(&etags_key, key)
+                .await
+                .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            changes.deleted += 1;
+        }
+    }
+
+    for object in objects {
+        let Some(content_type) = content_type_for_key(&object.key) else {
+            continue;
+        };
+        if !job.full && object.etag.is_some() && etags.get(&object.key) == object.etag.as_ref() {
+            changes.unchanged += 1;
+            continue;
+        }
+        if object.size > config.max_object_bytes {
+            tracing::warn!(key = %object.key, size = object.size, "skipping object over s3.max_object_bytes");
+            changes.skipped += 1;
+            continue;
+        }
+
+        let bytes = match s3.download(&job.bucket, &object.key).await {
+            Ok(bytes) => bytes,
+            Err(e) => return Ok(Err(e)),
+        };
+        let pages = match extractors.extract(content_type, &bytes).await {
+            Ok(pages) => pages,
+            Err(e) => {
+                tracing::warn!(key = %object.key, error = %e, "skipping object that failed extraction");
+                changes.skipped += 1;
+                continue;
+            }
+        };
+        drop(bytes);
+
+        let content = pages
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let embed_job =
+            EmbedDocumentJob::new(S3Connector::document_id(&job.bucket, &object.key), content)
+                .with_content_type(content_type)
+                .with_metadata(serde_json::json!({
+                    "source_url": object_url(&job.bucket, &object.key),
+                    "etag": object.etag,
+                }))
+                .with_tags(job.tags.clone())
+                .with_pages(pages)
+                .with_s3_object(S3ObjectVersion {
+                    bucket: job.bucket.clone(),
+                    key: object.key,
+                    etag: object.etag,
+                });
+        push_embed_job(conn, worker, &embed_job).await?;
+        changes.embed_job_ids.push(embed_job.job_id);
+    }
+
+    Ok(Ok(()))
+}
+
+/// Queues an embed job the way the API does, with a pending status.
+async fn push_embed_job(
+    conn: &mut Connection,
+    worker: &WorkerConfig,
+    job: &EmbedDocumentJob,
+) -> Result<()> {
+    traced(
+        "LPUSH",
+        queues::EMBED_QUEUE,
+        conn.lpush::<_, _, ()>(queues::EMBED_QUEUE, serde_json::to_string(job)?),
+    )
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    set_job_status(
+        conn,
+        worker,
+        queues::EMBED_QUEUE,
+        &JobResult::pending(job.job_id),
+    )
+    .await
+}
+
+/// Reports stale documents every `rag.freshness.report_interval_seconds`.
+/// Embeds the most frequent recent queries into the query cache, at
+/// startup and then every `rag.warm_queries.interval_seconds`.
+async fn query_warm_loop(
+    state: Arc<WorkerState>,
+    cache: Arc<QueryEmbeddingCache>,
+    log: Arc<RedisQueryLog>,
+) {
+    let warm = &state.config.config.rag.warm_queries;
+    let interval = Duration::from_secs(warm.interval_seconds.max(1));
+    loop {
+        let warmed = match log.frequent(warm.top_n).await {
+            Ok(queries) => warm_query_cache(state.embedding.as_ref(), &cache, &queries)
+                .await
+                .map(|embedded| (queries.len(), embedded)),
+            Err(e) => Err(e),
+        };
+        match warmed {
+            Ok((queries, embedded)) => {
+                tracing::info!(
+                    queries,
+                    embedded,
+                    cached = cache.len(),
+                    "query cache warmed"
+                )
+            }
+            Err(e) => tracing::warn!(error = %e, "query cache warming failed"),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn freshness_loop(state: Arc<WorkerState>) {
+    let interval = Duration::from_secs(
+        state
+            .config
+            .config
+            .rag
+            .freshness
+            .report_interval_seconds
+            .max(1),
+    );
+    loop {
+        if let Err(e) = run_freshness_report(&state).await {
+            tracing::error!(error = %e, "freshness report failed");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Stores a report of documents past their review or expiry date.
+///
+/// Only one worker reports per interval; the others find the lock taken.
+async fn run_freshness_report(state: &WorkerState) -> Result<()> {
+    let interval = state.config.config.rag.freshness.report_interval_seconds;
+    let mut conn = state.get_connection().await?;
+
+    let locked: Option<String> = redis::cmd("SET")
+        .arg(keys::FRESHNESS_LOCK)
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(interval.max(1))
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    if locked.is_none() {
+        return Ok(());
+    }
+
+    let chunks = match state.vector_store.list_chunks().await {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            tracing::error!(error = %e, "failed to list chunks for freshness report");
+            return Ok(());
+        }
+    };
+    let report = FreshnessReport::from_chunks(&chunks, chrono::Utc::now());
+
+    conn.set::<_, _, ()>(keys::FRESHNESS_REPORT, serde_json::to_string(&report)?)
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    tracing::info!(
+        scanned = report.scanned_chunks,
+        stale_documents = report.documents.len(),
+        "freshness report completed"
+    );
+    Ok(())
+}
+
+/// Deletes attachments of expired conversations every
+/// `worker.attachment_sweep_interval_seconds`.
+async fn attachment_sweep_loop(state: Arc<WorkerState>) {
+    let interval = Duration::from_secs(
+        state
+            .config
+            .config
+            .worker
+            .attachment_sweep_interval_seconds
+            .max(1),
+    );
+    loop {
+        if let Err(e) = sweep_attachments(&state).await {
+            tracing::error!(error = %e, "attachment sweep failed");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Removes the vectors of documents whose conversation no longer exists.
+async fn sweep_attachments(state: &WorkerState) -> Result<()> {
+    let mut conn = state.get_connection().await?;
+    let attachments: HashMap<String, String> = conn
+        .hgetall(keys::CONVERSATION_ATTACHMENTS)
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+    for (document_id, conversation_id) in attachments {
+        let (Ok(document), Ok(conversation)) =
+            (document_id.parse::<Uuid>(), conversation_id.parse::<Uuid>())
+        else {
+            continue;
+        };
+        let alive: bool = conn
+            .exists(keys::conversation(&conversation))
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        if alive {
+            continue;
+        }
+
+        if let Err(e) = state.vector_store.delete_by_document(document).await {
+            tracing::error!(error = %e, document_id = %document, "failed to delete attachment");
+            continue;
+        }
+        conn.hdel::<_, _, ()>(keys::CONVERSATION_ATTACHMENTS, &document_id)
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+        tracing::info!(document_id = %document, conversation_id = %conversation, "deleted expired attachment");
+    }
+    Ok(())
+}
+
+/// Applies the retention policy every `retention.interval_seconds`.
+async fn retention_loop(state: Arc<WorkerState>) {
+    let interval = Duration::from_secs(state.config.config.retention.interval_seconds.max(1));
+    loop {
+        if let Err(e) = run_retention(&state).await {
+            tracing::error!(error = %e, "retention sweep failed");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Anonymizes or deletes conversations past their rule and stores a report.
+///
+/// Only one worker sweeps per interval; the others find the lock taken.
+async fn run_retention(state: &WorkerState) -> Result<()> {
+    let config = &state.config.config.retention;
+    let mut conn = state.get_connection().await?;
+
+    let locked: Option<String> = redis::cmd("SET")
+        .arg(keys::RETENTION_LOCK)
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(config.interval_seconds.max(1))
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    if locked.is_none() {
+        return Ok(());
+    }
+
+    let policy = RetentionPolicy::new(config.default_rule.clone())
+        .with_tenant_rules(config.tenants.clone())
+        .with_hash_salt(&config.hash_salt);
+    let now = chrono::Utc::now();
+    let mut report = RetentionReport::new(now, config.dry_run);
+    let mut acted = HashMap::new();
+    // Index entries not updated for longer than the conversation TTL are
+    // mostly of expired conversations; live ones are re-added by the scan.
+    let ttl_ms = state.config.config.worker.conversation_ttl_seconds as i64 * 1000;
+    conn.zrembyscore::<_, _, _, ()>(
+        keys::CONVERSATIONS_BY_UPDATED,
+        "-inf",
+        now.timestamp_millis() - ttl_ms,
+    )
+    .await
+    .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+    let pattern = format!("{}*", keys::CONVERSATION_PREFIX);
+    let mut cursor: u64 = 0;
+
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(RETENTION_SCAN_BATCH)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+        for key in batch {
+            let json: Option<String> = conn
+                .get(&key)
+                .await
+                .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            let Some(json) = json else {
+                continue;
+            };
+            let mut conversation: Conversation = match serde_json::from_str(&json) {
+                Ok(conversation) => conversation,
+                Err(e) => {
+                    tracing::warn!(key, error = %e, "skipping unreadable conversation");
+                    continue;
+                }
+            };
+
+            report.scanned += 1;
+            // (Re-)indexes conversations the listing index is missing.
+            redis::cmd("ZADD")
+                .arg(keys::CONVERSATIONS_BY_UPDATED)
+                .arg("NX")
+                .arg(conversation.updated_at.timestamp_millis())
+                .arg(conversation.id.to_string())
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(|e| WorkerError::Redis(e.to_string()))?;
+            let Some(action) = policy.evaluate(&conversation, now) else {
+                continue;
+            };
+            report.record(&conversation, action);
+            acted.insert(conversation.id, action);
+            if config.dry_run {
+                continue;
+            }
+
+            match action {
+                RetentionAction::Anonymize => {
+                    policy.anonymize(&mut conversation, now);
+                    redis::cmd("SET")
+                        .arg(&key)
+                        .arg(serde_json::to_string(&conversation)?)
+                        .arg("KEEPTTL")
+                        .query_async::<()>(&mut conn)
+                        .await
+                        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+                }
+                RetentionAction::Delete => {
+                    redis::pipe()
+                        .atomic()
+                        .del(&key)
+                        .ignore()
+                        .zrem(keys::CONVERSATIONS_BY_UPDATED, conversation.id.to_string())
+                        .ignore()
+                        .query_async::<()>(&mut conn)
+                        .await
+                        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+                }
+            }
+        }
+
+        cursor = next;
+        if cursor == 0 {
+            break;
+        }
+    }
+
+    sweep_annotations(&mut conn, &policy, now, &acted, config.dry_run, &mut report).await?;
+
+    conn.set::<_, _, ()>(keys::RETENTION_REPORT, serde_json::to_string(&report)?)
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    tracing::info!(
+        scanned = report.scanned,
+        anonymized = report.anonymized,
+        deleted = report.deleted,
+        annotations_anonymized = report.annotations_anonymized,
+        annotations_deleted = report.annotations_deleted,
+        dry_run = report.dry_run,
+        "retention sweep completed"
+    );
+    Ok(())
+}
+
+/// Applies the retention policy to annotations, which copy conversation
+/// text and outlive the conversations' TTL: each follows the action just
+/// taken on its conversation, or its own age once that conversation expired.
+async fn sweep_annotations(
+    conn: &mut Connection,
+    policy: &RetentionPolicy,
+    now: chrono::DateTime<chrono::Utc>,
+    acted: &HashMap<Uuid, RetentionAction>,
+    dry_run: bool,
+    report: &mut RetentionReport,
+) -> Result<()> {
+    let index: HashMap<String, String> = conn
+        .hgetall(keys::ANNOTATION_INDEX)
+        .await
+        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+    let conversation_ids: HashSet<Uuid> = index.values().filter_map(|id| id.parse().ok()).collect();
+
+    for conversation_id in conversation_ids {
+        let key = keys::annotations(&conversation_id);
+        let annotations: HashMap<String, String> = conn
+            .hgetall(&key)
+            .await
+            .map_err(|e| WorkerError::Redis(e.to_string()))?;
+
+        for (id, json) in annotations {
+            let mut annotation: Annotation = match serde_json::from_str(&json) {
+                Ok(annotation) => annotation,
+                Err(e) => {
+                    tracing::warn!(annotation_id = %id, error = %e, "skipping unreadable annotation");
+                    continue;
+                }
+            };
+            let conversation_action = acted.get(&conversation_id).copied();
+            let Some(action) = policy.evaluate_annotation(&annotation, conversation_action, now)
+            else {
+                continue;
+            };
+            report.record_annotation(action);
+            if dry_run {
+                continue;
+            }
+
+            match action {
+                RetentionAction::Anonymize => {
+                    policy.anonymize_annotation(&mut annotation, now);
+                    conn.hset::<_, _, _, ()>(&key, &id, serde_json::to_string(&annotation)?)
+                        .await
+                        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+                }
+                RetentionAction::Delete => {
+                    redis::pipe()
+                        .atomic()
+                        .hdel(&key, &id)
+                        .ignore()
+                        .hdel(keys::ANNOTATION_INDEX, &id)
+                        .ignore()
+                        .query_async::<()>(&mut *conn)
+                        .await
+                        .map_err(|e| WorkerError::Redis(e.to_string()))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
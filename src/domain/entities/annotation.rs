@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A reviewer's verdict on one answer of a conversation, kept for building
+/// fine-tuning and evaluation datasets. The question and answer are copied
+/// in, so the label outlives the conversation's TTL; retention rules still
+/// anonymize or delete it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    /// Position of the labeled assistant message in the conversation.
+    pub message_index: usize,
+    pub question: String,
+    pub answer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<AnswerRating>,
+    /// Verdicts on chunks retrieved for the answer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SourceLabel>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anonymized_at: Option<DateTime<Utc>>,
+}
+
+impl Annotation {
+    pub fn new(
+        conversation_id: Uuid,
+        message_index: usize,
+        question: impl Into<String>,
+        answer: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            conversation_id,
+            message_index,
+            question: question.into(),
+            answer: answer.into(),
+            rating: None,
+            sources: Vec::new(),
+            tags: Vec::new(),
+            note: None,
+            author: None,
+            tenant_id: None,
+            created_at: Utc::now(),
+            anonymized_at: None,
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerRating {
+    Good,
+    Bad,
+}
+
+/// Whether a retrieved chunk was the right source for the answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLabel {
+    pub chunk_id: Uuid,
+    pub correct: bool,
+}
@@ -1,34 +1,201 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::domain::DomainError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
     pub id: Uuid,
     pub messages: Vec<Message>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Extra instructions for the agent in this conversation only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Client-defined attributes, e.g. plan or locale.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+    /// Documents given to the agent with every message of the conversation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<ContextDocument>,
+    /// Answers held back for human approval, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drafts: Vec<Draft>,
+    /// Conversations merged into this one, e.g. a widget chat continued on
+    /// Slack; their ids now resolve here.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<Uuid>,
+    /// Set once the retention policy has anonymized the conversation.
+    #[serde(default)]
+    pub anonymized_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl Conversation {
     pub fn new() -> Self {
+        Self::with_id(Uuid::new_v4())
+    }
+
+    pub fn with_id(id: Uuid) -> Self {
         let now = Utc::now();
         Self {
-            id: Uuid::new_v4(),
+            id,
             messages: Vec::new(),
+            tenant_id: None,
+            user_id: None,
+            system_prompt: None,
+            metadata: HashMap::new(),
+            context: Vec::new(),
+            drafts: Vec::new(),
+            merged: Vec::new(),
+            anonymized_at: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// Whether a request made for `tenant_id` may read or continue this
+    /// conversation: one with an owner only by that tenant, others by anyone.
+    pub fn is_accessible_by(&self, tenant_id: Option<&str>) -> bool {
+        self.tenant_id
+            .as_deref()
+            .map_or(true, |owner| Some(owner) == tenant_id)
+    }
+
     pub fn add_message(&mut self, role: MessageRole, content: impl Into<String>) {
-        self.messages.push(Message {
-            role,
-            content: content.into(),
-        });
+        self.push_message(Message::new(role, content));
+    }
+
+    pub fn push_message(&mut self, message: Message) {
+        self.messages.push(message);
         self.updated_at = Utc::now();
     }
 
+    /// Moves `other`'s history into this conversation, the older
+    /// conversation's messages first, each keeping its channel and the id
+    /// of the conversation it came from. Fails when the two belong to
+    /// different tenants or users.
+    pub fn merge(&mut self, other: Conversation) -> Result<(), DomainError> {
+        for (field, ours, theirs) in [
+            ("tenant", &self.tenant_id, &other.tenant_id),
+            ("user", &self.user_id, &other.user_id),
+        ] {
+            if let (Some(ours), Some(theirs)) = (ours, theirs) {
+                if ours != theirs {
+                    return Err(DomainError::validation(format!(
+                        "conversations belong to different {field}s"
+                    )));
+                }
+            }
+        }
+
+        let mut merged: Vec<Message> = other
+            .messages
+            .into_iter()
+            .map(|mut m| {
+                m.merged_from.get_or_insert(other.id);
+                m
+            })
+            .collect();
+        if other.created_at < self.created_at {
+            merged.append(&mut self.messages);
+            self.messages = merged;
+        } else {
+            self.messages.append(&mut merged);
+        }
+
+        self.tenant_id = self.tenant_id.take().or(other.tenant_id);
+        self.user_id = self.user_id.take().or(other.user_id);
+        self.system_prompt = self.system_prompt.take().or(other.system_prompt);
+        for (key, value) in other.metadata {
+            self.metadata.entry(key).or_insert(value);
+        }
+        for doc in other.context {
+            if !self.context.contains(&doc) {
+                self.context.push(doc);
+            }
+        }
+        self.drafts.extend(other.drafts);
+        self.merged.push(other.id);
+        self.merged.extend(other.merged);
+        self.created_at = self.created_at.min(other.created_at);
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// The conversation's system prompt and context documents as prompt text.
+    pub fn instructions(&self) -> Option<String> {
+        let mut sections: Vec<String> = self.system_prompt.iter().cloned().collect();
+        if !self.context.is_empty() {
+            let documents = self
+                .context
+                .iter()
+                .map(|doc| match &doc.title {
+                    Some(title) => format!("### {title}\n{}", doc.content),
+                    None => doc.content.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            sections.push(format!(
+                "Reference documents for this conversation:\n\n{documents}"
+            ));
+        }
+        (!sections.is_empty()).then(|| sections.join("\n\n"))
+    }
+
+    /// Approves a pending draft, adding its answer to the conversation.
+    /// Returns `None` when there is no pending draft with that id.
+    pub fn approve_draft(&mut self, draft_id: Uuid) -> Option<&Draft> {
+        let index = self
+            .drafts
+            .iter()
+            .position(|d| d.id == draft_id && d.status == DraftStatus::Pending)?;
+        // The answer goes out on the channel the question came from.
+        let channel = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::User)
+            .and_then(|m| m.channel.clone());
+        let content = self.drafts[index].content.clone();
+        self.push_message(Message {
+            channel,
+            ..Message::new(MessageRole::Assistant, content)
+        });
+
+        let draft = &mut self.drafts[index];
+        draft.status = DraftStatus::Approved;
+        draft.approved_at = Some(self.updated_at);
+        Some(draft)
+    }
+
+    /// The assistant message at `index` (default: the latest one) with its
+    /// index and the user message it answered, if any.
+    pub fn answer_turn(&self, index: Option<usize>) -> Option<(usize, Option<&str>, &str)> {
+        let index = match index {
+            Some(index) => index,
+            None => self
+                .messages
+                .iter()
+                .rposition(|m| m.role == MessageRole::Assistant)?,
+        };
+        let answer = self
+            .messages
+            .get(index)
+            .filter(|m| m.role == MessageRole::Assistant)?;
+        let question = self.messages[..index]
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.as_str());
+        Some((index, question, answer.content.as_str()))
+    }
+
     pub fn last_user_message(&self) -> Option<&str> {
         self.messages
             .iter()
@@ -44,10 +211,62 @@ impl Default for Conversation {
     }
 }
 
+/// A document pinned to one conversation rather than the knowledge base.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub content: String,
+}
+
+/// An assistant answer that is only released once a reviewer approves it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub id: Uuid,
+    /// Chat job that wrote the answer; its result is released on approval.
+    pub job_id: Uuid,
+    /// The answer as it joins the conversation history.
+    pub content: String,
+    /// Job result the client receives once the draft is approved.
+    pub result: serde_json::Value,
+    pub status: DraftStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+impl Draft {
+    pub fn new(job_id: Uuid, content: impl Into<String>, result: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            job_id,
+            content: content.into(),
+            result,
+            status: DraftStatus::Pending,
+            created_at: Utc::now(),
+            approved_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftStatus {
+    Pending,
+    Approved,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: MessageRole,
     pub content: String,
+    /// Channel the message was exchanged on, e.g. `widget` or `slack`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Conversation the message was first part of, when it was merged into
+    /// another one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_from: Option<Uuid>,
 }
 
 impl Message {
@@ -55,8 +274,15 @@ impl Message {
         Self {
             role,
             content: content.into(),
+            channel: None,
+            merged_from: None,
         }
     }
+
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = Some(channel.into());
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -76,3 +302,75 @@ impl MessageRole {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approve_draft_releases_answer_once() {
+        let mut conversation = Conversation::new();
+        conversation.add_message(MessageRole::User, "Can I get a refund?");
+        let draft = Draft::new(
+            Uuid::new_v4(),
+            "Yes, within 30 days.",
+            serde_json::json!({}),
+        );
+        let draft_id = draft.id;
+        conversation.drafts.push(draft);
+
+        let approved = conversation.approve_draft(draft_id).unwrap();
+        assert_eq!(approved.status, DraftStatus::Approved);
+        assert!(approved.approved_at.is_some());
+        assert_eq!(conversation.messages.len(), 2);
+        assert_eq!(conversation.messages[1].content, "Yes, within 30 days.");
+
+        assert!(conversation.approve_draft(draft_id).is_none());
+        assert_eq!(conversation.messages.len(), 2);
+
+        assert_eq!(
+            conversation.answer_turn(None),
+            Some((1, Some("Can I get a refund?"), "Yes, within 30 days."))
+        );
+        assert_eq!(conversation.answer_turn(Some(0)), None);
+    }
+
+    #[test]
+    fn test_owned_conversations_are_only_accessible_by_their_tenant() {
+        let mut conversation = Conversation::new();
+        assert!(conversation.is_accessible_by(None));
+        assert!(conversation.is_accessible_by(Some("acme")));
+
+        conversation.tenant_id = Some("acme".to_string());
+        assert!(conversation.is_accessible_by(Some("acme")));
+        assert!(!conversation.is_accessible_by(Some("globex")));
+        assert!(!conversation.is_accessible_by(None));
+    }
+
+    #[test]
+    fn test_merge_keeps_provenance_and_order() {
+        let mut widget = Conversation::new();
+        widget.user_id = Some("u1".to_string());
+        widget.push_message(Message::new(MessageRole::User, "Hi").with_channel("widget"));
+        let mut slack = Conversation::new();
+        slack.created_at = widget.created_at + chrono::Duration::minutes(5);
+        slack.push_message(Message::new(MessageRole::User, "Still there?").with_channel("slack"));
+
+        slack.merge(widget.clone()).unwrap();
+        let channels: Vec<_> = slack
+            .messages
+            .iter()
+            .map(|m| m.channel.as_deref())
+            .collect();
+        assert_eq!(channels, vec![Some("widget"), Some("slack")]);
+        assert_eq!(slack.messages[0].merged_from, Some(widget.id));
+        assert_eq!(slack.messages[1].merged_from, None);
+        assert_eq!(slack.user_id.as_deref(), Some("u1"));
+        assert_eq!(slack.merged, vec![widget.id]);
+        assert_eq!(slack.created_at, widget.created_at);
+
+        let mut other = Conversation::new();
+        other.user_id = Some("u2".to_string());
+        assert!(slack.merge(other).is_err());
+    }
+}
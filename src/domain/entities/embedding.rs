@@ -1,3 +1,4 @@
+use half::f16;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,22 +22,104 @@ impl Embedding {
     }
 
     pub fn cosine_similarity(&self, other: &Embedding) -> f32 {
-        if self.0.len() != other.0.len() || self.0.is_empty() {
-            return 0.0;
+        cosine(&self.0, other.0.iter().copied())
+    }
+}
+
+/// How a store or cache holds embeddings in memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingPrecision {
+    #[default]
+    F32,
+    /// Half the memory of `f32`.
+    F16,
+    /// A quarter of the memory of `f32`, scaled per vector.
+    Int8,
+}
+
+/// An embedding held at an [`EmbeddingPrecision`], dequantized when scored.
+#[derive(Debug, Clone)]
+pub enum QuantizedEmbedding {
+    F32(Vec<f32>),
+    F16(Vec<f16>),
+    /// Each value is `value * scale`, with the largest magnitude at 127.
+    Int8 {
+        values: Vec<i8>,
+        scale: f32,
+    },
+}
+
+impl QuantizedEmbedding {
+    pub fn new(embedding: Embedding, precision: EmbeddingPrecision) -> Self {
+        match precision {
+            EmbeddingPrecision::F32 => Self::F32(embedding.0),
+            EmbeddingPrecision::F16 => {
+                Self::F16(embedding.0.iter().map(|&x| f16::from_f32(x)).collect())
+            }
+            EmbeddingPrecision::Int8 => {
+                let max = embedding.0.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+                let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+                Self::Int8 {
+                    values: embedding
+                        .0
+                        .iter()
+                        .map(|x| (x / scale).round().clamp(-127.0, 127.0) as i8)
+                        .collect(),
+                    scale,
+                }
+            }
         }
+    }
 
-        let dot_product: f32 = self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum();
-        let norm_a: f32 = self.0.iter().map(|x| x * x).sum::<f32>().sqrt();
-        let norm_b: f32 = other.0.iter().map(|x| x * x).sum::<f32>().sqrt();
+    pub fn precision(&self) -> EmbeddingPrecision {
+        match self {
+            Self::F32(_) => EmbeddingPrecision::F32,
+            Self::F16(_) => EmbeddingPrecision::F16,
+            Self::Int8 { .. } => EmbeddingPrecision::Int8,
+        }
+    }
 
-        if norm_a == 0.0 || norm_b == 0.0 {
-            return 0.0;
+    pub fn dequantize(&self) -> Embedding {
+        match self {
+            Self::F32(values) => Embedding(values.clone()),
+            Self::F16(values) => Embedding(values.iter().map(|x| x.to_f32()).collect()),
+            Self::Int8 { values, scale } => {
+                Embedding(values.iter().map(|&x| x as f32 * scale).collect())
+            }
         }
+    }
 
-        dot_product / (norm_a * norm_b)
+    /// [`Embedding::cosine_similarity`] with `query`, dequantizing as it goes.
+    pub fn cosine_similarity(&self, query: &Embedding) -> f32 {
+        match self {
+            Self::F32(values) => cosine(&query.0, values.iter().copied()),
+            Self::F16(values) => cosine(&query.0, values.iter().map(|x| x.to_f32())),
+            Self::Int8 { values, scale } => {
+                cosine(&query.0, values.iter().map(|&x| x as f32 * scale))
+            }
+        }
     }
 }
 
+fn cosine(a: &[f32], b: impl ExactSizeIterator<Item = f32>) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let (mut dot_product, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot_product += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot_product / (norm_a.sqrt() * norm_b.sqrt())
+}
+
 impl From<Vec<f32>> for Embedding {
     fn from(vec: Vec<f32>) -> Self {
         Self(vec)
@@ -48,3 +131,24 @@ impl AsRef<[f32]> for Embedding {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantized_scores_stay_close_to_f32() {
+        let stored = Embedding::new((0..64).map(|i| ((i * 37) % 23) as f32 - 11.0).collect());
+        let query = Embedding::new((0..64).map(|i| ((i * 11) % 17) as f32 - 8.0).collect());
+        let exact = query.cosine_similarity(&stored);
+
+        for precision in [EmbeddingPrecision::F16, EmbeddingPrecision::Int8] {
+            let quantized = QuantizedEmbedding::new(stored.clone(), precision);
+            assert_eq!(quantized.precision(), precision);
+            assert!((quantized.cosine_similarity(&query) - exact).abs() < 0.01);
+            assert_eq!(quantized.dequantize().dimension(), 64);
+        }
+        let zero = QuantizedEmbedding::new(Embedding::new(vec![0.0; 4]), EmbeddingPrecision::Int8);
+        assert_eq!(zero.cosine_similarity(&Embedding::new(vec![1.0; 4])), 0.0);
+    }
+}
@@ -1,7 +1,13 @@
+mod annotation;
 mod conversation;
 mod document;
 mod embedding;
 
-pub use conversation::{Conversation, Message, MessageRole};
-pub use document::{chunk_content, ChunkMetadata, Document, DocumentChunk, SearchResult};
-pub use embedding::Embedding;
+pub use annotation::{Annotation, AnswerRating, SourceLabel};
+pub use conversation::{ContextDocument, Conversation, Draft, DraftStatus, Message, MessageRole};
+pub use document::{
+    annotate_line_ranges, annotate_markdown_sections, chunk_content, chunk_content_by,
+    split_markdown_sections, validate_tenant_id, ChunkMetadata, Document, DocumentChunk,
+    ExtractedPage, Freshness, SearchFilter, SearchResult, UNTENANTED,
+};
+pub use embedding::{Embedding, EmbeddingPrecision, QuantizedEmbedding};
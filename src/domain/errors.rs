@@ -16,6 +16,27 @@ pub enum DomainError {
 
     #[error("Timeout: {0}")]
     Timeout(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    /// A provider refused the request itself (a 4xx status), e.g. bad
+    /// credentials or an unknown model.
+    #[error("Rejected by provider: {0}")]
+    Rejected(String),
+}
+
+/// How an error should be handled by callers that could try again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A provider asked us to slow down; retry after backing off.
+    RateLimited,
+    /// A dependency was unreachable or slow; retrying may succeed.
+    Transient,
+    /// A bug or unexpected state; retrying won't help.
+    Permanent,
+    /// The request itself is wrong or refers to something missing.
+    InvalidInput,
 }
 
 impl DomainError {
@@ -38,6 +59,93 @@ impl DomainError {
     pub fn timeout(msg: impl Into<String>) -> Self {
         Self::Timeout(msg.into())
     }
+
+    pub fn rate_limited(msg: impl Into<String>) -> Self {
+        Self::RateLimited(msg.into())
+    }
+
+    /// An error reported by an LLM or embedding provider, recognising
+    /// rate limiting (HTTP 429, quota exhaustion) in its message.
+    pub fn provider(msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        let lower = msg.to_lowercase();
+        let rate_limited = [
+            "429",
+            "rate limit",
+            "too many requests",
+            "resource_exhausted",
+            "quota",
+        ]
+        .iter()
+        .any(|marker| lower.contains(marker));
+        if rate_limited {
+            Self::RateLimited(msg)
+        } else {
+            Self::ExternalService(msg)
+        }
+    }
+
+    /// An error reported by a provider with HTTP `status`: 429 is rate
+    /// limiting, 408 a timeout, any other 4xx a rejection of the request.
+    pub fn provider_status(status: u16, msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        match status {
+            429 => Self::RateLimited(msg),
+            408 => Self::Timeout(msg),
+            400..=499 => Self::Rejected(msg),
+            _ => Self::ExternalService(msg),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NotFound(_) | Self::Validation(_) => ErrorKind::InvalidInput,
+            Self::Internal(_) | Self::Rejected(_) => ErrorKind::Permanent,
+            Self::ExternalService(_) | Self::Timeout(_) => ErrorKind::Transient,
+            Self::RateLimited(_) => ErrorKind::RateLimited,
+        }
+    }
+
+    /// Whether the same call may succeed if tried again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind(), ErrorKind::RateLimited | ErrorKind::Transient)
+    }
 }
 
 pub type Result<T> = std::result::Result<T, DomainError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_dependency_failures_are_retryable() {
+        assert!(DomainError::timeout("qdrant").is_retryable());
+        assert!(DomainError::external("connection refused").is_retryable());
+        assert!(!DomainError::validation("empty message").is_retryable());
+        assert!(!DomainError::not_found("document").is_retryable());
+        assert!(!DomainError::internal("bad state").is_retryable());
+
+        let limited = DomainError::provider("HTTP 429 Too Many Requests");
+        assert_eq!(limited.kind(), ErrorKind::RateLimited);
+        assert!(limited.is_retryable());
+        assert_eq!(
+            DomainError::provider("connection reset").kind(),
+            ErrorKind::Transient
+        );
+    }
+
+    #[test]
+    fn test_provider_status_classifies_by_status_code() {
+        assert_eq!(
+            DomainError::provider_status(429, "slow down").kind(),
+            ErrorKind::RateLimited
+        );
+        assert!(DomainError::provider_status(503, "unavailable").is_retryable());
+        assert!(DomainError::provider_status(408, "timeout").is_retryable());
+        // A 5xx body mentioning a 4xx code is still a server error.
+        assert!(DomainError::provider_status(500, "upstream returned 404").is_retryable());
+        assert!(!DomainError::provider_status(401, "bad key").is_retryable());
+        assert!(!DomainError::provider_status(404, "no such model").is_retryable());
+    }
+}
@@ -3,4 +3,4 @@ pub mod errors;
 pub mod ports;
 
 pub use entities::*;
-pub use errors::{DomainError, Result};
+pub use errors::{DomainError, ErrorKind, Result};
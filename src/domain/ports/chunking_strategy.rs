@@ -0,0 +1,26 @@
+use crate::domain::{DocumentChunk, ExtractedPage};
+use uuid::Uuid;
+
+/// Splits document text into chunks for indexing.
+pub trait ChunkingStrategy: Send + Sync {
+    /// Chunks `content`, indexing chunks sequentially from 0.
+    fn chunk(&self, document_id: Uuid, content: &str) -> Vec<DocumentChunk>;
+
+    /// Chunks each page separately, tagging chunks with their page number.
+    ///
+    /// Chunk indices run sequentially across pages.
+    fn chunk_pages(&self, document_id: Uuid, pages: &[ExtractedPage]) -> Vec<DocumentChunk> {
+        let mut chunks = Vec::new();
+        for page in pages {
+            for chunk in self.chunk(document_id, &page.text) {
+                let mut metadata = chunk.metadata;
+                metadata.page = page.page;
+                chunks.push(
+                    DocumentChunk::new(document_id, chunk.content, chunks.len())
+                        .with_metadata(metadata),
+                );
+            }
+        }
+        chunks
+    }
+}
@@ -0,0 +1,9 @@
+use crate::domain::{errors::DomainError, ExtractedPage};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ContentExtractor: Send + Sync {
+    /// Whether this extractor handles the given MIME type.
+    fn supports(&self, content_type: &str) -> bool;
+    async fn extract(&self, bytes: &[u8]) -> Result<Vec<ExtractedPage>, DomainError>;
+}
@@ -1,4 +1,4 @@
-use crate::domain::{errors::DomainError, Document, DocumentChunk};
+use crate::domain::{errors::DomainError, Document, DocumentChunk, ExtractedPage};
 use async_trait::async_trait;
 use uuid::Uuid;
 
@@ -6,7 +6,29 @@ use uuid::Uuid;
 pub trait DocumentStore: Send + Sync {
     async fn save_document(&self, doc: &Document) -> Result<(), DomainError>;
     async fn get_document(&self, id: Uuid) -> Result<Option<Document>, DomainError>;
+    /// A document of `tenant_id` (or shared, for `None`) whose `content_hash` is `hash`.
+    async fn find_by_content_hash(
+        &self,
+        hash: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<Document>, DomainError>;
     async fn delete_document(&self, id: Uuid) -> Result<(), DomainError>;
     async fn save_chunks(&self, chunks: &[DocumentChunk]) -> Result<(), DomainError>;
     async fn get_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError>;
+    /// Keeps a document's extracted text so it can be re-chunked later.
+    async fn save_content(
+        &self,
+        document_id: Uuid,
+        pages: &[ExtractedPage],
+    ) -> Result<(), DomainError>;
+    async fn get_content(
+        &self,
+        document_id: Uuid,
+    ) -> Result<Option<Vec<ExtractedPage>>, DomainError>;
+    /// Replaces every stored chunk of a document.
+    async fn replace_chunks(
+        &self,
+        document_id: Uuid,
+        chunks: &[DocumentChunk],
+    ) -> Result<(), DomainError>;
 }
@@ -1,9 +1,37 @@
 use crate::domain::{errors::DomainError, Embedding};
 use async_trait::async_trait;
 
+/// What a text is embedded for. Some models embed stored documents and
+/// search queries differently; others ignore the distinction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingPurpose {
+    Document,
+    Query,
+}
+
 #[async_trait]
 pub trait EmbeddingService: Send + Sync {
     async fn embed(&self, text: &str) -> Result<Embedding, DomainError>;
     async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError>;
     fn dimension(&self) -> usize;
+
+    /// Embeds `text` for `purpose`; providers without input types use [`Self::embed`].
+    async fn embed_for(
+        &self,
+        text: &str,
+        purpose: EmbeddingPurpose,
+    ) -> Result<Embedding, DomainError> {
+        let _ = purpose;
+        self.embed(text).await
+    }
+
+    /// Batch form of [`Self::embed_for`].
+    async fn embed_batch_for(
+        &self,
+        texts: &[&str],
+        purpose: EmbeddingPurpose,
+    ) -> Result<Vec<Embedding>, DomainError> {
+        let _ = purpose;
+        self.embed_batch(texts).await
+    }
 }
@@ -1,9 +1,54 @@
 use crate::domain::errors::DomainError;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+/// Tokens a completion consumed, as the provider reported them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+}
 
 #[async_trait]
 pub trait LlmService: Send + Sync {
     async fn complete(&self, prompt: &str) -> Result<String, DomainError>;
     async fn complete_with_system(&self, system: &str, prompt: &str)
         -> Result<String, DomainError>;
+
+    /// Like [`complete_with_system`](Self::complete_with_system), also
+    /// reporting the tokens used; empty when the backend doesn't say.
+    async fn complete_with_usage(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<(String, TokenUsage), DomainError> {
+        self.complete_with_system(system, prompt)
+            .await
+            .map(|text| (text, TokenUsage::default()))
+    }
 }
@@ -1,9 +1,15 @@
+mod chunking_strategy;
+mod content_extractor;
 mod document_store;
 mod embedding;
 mod llm;
+mod query_log;
 mod vector_store;
 
+pub use chunking_strategy::ChunkingStrategy;
+pub use content_extractor::ContentExtractor;
 pub use document_store::DocumentStore;
-pub use embedding::EmbeddingService;
-pub use llm::LlmService;
-pub use vector_store::VectorStore;
+pub use embedding::{EmbeddingPurpose, EmbeddingService};
+pub use llm::{LlmService, TokenUsage};
+pub use query_log::QueryLog;
+pub use vector_store::{MalformedPoint, PointPage, VectorStore};
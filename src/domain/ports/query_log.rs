@@ -0,0 +1,11 @@
+use crate::domain::DomainError;
+use async_trait::async_trait;
+
+/// Counts the queries retrieval is asked for, so frequent ones can be
+/// prepared ahead of time.
+#[async_trait]
+pub trait QueryLog: Send + Sync {
+    async fn record(&self, query: &str) -> Result<(), DomainError>;
+    /// The most frequent recent queries, most frequent first.
+    async fn frequent(&self, limit: usize) -> Result<Vec<String>, DomainError>;
+}
@@ -1,15 +1,100 @@
-use crate::domain::{errors::DomainError, DocumentChunk, Embedding, SearchResult};
+use crate::domain::{errors::DomainError, DocumentChunk, Embedding, SearchFilter, SearchResult};
 use async_trait::async_trait;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// One page of stored points, as returned by [`VectorStore::scroll_points`].
+#[derive(Debug, Clone, Default)]
+pub struct PointPage {
+    pub points: Vec<(DocumentChunk, Embedding)>,
+    /// Chunk id to resume from, `None` once the store is exhausted.
+    pub next: Option<Uuid>,
+}
+
+/// A stored point whose payload can't be read as a chunk, with whatever
+/// could still be recovered from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedPoint {
+    pub id: String,
+    pub document_id: Option<Uuid>,
+    pub chunk_index: Option<usize>,
+    pub error: String,
+}
+
 #[async_trait]
 pub trait VectorStore: Send + Sync {
     async fn upsert(&self, chunk: &DocumentChunk, embedding: &Embedding)
         -> Result<(), DomainError>;
+    /// Length of the vectors the collection stores, for stores fixed to one.
+    fn dimension(&self) -> Option<usize> {
+        None
+    }
+    /// The `top_k` chunks nearest to `query` among those matching `filter`.
     async fn search(
         &self,
         query: &Embedding,
         top_k: usize,
+        filter: &SearchFilter,
     ) -> Result<Vec<SearchResult>, DomainError>;
+    /// The `top_k` chunks matching `filter` that best match `query` by keyword
+    /// (BM25). Stores without a keyword index return nothing.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let _ = (query, top_k, filter);
+        Ok(Vec::new())
+    }
+
     async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError>;
+    /// Returns every stored chunk, without embeddings.
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError>;
+
+    /// The stored chunks of one document in chunk order, without embeddings.
+    async fn document_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        let mut chunks: Vec<DocumentChunk> = self
+            .list_chunks()
+            .await?
+            .into_iter()
+            .filter(|chunk| chunk.document_id == document_id)
+            .collect();
+        chunks.sort_by_key(|chunk| chunk.chunk_index);
+        Ok(chunks)
+    }
+
+    /// Up to `limit` stored chunks with their embeddings, starting at chunk
+    /// `offset`, for copying a store to another backend.
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        let _ = (offset, limit);
+        Err(DomainError::internal(
+            "Vector store does not support exporting points",
+        ))
+    }
+
+    /// Stored vectors of the chunks with these ids; ids the store can't
+    /// find, or stores that can't look vectors up, are left out.
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        let _ = ids;
+        Ok(HashMap::new())
+    }
+
+    /// Points skipped by searches because their payload is unreadable.
+    /// Stores that keep chunks typed have none.
+    async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+        Ok(Vec::new())
+    }
+
+    /// Replaces the payload of the point with `chunk.id`, keeping its vector.
+    async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+        let _ = chunk;
+        Err(DomainError::internal(
+            "Vector store does not support rewriting payloads",
+        ))
+    }
 }
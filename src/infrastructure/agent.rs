@@ -1,38 +1,167 @@
-use rig::client::{CompletionClient, ProviderClient};
-use rig::completion::Prompt;
-use rig::providers::gemini;
-use std::sync::Arc;
-use std::time::Duration;
+use rig::tool::{Tool, ToolDyn};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
 use crate::application::RagService;
-use crate::domain::{DomainError, Message};
-use crate::infrastructure::config::{AppConfig, KnowledgeBaseToolConfig};
-use crate::infrastructure::tools::KnowledgeBaseTool;
+use crate::domain::{ports::TokenUsage, DomainError, Message, SearchFilter, SearchResult};
+use crate::infrastructure::circuit_breaker::CircuitBreaker;
+use crate::infrastructure::config::{AppConfig, KnowledgeBaseToolConfig, LlmProvider, ToolLimits};
+use crate::infrastructure::latency::{AgentTimings, StageTimer};
+use crate::infrastructure::llm::{GenerationParams, LlmClient};
+use crate::infrastructure::retry::{retry, should_retry, RetryPolicy};
+use crate::infrastructure::tools::{
+    KnowledgeBaseArgs, KnowledgeBaseTool, LimitedTool, RetrievedSources,
+};
 
+/// Tool-calling rounds allowed while streaming a response.
+const STREAM_MAX_TURNS: usize = 5;
+/// Added to the system prompt when the agent runs without its knowledge base.
+const DEGRADED_INSTRUCTIONS: &str = "The knowledge base is temporarily unavailable. \
+Answer from general knowledge and the conversation only, and say so when the \
+answer likely depends on documentation you cannot access.";
+/// Introduces knowledge base results retrieved up front for models without
+/// tool calling.
+const INLINE_CONTEXT_HEADER: &str =
+    "Knowledge base results for the user's message (use them if relevant):";
+
+/// How a run reaches the knowledge base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retrieval {
+    /// The model calls the knowledge base tool.
+    Tool,
+    /// Results for the user's message join the system prompt, for models
+    /// that can't call tools.
+    Inline,
+    /// Not at all; the vector store is unavailable.
+    Degraded,
+}
+
+/// Answer text streamed so far, shared between the agent and whoever polls it.
+#[derive(Debug, Default)]
+pub struct PartialResponse {
+    text: Mutex<String>,
+}
+
+impl PartialResponse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, delta: &str) {
+        self.text
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push_str(delta);
+    }
+
+    pub fn snapshot(&self) -> String {
+        self.text.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn clear(&self) {
+        self.text.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// An agent response with its timings and the sources the agent retrieved.
+#[derive(Debug, Clone)]
+pub struct AgentReply {
+    pub response: String,
+    pub timings: AgentTimings,
+    pub sources: Vec<SearchResult>,
+    /// Answered without the knowledge base tool because the vector store
+    /// was unavailable.
+    pub degraded: bool,
+    /// Provider and model that produced the answer.
+    pub provider: LlmProvider,
+    pub model: String,
+    /// Whether one of `llm.fallbacks` answered because the primary failed.
+    pub fallback: bool,
+    /// Tokens the answering attempt used across its tool-calling rounds, as
+    /// the provider reported them.
+    pub usage: TokenUsage,
+    /// Served from the response cache without calling the model.
+    pub cached: bool,
+}
+
+/// A provider and model the agent can answer with.
+#[derive(Clone)]
+struct LlmBackend {
+    client: LlmClient,
+    model: String,
+    /// Whether the model can call tools (see `llm.ollama.tools`).
+    tool_calling: bool,
+}
+
+#[derive(Clone)]
 pub struct ChatAgent {
-    client: gemini::Client,
+    client: LlmClient,
     model: String,
+    /// Sampling settings, shared by the fallbacks.
+    generation: GenerationParams,
     system_prompt: String,
     rag: Arc<RagService>,
     top_k: usize,
+    min_score: f32,
     tool_config: KnowledgeBaseToolConfig,
+    tool_limits: ToolLimits,
     timeout: Duration,
+    /// Retries of failed model calls, all within `timeout`.
+    retry: RetryPolicy,
+    /// Whether the model can call tools (see `llm.ollama.tools`).
+    tool_calling: bool,
+    /// Tried in order when the primary times out or answers 429 or 5xx.
+    fallbacks: Vec<LlmBackend>,
+    /// When set and open, chats run without the knowledge base tool.
+    vector_store_breaker: Option<Arc<CircuitBreaker>>,
+    /// Knowledge base results the tool returns instead of searching.
+    pinned_sources: Option<Arc<Vec<SearchResult>>>,
 }
 
 impl ChatAgent {
-    pub fn new(rag: Arc<RagService>, config: &AppConfig) -> Self {
-        Self {
-            client: gemini::Client::from_env(),
+    /// Builds the agent on the backend `llm.provider` names, falling back
+    /// to `llm.fallbacks`.
+    pub fn new(rag: Arc<RagService>, config: &AppConfig) -> Result<Self, DomainError> {
+        let llm = &config.config.llm;
+        let generation = llm.generation();
+        generation.validate()?;
+        let fallbacks = llm
+            .fallbacks
+            .iter()
+            .map(|fallback| {
+                let config = llm.for_fallback(fallback);
+                Ok(LlmBackend {
+                    client: LlmClient::from_config(&config)?,
+                    tool_calling: config.tool_calling(),
+                    model: config.model,
+                })
+            })
+            .collect::<Result<_, DomainError>>()?;
+        Ok(Self {
+            client: LlmClient::from_config(&config.config.llm)?,
             model: config.config.llm.model.clone(),
+            generation,
             system_prompt: config.prompts.agent.system.clone(),
             rag,
             top_k: config.config.rag.top_k,
+            min_score: config.config.rag.min_score,
             tool_config: config.config.tools.knowledge_base.clone(),
+            tool_limits: config
+                .config
+                .tools
+                .limits
+                .with_overrides(&config.config.tools.knowledge_base.limits),
             timeout: Duration::from_secs(config.config.llm.timeout_seconds),
-        }
+            retry: config.config.llm.retry,
+            tool_calling: config.config.llm.tool_calling(),
+            fallbacks,
+            vector_store_breaker: None,
+            pinned_sources: None,
+        })
     }
 
-    pub fn with_defaults(rag: Arc<RagService>) -> Self {
+    pub fn with_defaults(rag: Arc<RagService>) -> Result<Self, DomainError> {
         Self::new(rag, &AppConfig::default())
     }
 
@@ -41,21 +170,81 @@ impl ChatAgent {
         self
     }
 
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Retrieval over the knowledge_base tool's collection.
+    pub fn rag(&self) -> &Arc<RagService> {
+        &self.rag
+    }
+
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
         self.model = model.into();
         self
     }
 
+    pub fn system_prompt(&self) -> &str {
+        &self.system_prompt
+    }
+
     pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
         self.system_prompt = prompt.into();
         self
     }
 
+    /// Replaces the sampling settings `overrides` sets, e.g. with a chat
+    /// request's own.
+    pub fn with_generation(mut self, overrides: &GenerationParams) -> Self {
+        self.generation = self.generation.merged(overrides);
+        self
+    }
+
     pub fn with_top_k(mut self, top_k: usize) -> Self {
         self.top_k = top_k;
         self
     }
 
+    /// Degrades to answering without the knowledge base tool while
+    /// `breaker` is open, and retries a failed chat that way if it opened
+    /// during the run.
+    pub fn with_degraded_mode(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.vector_store_breaker = Some(breaker);
+        self
+    }
+
+    /// Answers from `sources` instead of searching the knowledge base, to
+    /// replay a past chat as it was.
+    pub fn with_pinned_sources(mut self, sources: Vec<SearchResult>) -> Self {
+        self.pinned_sources = Some(Arc::new(sources));
+        self
+    }
+
+    fn knowledge_base_unavailable(&self) -> bool {
+        self.vector_store_breaker
+            .as_ref()
+            .is_some_and(|breaker| breaker.is_open())
+    }
+
+    /// Ids of the chunks nearest `message` in the knowledge base tool's
+    /// collection, e.g. to tell whether a cached answer still applies. Costs
+    /// an embedding call and a vector search of its own.
+    pub async fn knowledge_base_ids(
+        &self,
+        message: &str,
+        filter: SearchFilter,
+    ) -> Result<Vec<Uuid>, DomainError> {
+        let filter = filter.with_default_tags(&self.tool_config.tags);
+        self.rag
+            .nearest_chunk_ids(message, self.top_k, &filter)
+            .await
+    }
+
     pub async fn chat(&self, message: &str) -> Result<String, DomainError> {
         self.chat_with_history(message, &[]).await
     }
@@ -65,21 +254,261 @@ impl ChatAgent {
         message: &str,
         history: &[Message],
     ) -> Result<String, DomainError> {
-        let tool = KnowledgeBaseTool::new(self.rag.clone(), self.top_k, self.tool_config.clone());
+        self.chat_with_history_timed(message, history)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// Like [`chat_with_history`](Self::chat_with_history), also reporting where the time went.
+    ///
+    /// LLM time is the agent's wall-clock time minus what the knowledge base tool spent.
+    pub async fn chat_with_history_timed(
+        &self,
+        message: &str,
+        history: &[Message],
+    ) -> Result<(String, AgentTimings), DomainError> {
+        self.chat_with_sources(message, history)
+            .await
+            .map(|reply| (reply.response, reply.timings))
+    }
 
-        let agent = self
+    /// Like [`chat_with_history_timed`](Self::chat_with_history_timed), also
+    /// returning metadata of every chunk the knowledge base tool retrieved.
+    pub async fn chat_with_sources(
+        &self,
+        message: &str,
+        history: &[Message],
+    ) -> Result<AgentReply, DomainError> {
+        self.run(message, history, None, SearchFilter::default(), None)
+            .await
+    }
+
+    /// Like [`chat_with_sources`](Self::chat_with_sources), streaming the answer
+    /// into `partial` as it is generated. `instructions` are appended to the
+    /// system prompt and `filter` restricts knowledge base searches, for this
+    /// request only.
+    pub async fn chat_streaming(
+        &self,
+        message: &str,
+        history: &[Message],
+        instructions: Option<String>,
+        filter: SearchFilter,
+        partial: &PartialResponse,
+    ) -> Result<AgentReply, DomainError> {
+        self.run(message, history, instructions, filter, Some(partial))
+            .await
+    }
+
+    async fn run(
+        &self,
+        message: &str,
+        history: &[Message],
+        instructions: Option<String>,
+        filter: SearchFilter,
+        partial: Option<&PartialResponse>,
+    ) -> Result<AgentReply, DomainError> {
+        let primary = LlmBackend {
+            client: self.client.clone(),
+            model: self.model.clone(),
+            tool_calling: self.tool_calling,
+        };
+        let mut reply = self
+            .run_on(
+                &primary,
+                message,
+                history,
+                instructions.clone(),
+                filter.clone(),
+                partial,
+            )
+            .await;
+        for backend in &self.fallbacks {
+            match &reply {
+                Err(e) if should_retry(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        fallback = backend.client.provider().as_str(),
+                        model = %backend.model,
+                        "LLM provider failed, falling back"
+                    );
+                }
+                _ => break,
+            }
+            if let Some(partial) = partial {
+                partial.clear();
+            }
+            reply = self
+                .run_on(
+                    backend,
+                    message,
+                    history,
+                    instructions.clone(),
+                    filter.clone(),
+                    partial,
+                )
+                .await
+                .map(|reply| AgentReply {
+                    fallback: true,
+                    ..reply
+                });
+        }
+        reply
+    }
+
+    /// Answers on `backend`, retrying without the knowledge base if the
+    /// vector store went down or with inline retrieval if the model turned
+    /// out not to call tools.
+    async fn run_on(
+        &self,
+        backend: &LlmBackend,
+        message: &str,
+        history: &[Message],
+        instructions: Option<String>,
+        filter: SearchFilter,
+        partial: Option<&PartialResponse>,
+    ) -> Result<AgentReply, DomainError> {
+        let retrieval = if self.knowledge_base_unavailable() {
+            Retrieval::Degraded
+        } else if backend.tool_calling {
+            Retrieval::Tool
+        } else {
+            Retrieval::Inline
+        };
+        let reply = self
+            .run_once(
+                backend,
+                message,
+                history,
+                instructions.clone(),
+                filter.clone(),
+                partial,
+                retrieval,
+            )
+            .await;
+        let fallback = match &reply {
+            Err(e) if retrieval != Retrieval::Degraded && self.knowledge_base_unavailable() => {
+                tracing::warn!(error = %e, "vector store became unavailable, retrying without knowledge base");
+                Retrieval::Degraded
+            }
+            Err(e) if retrieval == Retrieval::Tool && is_tool_calling_unsupported(e) => {
+                tracing::warn!(error = %e, model = %backend.model, "model can't call tools, retrying with inline retrieval");
+                Retrieval::Inline
+            }
+            _ => return reply,
+        };
+        if let Some(partial) = partial {
+            partial.clear();
+        }
+        self.run_once(
+            backend,
+            message,
+            history,
+            instructions,
+            filter,
+            partial,
+            fallback,
+        )
+        .await
+    }
+
+    async fn run_once(
+        &self,
+        backend: &LlmBackend,
+        message: &str,
+        history: &[Message],
+        instructions: Option<String>,
+        filter: SearchFilter,
+        partial: Option<&PartialResponse>,
+        retrieval: Retrieval,
+    ) -> Result<AgentReply, DomainError> {
+        let degraded = retrieval == Retrieval::Degraded;
+        let timer = Arc::new(StageTimer::new());
+        let sources = Arc::new(RetrievedSources::new());
+        let tool = KnowledgeBaseTool::new(self.rag.clone(), self.top_k, self.tool_config.clone())
+            .with_min_score(self.min_score)
+            .with_timer(timer.clone())
+            .with_sources(sources.clone())
+            .with_filter(filter);
+        let tool = match &self.pinned_sources {
+            Some(pinned) => tool.with_pinned_results(pinned.clone()),
+            None => tool,
+        };
+        let tool = LimitedTool::new(tool, self.tool_limits);
+
+        let started = Instant::now();
+        let mut context = None;
+        let mut tools: Vec<Box<dyn ToolDyn>> = Vec::new();
+        match retrieval {
+            Retrieval::Tool => tools.push(Box::new(tool)),
+            Retrieval::Inline => {
+                let results = Tool::call(
+                    &tool,
+                    KnowledgeBaseArgs {
+                        query: message.to_string(),
+                    },
+                )
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+                context = Some(format!("{INLINE_CONTEXT_HEADER}\n\n{results}"));
+            }
+            Retrieval::Degraded => {}
+        }
+
+        let preamble = [
+            Some(self.system_prompt.clone()),
+            instructions,
+            context,
+            degraded.then(|| DEGRADED_INSTRUCTIONS.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n\n");
+        let agent = backend
             .client
-            .agent(&self.model)
-            .preamble(&self.system_prompt)
-            .tool(tool)
-            .build();
+            .agent(&backend.model, &preamble, &self.generation, tools);
 
         let prompt = self.build_prompt(message, history);
 
-        tokio::time::timeout(self.timeout, agent.prompt(&prompt))
+        let run = retry(&self.retry, "agent_chat", || async {
+            // Start over from what a failed attempt streamed and retrieved.
+            if retrieval == Retrieval::Tool {
+                sources.take();
+            }
+            let Some(partial) = partial else {
+                return agent.prompt(&prompt, 0).await;
+            };
+            partial.clear();
+            agent
+                .stream(&prompt, STREAM_MAX_TURNS, |text| partial.push(text))
+                .await
+        });
+        let (response, usage) = tokio::time::timeout(self.timeout, run)
             .await
-            .map_err(|_| DomainError::timeout("Agent execution timed out"))?
-            .map_err(|e| DomainError::external(format!("Agent failed: {e}")))
+            .map_err(|_| DomainError::timeout("Agent execution timed out"))??;
+
+        let embedding = timer.embedding();
+        let retrieval = timer.retrieval();
+        let timings = AgentTimings {
+            embedding,
+            retrieval,
+            llm: started
+                .elapsed()
+                .saturating_sub(embedding)
+                .saturating_sub(retrieval),
+        };
+
+        Ok(AgentReply {
+            response,
+            timings,
+            sources: sources.take(),
+            degraded,
+            provider: backend.client.provider(),
+            model: backend.model.clone(),
+            fallback: false,
+            usage,
+            cached: false,
+        })
     }
 
     pub async fn chat_multi_turn(
@@ -87,19 +516,24 @@ impl ChatAgent {
         message: &str,
         max_turns: usize,
     ) -> Result<String, DomainError> {
-        let tool = KnowledgeBaseTool::new(self.rag.clone(), self.top_k, self.tool_config.clone());
+        let tool = KnowledgeBaseTool::new(self.rag.clone(), self.top_k, self.tool_config.clone())
+            .with_min_score(self.min_score);
+        let tool = LimitedTool::new(tool, self.tool_limits);
 
-        let agent = self
-            .client
-            .agent(&self.model)
-            .preamble(&self.system_prompt)
-            .tool(tool)
-            .build();
+        let agent = self.client.agent(
+            &self.model,
+            &self.system_prompt,
+            &self.generation,
+            vec![Box::new(tool)],
+        );
 
-        tokio::time::timeout(self.timeout, agent.prompt(message).multi_turn(max_turns))
+        let run = retry(&self.retry, "agent_chat", || {
+            agent.prompt(message, max_turns)
+        });
+        tokio::time::timeout(self.timeout, run)
             .await
             .map_err(|_| DomainError::timeout("Agent execution timed out"))?
-            .map_err(|e| DomainError::external(format!("Agent failed: {e}")))
+            .map(|(response, _)| response)
     }
 
     fn build_prompt(&self, message: &str, history: &[Message]) -> String {
@@ -119,3 +553,12 @@ impl ChatAgent {
         )
     }
 }
+
+/// Whether `error` says the model has no tool calling, as Ollama reports
+/// for models such as `gemma` and Bedrock for Titan.
+fn is_tool_calling_unsupported(error: &DomainError) -> bool {
+    let message = error.to_string().to_lowercase();
+    ["does not support tools", "doesn't support tool use"]
+        .iter()
+        .any(|marker| message.contains(marker))
+}
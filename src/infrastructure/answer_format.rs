@@ -0,0 +1,298 @@
+//! Per-request answer length and markup.
+//!
+//! The options are given to the model as instructions and then enforced on
+//! its reply, since models follow length and format hints only loosely.
+
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::chunking::count_tokens;
+
+/// Markup of the final answer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerFormat {
+    #[default]
+    Markdown,
+    /// No markup, for chat widgets that render text as is.
+    Plain,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnswerOptions {
+    pub max_answer_tokens: Option<usize>,
+    pub format: AnswerFormat,
+}
+
+impl AnswerOptions {
+    /// Extra system prompt lines asking the model for this length and format.
+    pub fn instructions(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        if let Some(max) = self.max_answer_tokens {
+            lines.push(format!(
+                "Keep your answer under {max} tokens (roughly {} words).",
+                max * 3 / 4
+            ));
+        }
+        match self.format {
+            AnswerFormat::Markdown => {}
+            AnswerFormat::Plain => {
+                lines.push("Answer in plain text without any Markdown or HTML.".to_string())
+            }
+            AnswerFormat::Html => lines.push(
+                "Format your answer with Markdown; it will be converted to HTML.".to_string(),
+            ),
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Cuts `answer` to `max_answer_tokens`, preferring a sentence boundary.
+    pub fn truncate(&self, answer: &str) -> String {
+        let Some(max) = self.max_answer_tokens else {
+            return answer.to_string();
+        };
+        if count_tokens(answer) <= max {
+            return answer.to_string();
+        }
+
+        // Binary search over word boundaries for the longest prefix that fits.
+        let boundaries: Vec<usize> = answer
+            .split_inclusive(char::is_whitespace)
+            .scan(0, |end, piece| {
+                *end += piece.len();
+                Some(*end)
+            })
+            .collect();
+        let fits = boundaries.partition_point(|&end| count_tokens(answer[..end].trim_end()) <= max);
+        let kept = match fits {
+            0 => "",
+            n => answer[..boundaries[n - 1]].trim_end(),
+        };
+
+        match kept.rfind(['.', '!', '?']) {
+            Some(end) if end >= kept.len() / 2 => kept[..=end].to_string(),
+            _ => format!("{kept}…"),
+        }
+    }
+
+    /// Renders a Markdown `answer` in the requested format.
+    pub fn render(&self, answer: &str) -> String {
+        match self.format {
+            AnswerFormat::Markdown => answer.to_string(),
+            AnswerFormat::Plain => markdown_to_plain(answer),
+            AnswerFormat::Html => markdown_to_html(answer),
+        }
+    }
+}
+
+/// Strips Markdown markup, keeping list bullets and link targets.
+pub fn markdown_to_plain(markdown: &str) -> String {
+    markdown
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .map(|line| {
+            let stripped = line.trim_start_matches(['#', '>']);
+            let line = if stripped.len() < line.len() {
+                stripped.trim_start()
+            } else {
+                line
+            };
+            let line = match line.strip_prefix("* ") {
+                Some(item) => format!("- {item}"),
+                None => line.to_string(),
+            };
+            render_inline(&line, false)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Converts the Markdown subset models produce (headings, lists, code
+/// blocks, emphasis, inline code and links) to HTML.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html: Vec<String> = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut list: Option<&str> = None;
+    let mut code: Option<Vec<String>> = None;
+
+    let flush_paragraph = |html: &mut Vec<String>, paragraph: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            html.push(format!("<p>{}</p>", paragraph.join("<br>")));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        if let Some(block) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                html.push(format!("<pre><code>{}</code></pre>", block.join("\n")));
+                code = None;
+            } else {
+                block.push(escape_html(line));
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .map(|item| ("ul", item))
+            .or_else(|| {
+                let (number, item) = trimmed.split_once(". ")?;
+                (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+                    .then_some(("ol", item))
+            });
+
+        if list.is_some() && item.map(|(tag, _)| tag) != list {
+            html.push(format!("</{}>", list.take().unwrap_or("ul")));
+        }
+
+        if trimmed.starts_with("```") {
+            flush_paragraph(&mut html, &mut paragraph);
+            code = Some(Vec::new());
+        } else if let Some((tag, item)) = item {
+            flush_paragraph(&mut html, &mut paragraph);
+            if list.is_none() {
+                html.push(format!("<{tag}>"));
+                list = Some(tag);
+            }
+            html.push(format!("<li>{}</li>", render_inline(item, true)));
+        } else if trimmed.starts_with('#') {
+            flush_paragraph(&mut html, &mut paragraph);
+            let level = trimmed.chars().take_while(|c| *c == '#').count().min(6);
+            let text = render_inline(trimmed[level..].trim(), true);
+            html.push(format!("<h{level}>{text}</h{level}>"));
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut html, &mut paragraph);
+        } else {
+            paragraph.push(render_inline(trimmed, true));
+        }
+    }
+
+    if let Some(block) = code {
+        html.push(format!("<pre><code>{}</code></pre>", block.join("\n")));
+    }
+    if let Some(tag) = list {
+        html.push(format!("</{tag}>"));
+    }
+    flush_paragraph(&mut html, &mut paragraph);
+    html.join("\n")
+}
+
+/// Renders emphasis, inline code and links as HTML, or drops their markup.
+fn render_inline(text: &str, html: bool) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                let code = &rest[1..=end];
+                if html {
+                    out.push_str(&format!("<code>{}</code>", escape_html(code)));
+                } else {
+                    out.push_str(code);
+                }
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some((label, url, len)) = parse_link(rest) {
+                if html {
+                    out.push_str(&format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(url),
+                        render_inline(label, true)
+                    ));
+                } else {
+                    out.push_str(&format!("{label} ({url})"));
+                }
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        // `_` is left alone so identifiers like `max_tokens` survive.
+        let emphasis = [("**", "strong"), ("*", "em")]
+            .into_iter()
+            .find_map(|(marker, tag)| {
+                let inner = rest.strip_prefix(marker)?;
+                if inner.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                let end = inner.find(marker).filter(|&end| end > 0)?;
+                Some((tag, &inner[..end], &inner[end + marker.len()..]))
+            });
+        if let Some((tag, body, after)) = emphasis {
+            let body = render_inline(body, html);
+            if html {
+                out.push_str(&format!("<{tag}>{body}</{tag}>"));
+            } else {
+                out.push_str(&body);
+            }
+            rest = after;
+            continue;
+        }
+
+        if html {
+            out.push_str(&escape_html(&c.to_string()));
+        } else {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+/// `[label](url)` at the start of `text`, with the length it spans.
+pub(crate) fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let url_end = text[label_end + 2..].find(')')? + label_end + 2;
+    Some((
+        &text[1..label_end],
+        &text[label_end + 2..url_end],
+        url_end + 1,
+    ))
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWER: &str =
+        "## Setup\n\nRun **make** or see [the guide](https://x.io/g).\n\n- one\n- `two`";
+
+    #[test]
+    fn test_render_formats() {
+        assert_eq!(
+            markdown_to_plain(ANSWER),
+            "Setup\n\nRun make or see the guide (https://x.io/g).\n\n- one\n- two"
+        );
+        assert_eq!(
+            markdown_to_html(ANSWER),
+            "<h2>Setup</h2>\n<p>Run <strong>make</strong> or see <a href=\"https://x.io/g\">the guide</a>.</p>\n\
+             <ul>\n<li>one</li>\n<li><code>two</code></li>\n</ul>"
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_sentence() {
+        let options = AnswerOptions {
+            max_answer_tokens: Some(6),
+            ..Default::default()
+        };
+        let answer = "First sentence here. Second sentence is much longer than that.";
+
+        assert_eq!(options.truncate(answer), "First sentence here.");
+        assert_eq!(AnswerOptions::default().truncate(answer), answer);
+    }
+}
@@ -0,0 +1,95 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::infrastructure::queue::Channel;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BannerPosition {
+    Prepend,
+    #[default]
+    Append,
+}
+
+/// Text added to answers, e.g. "AI-generated, verify critical information."
+#[derive(Debug, Clone, Deserialize)]
+pub struct Banner {
+    pub text: String,
+    #[serde(default)]
+    pub position: BannerPosition,
+    /// Channels that show the banner; empty means all.
+    #[serde(default)]
+    pub channels: Vec<Channel>,
+}
+
+impl Banner {
+    /// Decorates `response` if the banner is enabled for `channel`.
+    pub fn apply(&self, response: &str, channel: Channel) -> String {
+        if !self.channels.is_empty() && !self.channels.contains(&channel) {
+            return response.to_string();
+        }
+
+        match self.position {
+            BannerPosition::Prepend => format!("{}\n\n{}", self.text, response),
+            BannerPosition::Append => format!("{}\n\n{}", response.trim_end(), self.text),
+        }
+    }
+}
+
+/// A default banner plus per-agent overrides.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BannerConfig {
+    #[serde(default)]
+    pub default: Option<Banner>,
+    #[serde(default)]
+    pub agents: HashMap<String, Banner>,
+}
+
+impl BannerConfig {
+    pub fn for_agent(&self, agent_id: Option<&str>) -> Option<&Banner> {
+        agent_id
+            .and_then(|id| self.agents.get(id))
+            .or(self.default.as_ref())
+    }
+
+    pub fn decorate(&self, response: &str, agent_id: Option<&str>, channel: Channel) -> String {
+        match self.for_agent(agent_id) {
+            Some(banner) => banner.apply(response, channel),
+            None => response.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner_respects_agent_and_channel() {
+        let config = BannerConfig {
+            default: Some(Banner {
+                text: "AI-generated.".to_string(),
+                position: BannerPosition::Append,
+                channels: vec![Channel::Widget, Channel::Slack],
+            }),
+            agents: HashMap::from([(
+                "legal".to_string(),
+                Banner {
+                    text: "Not legal advice.".to_string(),
+                    position: BannerPosition::Prepend,
+                    channels: Vec::new(),
+                },
+            )]),
+        };
+
+        assert_eq!(config.decorate("Hi", None, Channel::Api), "Hi");
+        assert_eq!(
+            config.decorate("Hi", None, Channel::Slack),
+            "Hi\n\nAI-generated."
+        );
+        assert_eq!(
+            config.decorate("Hi", Some("legal"), Channel::Api),
+            "Not legal advice.\n\nHi"
+        );
+    }
+}
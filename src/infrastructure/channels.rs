@@ -0,0 +1,291 @@
+//! Reshaping of final answers for the apps they are delivered to.
+//!
+//! Answers are produced as Markdown; Slack, Telegram and SMS each render a
+//! different (or no) markup, and SMS also limits message length.
+
+use serde::Deserialize;
+
+use crate::infrastructure::answer_format::{escape_html, markdown_to_plain, parse_link};
+use crate::infrastructure::queue::Channel;
+
+/// Turns a Markdown answer into what a channel displays.
+pub trait ChannelFormatter: Send + Sync {
+    fn format(&self, markdown: &str) -> String;
+
+    /// Splits a formatted answer into the messages to send; one unless the
+    /// channel limits message length.
+    fn split(&self, formatted: &str) -> Vec<String> {
+        vec![formatted.to_string()]
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChannelsConfig {
+    pub sms: SmsConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SmsConfig {
+    /// Characters per message; longer answers are split between words.
+    pub max_chars: usize,
+}
+
+impl Default for SmsConfig {
+    fn default() -> Self {
+        Self { max_chars: 160 }
+    }
+}
+
+impl ChannelsConfig {
+    /// The formatter for `channel`, `None` where answers are shown as
+    /// requested with `format`.
+    pub fn formatter(&self, channel: Channel) -> Option<Box<dyn ChannelFormatter>> {
+        match channel {
+            Channel::Api | Channel::Widget => None,
+            Channel::Slack => Some(Box::new(SlackFormatter)),
+            Channel::Telegram => Some(Box::new(TelegramFormatter)),
+            Channel::Sms => Some(Box::new(SmsFormatter::new(self.sms.max_chars))),
+        }
+    }
+}
+
+/// Slack `mrkdwn`: `*bold*`, `_italic_`, `<url|label>` links, no headings.
+pub struct SlackFormatter;
+
+impl ChannelFormatter for SlackFormatter {
+    fn format(&self, markdown: &str) -> String {
+        render_blocks(markdown, &SLACK)
+    }
+}
+
+/// Telegram's HTML parse mode: `<b>`, `<i>`, `<code>`, `<pre>` and `<a>`
+/// only, with line breaks kept as is.
+pub struct TelegramFormatter;
+
+impl ChannelFormatter for TelegramFormatter {
+    fn format(&self, markdown: &str) -> String {
+        render_blocks(markdown, &TELEGRAM)
+    }
+}
+
+/// Plain text split into messages of at most `max_chars` characters.
+pub struct SmsFormatter {
+    max_chars: usize,
+}
+
+impl SmsFormatter {
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            max_chars: max_chars.max(1),
+        }
+    }
+}
+
+impl ChannelFormatter for SmsFormatter {
+    fn format(&self, markdown: &str) -> String {
+        markdown_to_plain(markdown)
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn split(&self, formatted: &str) -> Vec<String> {
+        let mut messages = Vec::new();
+        let mut current = String::new();
+        for word in formatted.split(' ') {
+            let mut word = word.to_string();
+            // Words longer than a whole message are cut wherever they overflow.
+            while word.chars().count() > self.max_chars {
+                if !current.is_empty() {
+                    messages.push(std::mem::take(&mut current));
+                }
+                let cut = word
+                    .char_indices()
+                    .nth(self.max_chars)
+                    .map_or(word.len(), |(i, _)| i);
+                messages.push(word[..cut].to_string());
+                word = word[cut..].to_string();
+            }
+            let needed = current.chars().count() + word.chars().count() + 1;
+            if !current.is_empty() && needed > self.max_chars {
+                messages.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&word);
+        }
+        if !current.trim().is_empty() {
+            messages.push(current);
+        }
+        messages
+    }
+}
+
+/// How one markup renders each Markdown construct.
+struct Dialect {
+    escape: fn(&str) -> String,
+    strong: (&'static str, &'static str),
+    em: (&'static str, &'static str),
+    code: (&'static str, &'static str),
+    link: fn(label: &str, url: &str) -> String,
+    heading: fn(&str) -> String,
+    code_block: fn(&[&str]) -> String,
+}
+
+const SLACK: Dialect = Dialect {
+    escape: escape_slack,
+    strong: ("*", "*"),
+    em: ("_", "_"),
+    code: ("`", "`"),
+    link: |label, url| format!("<{}|{label}>", escape_slack(url)),
+    heading: |text| format!("*{text}*"),
+    code_block: |lines| {
+        let code: Vec<String> = lines.iter().map(|line| escape_slack(line)).collect();
+        format!("```\n{}\n```", code.join("\n"))
+    },
+};
+
+const TELEGRAM: Dialect = Dialect {
+    escape: escape_html,
+    strong: ("<b>", "</b>"),
+    em: ("<i>", "</i>"),
+    code: ("<code>", "</code>"),
+    link: |label, url| format!("<a href=\"{}\">{label}</a>", escape_html(url)),
+    heading: |text| format!("<b>{text}</b>"),
+    code_block: |lines| {
+        let code: Vec<String> = lines.iter().map(|line| escape_html(line)).collect();
+        format!("<pre>{}</pre>", code.join("\n"))
+    },
+};
+
+/// Slack treats only `&`, `<` and `>` as control characters.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn render_blocks(markdown: &str, dialect: &Dialect) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    for line in markdown.lines() {
+        if let Some(block) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                out.push((dialect.code_block)(block));
+                code = None;
+            } else {
+                block.push(line);
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            code = Some(Vec::new());
+        } else if trimmed.starts_with('#') {
+            let text = trimmed.trim_start_matches('#').trim();
+            out.push((dialect.heading)(&render_inline(text, dialect)));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            out.push(format!("• {}", render_inline(item, dialect)));
+        } else {
+            out.push(render_inline(line, dialect));
+        }
+    }
+    if let Some(block) = code {
+        out.push((dialect.code_block)(&block));
+    }
+    out.join("\n")
+}
+
+/// Renders emphasis, inline code and links in `dialect`, escaping the rest;
+/// the same subset `answer_format` recognises.
+fn render_inline(text: &str, dialect: &Dialect) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                let (open, close) = dialect.code;
+                out.push_str(&format!(
+                    "{open}{}{close}",
+                    (dialect.escape)(&rest[1..=end])
+                ));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some((label, url, len)) = parse_link(rest) {
+                out.push_str(&(dialect.link)(&render_inline(label, dialect), url));
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        let emphasis = [("**", dialect.strong), ("*", dialect.em)]
+            .into_iter()
+            .find_map(|(marker, tags)| {
+                let inner = rest.strip_prefix(marker)?;
+                if inner.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                let end = inner.find(marker).filter(|&end| end > 0)?;
+                Some((tags, &inner[..end], &inner[end + marker.len()..]))
+            });
+        if let Some(((open, close), body, after)) = emphasis {
+            out.push_str(&format!("{open}{}{close}", render_inline(body, dialect)));
+            rest = after;
+            continue;
+        }
+
+        out.push_str(&(dialect.escape)(&c.to_string()));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWER: &str =
+        "## Setup\n\nRun **make** or see [the guide](https://x.io/g).\n\n- one\n- `a<b`";
+
+    #[test]
+    fn test_slack_and_telegram_markup() {
+        assert_eq!(
+            SlackFormatter.format(ANSWER),
+            "*Setup*\n\nRun *make* or see <https://x.io/g|the guide>.\n\n• one\n• `a&lt;b`"
+        );
+        assert_eq!(
+            TelegramFormatter.format(ANSWER),
+            "<b>Setup</b>\n\nRun <b>make</b> or see <a href=\"https://x.io/g\">the guide</a>.\n\n\
+             • one\n• <code>a&lt;b</code>"
+        );
+    }
+
+    #[test]
+    fn test_sms_splits_between_words() {
+        let sms = SmsFormatter::new(12);
+        let text = sms.format("**Reset** your password from Settings.");
+        assert_eq!(text, "Reset your password from Settings.");
+        assert_eq!(
+            sms.split(&text),
+            vec!["Reset your", "password", "from", "Settings."]
+        );
+        assert!(sms
+            .split("abcdefghijklmnopqrstuvwxyz")
+            .iter()
+            .all(|m| m.chars().count() <= 12));
+    }
+}
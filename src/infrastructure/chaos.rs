@@ -0,0 +1,323 @@
+//! Fault injection for resilience testing.
+//!
+//! Wraps the domain ports with decorators that delay or fail calls according to
+//! [`ChaosConfig`]. Decisions come from a seeded generator, so a given seed always
+//! produces the same sequence of faults. Only compiled with the `chaos` feature.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::domain::{
+    ports::{
+        EmbeddingPurpose, EmbeddingService, LlmService, MalformedPoint, PointPage, TokenUsage,
+        VectorStore,
+    },
+    DocumentChunk, DomainError, Embedding, SearchFilter, SearchResult,
+};
+use crate::infrastructure::config::{ChaosConfig, FaultRule};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultTarget {
+    Redis,
+    VectorStore,
+    Embedding,
+    Llm,
+}
+
+impl FaultTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Redis => "redis",
+            Self::VectorStore => "vector_store",
+            Self::Embedding => "embedding",
+            Self::Llm => "llm",
+        }
+    }
+}
+
+pub struct FaultInjector {
+    config: ChaosConfig,
+    state: Mutex<u64>,
+}
+
+impl FaultInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        let seed = config.seed;
+        Self {
+            config,
+            state: Mutex::new(seed),
+        }
+    }
+
+    fn rule(&self, target: FaultTarget) -> &FaultRule {
+        match target {
+            FaultTarget::Redis => &self.config.redis,
+            FaultTarget::VectorStore => &self.config.vector_store,
+            FaultTarget::Embedding => &self.config.embedding,
+            FaultTarget::Llm => &self.config.llm,
+        }
+    }
+
+    /// Next value in `[0, 1)` from a splitmix64 sequence.
+    fn roll(&self) -> f64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Applies the configured delay and failure for `target`, if any.
+    pub async fn inject(&self, target: FaultTarget) -> Result<(), DomainError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let rule = self.rule(target);
+        let delay = rule.delay_rate > 0.0 && self.roll() < rule.delay_rate;
+        let fail = rule.failure_rate > 0.0 && self.roll() < rule.failure_rate;
+
+        if delay {
+            tracing::debug!(
+                target = target.as_str(),
+                delay_ms = rule.delay_ms,
+                "injecting delay"
+            );
+            tokio::time::sleep(Duration::from_millis(rule.delay_ms)).await;
+        }
+
+        if fail {
+            tracing::debug!(target = target.as_str(), "injecting failure");
+            return Err(DomainError::external(format!(
+                "Injected {} fault",
+                target.as_str()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct FaultyVectorStore {
+    inner: Arc<dyn VectorStore>,
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultyVectorStore {
+    pub fn new(inner: Arc<dyn VectorStore>, injector: Arc<FaultInjector>) -> Self {
+        Self { inner, injector }
+    }
+}
+
+#[async_trait]
+impl VectorStore for FaultyVectorStore {
+    async fn upsert(
+        &self,
+        chunk: &DocumentChunk,
+        embedding: &Embedding,
+    ) -> Result<(), DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.upsert(chunk, embedding).await
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.inner.dimension()
+    }
+
+    async fn search(
+        &self,
+        query: &Embedding,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.search(query, top_k, filter).await
+    }
+
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.keyword_search(query, top_k, filter).await
+    }
+
+    async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.delete_by_document(document_id).await
+    }
+
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.list_chunks().await
+    }
+
+    async fn document_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.document_chunks(document_id).await
+    }
+
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.scroll_points(offset, limit).await
+    }
+
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.vectors(ids).await
+    }
+
+    async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.malformed_points().await
+    }
+
+    async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+        self.injector.inject(FaultTarget::VectorStore).await?;
+        self.inner.rewrite_payload(chunk).await
+    }
+}
+
+pub struct FaultyEmbedding {
+    inner: Arc<dyn EmbeddingService>,
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultyEmbedding {
+    pub fn new(inner: Arc<dyn EmbeddingService>, injector: Arc<FaultInjector>) -> Self {
+        Self { inner, injector }
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for FaultyEmbedding {
+    async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+        self.injector.inject(FaultTarget::Embedding).await?;
+        self.inner.embed(text).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        self.injector.inject(FaultTarget::Embedding).await?;
+        self.inner.embed_batch(texts).await
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+
+    async fn embed_for(
+        &self,
+        text: &str,
+        purpose: EmbeddingPurpose,
+    ) -> Result<Embedding, DomainError> {
+        self.injector.inject(FaultTarget::Embedding).await?;
+        self.inner.embed_for(text, purpose).await
+    }
+
+    async fn embed_batch_for(
+        &self,
+        texts: &[&str],
+        purpose: EmbeddingPurpose,
+    ) -> Result<Vec<Embedding>, DomainError> {
+        self.injector.inject(FaultTarget::Embedding).await?;
+        self.inner.embed_batch_for(texts, purpose).await
+    }
+}
+
+pub struct FaultyLlm {
+    inner: Arc<dyn LlmService>,
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultyLlm {
+    pub fn new(inner: Arc<dyn LlmService>, injector: Arc<FaultInjector>) -> Self {
+        Self { inner, injector }
+    }
+}
+
+#[async_trait]
+impl LlmService for FaultyLlm {
+    async fn complete(&self, prompt: &str) -> Result<String, DomainError> {
+        self.injector.inject(FaultTarget::Llm).await?;
+        self.inner.complete(prompt).await
+    }
+
+    async fn complete_with_system(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<String, DomainError> {
+        self.injector.inject(FaultTarget::Llm).await?;
+        self.inner.complete_with_system(system, prompt).await
+    }
+
+    async fn complete_with_usage(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<(String, TokenUsage), DomainError> {
+        self.injector.inject(FaultTarget::Llm).await?;
+        self.inner.complete_with_usage(system, prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::InMemoryVectorStore;
+
+    fn config(failure_rate: f64) -> ChaosConfig {
+        ChaosConfig {
+            enabled: true,
+            seed: 42,
+            vector_store: FaultRule {
+                failure_rate,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_always_failing_store() {
+        let injector = Arc::new(FaultInjector::new(config(1.0)));
+        let store = FaultyVectorStore::new(Arc::new(InMemoryVectorStore::new()), injector);
+
+        let result = store
+            .search(&Embedding::new(vec![1.0]), 1, &SearchFilter::default())
+            .await;
+        assert!(matches!(result, Err(DomainError::ExternalService(_))));
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_same_faults() {
+        let a = FaultInjector::new(config(0.5));
+        let b = FaultInjector::new(config(0.5));
+
+        for _ in 0..32 {
+            let fa = a.inject(FaultTarget::VectorStore).await.is_err();
+            let fb = b.inject(FaultTarget::VectorStore).await.is_err();
+            assert_eq!(fa, fb);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_never_fails() {
+        let injector = FaultInjector::new(ChaosConfig {
+            enabled: false,
+            ..config(1.0)
+        });
+        assert!(injector.inject(FaultTarget::VectorStore).await.is_ok());
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::{Arc, OnceLock};
+use tiktoken_rs::CoreBPE;
+use uuid::Uuid;
+
+use crate::domain::ports::ChunkingStrategy;
+use crate::domain::{
+    annotate_markdown_sections, chunk_content, chunk_content_by, split_markdown_sections,
+    DocumentChunk,
+};
+use crate::infrastructure::config::{ChunkerKind, RagConfig};
+
+static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+/// Separators the recursive chunker tries, coarsest first.
+const RECURSIVE_SEPARATORS: &[&str] = &["\n\n", "\n", ". ", " "];
+
+/// Number of `cl100k_base` tokens in `text`.
+///
+/// Falls back to a bytes/4 estimate if the tokenizer cannot be loaded.
+pub fn count_tokens(text: &str) -> usize {
+    let bpe = CL100K.get_or_init(|| {
+        tiktoken_rs::cl100k_base()
+            .inspect_err(|e| tracing::warn!(error = %e, "tokenizer unavailable, estimating"))
+            .ok()
+    });
+
+    match bpe {
+        Some(bpe) => bpe.encode_ordinary(text).len(),
+        None => text.len().div_ceil(4),
+    }
+}
+
+/// Builds the chunker selected by `rag.chunking_strategy`.
+pub fn chunker_from_config(config: &RagConfig) -> Arc<dyn ChunkingStrategy> {
+    let chunk_size = config.chunk_size;
+    match config.chunking_strategy {
+        ChunkerKind::Paragraph => Arc::new(ParagraphChunker::new(chunk_size)),
+        ChunkerKind::Recursive => Arc::new(RecursiveCharacterChunker::new(chunk_size)),
+        ChunkerKind::Markdown => Arc::new(MarkdownChunker::new(chunk_size)),
+        ChunkerKind::Tokens => Arc::new(TokenChunker::new(chunk_size)),
+    }
+}
+
+/// Joins paragraphs into chunks of up to `chunk_size` bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct ParagraphChunker {
+    chunk_size: usize,
+}
+
+impl ParagraphChunker {
+    pub fn new(chunk_size: usize) -> Self {
+        Self { chunk_size }
+    }
+}
+
+impl ChunkingStrategy for ParagraphChunker {
+    fn chunk(&self, document_id: Uuid, content: &str) -> Vec<DocumentChunk> {
+        chunk_content(document_id, content, self.chunk_size)
+    }
+}
+
+/// Joins paragraphs into chunks of up to `chunk_size` `cl100k_base` tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenChunker {
+    chunk_size: usize,
+}
+
+impl TokenChunker {
+    pub fn new(chunk_size: usize) -> Self {
+        Self { chunk_size }
+    }
+}
+
+impl ChunkingStrategy for TokenChunker {
+    fn chunk(&self, document_id: Uuid, content: &str) -> Vec<DocumentChunk> {
+        chunk_content_by(document_id, content, self.chunk_size, count_tokens)
+    }
+}
+
+/// Splits on paragraphs, then lines, sentences and words until every chunk
+/// fits in `chunk_size` characters; unbroken runs are cut at the limit.
+#[derive(Debug, Clone, Copy)]
+pub struct RecursiveCharacterChunker {
+    chunk_size: usize,
+}
+
+impl RecursiveCharacterChunker {
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    fn split(&self, text: &str, separators: &[&str]) -> Vec<String> {
+        let len = |s: &str| s.chars().count();
+        if len(text) <= self.chunk_size {
+            return vec![text.to_string()];
+        }
+
+        let Some((separator, finer)) = separators.split_first() else {
+            let chars: Vec<char> = text.chars().collect();
+            return chars
+                .chunks(self.chunk_size)
+                .map(|c| c.iter().collect())
+                .collect();
+        };
+
+        let mut pieces = Vec::new();
+        let mut current = String::new();
+        for part in text.split_inclusive(separator) {
+            if !current.is_empty() && len(&current) + len(part) > self.chunk_size {
+                pieces.push(std::mem::take(&mut current));
+            }
+            if len(part) > self.chunk_size {
+                pieces.extend(self.split(part, finer));
+            } else {
+                current.push_str(part);
+            }
+        }
+        if !current.is_empty() {
+            pieces.push(current);
+        }
+        pieces
+    }
+}
+
+impl ChunkingStrategy for RecursiveCharacterChunker {
+    fn chunk(&self, document_id: Uuid, content: &str) -> Vec<DocumentChunk> {
+        self.split(content, RECURSIVE_SEPARATORS)
+            .iter()
+            .map(|piece| piece.trim())
+            .filter(|piece| !piece.is_empty())
+            .enumerate()
+            .map(|(index, piece)| DocumentChunk::new(document_id, piece, index))
+            .collect()
+    }
+}
+
+/// Chunks each Markdown section separately, so no chunk spans two headings,
+/// and tags chunks with their section heading.
+#[derive(Debug, Clone, Copy)]
+pub struct MarkdownChunker {
+    chunk_size: usize,
+}
+
+impl MarkdownChunker {
+    pub fn new(chunk_size: usize) -> Self {
+        Self { chunk_size }
+    }
+}
+
+impl ChunkingStrategy for MarkdownChunker {
+    fn chunk(&self, document_id: Uuid, content: &str) -> Vec<DocumentChunk> {
+        let mut chunks: Vec<DocumentChunk> = split_markdown_sections(content)
+            .into_iter()
+            .flat_map(|section| chunk_content(document_id, section, self.chunk_size))
+            .enumerate()
+            .map(|(index, chunk)| DocumentChunk::new(document_id, chunk.content, index))
+            .collect();
+        annotate_markdown_sections(&mut chunks);
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ExtractedPage;
+
+    #[test]
+    fn test_chunk_pages_tags_page_numbers() {
+        let doc_id = Uuid::new_v4();
+        let pages = vec![
+            ExtractedPage::new(Some(1), "First page."),
+            ExtractedPage::new(Some(2), "Second page.\n\nMore on two."),
+        ];
+        let chunks = ParagraphChunker::new(15).chunk_pages(doc_id, &pages);
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].metadata.page, Some(1));
+        assert_eq!(chunks[1].metadata.page, Some(2));
+        assert_eq!(chunks[2].metadata.page, Some(2));
+        assert_eq!(chunks[2].chunk_index, 2);
+    }
+
+    #[test]
+    fn test_recursive_chunker_splits_long_paragraphs() {
+        let content = "One two three four. Five six seven.\n\nShort.";
+        let chunks = RecursiveCharacterChunker::new(20).chunk(Uuid::new_v4(), content);
+        let contents: Vec<_> = chunks.iter().map(|c| c.content.as_str()).collect();
+
+        assert_eq!(
+            contents,
+            vec!["One two three four.", "Five six seven.", "Short."]
+        );
+        assert!(chunks.iter().all(|c| c.content.chars().count() <= 20));
+    }
+
+    #[test]
+    fn test_markdown_chunker_keeps_sections_apart() {
+        let content = "# Setup\n\nInstall.\n\n# Usage\n\nRun.";
+        let chunks = MarkdownChunker::new(1000).chunk(Uuid::new_v4(), content);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].content, "# Setup\n\nInstall.");
+        assert_eq!(chunks[1].metadata.section.as_deref(), Some("Usage"));
+        assert_eq!(chunks[1].chunk_index, 1);
+    }
+}
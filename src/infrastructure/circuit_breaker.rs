@@ -0,0 +1,94 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Opens after `failure_threshold` consecutive failures of a dependency so
+/// callers can skip it for `open_for` instead of waiting on timeouts.
+///
+/// Once `open_for` has passed calls are let through again; the first
+/// success closes the breaker and another failure reopens it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether calls should currently be skipped.
+    pub fn is_open(&self) -> bool {
+        self.state()
+            .opened_at
+            .is_some_and(|at| at.elapsed() < self.open_for)
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state();
+        state.consecutive_failures = 0;
+        if state.opened_at.take().is_some() {
+            tracing::info!(dependency = self.name, "circuit closed");
+        }
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures < self.failure_threshold {
+            return;
+        }
+        let reopening = !state
+            .opened_at
+            .is_some_and(|at| at.elapsed() < self.open_for);
+        if reopening {
+            tracing::warn!(
+                dependency = self.name,
+                failures = state.consecutive_failures,
+                open_seconds = self.open_for.as_secs(),
+                "circuit opened"
+            );
+            state.opened_at = Some(Instant::now());
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_closes_on_success() {
+        let breaker = CircuitBreaker::new("qdrant", 2, Duration::from_secs(60));
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        let half_open = CircuitBreaker::new("qdrant", 1, Duration::ZERO);
+        half_open.record_failure();
+        assert!(!half_open.is_open());
+    }
+}
@@ -1,16 +1,204 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
+
+use crate::application::{
+    BillingRates, ConfidenceWeights, QueryTransform, RetentionRule, RetrievalOptions,
+    RetrievalStrategy, ScoreNormalization, StalePolicy,
+};
+use crate::domain::{ports::TokenUsage, EmbeddingPrecision, ErrorKind, SearchFilter};
+use crate::infrastructure::banner::BannerConfig;
+use crate::infrastructure::channels::ChannelsConfig;
+use crate::infrastructure::glossary::GlossaryConfig;
+use crate::infrastructure::llm::GenerationParams;
+use crate::infrastructure::migrations::MigrationsConfig;
+use crate::infrastructure::model_registry::ModelRegistryConfig;
+use crate::infrastructure::model_routing::ModelRoutingConfig;
+use crate::infrastructure::queue::Channel;
+use crate::infrastructure::redis_tracing::RedisTracingConfig;
+use crate::infrastructure::response_cache::ResponseCacheConfig;
+use crate::infrastructure::retry::RetryPolicy;
+use crate::infrastructure::safety::SafetyConfig;
+use crate::infrastructure::sanitize::SanitizeConfig;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub llm: LlmConfig,
     pub embedding: EmbeddingConfig,
     pub vector_store: VectorStoreConfig,
+    #[serde(default)]
+    pub document_store: DocumentStoreConfig,
     pub rag: RagConfig,
     pub worker: WorkerConfig,
     pub tools: ToolsConfig,
     #[serde(default)]
     pub cors: CorsConfig,
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// Postgres and vector collection schema versions.
+    #[serde(default)]
+    pub migrations: MigrationsConfig,
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    /// Slow-operation warnings for queue and conversation Redis calls.
+    #[serde(default)]
+    pub redis_tracing: RedisTracingConfig,
+    #[serde(default)]
+    pub chaos: ChaosConfig,
+    #[serde(default)]
+    pub git: GitConnectorConfig,
+    #[serde(default)]
+    pub crawl: CrawlerConfig,
+    #[serde(default)]
+    pub s3: S3ConnectorConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Disclaimers added to answers, per agent and channel.
+    #[serde(default)]
+    pub banners: BannerConfig,
+    /// Answer markup and message length per delivery channel.
+    #[serde(default)]
+    pub channels: ChannelsConfig,
+    /// Product terms defined to the model when a message mentions them.
+    #[serde(default)]
+    pub glossary: GlossaryConfig,
+    /// Cheap and premium models per agent, picked by query complexity.
+    #[serde(default)]
+    pub model_routing: ModelRoutingConfig,
+    /// Versioned model and prompt pairs served under logical model names.
+    #[serde(default)]
+    pub model_registry: ModelRegistryConfig,
+    #[serde(default)]
+    pub confidence: ConfidenceConfig,
+    /// Answering without the knowledge base while the vector store is down.
+    #[serde(default)]
+    pub degraded_mode: DegradedModeConfig,
+    /// Screening of ingested documents for disallowed content.
+    #[serde(default)]
+    pub safety: SafetyConfig,
+    /// Scripts, unsafe links and untrusted images stripped from answers.
+    #[serde(default)]
+    pub sanitize: SanitizeConfig,
+    /// Unit prices for the billing export.
+    #[serde(default)]
+    pub billing: BillingRates,
+    /// Server-sent event streams of chat answers.
+    #[serde(default)]
+    pub streaming: StreamingConfig,
+    /// Answers held for human approval, per agent or channel.
+    #[serde(default)]
+    pub drafts: DraftConfig,
+}
+
+/// Periodic anonymization/deletion of old conversations, run by the worker.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Only report what would happen; nothing is changed.
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default = "default_retention_interval")]
+    pub interval_seconds: u64,
+    /// Rule for conversations without a tenant-specific one.
+    #[serde(default)]
+    pub default_rule: Option<RetentionRule>,
+    #[serde(default)]
+    pub tenants: HashMap<String, RetentionRule>,
+    /// Salt for hashing user ids; set it to keep hashes unguessable.
+    #[serde(default)]
+    pub hash_salt: String,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dry_run: false,
+            interval_seconds: default_retention_interval(),
+            default_rule: None,
+            tenants: HashMap::new(),
+            hash_salt: String::new(),
+        }
+    }
+}
+
+fn default_retention_interval() -> u64 {
+    3600
+}
+
+/// Settings for ingesting documents from git repositories.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitConnectorConfig {
+    /// Where repositories are cloned, one subdirectory per repository.
+    #[serde(default = "default_git_checkout_dir")]
+    pub checkout_dir: String,
+    /// Globs indexed when a sync request names none.
+    #[serde(default = "default_git_globs")]
+    pub default_globs: Vec<String>,
+}
+
+impl Default for GitConnectorConfig {
+    fn default() -> Self {
+        Self {
+            checkout_dir: default_git_checkout_dir(),
+            default_globs: default_git_globs(),
+        }
+    }
+}
+
+fn default_git_checkout_dir() -> String {
+    "./data/git".to_string()
+}
+
+fn default_git_globs() -> Vec<String> {
+    vec!["docs/**/*.md".to_string()]
+}
+
+/// Settings for crawling websites into the knowledge base.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CrawlerConfig {
+    pub user_agent: String,
+    pub timeout_seconds: u64,
+    /// Link hops followed when a crawl request sets none, and the most it may set.
+    pub max_depth: usize,
+    /// Pages fetched when a crawl request sets no limit, and the most it may set.
+    pub max_pages: usize,
+}
+
+impl Default for CrawlerConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: "ai-agent-crawler/0.1".to_string(),
+            timeout_seconds: 15,
+            max_depth: 2,
+            max_pages: 100,
+        }
+    }
+}
+
+/// Settings for ingesting documents from S3 buckets.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct S3ConnectorConfig {
+    /// Defaults to the AWS environment / profile region.
+    pub region: Option<String>,
+    /// For S3-compatible stores such as MinIO.
+    pub endpoint_url: Option<String>,
+    /// Larger objects are skipped.
+    pub max_object_bytes: u64,
+}
+
+impl Default for S3ConnectorConfig {
+    fn default() -> Self {
+        Self {
+            region: None,
+            endpoint_url: None,
+            max_object_bytes: 20 * 1024 * 1024,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -25,13 +213,176 @@ impl CorsConfig {
     }
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Start with mutating endpoints disabled; `PUT /admin/read-only`
+    /// overrides this at runtime.
+    pub read_only: bool,
+}
+
+/// Fault injection settings, honoured only by builds with the `chaos` feature.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub redis: FaultRule,
+    #[serde(default)]
+    pub vector_store: FaultRule,
+    #[serde(default)]
+    pub embedding: FaultRule,
+    #[serde(default)]
+    pub llm: FaultRule,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FaultRule {
+    /// Probability in `[0, 1]` that a call fails.
+    #[serde(default)]
+    pub failure_rate: f64,
+    /// Probability in `[0, 1]` that a call is delayed by `delay_ms`.
+    #[serde(default)]
+    pub delay_rate: f64,
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct LlmConfig {
+    #[serde(default)]
+    pub provider: LlmProvider,
     pub model: String,
+    /// API endpoint replacing the provider's own, e.g. a vLLM or LiteLLM
+    /// server speaking the OpenAI API (with `provider: openai`).
+    #[serde(default)]
+    pub base_url: Option<String>,
     #[serde(default = "default_max_tokens")]
     pub max_tokens: usize,
+    /// Sampling temperature; unset keeps the provider's default.
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling cutoff; unset keeps the provider's default.
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Text that ends an answer when the model produces it.
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
     #[serde(default = "default_timeout_seconds")]
     pub timeout_seconds: u64,
+    /// Retries of failed model calls within a job.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub ollama: OllamaLlmConfig,
+    #[serde(default)]
+    pub bedrock: BedrockLlmConfig,
+    /// Providers the chat agent moves to, in order, when the one before
+    /// times out or answers 429 or 5xx.
+    #[serde(default)]
+    pub fallbacks: Vec<LlmFallback>,
+    /// Prices by model name, for the cost reported with each chat's usage.
+    #[serde(default)]
+    pub pricing: HashMap<String, TokenPrice>,
+}
+
+/// A model's price per 1000 tokens, in `billing.currency`.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct TokenPrice {
+    pub prompt_per_1k: f64,
+    pub completion_per_1k: f64,
+}
+
+impl TokenPrice {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt_per_1k
+            + usage.completion_tokens as f64 * self.completion_per_1k)
+            / 1000.0
+    }
+}
+
+/// A provider and model in `llm.fallbacks`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LlmFallback {
+    pub provider: LlmProvider,
+    pub model: String,
+    /// Replaces `llm.base_url` for this provider.
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl LlmConfig {
+    /// Settings for `fallback`, sharing everything else with the primary.
+    pub fn for_fallback(&self, fallback: &LlmFallback) -> LlmConfig {
+        LlmConfig {
+            provider: fallback.provider,
+            model: fallback.model.clone(),
+            base_url: fallback.base_url.clone(),
+            fallbacks: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// The sampling settings above, which chat requests may override.
+    pub fn generation(&self) -> GenerationParams {
+        GenerationParams {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            stop_sequences: self.stop_sequences.clone(),
+            max_tokens: Some(self.max_tokens),
+        }
+    }
+
+    /// Whether the model can call the knowledge base tool; if not, the agent
+    /// retrieves for the user's message up front instead.
+    pub fn tool_calling(&self) -> bool {
+        match self.provider {
+            LlmProvider::Ollama => self.ollama.tools,
+            LlmProvider::Bedrock => self.bedrock.tools,
+            _ => true,
+        }
+    }
+}
+
+/// Settings for `llm.provider: ollama`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OllamaLlmConfig {
+    pub base_url: String,
+    /// Whether `llm.model` supports tool calling; many local models don't.
+    pub tools: bool,
+}
+
+impl Default for OllamaLlmConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:11434".to_string(),
+            tools: true,
+        }
+    }
+}
+
+/// Settings for `llm.provider: bedrock`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BedrockLlmConfig {
+    /// AWS region of the Bedrock runtime; unset uses `AWS_REGION` or the
+    /// profile's region.
+    pub region: Option<String>,
+    /// Whether `llm.model` supports tool use; Titan text models don't.
+    pub tools: bool,
+}
+
+impl Default for BedrockLlmConfig {
+    fn default() -> Self {
+        Self {
+            region: None,
+            tools: true,
+        }
+    }
 }
 
 fn default_max_tokens() -> usize {
@@ -42,23 +393,626 @@ fn default_timeout_seconds() -> u64 {
     120
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProvider {
+    /// Gemini API (`GEMINI_API_KEY`).
+    #[default]
+    Gemini,
+    /// Anthropic Messages API (`ANTHROPIC_API_KEY`).
+    Anthropic,
+    /// OpenAI Chat Completions API (`OPENAI_API_KEY`, optional `OPENAI_BASE_URL`).
+    Openai,
+    /// A self-hosted Ollama server at `llm.ollama.base_url`.
+    Ollama,
+    /// AWS Bedrock (Claude, Titan, ...) with credentials from the AWS chain.
+    Bedrock,
+}
+
+impl LlmProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gemini => "gemini",
+            Self::Anthropic => "anthropic",
+            Self::Openai => "openai",
+            Self::Ollama => "ollama",
+            Self::Bedrock => "bedrock",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct EmbeddingConfig {
+    #[serde(default)]
+    pub provider: EmbeddingProvider,
     pub model: String,
+    /// OpenAI-compatible API to embed with instead of OpenAI's own (with
+    /// `provider: openai`), e.g. `http://vllm:8000/v1`.
+    #[serde(default)]
+    pub base_url: Option<String>,
     pub dimension: usize,
+    /// Most texts sent to the provider in one request.
+    #[serde(default = "default_embedding_batch_size")]
+    pub batch_size: usize,
+    /// Batch requests in flight at once.
+    #[serde(default = "default_embedding_concurrency")]
+    pub concurrency: usize,
+    /// Provider requests allowed per minute; unset means unlimited.
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub probe: DimensionProbeConfig,
+    #[serde(default)]
+    pub local: LocalEmbeddingConfig,
+    #[serde(default)]
+    pub ollama: OllamaEmbeddingConfig,
+    #[serde(default)]
+    pub cohere: CohereEmbeddingConfig,
+    /// Retries of failed embedding requests within a job.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+fn default_embedding_batch_size() -> usize {
+    100
+}
+
+fn default_embedding_concurrency() -> usize {
+    4
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProvider {
+    /// Gemini embeddings API (`GEMINI_API_KEY`).
+    #[default]
+    Gemini,
+    /// OpenAI embeddings API (`OPENAI_API_KEY`).
+    Openai,
+    /// In-process ONNX model via fastembed; needs the `local-embeddings` feature.
+    Local,
+    /// A self-hosted Ollama server at `embedding.ollama.base_url`.
+    Ollama,
+    /// Cohere embed API (`COHERE_API_KEY`); documents and queries get
+    /// their own input types.
+    Cohere,
+}
+
+/// Settings for `embedding.provider: local`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LocalEmbeddingConfig {
+    /// Where downloaded models are kept between restarts.
+    pub cache_dir: String,
+}
+
+impl Default for LocalEmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: ".fastembed_cache".to_string(),
+        }
+    }
+}
+
+/// Settings for `embedding.provider: ollama`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OllamaEmbeddingConfig {
+    pub base_url: String,
+}
+
+impl Default for OllamaEmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:11434".to_string(),
+        }
+    }
+}
+
+/// Settings for `embedding.provider: cohere`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CohereEmbeddingConfig {
+    pub base_url: String,
+}
+
+impl Default for CohereEmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.cohere.com".to_string(),
+        }
+    }
+}
+
+/// Startup check that embeds a canary string and compares the vector's
+/// length with `embedding.dimension`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DimensionProbeConfig {
+    pub enabled: bool,
+    pub canary: String,
+    pub on_mismatch: DimensionMismatch,
+}
+
+impl Default for DimensionProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            canary: "dimension probe".to_string(),
+            on_mismatch: DimensionMismatch::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DimensionMismatch {
+    /// Use the provider's dimension and log a warning.
+    #[default]
+    Correct,
+    /// Refuse to start.
+    Fail,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct VectorStoreConfig {
+    #[serde(default)]
+    pub backend: VectorStoreBackend,
     pub collection: String,
+    /// Report each point dropped for an unreadable payload as an error and
+    /// in the `vector_store_malformed_points_total` counter.
+    #[serde(default)]
+    pub strict_payloads: bool,
+    /// Payload fields Qdrant indexes when a collection is opened, so
+    /// filtered deletes and searches don't scan every point.
+    #[serde(default = "default_payload_indexes")]
+    pub payload_indexes: Vec<PayloadIndex>,
+    /// Directory on the worker that collection exports are written to and
+    /// imports read from.
+    #[serde(default = "default_dump_dir")]
+    pub dump_dir: String,
+    /// Qdrant HNSW index parameters; unset fields keep Qdrant's defaults.
+    #[serde(default)]
+    pub hnsw: HnswConfig,
+    /// Qdrant vector quantization, trading some recall for memory.
+    #[serde(default)]
+    pub quantization: QuantizationConfig,
+    /// Qdrant endpoints that serve searches while `QDRANT_URL` takes writes.
+    #[serde(default)]
+    pub read_replicas: ReadReplicasConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReadReplicasConfig {
+    /// gRPC URLs, e.g. one per region. Searches go to the one with the lowest
+    /// recent latency and fail over to the others, then to the primary.
+    pub urls: Vec<String>,
+    /// How long a replica is skipped after it was unreachable or timed out.
+    pub cooldown_seconds: u64,
+    /// How long a replica read may take before failing over.
+    pub timeout_ms: u64,
+}
+
+impl Default for ReadReplicasConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            cooldown_seconds: 30,
+            timeout_ms: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HnswConfig {
+    /// Edges per node; higher is more accurate and uses more memory (Qdrant default 16).
+    pub m: Option<u64>,
+    /// Neighbours considered while building; higher is more accurate and slower
+    /// to index (Qdrant default 100).
+    pub ef_construct: Option<u64>,
+}
+
+impl HnswConfig {
+    pub fn is_default(&self) -> bool {
+        self.m.is_none() && self.ef_construct.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuantizationConfig {
+    /// Full-precision vectors only.
+    #[default]
+    None,
+    /// int8 vectors, about 4x smaller.
+    Scalar {
+        /// Share of values kept inside the quantization range; outliers are clamped.
+        #[serde(default)]
+        quantile: Option<f32>,
+        /// Keep quantized vectors in RAM even when originals are on disk.
+        #[serde(default)]
+        always_ram: bool,
+    },
+    /// Product quantization, 4x to 64x smaller at a larger recall cost.
+    Product {
+        #[serde(default)]
+        compression: ProductCompression,
+        #[serde(default)]
+        always_ram: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProductCompression {
+    X4,
+    X8,
+    #[default]
+    X16,
+    X32,
+    X64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PayloadIndex {
+    /// Payload path, e.g. `document_id` or `metadata.tags`.
+    pub field: String,
+    #[serde(rename = "type", default)]
+    pub kind: PayloadIndexKind,
+}
+
+impl PayloadIndex {
+    pub fn new(field: impl Into<String>, kind: PayloadIndexKind) -> Self {
+        Self {
+            field: field.into(),
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadIndexKind {
+    #[default]
+    Keyword,
+    Integer,
+    Float,
+    Bool,
+    Datetime,
+    Uuid,
+}
+
+fn default_dump_dir() -> String {
+    "dumps".to_string()
+}
+
+pub fn default_payload_indexes() -> Vec<PayloadIndex> {
+    vec![
+        PayloadIndex::new("document_id", PayloadIndexKind::Keyword),
+        PayloadIndex::new("metadata.tags", PayloadIndexKind::Keyword),
+        PayloadIndex::new("metadata.conversation_id", PayloadIndexKind::Keyword),
+        PayloadIndex::new("metadata.tenant_id", PayloadIndexKind::Keyword),
+        PayloadIndex::new("metadata.content_type", PayloadIndexKind::Keyword),
+        PayloadIndex::new("created_at", PayloadIndexKind::Integer),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorStoreBackend {
+    /// Qdrant at `QDRANT_URL`.
+    #[default]
+    Qdrant,
+    /// RediSearch on the queue's Redis (`REDIS_URL`); needs Redis Stack or Redis 8.
+    Redis,
+}
+
+/// Confidence estimate added to chat results.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceConfig {
+    pub enabled: bool,
+    /// Ask the LLM to rate groundedness and its confidence (one extra call per answer).
+    pub judge: bool,
+    /// Scores at or above this are `high`.
+    pub high_threshold: f32,
+    /// Scores below this are `low`.
+    pub low_threshold: f32,
+    pub weights: ConfidenceWeights,
+}
+
+impl Default for ConfidenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            judge: true,
+            high_threshold: 0.75,
+            low_threshold: 0.4,
+            weights: ConfidenceWeights::default(),
+        }
+    }
+}
+
+/// Chat without the knowledge_base tool while the vector store's circuit
+/// breaker is open, instead of failing the job.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DegradedModeConfig {
+    pub enabled: bool,
+    /// Consecutive vector store failures that open the breaker.
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before the vector store is tried again.
+    pub open_seconds: u64,
+    /// Appended to answers given without the knowledge base.
+    pub notice: String,
+}
+
+impl Default for DegradedModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            failure_threshold: 3,
+            open_seconds: 30,
+            notice: "Note: the knowledge base is temporarily unavailable, so this answer may be incomplete.".to_string(),
+        }
+    }
+}
+
+/// `GET /chat/jobs/{job_id}/stream`: answer text is sent as it is generated,
+/// with heartbeats, and kept briefly in Redis so a client reconnecting with
+/// `Last-Event-ID` gets what it missed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StreamingConfig {
+    /// Comment sent on idle streams so proxies and mobile networks keep them open.
+    pub keep_alive_seconds: u64,
+    /// How long emitted chunks stay available for resumption.
+    pub buffer_ttl_seconds: u64,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive_seconds: 15,
+            buffer_ttl_seconds: 300,
+        }
+    }
+}
+
+/// Chats whose answers are stored as drafts, released only once approved via
+/// `POST /conversations/{id}/drafts/{draft_id}/approve`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DraftConfig {
+    pub agents: Vec<String>,
+    pub channels: Vec<Channel>,
+}
+
+impl DraftConfig {
+    pub fn requires_approval(&self, agent_id: Option<&str>, channel: Channel) -> bool {
+        self.channels.contains(&channel)
+            || agent_id.is_some_and(|id| self.agents.iter().any(|a| a == id))
+    }
+}
+
+/// Where the API keeps document records and their chunks.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DocumentStoreConfig {
+    #[serde(default)]
+    pub backend: DocumentStoreKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentStoreKind {
+    /// Documents only exist as chunks in the vector store.
+    #[default]
+    None,
+    /// In process memory, lost on restart; for tests, demos and local runs.
+    Memory,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RagConfig {
     pub top_k: usize,
+    /// Maximum chunk size, in the unit `chunking_strategy` measures.
     pub chunk_size: usize,
     #[serde(default = "default_min_score")]
     pub min_score: f32,
+    #[serde(default)]
+    pub chunking_strategy: ChunkerKind,
+    #[serde(default)]
+    pub rerank: RerankConfig,
+    #[serde(default)]
+    pub mmr: MmrConfig,
+    #[serde(default)]
+    pub collapse: CollapseConfig,
+    /// Add each result's neighbouring chunks (index ±1) from the document store.
+    #[serde(default)]
+    pub expand_neighbors: bool,
+    #[serde(default)]
+    pub query_transform: QueryTransform,
+    /// Rescale vector and keyword scores to 0..=1 so hybrid fusion and
+    /// `min_score` compare like with like.
+    #[serde(default)]
+    pub score_normalization: ScoreNormalization,
+    #[serde(default)]
+    pub freshness: FreshnessConfig,
+    #[serde(default)]
+    pub embedding_fallback: EmbeddingFallbackConfig,
+    #[serde(default)]
+    pub warm_queries: WarmQueriesConfig,
+    #[serde(default)]
+    pub chunk_titles: ChunkTitlesConfig,
+}
+
+/// Handling of documents past their `review_by` / `expires_at` dates.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FreshnessConfig {
+    pub stale_policy: StalePolicy,
+    /// Periodically report stale documents (see `/admin/freshness/report`).
+    pub report_enabled: bool,
+    pub report_interval_seconds: u64,
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        Self {
+            stale_policy: StalePolicy::default(),
+            report_enabled: false,
+            report_interval_seconds: 86400,
+        }
+    }
+}
+
+/// Maximal marginal relevance selection of the final results.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MmrConfig {
+    pub enabled: bool,
+    /// 1.0 ranks purely by relevance, 0.0 purely by diversity.
+    pub lambda: f32,
+    /// Results fetched to choose the `top_k` from.
+    pub candidates: usize,
+}
+
+impl Default for MmrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lambda: 0.5,
+            candidates: 20,
+        }
+    }
+}
+
+/// Collapsing of hits from the same document into one result.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CollapseConfig {
+    pub enabled: bool,
+    /// Results fetched before collapsing, so the `top_k` span several documents.
+    pub candidates: usize,
+}
+
+impl Default for CollapseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            candidates: 20,
+        }
+    }
+}
+
+/// Retrieval when the embedding provider fails: reuse a cached embedding of
+/// the same query, else search by keyword only.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingFallbackConfig {
+    pub enabled: bool,
+    /// Recent query embeddings kept for reuse.
+    pub cache_size: usize,
+    /// How the cache holds them: `f32`, `f16` or `int8`.
+    pub precision: EmbeddingPrecision,
+}
+
+impl Default for EmbeddingFallbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cache_size: 1000,
+            precision: EmbeddingPrecision::default(),
+        }
+    }
+}
+
+/// Worker-side cache of query embeddings, kept warm with the most frequent
+/// recent queries so common questions skip the embedding call.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WarmQueriesConfig {
+    pub enabled: bool,
+    /// Most frequent queries embedded ahead of time.
+    pub top_n: usize,
+    /// How often the cache is topped up; the first run is at startup.
+    pub interval_seconds: u64,
+    /// Days of queries counted.
+    pub window_days: u32,
+    pub cache_size: usize,
+    /// How the cache holds embeddings: `f32`, `f16` or `int8`.
+    pub precision: EmbeddingPrecision,
+}
+
+impl Default for WarmQueriesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            top_n: 100,
+            interval_seconds: 600,
+            window_days: 7,
+            cache_size: 2000,
+            precision: EmbeddingPrecision::default(),
+        }
+    }
+}
+
+/// Short LLM-written titles stored with each chunk at ingestion and shown
+/// in search results and citations.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ChunkTitlesConfig {
+    pub enabled: bool,
+    /// Model used for titles; defaults to `llm.model`. A small, cheap model is enough.
+    pub model: Option<String>,
+    pub max_chars: usize,
+}
+
+impl Default for ChunkTitlesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: None,
+            max_chars: 80,
+        }
+    }
+}
+
+/// LLM reranking of retrieved chunks.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RerankConfig {
+    pub enabled: bool,
+    /// Results fetched and scored before the best `top_k` are kept.
+    pub candidates: usize,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            candidates: 20,
+        }
+    }
+}
+
+/// How documents are split into chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkerKind {
+    /// Paragraphs joined up to `chunk_size` bytes.
+    #[default]
+    #[serde(alias = "bytes")]
+    Paragraph,
+    /// Paragraphs, lines, sentences then words, up to `chunk_size` characters.
+    Recursive,
+    /// Paragraph chunking within each Markdown section.
+    Markdown,
+    /// Paragraphs joined up to `chunk_size` `cl100k_base` tokens, which fill
+    /// chunks evenly regardless of script.
+    Tokens,
 }
 
 fn default_min_score() -> f32 {
@@ -69,19 +1023,270 @@ fn default_min_score() -> f32 {
 pub struct WorkerConfig {
     pub concurrency: usize,
     pub conversation_ttl_seconds: u64,
+    /// Default TTL for job results.
     pub result_ttl_seconds: u64,
+    /// Per job type (`chat`, `embed`, `index`, `reembed`, `git_sync`, `crawl`, `s3_sync`,
+    /// `export`, `import`) result TTLs.
+    #[serde(default)]
+    pub result_ttl_overrides: HashMap<String, u64>,
+    #[serde(default)]
+    pub history: JobHistoryConfig,
+    /// How often vectors of documents attached to expired conversations are deleted.
+    #[serde(default = "default_attachment_sweep_interval")]
+    pub attachment_sweep_interval_seconds: u64,
+    #[serde(default)]
+    pub retry: JobRetryConfig,
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
+    #[serde(default)]
+    pub replay: ReplayConfig,
+}
+
+impl WorkerConfig {
+    /// Result TTL for jobs from `queue`, falling back to `result_ttl_seconds`.
+    /// Priority queues (`jobs:chat:low`) share their job type's TTL.
+    pub fn result_ttl(&self, queue: &str) -> u64 {
+        let kind = queue.strip_prefix("jobs:").unwrap_or(queue);
+        let kind = kind.split(':').next().unwrap_or(kind);
+        self.result_ttl_overrides
+            .get(kind)
+            .copied()
+            .unwrap_or(self.result_ttl_seconds)
+    }
+}
+
+/// Snapshots of finished chat jobs for `POST /admin/jobs/{job_id}/replay`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReplayConfig {
+    pub enabled: bool,
+    /// How long a chat job can be replayed after it finished.
+    pub ttl_seconds: u64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_seconds: 604800,
+        }
+    }
+}
+
+/// Limits on the chat queue, so a backlog is refused up front instead of
+/// leaving users waiting on answers that will time out.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BackpressureConfig {
+    /// Queued chat jobs beyond which new ones are refused or deprioritized; 0 disables the limit.
+    pub max_queue_depth: usize,
+    pub when_full: QueueFullAction,
+    /// Chat job duration assumed for wait estimates until workers have
+    /// recorded real ones.
+    pub estimated_job_seconds: f64,
+}
+
+impl Default for BackpressureConfig {
+    fn default() -> Self {
+        Self {
+            max_queue_depth: 0,
+            when_full: QueueFullAction::default(),
+            estimated_job_seconds: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueFullAction {
+    /// `POST /chat` answers 503 with the estimated wait and a `Retry-After`.
+    #[default]
+    Reject,
+    /// Queue the job on `jobs:chat:low`, served only when the main chat queue is empty.
+    LowPriority,
+}
+
+/// Requeueing of chat and embed jobs that failed with a retryable error
+/// (timeouts, unreachable or rate-limiting providers); validation and
+/// internal errors fail the job straight away.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobRetryConfig {
+    /// Attempts including the first; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further one.
+    pub backoff_ms: u64,
+    /// First delay after a provider rate-limited us.
+    pub rate_limit_backoff_ms: u64,
+}
+
+impl Default for JobRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_ms: 1000,
+            rate_limit_backoff_ms: 5000,
+        }
+    }
+}
+
+impl JobRetryConfig {
+    /// How long to wait before retrying a job that failed on `attempt`
+    /// (0 for the first run) with an error of `kind`.
+    pub fn delay(&self, attempt: u32, kind: ErrorKind) -> Duration {
+        let base = match kind {
+            ErrorKind::RateLimited => self.rate_limit_backoff_ms,
+            _ => self.backoff_ms,
+        };
+        Duration::from_millis(base.saturating_mul(1 << attempt.min(10)))
+    }
+}
+
+/// Compact audit trail of finished jobs, kept after their results expire.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobHistoryConfig {
+    /// Newest entries kept; 0 disables the history list.
+    #[serde(default = "default_history_max_entries")]
+    pub max_entries: usize,
+    /// Also keep every record in Postgres (`DATABASE_URL`) for reporting.
+    #[serde(default)]
+    pub postgres: bool,
+}
+
+impl Default for JobHistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: default_history_max_entries(),
+            postgres: false,
+        }
+    }
+}
+
+fn default_history_max_entries() -> usize {
+    10_000
+}
+
+fn default_attachment_sweep_interval() -> u64 {
+    300
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ToolsConfig {
+    /// Limits applied to every tool unless the tool overrides them.
+    #[serde(default)]
+    pub limits: ToolLimits,
     pub knowledge_base: KnowledgeBaseToolConfig,
 }
 
+/// Resource limits enforced around each tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ToolLimits {
+    pub timeout_seconds: u64,
+    /// Longer output is cut at this many bytes and marked as truncated.
+    pub max_output_bytes: usize,
+    pub max_calls_per_turn: usize,
+}
+
+impl Default for ToolLimits {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 15,
+            max_output_bytes: 16 * 1024,
+            max_calls_per_turn: 5,
+        }
+    }
+}
+
+impl ToolLimits {
+    pub fn with_overrides(self, overrides: &ToolLimitOverrides) -> Self {
+        Self {
+            timeout_seconds: overrides.timeout_seconds.unwrap_or(self.timeout_seconds),
+            max_output_bytes: overrides.max_output_bytes.unwrap_or(self.max_output_bytes),
+            max_calls_per_turn: overrides
+                .max_calls_per_turn
+                .unwrap_or(self.max_calls_per_turn),
+        }
+    }
+}
+
+/// Per-tool replacements for individual [`ToolLimits`] fields.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ToolLimitOverrides {
+    pub timeout_seconds: Option<u64>,
+    pub max_output_bytes: Option<usize>,
+    pub max_calls_per_turn: Option<usize>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct KnowledgeBaseToolConfig {
     pub name: String,
     pub description: String,
     pub no_results_message: String,
+    /// Collection the tool searches; falls back to `vector_store.collection`.
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[serde(default)]
+    pub strategy: RetrievalStrategy,
+    #[serde(default)]
+    pub limits: ToolLimitOverrides,
+    #[serde(default)]
+    pub context: ContextFormatConfig,
+    /// Only search documents with one of these tags; empty searches everything.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// How retrieved chunks are laid out for the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextFormat {
+    /// `[1] (source: ...) text`
+    #[default]
+    Numbered,
+    /// `<document index="1" source="...">text</document>`
+    Xml,
+    /// A bold header per chunk followed by the text as a `>` quote.
+    Markdown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ContextFormatConfig {
+    pub format: ContextFormat,
+    pub include_scores: bool,
+    /// Name each chunk's source document (path, lines and commit where known) and title.
+    pub include_sources: bool,
+    /// Cap on the assembled context; chunks past it are dropped.
+    pub max_chars: Option<usize>,
+}
+
+impl Default for ContextFormatConfig {
+    fn default() -> Self {
+        Self {
+            format: ContextFormat::default(),
+            include_scores: false,
+            include_sources: true,
+            max_chars: None,
+        }
+    }
+}
+
+impl KnowledgeBaseToolConfig {
+    pub fn collection<'a>(&'a self, vector_store: &'a VectorStoreConfig) -> &'a str {
+        self.collection
+            .as_deref()
+            .unwrap_or(&vector_store.collection)
+    }
+
+    pub fn retrieval_options(&self, rag: &RagConfig) -> RetrievalOptions {
+        RetrievalOptions {
+            top_k: rag.top_k,
+            strategy: self.strategy,
+            min_score: rag.min_score,
+            filter: SearchFilter::default().with_default_tags(&self.tags),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -93,6 +1298,9 @@ pub struct PromptsConfig {
 #[derive(Debug, Clone, Deserialize)]
 pub struct AgentPrompts {
     pub system: String,
+    /// System prompts pinned by model registry releases, by version.
+    #[serde(default)]
+    pub versions: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -145,35 +1353,107 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             llm: LlmConfig {
+                provider: LlmProvider::default(),
                 model: "gemini-3-flash-preview".to_string(),
+                base_url: None,
                 max_tokens: 4096,
+                temperature: None,
+                top_p: None,
+                stop_sequences: Vec::new(),
                 timeout_seconds: 120,
+                retry: RetryPolicy::default(),
+                ollama: OllamaLlmConfig::default(),
+                bedrock: BedrockLlmConfig::default(),
+                fallbacks: Vec::new(),
+                pricing: HashMap::new(),
             },
             embedding: EmbeddingConfig {
+                provider: EmbeddingProvider::default(),
                 model: "gemini-embedding-001".to_string(),
+                base_url: None,
                 dimension: 768,
+                batch_size: default_embedding_batch_size(),
+                concurrency: default_embedding_concurrency(),
+                requests_per_minute: None,
+                probe: DimensionProbeConfig::default(),
+                local: LocalEmbeddingConfig::default(),
+                ollama: OllamaEmbeddingConfig::default(),
+                cohere: CohereEmbeddingConfig::default(),
+                retry: RetryPolicy::default(),
             },
             vector_store: VectorStoreConfig {
+                backend: VectorStoreBackend::default(),
                 collection: "knowledge_base".to_string(),
+                strict_payloads: false,
+                payload_indexes: default_payload_indexes(),
+                dump_dir: default_dump_dir(),
+                hnsw: HnswConfig::default(),
+                quantization: QuantizationConfig::default(),
+                read_replicas: ReadReplicasConfig::default(),
             },
+            document_store: DocumentStoreConfig::default(),
             rag: RagConfig {
                 top_k: 5,
                 chunk_size: 1000,
                 min_score: 0.7,
+                chunking_strategy: ChunkerKind::default(),
+                rerank: RerankConfig::default(),
+                mmr: MmrConfig::default(),
+                collapse: CollapseConfig::default(),
+                expand_neighbors: false,
+                query_transform: QueryTransform::default(),
+                score_normalization: ScoreNormalization::default(),
+                freshness: FreshnessConfig::default(),
+                embedding_fallback: EmbeddingFallbackConfig::default(),
+                warm_queries: WarmQueriesConfig::default(),
+                chunk_titles: ChunkTitlesConfig::default(),
             },
             worker: WorkerConfig {
                 concurrency: 4,
                 conversation_ttl_seconds: 3600,
                 result_ttl_seconds: 86400,
+                result_ttl_overrides: HashMap::new(),
+                history: JobHistoryConfig::default(),
+                attachment_sweep_interval_seconds: default_attachment_sweep_interval(),
+                retry: JobRetryConfig::default(),
+                backpressure: BackpressureConfig::default(),
+                replay: ReplayConfig::default(),
             },
             tools: ToolsConfig {
+                limits: ToolLimits::default(),
                 knowledge_base: KnowledgeBaseToolConfig {
                     name: "knowledge_base".to_string(),
                     description: "Search the knowledge base for relevant information.".to_string(),
                     no_results_message: "No relevant documents found.".to_string(),
+                    collection: None,
+                    strategy: RetrievalStrategy::default(),
+                    limits: ToolLimitOverrides::default(),
+                    context: ContextFormatConfig::default(),
+                    tags: Vec::new(),
                 },
             },
             cors: CorsConfig::default(),
+            api: ApiConfig::default(),
+            migrations: MigrationsConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            redis_tracing: RedisTracingConfig::default(),
+            chaos: ChaosConfig::default(),
+            git: GitConnectorConfig::default(),
+            crawl: CrawlerConfig::default(),
+            s3: S3ConnectorConfig::default(),
+            retention: RetentionConfig::default(),
+            banners: BannerConfig::default(),
+            channels: ChannelsConfig::default(),
+            glossary: GlossaryConfig::default(),
+            model_routing: ModelRoutingConfig::default(),
+            model_registry: ModelRegistryConfig::default(),
+            confidence: ConfidenceConfig::default(),
+            degraded_mode: DegradedModeConfig::default(),
+            safety: SafetyConfig::default(),
+            sanitize: SanitizeConfig::default(),
+            billing: BillingRates::default(),
+            streaming: StreamingConfig::default(),
+            drafts: DraftConfig::default(),
         }
     }
 }
@@ -183,6 +1463,7 @@ impl Default for PromptsConfig {
         Self {
             agent: AgentPrompts {
                 system: "You are a helpful assistant. Use the knowledge_base tool to search for relevant information when needed.".to_string(),
+                versions: HashMap::new(),
             },
             tools: ToolPrompts {
                 knowledge_base: KnowledgeBasePrompts {
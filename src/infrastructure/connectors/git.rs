@@ -0,0 +1,267 @@
+use glob::{MatchOptions, Pattern};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::domain::DomainError;
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A matching file at the synced commit.
+#[derive(Debug, Clone)]
+pub struct GitFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// What changed in a repository since the last synced commit.
+#[derive(Debug, Clone)]
+pub struct GitChanges {
+    pub head: String,
+    /// Added or modified files (every matching file on a full sync).
+    pub changed: Vec<GitFile>,
+    pub deleted: Vec<String>,
+}
+
+/// Keeps local clones of git repositories and reports changed files.
+///
+/// Shells out to the `git` CLI, which must be on `PATH`.
+pub struct GitConnector {
+    checkout_dir: PathBuf,
+}
+
+impl GitConnector {
+    pub fn new(checkout_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            checkout_dir: checkout_dir.into(),
+        }
+    }
+
+    /// Accepts only `https://`, `ssh://` and `git@host:path` remotes, so a
+    /// caller can't pass git options or clone local paths.
+    pub fn validate_url(url: &str) -> Result<(), DomainError> {
+        let remote =
+            url.starts_with("https://") || url.starts_with("ssh://") || url.starts_with("git@");
+        if !remote || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(DomainError::validation(
+                "repository URL must be https://, ssh:// or git@host:path",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Stable document id for a file, so re-syncs replace its chunks.
+    pub fn document_id(url: &str, path: &str) -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, format!("{url}#{path}").as_bytes())
+    }
+
+    /// Clones or fast-forwards `url` and lists files matching `globs`.
+    ///
+    /// With `since`, only files changed after that commit are returned; if the
+    /// commit is unknown (e.g. after a force-push) every matching file is.
+    pub async fn sync(
+        &self,
+        url: &str,
+        branch: Option<&str>,
+        globs: &[String],
+        since: Option<&str>,
+    ) -> Result<GitChanges, DomainError> {
+        let patterns = globs
+            .iter()
+            .map(|g| Pattern::new(g))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DomainError::validation(format!("invalid glob: {e}")))?;
+        let matches = |path: &str| patterns.iter().any(|p| p.matches_with(path, MATCH_OPTIONS));
+
+        Self::validate_url(url)?;
+        if branch.is_some_and(|b| b.starts_with('-')) {
+            return Err(DomainError::validation("invalid branch name"));
+        }
+        let dir = self.checkout(url, branch).await?;
+        let head = git(&dir, &["rev-parse", "HEAD"]).await?.trim().to_string();
+
+        let since = match since {
+            Some(commit) if commit == head => {
+                return Ok(GitChanges {
+                    head,
+                    changed: Vec::new(),
+                    deleted: Vec::new(),
+                })
+            }
+            Some(commit)
+                if git(&dir, &["cat-file", "-e", &format!("{commit}^{{commit}}")])
+                    .await
+                    .is_ok() =>
+            {
+                Some(commit)
+            }
+            _ => None,
+        };
+
+        let (changed_paths, deleted) = match since {
+            Some(commit) => {
+                let diff = git(
+                    &dir,
+                    &["diff", "--name-status", "--no-renames", "-z", commit, &head],
+                )
+                .await?;
+                parse_name_status(&diff)
+            }
+            None => {
+                let files = git(&dir, &["ls-files", "-z"]).await?;
+                let paths = files.split('\0').filter(|p| !p.is_empty());
+                (paths.map(str::to_string).collect(), Vec::new())
+            }
+        };
+
+        let mut changed = Vec::new();
+        for path in changed_paths.into_iter().filter(|p| matches(p)) {
+            // A committed symlink could point anywhere on this host.
+            let file = dir.join(&path);
+            match tokio::fs::symlink_metadata(&file).await {
+                Ok(meta) if meta.file_type().is_file() => {}
+                Ok(_) => {
+                    tracing::warn!(path, "skipping symlink or non-regular file");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!(path, error = %e, "skipping unreadable file");
+                    continue;
+                }
+            }
+            match tokio::fs::read_to_string(&file).await {
+                Ok(content) => changed.push(GitFile { path, content }),
+                Err(e) => tracing::warn!(path, error = %e, "skipping unreadable file"),
+            }
+        }
+
+        Ok(GitChanges {
+            head,
+            changed,
+            deleted: deleted.into_iter().filter(|p| matches(p)).collect(),
+        })
+    }
+
+    async fn checkout(&self, url: &str, branch: Option<&str>) -> Result<PathBuf, DomainError> {
+        let dir = self.checkout_dir.join(repo_dir_name(url));
+
+        if !dir.join(".git").exists() {
+            tokio::fs::create_dir_all(&self.checkout_dir)
+                .await
+                .map_err(|e| DomainError::internal(e.to_string()))?;
+
+            let dir_arg = dir.to_string_lossy().to_string();
+            let mut args = vec!["clone", "--quiet"];
+            if let Some(branch) = branch {
+                args.extend(["--branch", branch]);
+            }
+            args.extend(["--", url, dir_arg.as_str()]);
+            git(&self.checkout_dir, &args).await?;
+            return Ok(dir);
+        }
+
+        git(&dir, &["fetch", "--quiet", "--prune", "origin"]).await?;
+        let target = match branch {
+            Some(branch) => format!("origin/{branch}"),
+            None => "origin/HEAD".to_string(),
+        };
+        git(&dir, &["reset", "--quiet", "--hard", &target]).await?;
+        Ok(dir)
+    }
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<String, DomainError> {
+    // Never clone over file:// or let remotes pick transports like ext::.
+    let output = Command::new("git")
+        .args(["-c", "protocol.file.allow=never"])
+        .args(args)
+        .env("GIT_PROTOCOL_FROM_USER", "0")
+        .current_dir(dir)
+        .output()
+        .await
+        .map_err(|e| DomainError::external(format!("failed to run git: {e}")))?;
+
+    if !output.status.success() {
+        return Err(DomainError::external(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Directory name for a clone: readable repo name plus a hash of the URL.
+fn repo_dir_name(url: &str) -> String {
+    let name: String = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect();
+    let hash = Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes()).simple();
+    format!("{name}-{}", &hash.to_string()[..12])
+}
+
+/// Splits `git diff --name-status -z` output into (changed, deleted) paths.
+fn parse_name_status(output: &str) -> (Vec<String>, Vec<String>) {
+    let mut changed = Vec::new();
+    let mut deleted = Vec::new();
+    let mut fields = output.split('\0').filter(|f| !f.is_empty());
+
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        if status.starts_with('D') {
+            deleted.push(path.to_string());
+        } else {
+            changed.push(path.to_string());
+        }
+    }
+
+    (changed, deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_status() {
+        let (changed, deleted) = parse_name_status("M\0docs/a.md\0D\0docs/b.md\0A\0c.md\0");
+        assert_eq!(changed, vec!["docs/a.md", "c.md"]);
+        assert_eq!(deleted, vec!["docs/b.md"]);
+    }
+
+    #[test]
+    fn test_validate_url_allows_only_remote_transports() {
+        assert!(GitConnector::validate_url("https://github.com/org/handbook.git").is_ok());
+        assert!(GitConnector::validate_url("ssh://git@github.com/org/handbook.git").is_ok());
+        assert!(GitConnector::validate_url("git@github.com:org/handbook.git").is_ok());
+
+        for url in [
+            "--upload-pack=touch /tmp/pwned",
+            "-c core.sshCommand=sh",
+            "file:///etc",
+            "/srv/private-repo",
+            "ext::sh -c id",
+            "https://github.com/org/repo --upload-pack=x",
+        ] {
+            assert!(GitConnector::validate_url(url).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_repo_dir_name_is_stable_and_readable() {
+        let name = repo_dir_name("https://github.com/org/handbook.git");
+        assert!(name.starts_with("handbook-"));
+        assert_eq!(name, repo_dir_name("https://github.com/org/handbook.git"));
+        assert_ne!(name, repo_dir_name("https://github.com/other/handbook.git"));
+    }
+}
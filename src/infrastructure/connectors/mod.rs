@@ -0,0 +1,7 @@
+mod git;
+mod s3;
+mod web;
+
+pub use git::{GitChanges, GitConnector, GitFile};
+pub use s3::{content_type_for_key, object_url, S3Connector, S3Object};
+pub use web::{parse_html, Crawl, CrawlLimits, HtmlPage, WebCrawler, WebPage};
@@ -0,0 +1,136 @@
+use aws_sdk_s3::config::{BehaviorVersion, Region};
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::Client;
+use uuid::Uuid;
+
+use crate::domain::DomainError;
+
+/// An object listed under a bucket prefix.
+#[derive(Debug, Clone)]
+pub struct S3Object {
+    pub key: String,
+    pub etag: Option<String>,
+    pub size: u64,
+}
+
+/// Lists and downloads objects from S3 or an S3-compatible store.
+///
+/// Credentials come from the standard AWS chain (environment, profile,
+/// instance role).
+pub struct S3Connector {
+    client: Client,
+}
+
+impl S3Connector {
+    /// `endpoint_url` targets an S3-compatible store such as MinIO, using
+    /// path-style addressing.
+    pub async fn new(region: Option<&str>, endpoint_url: Option<&str>) -> Self {
+        let mut loader = aws_config::defaults(BehaviorVersion::latest());
+        if let Some(region) = region {
+            loader = loader.region(Region::new(region.to_string()));
+        }
+        let shared = loader.load().await;
+
+        let mut config = aws_sdk_s3::config::Builder::from(&shared);
+        if let Some(endpoint_url) = endpoint_url {
+            config = config.endpoint_url(endpoint_url).force_path_style(true);
+        }
+        Self {
+            client: Client::from_conf(config.build()),
+        }
+    }
+
+    /// Stable document id for an object, so re-syncs replace its chunks.
+    pub fn document_id(bucket: &str, key: &str) -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, object_url(bucket, key).as_bytes())
+    }
+
+    /// Every object under `prefix`, following pagination.
+    pub async fn list(&self, bucket: &str, prefix: &str) -> Result<Vec<S3Object>, DomainError> {
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+
+        let mut objects = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| {
+                DomainError::external(format!(
+                    "Failed to list s3://{bucket}/{prefix}: {}",
+                    DisplayErrorContext(e)
+                ))
+            })?;
+            for object in page.contents() {
+                let Some(key) = object.key() else {
+                    continue;
+                };
+                objects.push(S3Object {
+                    key: key.to_string(),
+                    etag: object.e_tag().map(str::to_string),
+                    size: object.size().unwrap_or(0).max(0) as u64,
+                });
+            }
+        }
+        Ok(objects)
+    }
+
+    pub async fn download(&self, bucket: &str, key: &str) -> Result<Vec<u8>, DomainError> {
+        let url = object_url(bucket, key);
+        let object = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                DomainError::external(format!(
+                    "Failed to download {url}: {}",
+                    DisplayErrorContext(e)
+                ))
+            })?;
+        let bytes = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| DomainError::external(format!("Failed to read {url}: {e}")))?;
+        Ok(bytes.into_bytes().to_vec())
+    }
+}
+
+/// `s3://bucket/key`, recorded as the source of indexed objects.
+pub fn object_url(bucket: &str, key: &str) -> String {
+    format!("s3://{bucket}/{key}")
+}
+
+/// MIME type for keys with an extension the extractors handle, else `None`.
+pub fn content_type_for_key(key: &str) -> Option<&'static str> {
+    let (_, extension) = key.rsplit_once('.')?;
+    match extension.to_ascii_lowercase().as_str() {
+        "md" | "markdown" => Some("text/markdown"),
+        "txt" | "text" => Some("text/plain"),
+        "csv" => Some("text/csv"),
+        "json" => Some("application/json"),
+        "pdf" => Some("application/pdf"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_for_key() {
+        assert_eq!(content_type_for_key("docs/Guide.MD"), Some("text/markdown"));
+        assert_eq!(
+            content_type_for_key("manuals/v2.1/setup.pdf"),
+            Some("application/pdf")
+        );
+        assert_eq!(content_type_for_key("images/logo.png"), None);
+        assert_eq!(content_type_for_key("README"), None);
+    }
+}
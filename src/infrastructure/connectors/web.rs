@@ -0,0 +1,519 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Url};
+use std::collections::{HashSet, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::domain::DomainError;
+
+/// Responses larger than this are skipped.
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Redirects followed per request, all on the requested host.
+const MAX_REDIRECTS: usize = 5;
+/// Elements whose content is never visible text.
+const SKIPPED_ELEMENTS: [&str; 5] = ["script", "style", "noscript", "template", "svg"];
+/// Elements that start a new paragraph of extracted text.
+const BLOCK_ELEMENTS: [&str; 24] = [
+    "p",
+    "div",
+    "br",
+    "li",
+    "ul",
+    "ol",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "tr",
+    "table",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "main",
+    "pre",
+    "blockquote",
+    "dt",
+    "dd",
+];
+
+/// A fetched HTML page converted to text.
+#[derive(Debug, Clone)]
+pub struct WebPage {
+    pub url: String,
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// How far a crawl may go from its start URL.
+#[derive(Debug, Clone, Copy)]
+pub struct CrawlLimits {
+    /// Link hops followed from the start page; sitemap entries count as the
+    /// sitemap's own depth.
+    pub max_depth: usize,
+    /// Pages fetched before the crawl stops; sitemaps don't count.
+    pub max_pages: usize,
+}
+
+/// Fetches pages over HTTP for [`Crawl`]s.
+pub struct WebCrawler {
+    client: reqwest::Client,
+}
+
+impl WebCrawler {
+    pub fn new(user_agent: &str, timeout: Duration) -> Result<Self, DomainError> {
+        // Crawls are started by API callers, so neither redirects nor DNS may
+        // lead them to another host or into the private network.
+        let redirects = redirect::Policy::custom(|attempt| {
+            let same_host = attempt
+                .previous()
+                .first()
+                .is_some_and(|first| first.host_str() == attempt.url().host_str());
+            if attempt.previous().len() > MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if !same_host || !matches!(attempt.url().scheme(), "http" | "https") {
+                attempt.stop()
+            } else {
+                attempt.follow()
+            }
+        });
+        let client = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .timeout(timeout)
+            .redirect(redirects)
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .map_err(|e| DomainError::internal(format!("Failed to build HTTP client: {e}")))?;
+        Ok(Self { client })
+    }
+
+    /// Stable document id for a page, so re-crawls replace its chunks.
+    pub fn document_id(url: &str) -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes())
+    }
+
+    /// Starts a crawl at `start`, a page or a sitemap, staying on its host.
+    pub fn crawl(&self, start: &str, limits: CrawlLimits) -> Result<Crawl<'_>, DomainError> {
+        let start = Url::parse(start)
+            .map_err(|e| DomainError::validation(format!("invalid URL {start}: {e}")))?;
+        let host = start
+            .host_str()
+            .filter(|_| matches!(start.scheme(), "http" | "https"))
+            .ok_or_else(|| DomainError::validation(format!("not an http(s) URL: {start}")))?
+            .to_string();
+
+        let mut crawl = Crawl {
+            crawler: self,
+            host,
+            limits,
+            frontier: VecDeque::new(),
+            seen: HashSet::new(),
+            fetched: 0,
+        };
+        crawl.enqueue(start, 0);
+        Ok(crawl)
+    }
+
+    async fn fetch(&self, url: &Url) -> Result<String, DomainError> {
+        // Names are checked by `PublicResolver`; literal addresses skip DNS.
+        let literal = url
+            .host_str()
+            .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+            .and_then(|host| host.parse::<IpAddr>().ok());
+        if literal.is_some_and(|ip| !is_public(ip)) {
+            return Err(DomainError::validation(format!(
+                "{url} is not a public address"
+            )));
+        }
+
+        let mut response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| DomainError::external(e.to_string()))?;
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("text/html")
+            .to_ascii_lowercase();
+        if !(content_type.contains("html") || content_type.contains("xml")) {
+            return Err(DomainError::validation(format!(
+                "unsupported content type {content_type}"
+            )));
+        }
+        if response.content_length().unwrap_or(0) > MAX_PAGE_BYTES as u64 {
+            return Err(DomainError::validation("page too large"));
+        }
+
+        // Chunked responses carry no length, so the cap holds while reading.
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?
+        {
+            if body.len() + chunk.len() > MAX_PAGE_BYTES {
+                return Err(DomainError::validation("page too large"));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+/// Resolves names with the system resolver and drops private, loopback,
+/// link-local and other non-public addresses.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} has no public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is routable on the public internet.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // Carrier-grade NAT and "this network".
+                || (a == 100 && (64..128).contains(&b))
+                || a == 0)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local (fc00::/7) and link-local (fe80::/10).
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// A breadth-first crawl in progress; pull pages with [`next_page`](Self::next_page).
+pub struct Crawl<'a> {
+    crawler: &'a WebCrawler,
+    host: String,
+    limits: CrawlLimits,
+    frontier: VecDeque<(Url, usize)>,
+    seen: HashSet<Url>,
+    fetched: usize,
+}
+
+impl Crawl<'_> {
+    /// The next page, or `None` once the crawl is exhausted or hit `max_pages`.
+    ///
+    /// Pages that fail to load are logged and skipped.
+    pub async fn next_page(&mut self) -> Option<WebPage> {
+        while self.fetched < self.limits.max_pages {
+            let (url, depth) = self.frontier.pop_front()?;
+            let body = match self.crawler.fetch(&url).await {
+                Ok(body) => body,
+                Err(e) => {
+                    tracing::warn!(url = %url, error = %e, "skipping page");
+                    continue;
+                }
+            };
+
+            if is_sitemap(&body) {
+                for location in sitemap_locations(&body) {
+                    if let Ok(location) = Url::parse(&location) {
+                        self.enqueue(location, depth);
+                    }
+                }
+                continue;
+            }
+
+            self.fetched += 1;
+            let page = parse_html(&body);
+            if depth < self.limits.max_depth {
+                for link in &page.links {
+                    if let Ok(link) = url.join(link) {
+                        self.enqueue(link, depth + 1);
+                    }
+                }
+            }
+            return Some(WebPage {
+                url: url.to_string(),
+                title: page.title,
+                text: page.text,
+            });
+        }
+        None
+    }
+
+    /// Pages fetched so far.
+    pub fn fetched(&self) -> usize {
+        self.fetched
+    }
+
+    /// URLs waiting to be fetched.
+    pub fn queued(&self) -> usize {
+        self.frontier.len()
+    }
+
+    fn enqueue(&mut self, mut url: Url, depth: usize) {
+        url.set_fragment(None);
+        let same_site =
+            matches!(url.scheme(), "http" | "https") && url.host_str() == Some(self.host.as_str());
+        if same_site && self.seen.insert(url.clone()) {
+            self.frontier.push_back((url, depth));
+        }
+    }
+}
+
+/// Text, title and raw link targets of an HTML document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HtmlPage {
+    pub title: Option<String>,
+    /// Visible text, one paragraph per block element.
+    pub text: String,
+    /// `href`s of `<a>` elements, unresolved.
+    pub links: Vec<String>,
+}
+
+/// Extracts the visible text, title and links of an HTML document.
+///
+/// A small tag scanner rather than a full parser: good enough for indexing
+/// documentation sites, not for arbitrary markup.
+pub fn parse_html(html: &str) -> HtmlPage {
+    let mut page = HtmlPage::default();
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(comment) = after.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = after.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &after[..end];
+        rest = &after[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if !closing && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            let end = find_ignore_case(rest, &format!("</{name}")).unwrap_or(rest.len());
+            rest = &rest[end..];
+            continue;
+        }
+        if !closing && name == "title" {
+            let end = find_ignore_case(rest, "</title").unwrap_or(rest.len());
+            page.title = Some(collapse_whitespace(&decode_entities(&rest[..end])))
+                .filter(|title| !title.is_empty());
+            rest = &rest[end..];
+            continue;
+        }
+        if !closing && name == "a" {
+            if let Some(href) = attribute(tag, "href") {
+                page.links.push(decode_entities(href));
+            }
+        }
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            text.push('\n');
+        }
+    }
+    text.push_str(rest);
+
+    page.text = decode_entities(&text)
+        .lines()
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    page
+}
+
+fn is_sitemap(body: &str) -> bool {
+    let end = (0..=body.len().min(1024))
+        .rev()
+        .find(|&i| body.is_char_boundary(i))
+        .unwrap_or(0);
+    let head = body[..end].to_ascii_lowercase();
+    head.contains("<urlset") || head.contains("<sitemapindex")
+}
+
+/// The `<loc>` entries of a sitemap or sitemap index.
+fn sitemap_locations(xml: &str) -> Vec<String> {
+    xml.split("<loc>")
+        .skip(1)
+        .filter_map(|entry| entry.split_once("</loc>"))
+        .map(|(location, _)| decode_entities(location.trim()))
+        .collect()
+}
+
+/// Value of attribute `name` in the inside of a start tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let pattern = format!("{name}=");
+    let start = lower.match_indices(&pattern).find_map(|(i, _)| {
+        let before = lower[..i].chars().next_back();
+        before
+            .is_some_and(char::is_whitespace)
+            .then_some(i + pattern.len())
+    })?;
+
+    let value = &tag[start..];
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next(),
+        _ => value.split(|c: char| c.is_whitespace()).next(),
+    }
+}
+
+/// Byte offset of `needle` (lowercase ASCII) in `haystack`, ignoring ASCII case.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(needle)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => {
+                    let code = match entity.strip_prefix('#') {
+                        Some(hex) if hex.starts_with(['x', 'X']) => {
+                            u32::from_str_radix(&hex[1..], 16).ok()
+                        }
+                        Some(decimal) => decimal.parse().ok(),
+                        None => None,
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_html_extracts_text_title_and_links() {
+        let html = r#"<!DOCTYPE html><html><head><title> Setup &amp; Install </title>
+            <style>body { color: red }</style><script>var a = "<p>";</script></head>
+            <body><nav><a href="/docs/a">A</a> <A HREF='b.html#top'>B</A></nav>
+            <!-- hidden --><h1>Install</h1><p>Run <b>make</b>&nbsp;now.</p><ul><li>one</li></ul>
+            </body></html>"#;
+
+        let page = parse_html(html);
+        assert_eq!(page.title.as_deref(), Some("Setup & Install"));
+        assert_eq!(page.text, "A B\n\nInstall\n\nRun make now.\n\none");
+        assert_eq!(page.links, vec!["/docs/a", "b.html#top"]);
+    }
+
+    #[test]
+    fn test_only_public_addresses_are_crawled() {
+        for ip in ["93.184.216.34", "2606:2800:220:1::"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_private_literal_addresses() {
+        let crawler = WebCrawler::new("test", Duration::from_secs(1)).unwrap();
+        let url = Url::parse("http://169.254.169.254/latest/meta-data/").unwrap();
+        assert!(crawler.fetch(&url).await.is_err());
+    }
+
+    #[test]
+    fn test_sitemap_locations() {
+        let xml =
+            "<?xml version=\"1.0\"?><urlset><url><loc> https://x.io/a?b=1&amp;c=2 </loc></url>\
+                   <url><loc>https://x.io/b</loc></url></urlset>";
+        assert!(is_sitemap(xml));
+        assert_eq!(
+            sitemap_locations(xml),
+            vec!["https://x.io/a?b=1&c=2", "https://x.io/b"]
+        );
+    }
+}
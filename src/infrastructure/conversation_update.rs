@@ -0,0 +1,88 @@
+//! Read-modify-write of stored conversations without losing concurrent
+//! updates, e.g. a draft approved while the worker is still answering.
+
+use deadpool_redis::redis::{self, AsyncCommands, RedisError};
+use deadpool_redis::Connection;
+use uuid::Uuid;
+
+use crate::domain::Conversation;
+use crate::infrastructure::{keys, traced};
+
+/// Attempts before giving up on a conversation that keeps changing.
+const MAX_ATTEMPTS: usize = 10;
+
+/// Applies `apply` to the stored conversation `id` (`None` when there is
+/// none) and saves what it returns with `ttl_seconds`, retrying from a fresh
+/// read whenever the conversation changed in between (Redis `WATCH`). The
+/// conversation is re-scored in [`keys::CONVERSATIONS_BY_UPDATED`] with it.
+///
+/// `apply` may refuse the update with `Err`, which is returned without
+/// saving.
+pub async fn update_conversation<T, E>(
+    conn: &mut Connection,
+    id: &Uuid,
+    ttl_seconds: u64,
+    mut apply: impl FnMut(Option<Conversation>) -> Result<(Conversation, T), E>,
+) -> Result<Result<T, E>, RedisError> {
+    let key = keys::conversation(id);
+    for _ in 0..MAX_ATTEMPTS {
+        traced(
+            "WATCH",
+            &key,
+            redis::cmd("WATCH").arg(&key).query_async::<()>(&mut *conn),
+        )
+        .await?;
+        let stored: Option<String> = traced("GET", &key, conn.get(&key)).await?;
+        let stored = stored
+            .map(|json| serde_json::from_str::<Conversation>(&json))
+            .transpose()
+            .map_err(decode_error)?;
+
+        let (conversation, output) = match apply(stored) {
+            Ok(updated) => updated,
+            Err(e) => {
+                traced(
+                    "UNWATCH",
+                    &key,
+                    redis::cmd("UNWATCH").query_async::<()>(&mut *conn),
+                )
+                .await?;
+                return Ok(Err(e));
+            }
+        };
+        let json = serde_json::to_string(&conversation).map_err(decode_error)?;
+        // EXEC answers nil when the watched key changed since WATCH.
+        let saved: Option<()> = traced(
+            "MULTI",
+            &key,
+            redis::pipe()
+                .atomic()
+                .set_ex(&key, json, ttl_seconds)
+                .ignore()
+                .zadd(
+                    keys::CONVERSATIONS_BY_UPDATED,
+                    id.to_string(),
+                    conversation.updated_at.timestamp_millis(),
+                )
+                .ignore()
+                .query_async(&mut *conn),
+        )
+        .await?;
+        if saved.is_some() {
+            return Ok(Ok(output));
+        }
+    }
+    Err(RedisError::from((
+        redis::ErrorKind::TryAgain,
+        "conversation kept changing",
+        key,
+    )))
+}
+
+fn decode_error(e: serde_json::Error) -> RedisError {
+    RedisError::from((
+        redis::ErrorKind::TypeError,
+        "invalid conversation JSON",
+        e.to_string(),
+    ))
+}
@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::{ports::DocumentStore, Document, DocumentChunk, DomainError, ExtractedPage};
+
+/// Keeps documents and their chunks in process memory; contents are lost on restart.
+pub struct InMemoryDocumentStore {
+    documents: RwLock<HashMap<Uuid, Document>>,
+    chunks: RwLock<HashMap<Uuid, Vec<DocumentChunk>>>,
+    contents: RwLock<HashMap<Uuid, Vec<ExtractedPage>>>,
+}
+
+impl InMemoryDocumentStore {
+    pub fn new() -> Self {
+        Self {
+            documents: RwLock::new(HashMap::new()),
+            chunks: RwLock::new(HashMap::new()),
+            contents: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryDocumentStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DocumentStore for InMemoryDocumentStore {
+    async fn save_document(&self, doc: &Document) -> Result<(), DomainError> {
+        self.documents
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?
+            .insert(doc.id, doc.clone());
+        Ok(())
+    }
+
+    async fn get_document(&self, id: Uuid) -> Result<Option<Document>, DomainError> {
+        let documents = self
+            .documents
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+        Ok(documents.get(&id).cloned())
+    }
+
+    async fn find_by_content_hash(
+        &self,
+        hash: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Option<Document>, DomainError> {
+        let documents = self
+            .documents
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+        Ok(documents
+            .values()
+            .find(|doc| {
+                doc.content_hash.as_deref() == Some(hash) && doc.tenant_id.as_deref() == tenant_id
+            })
+            .cloned())
+    }
+
+    async fn delete_document(&self, id: Uuid) -> Result<(), DomainError> {
+        self.documents
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?
+            .remove(&id);
+        self.chunks
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?
+            .remove(&id);
+        self.contents
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?
+            .remove(&id);
+        Ok(())
+    }
+
+    async fn save_chunks(&self, chunks: &[DocumentChunk]) -> Result<(), DomainError> {
+        let mut store = self
+            .chunks
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+
+        for chunk in chunks {
+            let saved = store.entry(chunk.document_id).or_default();
+            saved.retain(|c| c.id != chunk.id);
+            saved.push(chunk.clone());
+        }
+        Ok(())
+    }
+
+    async fn get_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        let store = self
+            .chunks
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+
+        let mut chunks = store.get(&document_id).cloned().unwrap_or_default();
+        chunks.sort_by_key(|c| c.chunk_index);
+        Ok(chunks)
+    }
+
+    async fn save_content(
+        &self,
+        document_id: Uuid,
+        pages: &[ExtractedPage],
+    ) -> Result<(), DomainError> {
+        self.contents
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?
+            .insert(document_id, pages.to_vec());
+        Ok(())
+    }
+
+    async fn get_content(
+        &self,
+        document_id: Uuid,
+    ) -> Result<Option<Vec<ExtractedPage>>, DomainError> {
+        let contents = self
+            .contents
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+        Ok(contents.get(&document_id).cloned())
+    }
+
+    async fn replace_chunks(
+        &self,
+        document_id: Uuid,
+        chunks: &[DocumentChunk],
+    ) -> Result<(), DomainError> {
+        self.chunks
+            .write()
+            .map_err(|e| DomainError::internal(e.to_string()))?
+            .insert(document_id, chunks.to_vec());
+        Ok(())
+    }
+}
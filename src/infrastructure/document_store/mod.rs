@@ -0,0 +1,16 @@
+mod in_memory;
+
+use std::sync::Arc;
+
+use crate::domain::ports::DocumentStore;
+use crate::infrastructure::config::{DocumentStoreConfig, DocumentStoreKind};
+
+pub use in_memory::InMemoryDocumentStore;
+
+/// Builds the configured document store, `None` if documents aren't stored.
+pub fn document_store_from_config(config: &DocumentStoreConfig) -> Option<Arc<dyn DocumentStore>> {
+    match config.backend {
+        DocumentStoreKind::None => None,
+        DocumentStoreKind::Memory => Some(Arc::new(InMemoryDocumentStore::new())),
+    }
+}
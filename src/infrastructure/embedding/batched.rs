@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::domain::{
+    ports::{EmbeddingPurpose, EmbeddingService},
+    DomainError, Embedding,
+};
+use crate::infrastructure::config::EmbeddingConfig;
+
+/// Splits batches into requests of at most `embedding.batch_size` texts,
+/// sends up to `embedding.concurrency` of them at once and spaces requests
+/// to stay under `embedding.requests_per_minute`.
+pub struct BatchedEmbedding {
+    inner: Arc<dyn EmbeddingService>,
+    batch_size: usize,
+    concurrency: usize,
+    /// Earliest start of the next request, when rate limited.
+    next_slot: Option<(Mutex<Instant>, Duration)>,
+}
+
+impl BatchedEmbedding {
+    pub fn new(inner: Arc<dyn EmbeddingService>, config: &EmbeddingConfig) -> Self {
+        Self {
+            inner,
+            batch_size: config.batch_size.max(1),
+            concurrency: config.concurrency.max(1),
+            next_slot: config
+                .requests_per_minute
+                .filter(|rpm| *rpm > 0)
+                .map(|rpm| (Mutex::new(Instant::now()), Duration::from_secs(60) / rpm)),
+        }
+    }
+
+    /// Waits for this request's turn under the rate limit.
+    async fn pace(&self) {
+        let Some((next_slot, interval)) = &self.next_slot else {
+            return;
+        };
+        let start = {
+            let mut next = next_slot.lock().await;
+            let start = (*next).max(Instant::now());
+            *next = start + *interval;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+
+    async fn send_batch(
+        &self,
+        batch: &[&str],
+        purpose: EmbeddingPurpose,
+    ) -> Result<Vec<Embedding>, DomainError> {
+        self.pace().await;
+        self.inner.embed_batch_for(batch, purpose).await
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for BatchedEmbedding {
+    async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+        self.pace().await;
+        self.inner.embed(text).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        self.embed_batch_for(texts, EmbeddingPurpose::Document)
+            .await
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+
+    async fn embed_for(
+        &self,
+        text: &str,
+        purpose: EmbeddingPurpose,
+    ) -> Result<Embedding, DomainError> {
+        self.pace().await;
+        self.inner.embed_for(text, purpose).await
+    }
+
+    async fn embed_batch_for(
+        &self,
+        texts: &[&str],
+        purpose: EmbeddingPurpose,
+    ) -> Result<Vec<Embedding>, DomainError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let requests: Vec<_> = texts
+            .chunks(self.batch_size)
+            .map(|batch| self.send_batch(batch, purpose))
+            .collect();
+        let batches: Vec<Vec<Embedding>> = futures::stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+        Ok(batches.into_iter().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::AppConfig;
+    use std::sync::Mutex as StdMutex;
+
+    /// Records the size of every batch it is sent.
+    #[derive(Default)]
+    struct RecordingEmbedding {
+        batches: StdMutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl EmbeddingService for RecordingEmbedding {
+        async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+            Ok(Embedding::new(vec![text.len() as f32]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            self.batches.lock().unwrap().push(texts.len());
+            Ok(texts
+                .iter()
+                .map(|t| Embedding::new(vec![t.len() as f32]))
+                .collect())
+        }
+
+        fn dimension(&self) -> usize {
+            1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_splits_batches_and_keeps_order() {
+        let mut config = AppConfig::default().config.embedding;
+        config.batch_size = 2;
+        config.concurrency = 2;
+        let inner = Arc::new(RecordingEmbedding::default());
+        let embedding = BatchedEmbedding::new(inner.clone(), &config);
+
+        let texts = ["a", "bb", "ccc", "dddd", "eeeee"];
+        let embeddings = embedding.embed_batch(&texts).await.unwrap();
+
+        let lengths: Vec<f32> = embeddings.iter().map(|e| e.as_slice()[0]).collect();
+        assert_eq!(lengths, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let mut batches = inner.batches.lock().unwrap().clone();
+        batches.sort();
+        assert_eq!(batches, vec![1, 2, 2]);
+    }
+}
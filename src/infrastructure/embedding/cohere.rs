@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    ports::{EmbeddingPurpose, EmbeddingService},
+    DomainError, Embedding,
+};
+use crate::infrastructure::config::EmbeddingConfig;
+
+/// Texts per request; Cohere's embed endpoint accepts at most 96.
+const COHERE_BATCH_SIZE: usize = 96;
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    texts: &'a [&'a str],
+    input_type: &'static str,
+    embedding_types: [&'static str; 1],
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: EmbedVectors,
+}
+
+#[derive(Deserialize)]
+struct EmbedVectors {
+    float: Vec<Vec<f32>>,
+}
+
+fn input_type(purpose: EmbeddingPurpose) -> &'static str {
+    match purpose {
+        EmbeddingPurpose::Document => "search_document",
+        EmbeddingPurpose::Query => "search_query",
+    }
+}
+
+/// Embeddings from Cohere's embed API (`COHERE_API_KEY`), which embeds
+/// documents and search queries with different input types.
+pub struct CohereEmbedding {
+    http: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    dimension: usize,
+}
+
+impl CohereEmbedding {
+    pub fn from_config(config: &EmbeddingConfig) -> Result<Self, DomainError> {
+        let api_key = std::env::var("COHERE_API_KEY").map_err(|_| {
+            DomainError::validation("embedding.provider cohere needs COHERE_API_KEY")
+        })?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            url: format!("{}/v2/embed", config.cohere.base_url.trim_end_matches('/')),
+            api_key,
+            model: config.model.clone(),
+            dimension: config.dimension,
+        })
+    }
+
+    async fn request(
+        &self,
+        texts: &[&str],
+        purpose: EmbeddingPurpose,
+    ) -> Result<Vec<Embedding>, DomainError> {
+        let response = self
+            .http
+            .post(&self.url)
+            .bearer_auth(&self.api_key)
+            .json(&EmbedRequest {
+                model: &self.model,
+                texts,
+                input_type: input_type(purpose),
+                embedding_types: ["float"],
+            })
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    DomainError::timeout(format!("Cohere embedding timed out: {e}"))
+                } else {
+                    DomainError::external(format!("Cohere unreachable: {e}"))
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(DomainError::provider_status(
+                status.as_u16(),
+                format!("Cohere embedding failed ({status}): {body}"),
+            ));
+        }
+
+        let body: EmbedResponse = response
+            .json()
+            .await
+            .map_err(|e| DomainError::provider(format!("Invalid Cohere response: {e}")))?;
+        if body.embeddings.float.len() != texts.len() {
+            return Err(DomainError::internal(format!(
+                "Cohere returned {} embeddings for {} texts",
+                body.embeddings.float.len(),
+                texts.len()
+            )));
+        }
+        Ok(body
+            .embeddings
+            .float
+            .into_iter()
+            .map(Embedding::new)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for CohereEmbedding {
+    async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+        self.embed_for(text, EmbeddingPurpose::Document).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        self.embed_batch_for(texts, EmbeddingPurpose::Document)
+            .await
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    async fn embed_for(
+        &self,
+        text: &str,
+        purpose: EmbeddingPurpose,
+    ) -> Result<Embedding, DomainError> {
+        self.request(&[text], purpose)
+            .await?
+            .pop()
+            .ok_or_else(|| DomainError::internal("No embedding returned"))
+    }
+
+    async fn embed_batch_for(
+        &self,
+        texts: &[&str],
+        purpose: EmbeddingPurpose,
+    ) -> Result<Vec<Embedding>, DomainError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(COHERE_BATCH_SIZE) {
+            embeddings.extend(self.request(batch, purpose).await?);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_carries_input_type_for_purpose() {
+        let texts = ["refund policy"];
+        let request = EmbedRequest {
+            model: "embed-english-v3.0",
+            texts: &texts,
+            input_type: input_type(EmbeddingPurpose::Query),
+            embedding_types: ["float"],
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["input_type"], "search_query");
+        assert_eq!(json["embedding_types"][0], "float");
+        assert_eq!(input_type(EmbeddingPurpose::Document), "search_document");
+    }
+}
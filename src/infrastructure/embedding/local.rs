@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding as OnnxEmbedding};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::domain::{ports::EmbeddingService, DomainError, Embedding};
+use crate::infrastructure::config::EmbeddingConfig;
+
+/// Embeds text in-process with a local ONNX model from fastembed (e.g.
+/// `BAAI/bge-small-en-v1.5`), so no embedding API is called. The model is
+/// downloaded into `embedding.local.cache_dir` on first use.
+pub struct FastEmbedService {
+    model: Arc<Mutex<OnnxEmbedding>>,
+    dimension: usize,
+}
+
+impl FastEmbedService {
+    /// Loads the model named by `embedding.model`.
+    pub fn from_config(config: &EmbeddingConfig) -> Result<Self, DomainError> {
+        let (model, dimension) = supported_model(&config.model)?;
+        let options = InitOptions::new(model)
+            .with_cache_dir(PathBuf::from(&config.local.cache_dir))
+            .with_show_download_progress(false);
+        let model = OnnxEmbedding::try_new(options)
+            .map_err(|e| DomainError::external(format!("Failed to load {}: {e}", config.model)))?;
+        tracing::info!(model = %config.model, dimension, "local embedding model loaded");
+
+        Ok(Self {
+            model: Arc::new(Mutex::new(model)),
+            dimension,
+        })
+    }
+}
+
+/// The fastembed model with the given code and its dimension.
+fn supported_model(code: &str) -> Result<(EmbeddingModel, usize), DomainError> {
+    let models = OnnxEmbedding::list_supported_models();
+    models
+        .iter()
+        .find(|info| info.model_code.eq_ignore_ascii_case(code))
+        .map(|info| (info.model.clone(), info.dim))
+        .ok_or_else(|| {
+            let known: Vec<&str> = models.iter().map(|info| info.model_code.as_str()).collect();
+            DomainError::validation(format!(
+                "Unknown local embedding model {code}; supported: {}",
+                known.join(", ")
+            ))
+        })
+}
+
+#[async_trait]
+impl EmbeddingService for FastEmbedService {
+    async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+        self.embed_batch(&[text])
+            .await?
+            .pop()
+            .ok_or_else(|| DomainError::internal("No embedding returned"))
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Inference is CPU-bound, so it runs off the async workers.
+        let model = self.model.clone();
+        let texts: Vec<String> = texts.iter().map(|t| t.to_string()).collect();
+        let vectors = tokio::task::spawn_blocking(move || {
+            let mut model = model
+                .lock()
+                .map_err(|_| DomainError::internal("Local embedding model lock poisoned"))?;
+            model
+                .embed(texts, None)
+                .map_err(|e| DomainError::internal(format!("Local embedding failed: {e}")))
+        })
+        .await
+        .map_err(|e| DomainError::internal(format!("Local embedding task failed: {e}")))??;
+
+        Ok(vectors.into_iter().map(Embedding::new).collect())
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_model_matches_code_case_insensitively() {
+        let (_, dimension) = supported_model("baai/bge-small-en-v1.5").unwrap();
+        assert_eq!(dimension, 384);
+
+        let err = supported_model("not-a-model").unwrap_err();
+        assert!(err.to_string().contains("BAAI/bge-small-en-v1.5"));
+    }
+}
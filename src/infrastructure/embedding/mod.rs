@@ -1,3 +1,48 @@
+mod batched;
+mod cohere;
+#[cfg(feature = "local-embeddings")]
+mod local;
+mod ollama;
+mod openai;
+mod probe;
 mod text;
 
+use std::sync::Arc;
+
+pub use batched::BatchedEmbedding;
+pub use cohere::CohereEmbedding;
+#[cfg(feature = "local-embeddings")]
+pub use local::FastEmbedService;
+pub use ollama::OllamaEmbedding;
+pub use openai::OpenAiEmbedding;
+pub use probe::{check_store_dimension, probe_dimension, DimensionProbe};
 pub use text::TextEmbedding;
+
+use crate::domain::{ports::EmbeddingService, DomainError};
+use crate::infrastructure::config::{EmbeddingConfig, EmbeddingProvider};
+
+/// Builds the embedding service selected by `embedding.provider`, batched and
+/// rate limited per `embedding`.
+pub fn embedding_from_config(
+    config: &EmbeddingConfig,
+) -> Result<Arc<dyn EmbeddingService>, DomainError> {
+    let provider = provider_from_config(config)?;
+    Ok(Arc::new(BatchedEmbedding::new(provider, config)))
+}
+
+fn provider_from_config(
+    config: &EmbeddingConfig,
+) -> Result<Arc<dyn EmbeddingService>, DomainError> {
+    match config.provider {
+        EmbeddingProvider::Gemini => Ok(Arc::new(TextEmbedding::from_config(config))),
+        EmbeddingProvider::Openai => Ok(Arc::new(OpenAiEmbedding::from_config(config)?)),
+        EmbeddingProvider::Ollama => Ok(Arc::new(OllamaEmbedding::from_config(config))),
+        EmbeddingProvider::Cohere => Ok(Arc::new(CohereEmbedding::from_config(config)?)),
+        #[cfg(feature = "local-embeddings")]
+        EmbeddingProvider::Local => Ok(Arc::new(FastEmbedService::from_config(config)?)),
+        #[cfg(not(feature = "local-embeddings"))]
+        EmbeddingProvider::Local => Err(DomainError::validation(
+            "embedding.provider local needs a build with the local-embeddings feature",
+        )),
+    }
+}
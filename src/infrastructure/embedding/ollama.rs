@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{ports::EmbeddingService, DomainError, Embedding};
+use crate::infrastructure::config::EmbeddingConfig;
+
+/// Texts embedded at once; Ollama's `/api/embeddings` takes one prompt per call.
+const OLLAMA_CONCURRENCY: usize = 4;
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+/// Embeddings from a self-hosted Ollama server.
+pub struct OllamaEmbedding {
+    http: reqwest::Client,
+    url: String,
+    model: String,
+    dimension: usize,
+}
+
+impl OllamaEmbedding {
+    pub fn from_config(config: &EmbeddingConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: format!(
+                "{}/api/embeddings",
+                config.ollama.base_url.trim_end_matches('/')
+            ),
+            model: config.model.clone(),
+            dimension: config.dimension,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for OllamaEmbedding {
+    async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+        let response = self
+            .http
+            .post(&self.url)
+            .json(&EmbeddingRequest {
+                model: &self.model,
+                prompt: text,
+            })
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    DomainError::timeout(format!("Ollama embedding timed out: {e}"))
+                } else {
+                    DomainError::external(format!("Ollama unreachable: {e}"))
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(DomainError::provider_status(
+                status.as_u16(),
+                format!("Ollama embedding failed ({status}): {body}"),
+            ));
+        }
+
+        let body: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| DomainError::provider(format!("Invalid Ollama response: {e}")))?;
+        if body.embedding.is_empty() {
+            return Err(DomainError::internal("No embedding returned"));
+        }
+        Ok(Embedding::new(body.embedding))
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(OLLAMA_CONCURRENCY) {
+            embeddings.extend(try_join_all(batch.iter().map(|text| self.embed(text))).await?);
+        }
+        Ok(embeddings)
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::config::{EmbeddingProvider, OllamaEmbeddingConfig};
+    use crate::infrastructure::AppConfig;
+
+    #[test]
+    fn test_from_config_targets_embeddings_endpoint() {
+        let mut config = AppConfig::default().config.embedding;
+        config.provider = EmbeddingProvider::Ollama;
+        config.model = "nomic-embed-text".to_string();
+        config.ollama = OllamaEmbeddingConfig {
+            base_url: "http://ollama:11434/".to_string(),
+        };
+
+        let embedding = OllamaEmbedding::from_config(&config);
+        assert_eq!(embedding.url, "http://ollama:11434/api/embeddings");
+        assert_eq!(embedding.model, "nomic-embed-text");
+    }
+}
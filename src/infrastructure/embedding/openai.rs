@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{ports::EmbeddingService, DomainError, Embedding};
+use crate::infrastructure::config::EmbeddingConfig;
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Inputs per request; OpenAI accepts at most 2048.
+const OPENAI_BATCH_SIZE: usize = 2048;
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [&'a str],
+    /// Only `text-embedding-3` models can shorten their vectors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Embeddings from the OpenAI API (`OPENAI_API_KEY`) or a compatible one at
+/// `embedding.base_url`.
+pub struct OpenAiEmbedding {
+    http: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    dimension: usize,
+}
+
+impl OpenAiEmbedding {
+    pub fn from_config(config: &EmbeddingConfig) -> Result<Self, DomainError> {
+        let api_key = std::env::var("OPENAI_API_KEY").map_err(|_| {
+            DomainError::validation("embedding.provider openai needs OPENAI_API_KEY")
+        })?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            url: format!(
+                "{}/embeddings",
+                config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or(OPENAI_BASE_URL)
+                    .trim_end_matches('/')
+            ),
+            api_key,
+            model: config.model.clone(),
+            dimension: config.dimension,
+        })
+    }
+
+    fn request<'a>(&'a self, input: &'a [&'a str]) -> EmbeddingRequest<'a> {
+        EmbeddingRequest {
+            model: &self.model,
+            input,
+            dimensions: self
+                .model
+                .starts_with("text-embedding-3")
+                .then_some(self.dimension),
+        }
+    }
+
+    async fn embed_chunk(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        let response = self
+            .http
+            .post(&self.url)
+            .bearer_auth(&self.api_key)
+            .json(&self.request(texts))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    DomainError::timeout(format!("OpenAI embedding timed out: {e}"))
+                } else {
+                    DomainError::external(format!("OpenAI unreachable: {e}"))
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(DomainError::provider_status(
+                status.as_u16(),
+                format!("OpenAI embedding failed ({status}): {body}"),
+            ));
+        }
+
+        let mut body: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| DomainError::provider(format!("Invalid OpenAI response: {e}")))?;
+        if body.data.len() != texts.len() {
+            return Err(DomainError::internal(format!(
+                "OpenAI returned {} embeddings for {} texts",
+                body.data.len(),
+                texts.len()
+            )));
+        }
+        body.data.sort_by_key(|d| d.index);
+        Ok(body
+            .data
+            .into_iter()
+            .map(|d| Embedding::new(d.embedding))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for OpenAiEmbedding {
+    async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
+        self.embed_chunk(&[text])
+            .await?
+            .pop()
+            .ok_or_else(|| DomainError::internal("No embedding returned"))
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(OPENAI_BATCH_SIZE) {
+            embeddings.extend(self.embed_chunk(batch).await?);
+        }
+        Ok(embeddings)
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_shortens_only_v3_models() {
+        let mut embedding = OpenAiEmbedding {
+            http: reqwest::Client::new(),
+            url: format!("{OPENAI_BASE_URL}/embeddings"),
+            api_key: "test".to_string(),
+            model: "text-embedding-3-small".to_string(),
+            dimension: 768,
+        };
+        let texts = ["refund policy"];
+
+        let json = serde_json::to_value(embedding.request(&texts)).unwrap();
+        assert_eq!(json["dimensions"], 768);
+        assert_eq!(json["input"][0], "refund policy");
+
+        embedding.model = "text-embedding-ada-002".to_string();
+        let json = serde_json::to_value(embedding.request(&texts)).unwrap();
+        assert!(json.get("dimensions").is_none());
+    }
+}
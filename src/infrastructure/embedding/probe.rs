@@ -0,0 +1,112 @@
+use crate::domain::{
+    ports::{EmbeddingService, VectorStore},
+    DomainError,
+};
+
+/// What the embedding provider returned for the startup canary, compared
+/// with the configured `embedding.dimension`.
+#[derive(Debug)]
+pub enum DimensionProbe {
+    Matches(usize),
+    Mismatch {
+        configured: usize,
+        actual: usize,
+    },
+    /// The provider couldn't be reached; the configured dimension is kept.
+    Unavailable(DomainError),
+}
+
+impl DimensionProbe {
+    /// The dimension to build collections with.
+    pub fn dimension(&self, configured: usize) -> usize {
+        match self {
+            Self::Matches(dimension)
+            | Self::Mismatch {
+                actual: dimension, ..
+            } => *dimension,
+            Self::Unavailable(_) => configured,
+        }
+    }
+}
+
+/// Embeds `canary` and checks the vector length against `configured`.
+pub async fn probe_dimension(
+    service: &dyn EmbeddingService,
+    canary: &str,
+    configured: usize,
+) -> DimensionProbe {
+    match service.embed(canary).await {
+        Ok(embedding) if embedding.dimension() == 0 => DimensionProbe::Unavailable(
+            DomainError::external("embedding provider returned an empty vector"),
+        ),
+        Ok(embedding) if embedding.dimension() == configured => DimensionProbe::Matches(configured),
+        Ok(embedding) => DimensionProbe::Mismatch {
+            configured,
+            actual: embedding.dimension(),
+        },
+        Err(e) => DimensionProbe::Unavailable(e),
+    }
+}
+
+/// Refuses to pair an embedding service with a collection storing vectors
+/// of another length, before the first upsert or search fails on it.
+pub fn check_store_dimension(
+    embedding: &dyn EmbeddingService,
+    store: &dyn VectorStore,
+    collection: &str,
+) -> Result<(), DomainError> {
+    match store.dimension() {
+        Some(stored) if stored != embedding.dimension() => Err(DomainError::validation(format!(
+            "embedding service returns {}-dimensional vectors but collection {collection} \
+             stores {stored}-dimensional ones",
+            embedding.dimension()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Embedding;
+    use async_trait::async_trait;
+
+    struct FixedEmbedding(usize);
+
+    #[async_trait]
+    impl EmbeddingService for FixedEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Embedding, DomainError> {
+            Ok(Embedding::new(vec![0.5; self.0]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Embedding>, DomainError> {
+            Ok(texts
+                .iter()
+                .map(|_| Embedding::new(vec![0.5; self.0]))
+                .collect())
+        }
+
+        fn dimension(&self) -> usize {
+            768
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_reports_provider_dimension() {
+        let probe = probe_dimension(&FixedEmbedding(768), "canary", 768).await;
+        assert!(matches!(probe, DimensionProbe::Matches(768)));
+
+        let probe = probe_dimension(&FixedEmbedding(3072), "canary", 768).await;
+        assert!(matches!(
+            probe,
+            DimensionProbe::Mismatch {
+                configured: 768,
+                actual: 3072
+            }
+        ));
+        assert_eq!(probe.dimension(768), 3072);
+
+        let probe = probe_dimension(&FixedEmbedding(0), "canary", 768).await;
+        assert_eq!(probe.dimension(768), 768);
+    }
+}
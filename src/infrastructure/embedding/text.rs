@@ -1,33 +1,42 @@
 use async_trait::async_trait;
 use rig::client::{EmbeddingsClient, ProviderClient};
 use rig::embeddings::EmbeddingsBuilder;
-use rig::providers::gemini;
+use rig::providers::gemini::{self, embedding::EmbeddingModel};
 
 use crate::domain::{ports::EmbeddingService, DomainError, Embedding};
 use crate::infrastructure::config::EmbeddingConfig;
+use crate::infrastructure::retry::{provider_error, retry, RetryPolicy};
 
+/// Gemini embeddings through rig (`GEMINI_API_KEY`). The client and model
+/// handle are built once, so calls share one connection pool. Failed
+/// requests are retried per `embedding.retry`.
 pub struct TextEmbedding {
-    model: String,
+    client: gemini::Client,
+    model: EmbeddingModel,
     dimension: usize,
+    retry: RetryPolicy,
 }
 
 impl TextEmbedding {
     pub fn new() -> Self {
+        let client = gemini::Client::from_env();
         Self {
-            model: "gemini-embedding-001".to_string(),
+            model: client.embedding_model("gemini-embedding-001"),
+            client,
             dimension: 768,
+            retry: RetryPolicy::default(),
         }
     }
 
     pub fn from_config(config: &EmbeddingConfig) -> Self {
-        Self {
-            model: config.model.clone(),
-            dimension: config.dimension,
-        }
+        Self::new()
+            .with_model(&config.model)
+            .with_dimension(config.dimension)
+            .with_retry(config.retry)
     }
 
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
-        self.model = model.into();
+        self.model = self.client.embedding_model(model.into());
         self
     }
 
@@ -35,6 +44,11 @@ impl TextEmbedding {
         self.dimension = dimension;
         self
     }
+
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
 }
 
 impl Default for TextEmbedding {
@@ -46,15 +60,15 @@ impl Default for TextEmbedding {
 #[async_trait]
 impl EmbeddingService for TextEmbedding {
     async fn embed(&self, text: &str) -> Result<Embedding, DomainError> {
-        let client = gemini::Client::from_env();
-        let model = client.embedding_model(&self.model);
-
-        let embeddings = EmbeddingsBuilder::new(model)
-            .document(text)
-            .map_err(|e| DomainError::external(e.to_string()))?
-            .build()
-            .await
-            .map_err(|e| DomainError::external(e.to_string()))?;
+        let embeddings = retry(&self.retry, "gemini_embed", || async {
+            EmbeddingsBuilder::new(self.model.clone())
+                .document(text)
+                .map_err(|e| provider_error(&e, e.to_string()))?
+                .build()
+                .await
+                .map_err(|e| provider_error(&e, e.to_string()))
+        })
+        .await?;
 
         embeddings
             .into_iter()
@@ -71,20 +85,19 @@ impl EmbeddingService for TextEmbedding {
             return Ok(Vec::new());
         }
 
-        let client = gemini::Client::from_env();
-        let model = client.embedding_model(&self.model);
-
-        let mut builder = EmbeddingsBuilder::new(model);
-        for text in texts {
-            builder = builder
-                .document(*text)
-                .map_err(|e| DomainError::external(e.to_string()))?;
-        }
-
-        let embeddings = builder
-            .build()
-            .await
-            .map_err(|e| DomainError::external(e.to_string()))?;
+        let embeddings = retry(&self.retry, "gemini_embed_batch", || async {
+            let mut builder = EmbeddingsBuilder::new(self.model.clone());
+            for text in texts {
+                builder = builder
+                    .document(*text)
+                    .map_err(|e| provider_error(&e, e.to_string()))?;
+            }
+            builder
+                .build()
+                .await
+                .map_err(|e| provider_error(&e, e.to_string()))
+        })
+        .await?;
 
         Ok(embeddings
             .into_iter()
@@ -0,0 +1,60 @@
+mod pdf;
+mod text;
+
+use std::sync::Arc;
+
+use crate::domain::{ports::ContentExtractor, DomainError, ExtractedPage};
+
+pub use pdf::PdfExtractor;
+pub use text::PlainTextExtractor;
+
+/// Picks a content extractor by MIME type.
+#[derive(Clone)]
+pub struct ExtractorRegistry {
+    extractors: Vec<Arc<dyn ContentExtractor>>,
+}
+
+impl ExtractorRegistry {
+    pub fn new() -> Self {
+        Self {
+            extractors: Vec::new(),
+        }
+    }
+
+    pub fn with_extractor(mut self, extractor: Arc<dyn ContentExtractor>) -> Self {
+        self.extractors.push(extractor);
+        self
+    }
+
+    pub async fn extract(
+        &self,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> Result<Vec<ExtractedPage>, DomainError> {
+        // Ignore parameters such as "; charset=utf-8".
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        let extractor = self
+            .extractors
+            .iter()
+            .find(|e| e.supports(&mime))
+            .ok_or_else(|| {
+                DomainError::validation(format!("unsupported content type: {content_type}"))
+            })?;
+
+        extractor.extract(bytes).await
+    }
+}
+
+impl Default for ExtractorRegistry {
+    fn default() -> Self {
+        Self::new()
+            .with_extractor(Arc::new(PlainTextExtractor))
+            .with_extractor(Arc::new(PdfExtractor))
+    }
+}
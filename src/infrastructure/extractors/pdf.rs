@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use crate::domain::{ports::ContentExtractor, DomainError, ExtractedPage};
+
+/// Extracts text from PDFs page by page, numbering pages from 1.
+pub struct PdfExtractor;
+
+#[async_trait]
+impl ContentExtractor for PdfExtractor {
+    fn supports(&self, content_type: &str) -> bool {
+        content_type == "application/pdf"
+    }
+
+    async fn extract(&self, bytes: &[u8]) -> Result<Vec<ExtractedPage>, DomainError> {
+        let bytes = bytes.to_vec();
+        // PDF parsing is CPU-bound; keep it off the async executor.
+        let pages = tokio::task::spawn_blocking(move || {
+            pdf_extract::extract_text_from_mem_by_pages(&bytes)
+        })
+        .await
+        .map_err(|e| DomainError::internal(e.to_string()))?
+        .map_err(|e| DomainError::validation(format!("failed to parse PDF: {e}")))?;
+
+        Ok(pages
+            .into_iter()
+            .enumerate()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(i, text)| ExtractedPage::new(Some(i + 1), text))
+            .collect())
+    }
+}
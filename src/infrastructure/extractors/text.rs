@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::domain::{ports::ContentExtractor, DomainError, ExtractedPage};
+
+/// Passes UTF-8 text formats through unchanged, as a single unpaged entry.
+pub struct PlainTextExtractor;
+
+#[async_trait]
+impl ContentExtractor for PlainTextExtractor {
+    fn supports(&self, content_type: &str) -> bool {
+        content_type.starts_with("text/") || content_type == "application/json"
+    }
+
+    async fn extract(&self, bytes: &[u8]) -> Result<Vec<ExtractedPage>, DomainError> {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|e| DomainError::validation(format!("content is not UTF-8: {e}")))?;
+        Ok(vec![ExtractedPage::new(None, text)])
+    }
+}
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A product term the model should explain and name consistently.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    pub definition: String,
+    /// Wording answers should use for the term, e.g. an approved translation.
+    #[serde(default)]
+    pub preferred: Option<String>,
+    /// Other spellings that should also match the entry.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl GlossaryEntry {
+    /// Whether the term or an alias appears in `text` as whole words, ignoring case.
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        std::iter::once(&self.term)
+            .chain(&self.aliases)
+            .any(|term| contains_phrase(&text, &term.to_lowercase()))
+    }
+
+    fn line(&self) -> String {
+        match &self.preferred {
+            Some(preferred) => format!(
+                "- {}: {} Always write it as \"{preferred}\".",
+                self.term, self.definition
+            ),
+            None => format!("- {}: {}", self.term, self.definition),
+        }
+    }
+}
+
+/// Glossary entries for every agent plus per-agent additions; an agent's
+/// entry replaces a shared one with the same term.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GlossaryConfig {
+    #[serde(default)]
+    pub default: Vec<GlossaryEntry>,
+    #[serde(default)]
+    pub agents: HashMap<String, Vec<GlossaryEntry>>,
+}
+
+impl GlossaryConfig {
+    pub fn for_agent(&self, agent_id: Option<&str>) -> Vec<&GlossaryEntry> {
+        let agent = agent_id
+            .and_then(|id| self.agents.get(id))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let shared = self.default.iter().filter(|entry| {
+            !agent
+                .iter()
+                .any(|a| a.term.eq_ignore_ascii_case(&entry.term))
+        });
+        agent.iter().chain(shared).collect()
+    }
+
+    /// Prompt lines defining the glossary terms that appear in `message`.
+    pub fn instructions(&self, message: &str, agent_id: Option<&str>) -> Option<String> {
+        let lines: Vec<String> = self
+            .for_agent(agent_id)
+            .into_iter()
+            .filter(|entry| entry.matches(message))
+            .map(GlossaryEntry::line)
+            .collect();
+
+        (!lines.is_empty()).then(|| {
+            format!(
+                "Use these product terms exactly as defined:\n{}",
+                lines.join("\n")
+            )
+        })
+    }
+}
+
+/// `phrase` occurs in `text` with no letters or digits directly around it.
+pub(crate) fn contains_phrase(text: &str, phrase: &str) -> bool {
+    if phrase.is_empty() {
+        return false;
+    }
+    text.match_indices(phrase).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(term: &str, definition: &str) -> GlossaryEntry {
+        GlossaryEntry {
+            term: term.to_string(),
+            definition: definition.to_string(),
+            preferred: None,
+            aliases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_injects_matching_terms_per_agent() {
+        let config = GlossaryConfig {
+            default: vec![
+                GlossaryEntry {
+                    aliases: vec!["work space".to_string()],
+                    preferred: Some("Workspace".to_string()),
+                    ..entry("workspace", "A team's shared area.")
+                },
+                entry("Seat", "One billable user."),
+            ],
+            agents: HashMap::from([(
+                "sales".to_string(),
+                vec![entry("seat", "A licensed user on any plan.")],
+            )]),
+        };
+
+        assert_eq!(config.instructions("How do seatbelts work?", None), None);
+        assert_eq!(
+            config
+                .instructions("Add a seat to my Work Space", None)
+                .unwrap(),
+            "Use these product terms exactly as defined:\n\
+             - workspace: A team's shared area. Always write it as \"Workspace\".\n\
+             - Seat: One billable user."
+        );
+        assert_eq!(
+            config
+                .instructions("What is a seat?", Some("sales"))
+                .unwrap(),
+            "Use these product terms exactly as defined:\n- seat: A licensed user on any plan."
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+
+use crate::application::TenantUsage;
+use crate::domain::DomainError;
+use crate::infrastructure::{migrate_postgres, JobSummary, QueueJobStatus};
+
+/// Finished-job records in Postgres, kept for reporting after the Redis
+/// status keys and history list have expired.
+#[derive(Clone)]
+pub struct PostgresJobHistory {
+    pool: PgPool,
+}
+
+impl PostgresJobHistory {
+    /// Connects without touching the schema; see [`migrate`](Self::migrate).
+    pub async fn connect(database_url: &str) -> Result<Self, DomainError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect(database_url)
+            .await
+            .map_err(|e| DomainError::external(format!("Postgres connection failed: {e}")))?;
+        Ok(Self { pool })
+    }
+
+    /// Applies pending migrations from `migrations/`, returning the schema
+    /// version.
+    pub async fn migrate(&self) -> Result<i64, DomainError> {
+        migrate_postgres(&self.pool).await
+    }
+
+    /// Inserts `records`, skipping any already written, in one transaction.
+    pub async fn insert(&self, records: &[JobSummary]) -> Result<(), DomainError> {
+        let db_error =
+            |e: sqlx::Error| DomainError::external(format!("job_history insert failed: {e}"));
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for record in records {
+            sqlx::query(
+                "INSERT INTO job_history
+                    (job_id, job_type, status, tenant_id, tokens, duration_ms, error, completed_at,
+                     stored_bytes)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (job_id) DO NOTHING",
+            )
+            .bind(record.job_id)
+            .bind(record.job_type().to_string())
+            .bind(status_name(record.status))
+            .bind(record.tenant_id.clone())
+            .bind(record.tokens.map(|t| t as i64))
+            .bind(record.duration_ms.map(|d| d as i64))
+            .bind(record.error.clone())
+            .bind(record.completed_at.unwrap_or_else(Utc::now))
+            .bind(record.stored_bytes.map(|b| b as i64))
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)
+    }
+
+    /// Tokens, completed chat messages and stored bytes per tenant for jobs
+    /// finished in `[from, to)`.
+    pub async fn usage_by_tenant(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<TenantUsage>, DomainError> {
+        let db_error =
+            |e: sqlx::Error| DomainError::external(format!("job_history query failed: {e}"));
+        let rows = sqlx::query(
+            "SELECT tenant_id,
+                    COALESCE(SUM(tokens), 0)::BIGINT AS tokens,
+                    COUNT(*) FILTER (WHERE job_type = 'chat' AND status = 'completed') AS messages,
+                    COALESCE(SUM(stored_bytes), 0)::BIGINT AS stored_bytes
+             FROM job_history
+             WHERE completed_at >= $1 AND completed_at < $2
+             GROUP BY tenant_id",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(TenantUsage {
+                    tenant_id: row.try_get("tenant_id")?,
+                    tokens: row.try_get::<i64, _>("tokens")?.max(0) as u64,
+                    messages: row.try_get::<i64, _>("messages")?.max(0) as u64,
+                    stored_bytes: row.try_get::<i64, _>("stored_bytes")?.max(0) as u64,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(db_error)
+    }
+}
+
+fn status_name(status: QueueJobStatus) -> &'static str {
+    match status {
+        QueueJobStatus::Pending => "pending",
+        QueueJobStatus::Processing => "processing",
+        QueueJobStatus::Completed => "completed",
+        QueueJobStatus::Failed => "failed",
+        QueueJobStatus::Cancelled => "cancelled",
+    }
+}
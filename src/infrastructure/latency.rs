@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::application::RetrievalTimings;
+
+const STAGE_HISTOGRAM: &str = "chat_stage_duration_seconds";
+
+/// Accumulates retrieval time across every tool call made during one agent run.
+#[derive(Debug, Default)]
+pub struct StageTimer {
+    embedding_us: AtomicU64,
+    retrieval_us: AtomicU64,
+}
+
+impl StageTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_retrieval(&self, timings: &RetrievalTimings) {
+        self.embedding_us
+            .fetch_add(timings.embedding.as_micros() as u64, Ordering::Relaxed);
+        self.retrieval_us
+            .fetch_add(timings.search.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn embedding(&self) -> Duration {
+        Duration::from_micros(self.embedding_us.load(Ordering::Relaxed))
+    }
+
+    pub fn retrieval(&self) -> Duration {
+        Duration::from_micros(self.retrieval_us.load(Ordering::Relaxed))
+    }
+}
+
+/// Per-stage timings of an agent run, excluding queueing and post-processing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AgentTimings {
+    pub embedding: Duration,
+    pub retrieval: Duration,
+    pub llm: Duration,
+}
+
+/// Where the time of a chat job went, in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyBreakdown {
+    pub queue_wait_ms: u64,
+    pub embedding_ms: u64,
+    pub retrieval_ms: u64,
+    pub llm_ms: u64,
+    pub post_processing_ms: u64,
+    pub total_ms: u64,
+}
+
+impl LatencyBreakdown {
+    pub fn new(queue_wait: Duration, agent: AgentTimings, post_processing: Duration) -> Self {
+        let total = queue_wait + agent.embedding + agent.retrieval + agent.llm + post_processing;
+        Self {
+            queue_wait_ms: queue_wait.as_millis() as u64,
+            embedding_ms: agent.embedding.as_millis() as u64,
+            retrieval_ms: agent.retrieval.as_millis() as u64,
+            llm_ms: agent.llm.as_millis() as u64,
+            post_processing_ms: post_processing.as_millis() as u64,
+            total_ms: total.as_millis() as u64,
+        }
+    }
+
+    /// Records every stage into the `chat_stage_duration_seconds` histogram.
+    pub fn record(&self) {
+        let stages = [
+            ("queue_wait", self.queue_wait_ms),
+            ("embedding", self.embedding_ms),
+            ("retrieval", self.retrieval_ms),
+            ("llm", self.llm_ms),
+            ("post_processing", self.post_processing_ms),
+            ("total", self.total_ms),
+        ];
+
+        for (stage, ms) in stages {
+            ::metrics::histogram!(STAGE_HISTOGRAM, "stage" => stage).record(ms as f64 / 1000.0);
+        }
+    }
+}
@@ -3,21 +3,43 @@ use rig::client::{CompletionClient, ProviderClient};
 use rig::completion::Prompt;
 use rig::providers::anthropic;
 
-use crate::domain::{ports::LlmService, DomainError};
+use crate::domain::{
+    ports::{LlmService, TokenUsage},
+    DomainError,
+};
+use crate::infrastructure::config::LlmProvider;
+use crate::infrastructure::llm::generation::GenerationParams;
+use crate::infrastructure::retry::{provider_error, retry, RetryPolicy};
 
 const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
 
 pub struct AnthropicLlm {
     model: String,
+    generation: GenerationParams,
+    retry: RetryPolicy,
 }
 
 impl AnthropicLlm {
     pub fn new(model: impl Into<String>) -> Self {
         Self {
             model: model.into(),
+            generation: GenerationParams::default(),
+            retry: RetryPolicy::default(),
         }
     }
 
+    /// Retries failed completions per `policy` (see `llm.retry`).
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Samples per `params` (see the `llm` generation settings).
+    pub fn with_generation(mut self, params: GenerationParams) -> Self {
+        self.generation = params;
+        self
+    }
+
     pub fn default_model() -> Self {
         Self::new(DEFAULT_MODEL)
     }
@@ -27,11 +49,17 @@ impl AnthropicLlm {
 impl LlmService for AnthropicLlm {
     async fn complete(&self, prompt: &str) -> Result<String, DomainError> {
         let client = anthropic::Client::from_env();
-        let agent = client.agent(&self.model).build();
-        agent
-            .prompt(prompt)
-            .await
-            .map_err(|e| DomainError::external(e.to_string()))
+        let agent = self
+            .generation
+            .apply(LlmProvider::Anthropic, client.agent(&self.model))
+            .build();
+        retry(&self.retry, "anthropic_complete", || async {
+            agent
+                .prompt(prompt)
+                .await
+                .map_err(|e| provider_error(&e, e.to_string()))
+        })
+        .await
     }
 
     async fn complete_with_system(
@@ -39,11 +67,34 @@ impl LlmService for AnthropicLlm {
         system: &str,
         prompt: &str,
     ) -> Result<String, DomainError> {
-        let client = anthropic::Client::from_env();
-        let agent = client.agent(&self.model).preamble(system).build();
-        agent
-            .prompt(prompt)
+        self.complete_with_usage(system, prompt)
             .await
-            .map_err(|e| DomainError::external(e.to_string()))
+            .map(|(text, _)| text)
+    }
+
+    async fn complete_with_usage(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<(String, TokenUsage), DomainError> {
+        let client = anthropic::Client::from_env();
+        let agent = self
+            .generation
+            .apply(LlmProvider::Anthropic, client.agent(&self.model))
+            .preamble(system)
+            .build();
+        retry(&self.retry, "anthropic_complete", || async {
+            let response = agent
+                .prompt(prompt)
+                .extended_details()
+                .await
+                .map_err(|e| provider_error(&e, e.to_string()))?;
+            let usage = response.total_usage;
+            Ok((
+                response.output,
+                TokenUsage::new(usage.input_tokens, usage.output_tokens),
+            ))
+        })
+        .await
     }
 }
@@ -0,0 +1,598 @@
+//! AWS Bedrock through the Converse API, which serves Claude, Titan and the
+//! other Bedrock text models with one request shape.
+//!
+//! Requests are signed with SigV4 using the standard AWS credential chain
+//! (environment, profile, SSO, instance or task role), so no provider API
+//! key leaves AWS.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use aws_config::{BehaviorVersion, Region};
+use aws_credential_types::provider::{ProvideCredentials, SharedCredentialsProvider};
+use aws_credential_types::Credentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use rig::completion::{
+    AssistantContent, CompletionError, CompletionModel, CompletionRequest, CompletionResponse,
+    GetTokenUsage, Usage,
+};
+use rig::message::{DocumentSourceKind, Message, ToolResultContent, UserContent};
+use rig::streaming::{RawStreamingChoice, RawStreamingToolCall, StreamingCompletionResponse};
+use rig::OneOrMany;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, OnceCell};
+
+/// Signing name of the Bedrock runtime API.
+const SERVICE: &str = "bedrock";
+/// Credentials expiring sooner than this are refreshed before signing.
+const CREDENTIALS_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+/// Bedrock runtime endpoint for one region, with credentials loaded on
+/// first use.
+#[derive(Clone)]
+pub struct BedrockClient {
+    http: reqwest::Client,
+    region: Option<String>,
+    base_url: Option<String>,
+    aws: Arc<OnceCell<AwsContext>>,
+}
+
+struct AwsContext {
+    region: String,
+    provider: SharedCredentialsProvider,
+    credentials: Mutex<Option<Credentials>>,
+}
+
+impl BedrockClient {
+    /// `region` falls back to the AWS chain (`AWS_REGION`, profile);
+    /// `base_url` replaces `https://bedrock-runtime.{region}.amazonaws.com`,
+    /// e.g. with a VPC endpoint.
+    pub fn new(region: Option<String>, base_url: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            region,
+            base_url,
+            aws: Arc::new(OnceCell::new()),
+        }
+    }
+
+    pub fn completion_model(&self, model: &str) -> BedrockCompletionModel {
+        BedrockCompletionModel::make(self, model)
+    }
+
+    async fn aws(&self) -> Result<&AwsContext, CompletionError> {
+        self.aws
+            .get_or_try_init(|| async {
+                let mut loader = aws_config::defaults(BehaviorVersion::latest());
+                if let Some(region) = &self.region {
+                    loader = loader.region(Region::new(region.clone()));
+                }
+                let shared = loader.load().await;
+                let region = shared
+                    .region()
+                    .map(|r| r.as_ref().to_string())
+                    .ok_or_else(|| {
+                        CompletionError::ProviderError(
+                            "Bedrock needs llm.bedrock.region or AWS_REGION".to_string(),
+                        )
+                    })?;
+                let provider = shared.credentials_provider().ok_or_else(|| {
+                    CompletionError::ProviderError("No AWS credentials found".to_string())
+                })?;
+                Ok(AwsContext {
+                    region,
+                    provider,
+                    credentials: Mutex::new(None),
+                })
+            })
+            .await
+    }
+
+    async fn converse(
+        &self,
+        model: &str,
+        request: &ConverseRequest,
+    ) -> Result<ConverseResponse, CompletionError> {
+        let aws = self.aws().await?;
+        let endpoint = match &self.base_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("https://bedrock-runtime.{}.amazonaws.com", aws.region),
+        };
+        let url = format!("{endpoint}/model/{}/converse", encode_path_segment(model));
+        let body = serde_json::to_vec(request)?;
+
+        let identity = aws.credentials().await?.into();
+        let params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&aws.region)
+            .name(SERVICE)
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .map_err(|e| CompletionError::ProviderError(format!("SigV4 signing failed: {e}")))?
+            .into();
+        let headers = [("content-type", "application/json")];
+        let signable = SignableRequest::new(
+            "POST",
+            url.as_str(),
+            headers.into_iter(),
+            SignableBody::Bytes(&body),
+        )
+        .and_then(|signable| sign(signable, &params))
+        .map_err(|e| CompletionError::ProviderError(format!("SigV4 signing failed: {e}")))?;
+        let (instructions, _) = signable.into_parts();
+
+        let mut http_request = self.http.post(&url);
+        for (name, value) in headers.into_iter().chain(instructions.headers()) {
+            http_request = http_request.header(name, value);
+        }
+        let response =
+            http_request.body(body).send().await.map_err(|e| {
+                CompletionError::ProviderError(format!("Bedrock request failed: {e}"))
+            })?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| CompletionError::ProviderError(format!("Bedrock request failed: {e}")))?;
+        if !status.is_success() {
+            return Err(CompletionError::ProviderError(format!(
+                "Bedrock returned {status}: {text}"
+            )));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+impl AwsContext {
+    async fn credentials(&self) -> Result<Credentials, CompletionError> {
+        let mut cached = self.credentials.lock().await;
+        let fresh_until = SystemTime::now() + CREDENTIALS_REFRESH_MARGIN;
+        if let Some(credentials) = cached
+            .as_ref()
+            .filter(|c| c.expiry().map_or(true, |expiry| expiry > fresh_until))
+        {
+            return Ok(credentials.clone());
+        }
+        let credentials =
+            self.provider.provide_credentials().await.map_err(|e| {
+                CompletionError::ProviderError(format!("AWS credentials failed: {e}"))
+            })?;
+        *cached = Some(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+/// A Bedrock model id or inference profile ARN, for rig agents.
+#[derive(Clone)]
+pub struct BedrockCompletionModel {
+    client: BedrockClient,
+    model: String,
+}
+
+impl CompletionModel for BedrockCompletionModel {
+    type Response = ConverseResponse;
+    type StreamingResponse = ConverseResponse;
+    type Client = BedrockClient;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self {
+            client: client.clone(),
+            model: model.into(),
+        }
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<ConverseResponse>, CompletionError> {
+        let request = ConverseRequest::try_from(request)?;
+        let response = self.client.converse(&self.model, &request).await?;
+        let content = response
+            .output
+            .message
+            .iter()
+            .flat_map(|message| &message.content)
+            .filter_map(ContentBlock::assistant_content);
+        let choice = OneOrMany::many(content).map_err(|_| {
+            CompletionError::ResponseError("Bedrock returned no content".to_string())
+        })?;
+        Ok(CompletionResponse {
+            choice,
+            usage: response.token_usage().unwrap_or_default(),
+            raw_response: response,
+        })
+    }
+
+    /// Converse answers arrive whole: the stream yields the complete text and
+    /// tool calls at once.
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<ConverseResponse>, CompletionError> {
+        let response = self.completion(request).await?;
+        let mut items: Vec<Result<RawStreamingChoice<ConverseResponse>, CompletionError>> =
+            Vec::new();
+        for content in response.choice {
+            match content {
+                AssistantContent::Text(text) => {
+                    items.push(Ok(RawStreamingChoice::Message(text.text)))
+                }
+                AssistantContent::ToolCall(call) => items.push(Ok(RawStreamingChoice::ToolCall(
+                    RawStreamingToolCall::new(call.id, call.function.name, call.function.arguments),
+                ))),
+                _ => {}
+            }
+        }
+        items.push(Ok(RawStreamingChoice::FinalResponse(response.raw_response)));
+        Ok(StreamingCompletionResponse::stream(Box::pin(
+            futures::stream::iter(items),
+        )))
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseRequest {
+    messages: Vec<ConverseMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<TextBlock>,
+    inference_config: InferenceConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    additional_model_request_fields: Option<serde_json::Value>,
+}
+
+/// Also read from an `inferenceConfig` object in the request's additional
+/// params, which is how `top_p` and `stop_sequences` arrive.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InferenceConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolConfig {
+    tools: Vec<ToolEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolEntry {
+    tool_spec: ToolSpec,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolSpec {
+    name: String,
+    description: String,
+    input_schema: InputSchema,
+}
+
+#[derive(Debug, Serialize)]
+struct InputSchema {
+    json: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct TextBlock {
+    text: String,
+}
+
+impl TryFrom<CompletionRequest> for ConverseRequest {
+    type Error = CompletionError;
+
+    fn try_from(request: CompletionRequest) -> Result<Self, CompletionError> {
+        let documents = request.normalized_documents();
+        let messages = converse_messages(documents.into_iter().chain(request.chat_history))?;
+        let tool_config = (!request.tools.is_empty()).then(|| ToolConfig {
+            tools: request
+                .tools
+                .into_iter()
+                .map(|tool| ToolEntry {
+                    tool_spec: ToolSpec {
+                        name: tool.name,
+                        description: tool.description,
+                        input_schema: InputSchema {
+                            json: tool.parameters,
+                        },
+                    },
+                })
+                .collect(),
+        });
+        let mut additional = request.additional_params;
+        let inference = additional
+            .as_mut()
+            .and_then(|params| params.as_object_mut())
+            .and_then(|params| params.remove("inferenceConfig"));
+        let inference_config = InferenceConfig {
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            ..inference
+                .map(serde_json::from_value::<InferenceConfig>)
+                .transpose()?
+                .unwrap_or_default()
+        };
+        Ok(Self {
+            messages,
+            system: request
+                .preamble
+                .map(|text| vec![TextBlock { text }])
+                .unwrap_or_default(),
+            inference_config,
+            tool_config,
+            additional_model_request_fields: additional
+                .filter(|params| params.as_object().map_or(true, |o| !o.is_empty())),
+        })
+    }
+}
+
+/// Converts rig messages, merging consecutive ones from the same role since
+/// Converse requires the roles to alternate.
+fn converse_messages(
+    messages: impl IntoIterator<Item = Message>,
+) -> Result<Vec<ConverseMessage>, CompletionError> {
+    let mut converted: Vec<ConverseMessage> = Vec::new();
+    for message in messages {
+        let (role, content) = match message {
+            Message::User { content } => (
+                "user",
+                content
+                    .into_iter()
+                    .map(ContentBlock::from_user)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Message::Assistant { content, .. } => (
+                "assistant",
+                content
+                    .into_iter()
+                    .filter_map(ContentBlock::from_assistant)
+                    .collect(),
+            ),
+        };
+        if content.is_empty() {
+            continue;
+        }
+        match converted.last_mut() {
+            Some(last) if last.role == role => last.content.extend(content),
+            _ => converted.push(ConverseMessage {
+                role: role.to_string(),
+                content,
+            }),
+        }
+    }
+    Ok(converted)
+}
+
+/// Percent-encodes a model id or ARN for use as one URL path segment.
+fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Body of a Converse response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConverseResponse {
+    output: ConverseOutput,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<ConverseUsage>,
+}
+
+impl GetTokenUsage for ConverseResponse {
+    fn token_usage(&self) -> Option<Usage> {
+        self.usage.as_ref().map(|reported| {
+            let mut usage = Usage::new();
+            usage.input_tokens = reported.input_tokens;
+            usage.output_tokens = reported.output_tokens;
+            usage.total_tokens = reported.total_tokens;
+            usage
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConverseOutput {
+    #[serde(default)]
+    message: Option<ConverseMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConverseMessage {
+    role: String,
+    content: Vec<ContentBlock>,
+}
+
+/// One content block; Converse names the block's kind by its only key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContentBlock {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_use: Option<ToolUseBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_result: Option<ToolResultBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolUseBlock {
+    tool_use_id: String,
+    name: String,
+    input: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolResultBlock {
+    tool_use_id: String,
+    content: Vec<TextBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+    total_tokens: u64,
+}
+
+impl ContentBlock {
+    fn text(text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..Self::default()
+        }
+    }
+
+    fn from_user(content: UserContent) -> Result<Self, CompletionError> {
+        match content {
+            UserContent::Text(text) => Ok(Self::text(text.text)),
+            UserContent::ToolResult(result) => Ok(Self {
+                tool_result: Some(ToolResultBlock {
+                    tool_use_id: result.id,
+                    content: result
+                        .content
+                        .into_iter()
+                        .filter_map(|content| match content {
+                            ToolResultContent::Text(text) => Some(TextBlock { text: text.text }),
+                            ToolResultContent::Image(_) => None,
+                        })
+                        .collect(),
+                }),
+                ..Self::default()
+            }),
+            UserContent::Document(document) => match document.data {
+                DocumentSourceKind::String(text) => Ok(Self::text(text)),
+                _ => Err(unsupported("non-text documents")),
+            },
+            UserContent::Image(_) | UserContent::Audio(_) | UserContent::Video(_) => {
+                Err(unsupported("media content"))
+            }
+        }
+    }
+
+    fn from_assistant(content: AssistantContent) -> Option<Self> {
+        match content {
+            AssistantContent::Text(text) => Some(Self::text(text.text)),
+            AssistantContent::ToolCall(call) => Some(Self {
+                tool_use: Some(ToolUseBlock {
+                    tool_use_id: call.id,
+                    name: call.function.name,
+                    input: call.function.arguments,
+                }),
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    fn assistant_content(&self) -> Option<AssistantContent> {
+        if let Some(tool_use) = &self.tool_use {
+            return Some(AssistantContent::tool_call(
+                &tool_use.tool_use_id,
+                &tool_use.name,
+                tool_use.input.clone(),
+            ));
+        }
+        self.text.as_deref().map(AssistantContent::text)
+    }
+}
+
+fn unsupported(what: &str) -> CompletionError {
+    CompletionError::ProviderError(format!("Bedrock Converse does not accept {what} here"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converse_request_merges_roles_and_declares_tools() {
+        let request = CompletionRequest {
+            preamble: Some("Be brief.".to_string()),
+            chat_history: OneOrMany::many(vec![
+                Message::user("Hi"),
+                Message::user("What is SSO?"),
+                Message::Assistant {
+                    id: None,
+                    content: OneOrMany::one(AssistantContent::tool_call(
+                        "t1",
+                        "knowledge_base",
+                        serde_json::json!({"query": "SSO"}),
+                    )),
+                },
+                Message::tool_result("t1", "Single sign-on."),
+            ])
+            .unwrap(),
+            documents: Vec::new(),
+            tools: vec![rig::completion::ToolDefinition {
+                name: "knowledge_base".to_string(),
+                description: "Search".to_string(),
+                parameters: serde_json::json!({"type": "object"}),
+            }],
+            temperature: None,
+            max_tokens: Some(512),
+            tool_choice: None,
+            additional_params: Some(serde_json::json!({
+                "inferenceConfig": {"topP": 0.9, "stopSequences": ["###"]}
+            })),
+        };
+
+        let body = serde_json::to_value(ConverseRequest::try_from(request).unwrap()).unwrap();
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([
+                {"role": "user", "content": [{"text": "Hi"}, {"text": "What is SSO?"}]},
+                {"role": "assistant", "content": [
+                    {"toolUse": {"toolUseId": "t1", "name": "knowledge_base", "input": {"query": "SSO"}}}
+                ]},
+                {"role": "user", "content": [
+                    {"toolResult": {"toolUseId": "t1", "content": [{"text": "Single sign-on."}]}}
+                ]}
+            ])
+        );
+        assert_eq!(body["system"], serde_json::json!([{"text": "Be brief."}]));
+        assert_eq!(
+            body["inferenceConfig"],
+            serde_json::json!({"maxTokens": 512, "topP": 0.9, "stopSequences": ["###"]})
+        );
+        assert!(body.get("additionalModelRequestFields").is_none());
+        assert_eq!(
+            body["toolConfig"]["tools"][0]["toolSpec"]["name"],
+            "knowledge_base"
+        );
+    }
+
+    #[test]
+    fn test_model_ids_are_encoded_as_one_path_segment() {
+        assert_eq!(
+            encode_path_segment("anthropic.claude-3-5-sonnet-20240620-v1:0"),
+            "anthropic.claude-3-5-sonnet-20240620-v1%3A0"
+        );
+        assert_eq!(
+            encode_path_segment("arn:aws:bedrock:us-east-1:1:inference-profile/x"),
+            "arn%3Aaws%3Abedrock%3Aus-east-1%3A1%3Ainference-profile%2Fx"
+        );
+    }
+}
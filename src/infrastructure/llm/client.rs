@@ -0,0 +1,330 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use rig::agent::{Agent, AgentBuilder, MultiTurnStreamItem};
+use rig::client::{CompletionClient, Nothing};
+use rig::completion::{CompletionModel, GetTokenUsage, Prompt, Usage};
+use rig::providers::{anthropic, gemini, ollama, openai};
+use rig::streaming::{StreamedAssistantContent, StreamingPrompt};
+use rig::tool::ToolDyn;
+use rig::wasm_compat::WasmCompatSend;
+
+use crate::domain::{
+    ports::{LlmService, TokenUsage},
+    DomainError,
+};
+use crate::infrastructure::config::{LlmConfig, LlmProvider};
+use crate::infrastructure::llm::bedrock::{BedrockClient, BedrockCompletionModel};
+use crate::infrastructure::llm::generation::GenerationParams;
+use crate::infrastructure::retry::{provider_error, retry, RetryPolicy};
+
+type AgentOf<C> = Agent<<C as CompletionClient>::CompletionModel>;
+
+/// A completion client for the backend `llm.provider` names.
+#[derive(Clone)]
+pub enum LlmClient {
+    Gemini(gemini::Client),
+    Anthropic(anthropic::Client),
+    /// Chat Completions API, which OpenAI-compatible servers implement too.
+    Openai(openai::CompletionsClient),
+    Ollama(ollama::Client),
+    /// Bedrock's Converse API, signed with the AWS credential chain.
+    Bedrock(BedrockClient),
+}
+
+impl LlmClient {
+    /// Builds the client for `config.provider`, failing instead of panicking
+    /// when its API key is missing. `llm.base_url` replaces the provider's
+    /// endpoint, e.g. with an OpenAI-compatible gateway.
+    pub fn from_config(config: &LlmConfig) -> Result<Self, DomainError> {
+        let base_url = config
+            .base_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/').to_string());
+        let client = match config.provider {
+            LlmProvider::Gemini => {
+                let key = required_env(config.provider, "GEMINI_API_KEY")?;
+                let builder = gemini::Client::builder().api_key(key);
+                match base_url {
+                    Some(url) => builder.base_url(url).build(),
+                    None => builder.build(),
+                }
+                .map(Self::Gemini)
+            }
+            LlmProvider::Anthropic => {
+                let key = required_env(config.provider, "ANTHROPIC_API_KEY")?;
+                let builder = anthropic::Client::builder().api_key(key);
+                match base_url {
+                    Some(url) => builder.base_url(url).build(),
+                    None => builder.build(),
+                }
+                .map(Self::Anthropic)
+            }
+            LlmProvider::Openai => {
+                let key = required_env(config.provider, "OPENAI_API_KEY")?;
+                let builder = openai::CompletionsClient::builder().api_key(key);
+                match base_url.or_else(|| std::env::var("OPENAI_BASE_URL").ok()) {
+                    Some(url) => builder.base_url(url).build(),
+                    None => builder.build(),
+                }
+                .map(Self::Openai)
+            }
+            LlmProvider::Ollama => {
+                let url = base_url
+                    .unwrap_or_else(|| config.ollama.base_url.trim_end_matches('/').to_string());
+                ollama::Client::builder()
+                    .api_key(Nothing)
+                    .base_url(url)
+                    .build()
+                    .map(Self::Ollama)
+            }
+            LlmProvider::Bedrock => Ok(Self::Bedrock(BedrockClient::new(
+                config.bedrock.region.clone(),
+                base_url,
+            ))),
+        };
+        client.map_err(|e| {
+            DomainError::validation(format!("llm.provider {}: {e}", config.provider.as_str()))
+        })
+    }
+
+    pub fn provider(&self) -> LlmProvider {
+        match self {
+            Self::Gemini(_) => LlmProvider::Gemini,
+            Self::Anthropic(_) => LlmProvider::Anthropic,
+            Self::Openai(_) => LlmProvider::Openai,
+            Self::Ollama(_) => LlmProvider::Ollama,
+            Self::Bedrock(_) => LlmProvider::Bedrock,
+        }
+    }
+
+    /// An agent on `model` with `preamble` (none when empty), sampling per
+    /// `params`, and `tools`.
+    pub fn agent(
+        &self,
+        model: &str,
+        preamble: &str,
+        params: &GenerationParams,
+        tools: Vec<Box<dyn ToolDyn>>,
+    ) -> LlmAgent {
+        let provider = self.provider();
+        match self {
+            Self::Gemini(client) => LlmAgent::Gemini(configure(
+                params.apply(provider, client.agent(model)),
+                preamble,
+                tools,
+            )),
+            Self::Anthropic(client) => LlmAgent::Anthropic(configure(
+                params.apply(provider, client.agent(model)),
+                preamble,
+                tools,
+            )),
+            Self::Openai(client) => LlmAgent::Openai(configure(
+                params.apply(provider, client.agent(model)),
+                preamble,
+                tools,
+            )),
+            Self::Ollama(client) => LlmAgent::Ollama(configure(
+                params.apply(provider, client.agent(model)),
+                preamble,
+                tools,
+            )),
+            Self::Bedrock(client) => LlmAgent::Bedrock(configure(
+                params.apply(provider, AgentBuilder::new(client.completion_model(model))),
+                preamble,
+                tools,
+            )),
+        }
+    }
+}
+
+fn required_env(provider: LlmProvider, var: &str) -> Result<String, DomainError> {
+    std::env::var(var).map_err(|_| {
+        DomainError::validation(format!("llm.provider {} needs {var}", provider.as_str()))
+    })
+}
+
+fn configure<M: CompletionModel>(
+    mut builder: AgentBuilder<M>,
+    preamble: &str,
+    tools: Vec<Box<dyn ToolDyn>>,
+) -> Agent<M> {
+    if !preamble.is_empty() {
+        builder = builder.preamble(preamble);
+    }
+    if tools.is_empty() {
+        builder.build()
+    } else {
+        builder.tools(tools).build()
+    }
+}
+
+/// An agent built by [`LlmClient::agent`], whichever provider serves it.
+pub enum LlmAgent {
+    Gemini(AgentOf<gemini::Client>),
+    Anthropic(AgentOf<anthropic::Client>),
+    Openai(AgentOf<openai::CompletionsClient>),
+    Ollama(AgentOf<ollama::Client>),
+    Bedrock(Agent<BedrockCompletionModel>),
+}
+
+impl LlmAgent {
+    /// Answers `prompt`, allowing `max_turns` tool-calling rounds beyond the
+    /// first, with the tokens all rounds used.
+    pub async fn prompt(
+        &self,
+        prompt: &str,
+        max_turns: usize,
+    ) -> Result<(String, TokenUsage), DomainError> {
+        match self {
+            Self::Gemini(agent) => prompt_agent(agent, prompt, max_turns).await,
+            Self::Anthropic(agent) => prompt_agent(agent, prompt, max_turns).await,
+            Self::Openai(agent) => prompt_agent(agent, prompt, max_turns).await,
+            Self::Ollama(agent) => prompt_agent(agent, prompt, max_turns).await,
+            Self::Bedrock(agent) => prompt_agent(agent, prompt, max_turns).await,
+        }
+    }
+
+    /// Like [`prompt`](Self::prompt), passing answer text to `on_text` as it
+    /// streams in.
+    pub async fn stream(
+        &self,
+        prompt: &str,
+        max_turns: usize,
+        on_text: impl FnMut(&str),
+    ) -> Result<(String, TokenUsage), DomainError> {
+        match self {
+            Self::Gemini(agent) => stream_agent(agent, prompt, max_turns, on_text).await,
+            Self::Anthropic(agent) => stream_agent(agent, prompt, max_turns, on_text).await,
+            Self::Openai(agent) => stream_agent(agent, prompt, max_turns, on_text).await,
+            Self::Ollama(agent) => stream_agent(agent, prompt, max_turns, on_text).await,
+            Self::Bedrock(agent) => stream_agent(agent, prompt, max_turns, on_text).await,
+        }
+    }
+}
+
+async fn prompt_agent<M: CompletionModel>(
+    agent: &Agent<M>,
+    prompt: &str,
+    max_turns: usize,
+) -> Result<(String, TokenUsage), DomainError> {
+    agent
+        .prompt(prompt)
+        .multi_turn(max_turns)
+        .extended_details()
+        .await
+        .map(|response| (response.output, token_usage(response.total_usage)))
+        .map_err(|e| provider_error(&e, format!("Agent failed: {e}")))
+}
+
+async fn stream_agent<M>(
+    agent: &Agent<M>,
+    prompt: &str,
+    max_turns: usize,
+    mut on_text: impl FnMut(&str),
+) -> Result<(String, TokenUsage), DomainError>
+where
+    M: CompletionModel + 'static,
+    M::StreamingResponse: GetTokenUsage + WasmCompatSend,
+{
+    let mut stream = agent.stream_prompt(prompt).multi_turn(max_turns).await;
+    let mut streamed = String::new();
+    let mut response = None;
+    while let Some(item) = stream.next().await {
+        match item.map_err(|e| provider_error(&e, format!("Agent failed: {e}")))? {
+            MultiTurnStreamItem::StreamAssistantItem(StreamedAssistantContent::Text(text)) => {
+                on_text(&text.text);
+                streamed.push_str(&text.text);
+            }
+            MultiTurnStreamItem::FinalResponse(end) => {
+                response = Some((end.response().to_string(), token_usage(end.usage())))
+            }
+            _ => {}
+        }
+    }
+    Ok(response.unwrap_or((streamed, TokenUsage::default())))
+}
+
+fn token_usage(usage: Usage) -> TokenUsage {
+    TokenUsage::new(usage.input_tokens, usage.output_tokens)
+}
+
+/// [`LlmService`] on the configured provider, for reranking, query
+/// transforms and the other single-shot completions.
+pub struct ProviderLlm {
+    client: LlmClient,
+    model: String,
+    generation: GenerationParams,
+    retry: RetryPolicy,
+}
+
+impl ProviderLlm {
+    pub fn new(client: LlmClient, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            generation: GenerationParams {
+                max_tokens: Some(4096),
+                ..Default::default()
+            },
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.generation.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_generation(mut self, params: GenerationParams) -> Self {
+        self.generation = params;
+        self
+    }
+
+    /// Retries failed completions per `policy` (see `llm.retry`).
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+}
+
+#[async_trait]
+impl LlmService for ProviderLlm {
+    async fn complete(&self, prompt: &str) -> Result<String, DomainError> {
+        self.complete_with_system("", prompt).await
+    }
+
+    async fn complete_with_system(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<String, DomainError> {
+        self.complete_with_usage(system, prompt)
+            .await
+            .map(|(text, _)| text)
+    }
+
+    async fn complete_with_usage(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<(String, TokenUsage), DomainError> {
+        let agent = self
+            .client
+            .agent(&self.model, system, &self.generation, Vec::new());
+        retry(&self.retry, "llm_complete", || agent.prompt(prompt, 0)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::config::Config;
+
+    #[test]
+    fn test_builds_the_configured_provider() {
+        let mut config = Config::default().llm;
+        config.provider = LlmProvider::Ollama;
+        let client = LlmClient::from_config(&config).unwrap();
+        assert_eq!(client.provider(), LlmProvider::Ollama);
+    }
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use rig::client::{CompletionClient, ProviderClient};
+use rig::completion::Prompt;
+use rig::providers::gemini;
+
+use crate::domain::{ports::LlmService, DomainError};
+use crate::infrastructure::retry::{provider_error, retry, RetryPolicy};
+
+pub struct GeminiLlm {
+    model: String,
+    retry: RetryPolicy,
+}
+
+impl GeminiLlm {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Retries failed completions per `policy` (see `llm.retry`).
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+}
+
+#[async_trait]
+impl LlmService for GeminiLlm {
+    async fn complete(&self, prompt: &str) -> Result<String, DomainError> {
+        let client = gemini::Client::from_env();
+        let agent = client.agent(&self.model).build();
+        retry(&self.retry, "gemini_complete", || async {
+            agent
+                .prompt(prompt)
+                .await
+                .map_err(|e| provider_error(&e, e.to_string()))
+        })
+        .await
+    }
+
+    async fn complete_with_system(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<String, DomainError> {
+        let client = gemini::Client::from_env();
+        let agent = client.agent(&self.model).preamble(system).build();
+        retry(&self.retry, "gemini_complete", || async {
+            agent
+                .prompt(prompt)
+                .await
+                .map_err(|e| provider_error(&e, e.to_string()))
+        })
+        .await
+    }
+}
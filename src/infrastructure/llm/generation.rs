@@ -0,0 +1,176 @@
+use rig::agent::AgentBuilder;
+use rig::completion::CompletionModel;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::domain::DomainError;
+use crate::infrastructure::config::LlmProvider;
+
+/// Sampling settings for a completion. Unset fields keep the provider's
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Text that ends the answer when the model produces it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    /// Upper bound on the tokens the model generates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+}
+
+impl GenerationParams {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These settings with the fields `overrides` sets replaced.
+    pub fn merged(&self, overrides: &GenerationParams) -> Self {
+        Self {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            stop_sequences: if overrides.stop_sequences.is_empty() {
+                self.stop_sequences.clone()
+            } else {
+                overrides.stop_sequences.clone()
+            },
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+        }
+    }
+
+    /// Rejects values no provider accepts.
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if let Some(temperature) = self.temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+            return Err(DomainError::validation(format!(
+                "temperature must be between 0 and 2, got {temperature}"
+            )));
+        }
+        if let Some(top_p) = self.top_p.filter(|p| !(*p > 0.0 && *p <= 1.0)) {
+            return Err(DomainError::validation(format!(
+                "top_p must be in (0, 1], got {top_p}"
+            )));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(DomainError::validation("max_tokens must be positive"));
+        }
+        if self.stop_sequences.iter().any(String::is_empty) {
+            return Err(DomainError::validation("stop_sequences must not be empty"));
+        }
+        Ok(())
+    }
+
+    /// Sets these parameters on `builder`, in the request fields `provider`
+    /// expects.
+    pub(crate) fn apply<M: CompletionModel>(
+        &self,
+        provider: LlmProvider,
+        mut builder: AgentBuilder<M>,
+    ) -> AgentBuilder<M> {
+        if let Some(max_tokens) = self.max_tokens {
+            builder = builder.max_tokens(max_tokens as u64);
+        }
+        if let Some(temperature) = self.temperature {
+            builder = builder.temperature(temperature);
+        }
+        match self.additional_params(provider) {
+            Some(params) => builder.additional_params(params),
+            None => builder,
+        }
+    }
+
+    /// `top_p` and `stop_sequences`, which rig has no request fields for,
+    /// under the names `provider` uses.
+    fn additional_params(&self, provider: LlmProvider) -> Option<Value> {
+        let mut params = Map::new();
+        let stop = (!self.stop_sequences.is_empty()).then(|| json!(self.stop_sequences));
+        let (top_p_key, stop_key) = match provider {
+            LlmProvider::Gemini => ("topP", "stopSequences"),
+            LlmProvider::Anthropic => ("top_p", "stop_sequences"),
+            LlmProvider::Openai | LlmProvider::Ollama => ("top_p", "stop"),
+            LlmProvider::Bedrock => ("topP", "stopSequences"),
+        };
+        if let Some(top_p) = self.top_p {
+            params.insert(top_p_key.to_string(), json!(top_p));
+        }
+        if let Some(stop) = stop {
+            params.insert(stop_key.to_string(), stop);
+        }
+        match provider {
+            // rig only passes temperature and max_tokens on to Gemini
+            // inside a generationConfig, so always send one.
+            LlmProvider::Gemini => Some(json!({ "generationConfig": params })),
+            // Ollama reads max_tokens from its options as num_predict.
+            LlmProvider::Ollama => {
+                if let Some(max_tokens) = self.max_tokens {
+                    params.insert("num_predict".to_string(), json!(max_tokens));
+                }
+                (!params.is_empty()).then_some(Value::Object(params))
+            }
+            LlmProvider::Bedrock => {
+                (!params.is_empty()).then(|| json!({ "inferenceConfig": params }))
+            }
+            LlmProvider::Anthropic | LlmProvider::Openai => {
+                (!params.is_empty()).then_some(Value::Object(params))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_replace_only_what_they_set() {
+        let base = GenerationParams {
+            temperature: Some(0.2),
+            stop_sequences: vec!["END".to_string()],
+            max_tokens: Some(4096),
+            ..Default::default()
+        };
+        let overrides = GenerationParams {
+            temperature: Some(0.9),
+            top_p: Some(0.5),
+            ..Default::default()
+        };
+
+        let merged = base.merged(&overrides);
+        assert_eq!(merged.temperature, Some(0.9));
+        assert_eq!(merged.top_p, Some(0.5));
+        assert_eq!(merged.stop_sequences, vec!["END".to_string()]);
+        assert_eq!(merged.max_tokens, Some(4096));
+        assert!(GenerationParams {
+            top_p: Some(1.5),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_additional_params_use_provider_names() {
+        let params = GenerationParams {
+            top_p: Some(0.9),
+            stop_sequences: vec!["###".to_string()],
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            params.additional_params(LlmProvider::Gemini),
+            Some(json!({ "generationConfig": { "topP": 0.9, "stopSequences": ["###"] } }))
+        );
+        assert_eq!(
+            params.additional_params(LlmProvider::Ollama),
+            Some(json!({ "top_p": 0.9, "stop": ["###"], "num_predict": 256 }))
+        );
+        assert_eq!(
+            GenerationParams::default().additional_params(LlmProvider::Anthropic),
+            None
+        );
+    }
+}
@@ -1,3 +1,31 @@
 mod anthropic;
+mod bedrock;
+mod client;
+mod gemini;
+mod generation;
+
+use std::sync::Arc;
 
 pub use anthropic::AnthropicLlm;
+pub use bedrock::{BedrockClient, BedrockCompletionModel, ConverseResponse};
+pub use client::{LlmAgent, LlmClient, ProviderLlm};
+pub use gemini::GeminiLlm;
+pub use generation::GenerationParams;
+
+use crate::domain::{ports::LlmService, DomainError};
+use crate::infrastructure::config::LlmConfig;
+
+/// Builds an [`LlmService`] for `model` on the backend selected by
+/// `llm.provider`, retrying per `llm.retry` and sampling per the `llm`
+/// generation settings.
+pub fn llm_from_config(
+    config: &LlmConfig,
+    model: &str,
+) -> Result<Arc<dyn LlmService>, DomainError> {
+    let client = LlmClient::from_config(config)?;
+    Ok(Arc::new(
+        ProviderLlm::new(client, model)
+            .with_generation(config.generation())
+            .with_retry(config.retry),
+    ))
+}
@@ -0,0 +1,115 @@
+//! Versioned schema setup for the stores the service owns.
+//!
+//! Postgres migrations live in `migrations/` and are tracked by sqlx in
+//! `_sqlx_migrations`. Vector collections are set up idempotently by their
+//! stores' constructors; the version applied to each collection is recorded
+//! in Redis so a release expecting a newer layout can tell.
+
+use deadpool_redis::redis::AsyncCommands;
+use deadpool_redis::Pool;
+use serde::Deserialize;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPool;
+
+use crate::domain::DomainError;
+use crate::infrastructure::config::{Config, VectorStoreBackend};
+use crate::infrastructure::{keys, QdrantVectorStore, RedisVectorStore};
+
+static POSTGRES: Migrator = sqlx::migrate!();
+
+/// Bumped whenever the collection or payload index setup changes.
+pub const VECTOR_STORE_SCHEMA_VERSION: i64 = 1;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MigrationsConfig {
+    /// Apply pending migrations when the binaries start; otherwise run
+    /// `worker migrate` before rolling out a release.
+    pub on_startup: bool,
+}
+
+impl Default for MigrationsConfig {
+    fn default() -> Self {
+        Self { on_startup: true }
+    }
+}
+
+/// Applies pending Postgres migrations, returning the latest version. Safe
+/// to run from several processes at once: sqlx holds an advisory lock.
+pub async fn migrate_postgres(pool: &PgPool) -> Result<i64, DomainError> {
+    POSTGRES
+        .run(pool)
+        .await
+        .map_err(|e| DomainError::external(format!("Postgres migration failed: {e}")))?;
+    Ok(POSTGRES.iter().map(|m| m.version).max().unwrap_or_default())
+}
+
+/// Creates (or updates the settings and payload indexes of) the ingestion
+/// collection and the knowledge_base tool's collection, then records
+/// [`VECTOR_STORE_SCHEMA_VERSION`] for each.
+pub async fn migrate_vector_store(
+    config: &Config,
+    qdrant_url: &str,
+    redis_pool: &Pool,
+) -> Result<(), DomainError> {
+    let mut collections = vec![config.vector_store.collection.as_str()];
+    let tool_collection = config.tools.knowledge_base.collection(&config.vector_store);
+    if !collections.contains(&tool_collection) {
+        collections.push(tool_collection);
+    }
+
+    let mut conn = redis_pool
+        .get()
+        .await
+        .map_err(|e| DomainError::external(format!("Redis pool error: {e}")))?;
+    for collection in collections {
+        let recorded: Option<i64> = conn
+            .hget(keys::VECTOR_STORE_MIGRATIONS, collection)
+            .await
+            .map_err(|e| DomainError::external(format!("Redis error: {e}")))?;
+        if let Some(version) = recorded.filter(|&v| v > VECTOR_STORE_SCHEMA_VERSION) {
+            return Err(DomainError::validation(format!(
+                "collection {collection} was migrated to version {version} by a newer release"
+            )));
+        }
+
+        let dimension = config.embedding.dimension;
+        match config.vector_store.backend {
+            VectorStoreBackend::Qdrant => {
+                QdrantVectorStore::new(qdrant_url, collection, dimension, &config.vector_store)
+                    .await?;
+            }
+            VectorStoreBackend::Redis => {
+                RedisVectorStore::new(redis_pool.clone(), collection, dimension).await?;
+            }
+        }
+
+        conn.hset::<_, _, _, ()>(
+            keys::VECTOR_STORE_MIGRATIONS,
+            collection,
+            VECTOR_STORE_SCHEMA_VERSION,
+        )
+        .await
+        .map_err(|e| DomainError::external(format!("Redis error: {e}")))?;
+        if recorded != Some(VECTOR_STORE_SCHEMA_VERSION) {
+            tracing::info!(
+                collection,
+                from = recorded,
+                to = VECTOR_STORE_SCHEMA_VERSION,
+                "vector collection migrated"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_migrations_are_embedded_in_order() {
+        let versions: Vec<i64> = POSTGRES.iter().map(|m| m.version).collect();
+        assert_eq!(versions, vec![1, 2]);
+    }
+}
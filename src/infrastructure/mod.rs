@@ -1,17 +1,89 @@
 pub mod agent;
+pub mod answer_format;
+pub mod banner;
+pub mod channels;
+#[cfg(any(test, feature = "chaos"))]
+pub mod chaos;
+pub mod chunking;
+pub mod circuit_breaker;
 pub mod config;
+pub mod connectors;
+pub mod conversation_update;
+pub mod document_store;
 pub mod embedding;
+pub mod extractors;
+pub mod glossary;
+pub mod job_history;
+pub mod latency;
 pub mod llm;
+pub mod migrations;
+pub mod model_registry;
+pub mod model_routing;
+pub mod permalinks;
+pub mod query_log;
 pub mod queue;
+pub mod redis_tracing;
+pub mod response_cache;
+pub mod retrieval;
+pub mod retry;
+pub mod safety;
+pub mod sanitize;
 pub mod tools;
 pub mod vector_store;
 
-pub use agent::ChatAgent;
+pub use agent::{AgentReply, ChatAgent, PartialResponse};
+pub use answer_format::{AnswerFormat, AnswerOptions};
+pub use banner::{Banner, BannerConfig, BannerPosition};
+pub use channels::{
+    ChannelFormatter, ChannelsConfig, SlackFormatter, SmsFormatter, TelegramFormatter,
+};
+pub use chunking::{
+    chunker_from_config, count_tokens, MarkdownChunker, ParagraphChunker,
+    RecursiveCharacterChunker, TokenChunker,
+};
+pub use circuit_breaker::CircuitBreaker;
 pub use config::{AppConfig, Config, PromptsConfig};
-pub use embedding::TextEmbedding;
-pub use llm::AnthropicLlm;
+pub use connectors::{
+    content_type_for_key, object_url, parse_html, Crawl, CrawlLimits, GitChanges, GitConnector,
+    GitFile, HtmlPage, S3Connector, S3Object, WebCrawler, WebPage,
+};
+pub use conversation_update::update_conversation;
+pub use document_store::{document_store_from_config, InMemoryDocumentStore};
+#[cfg(feature = "local-embeddings")]
+pub use embedding::FastEmbedService;
+pub use embedding::{
+    check_store_dimension, embedding_from_config, probe_dimension, BatchedEmbedding,
+    CohereEmbedding, DimensionProbe, OllamaEmbedding, OpenAiEmbedding, TextEmbedding,
+};
+pub use extractors::{ExtractorRegistry, PdfExtractor, PlainTextExtractor};
+pub use glossary::{GlossaryConfig, GlossaryEntry};
+pub use job_history::PostgresJobHistory;
+pub use latency::{AgentTimings, LatencyBreakdown, StageTimer};
+pub use llm::{
+    llm_from_config, AnthropicLlm, BedrockClient, BedrockCompletionModel, GeminiLlm,
+    GenerationParams, LlmAgent, LlmClient, ProviderLlm,
+};
+pub use migrations::{
+    migrate_postgres, migrate_vector_store, MigrationsConfig, VECTOR_STORE_SCHEMA_VERSION,
+};
+pub use model_registry::{ModelRegistryConfig, ModelRelease, ModelRollout};
+pub use model_routing::{ModelRoute, ModelRoutingConfig, ModelTier, ModelTiers};
+pub use permalinks::{append_source_links, source_links, SourceLink};
+pub use query_log::RedisQueryLog;
 pub use queue::{
-    keys, queues, EmbedDocumentJob, IndexDocumentJob, JobResult, ProcessChatJob, QueueJobStatus,
+    keys, queues, Channel, ChatReplay, ChatSnapshot, CrawlSiteJob, EmbedDocumentJob,
+    ExportCollectionJob, ImportCollectionJob, IndexDocumentJob, JobResult, JobSummary,
+    ProcessChatJob, QueueJobStatus, ReembedCollectionJob, S3ObjectVersion, S3SyncJob, StreamUpdate,
+    SyncGitRepoJob, VersionCompatibility, PRODUCER_VERSION,
+};
+pub use redis_tracing::{traced, RedisTracingConfig};
+pub use response_cache::{RedisResponseCache, ResponseCacheConfig, ResponseKey};
+pub use retrieval::{configure_retrieval, vector_store_from_config};
+pub use retry::{provider_error, retry, should_retry, RetryPolicy};
+pub use safety::{QuarantinedDocument, SafetyAction, SafetyConfig};
+pub use sanitize::SanitizeConfig;
+pub use tools::{ContextFormatter, KnowledgeBaseTool, LimitedTool, RetrievedSources};
+pub use vector_store::{
+    GuardedVectorStore, InMemoryVectorStore, QdrantSnapshots, QdrantVectorStore, RedisVectorStore,
+    SnapshotInfo, SwitchableVectorStore, VectorStoreHealth,
 };
-pub use tools::KnowledgeBaseTool;
-pub use vector_store::{InMemoryVectorStore, QdrantVectorStore};
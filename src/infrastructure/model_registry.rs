@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::infrastructure::config::PromptsConfig;
+
+/// One versioned pairing of a provider model and a system prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRelease {
+    pub version: u32,
+    /// Provider model ID, e.g. a fine-tuned model name.
+    pub model: String,
+    /// Key into `agent.versions` in prompts.yaml; unset uses `agent.system`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+}
+
+impl ModelRelease {
+    /// The pinned system prompt, or `None` when its version is missing from
+    /// prompts.yaml.
+    pub fn system_prompt<'a>(&self, prompts: &'a PromptsConfig) -> Option<&'a str> {
+        match &self.prompt_version {
+            Some(version) => prompts.agent.versions.get(version).map(String::as_str),
+            None => Some(&prompts.agent.system),
+        }
+    }
+}
+
+/// Logical model names (e.g. "support-v3") and the releases each can serve,
+/// plus which name every agent answers with. The live release of a name is
+/// set through the admin API; until then the highest version serves.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ModelRegistryConfig {
+    #[serde(default)]
+    pub models: HashMap<String, Vec<ModelRelease>>,
+    /// Logical model for agents without an entry in `agents`.
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Agent ID to logical model name.
+    #[serde(default)]
+    pub agents: HashMap<String, String>,
+}
+
+impl ModelRegistryConfig {
+    /// The logical model the agent answers with; `None` keeps `llm.model`.
+    pub fn model_for_agent(&self, agent_id: Option<&str>) -> Option<&str> {
+        agent_id
+            .and_then(|id| self.agents.get(id))
+            .or(self.default.as_ref())
+            .map(String::as_str)
+    }
+
+    pub fn release(&self, name: &str, version: u32) -> Option<&ModelRelease> {
+        self.models.get(name)?.iter().find(|r| r.version == version)
+    }
+
+    /// The release serving `name`: `active` when it names a known version,
+    /// else the highest one.
+    pub fn resolve(&self, name: &str, active: Option<u32>) -> Option<&ModelRelease> {
+        active
+            .and_then(|version| self.release(name, version))
+            .or_else(|| self.models.get(name)?.iter().max_by_key(|r| r.version))
+    }
+}
+
+/// An admin switch of a logical model's live release, kept for audit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRollout {
+    pub model: String,
+    pub version: u32,
+    /// The version that served before; `None` when none was ever set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_active_release_else_latest() {
+        let registry: ModelRegistryConfig = serde_yaml::from_str(
+            r#"
+models:
+  support-v3:
+    - { version: 1, model: "ft:support:001" }
+    - { version: 2, model: "ft:support:002", prompt_version: "v2" }
+agents:
+  support: support-v3
+"#,
+        )
+        .unwrap();
+
+        let name = registry.model_for_agent(Some("support")).unwrap();
+        assert_eq!(registry.resolve(name, None).unwrap().version, 2);
+        assert_eq!(
+            registry.resolve(name, Some(1)).unwrap().model,
+            "ft:support:001"
+        );
+        assert_eq!(registry.resolve(name, Some(9)).unwrap().version, 2);
+        assert!(registry.model_for_agent(Some("sales")).is_none());
+
+        let mut prompts = PromptsConfig::default();
+        let release = registry.release(name, 2).unwrap();
+        assert_eq!(release.system_prompt(&prompts), None);
+        prompts
+            .agent
+            .versions
+            .insert("v2".to_string(), "Answer as support.".to_string());
+        assert_eq!(release.system_prompt(&prompts), Some("Answer as support."));
+    }
+}
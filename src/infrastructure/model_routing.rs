@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::infrastructure::glossary::contains_phrase;
+
+/// Phrases that mark a question as needing reasoning rather than a lookup.
+const COMPLEX_MARKERS: &[&str] = &[
+    "why",
+    "explain",
+    "compare",
+    "comparison",
+    "difference between",
+    "versus",
+    "vs",
+    "pros and cons",
+    "trade-off",
+    "tradeoff",
+    "step by step",
+    "analyze",
+    "analyse",
+    "design",
+    "troubleshoot",
+    "debug",
+];
+
+/// A cheap model for simple queries and a premium one for the rest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelTiers {
+    /// Model for short, FAQ-like queries.
+    pub fast: String,
+    /// Model for everything else; `llm.model` when unset.
+    #[serde(default)]
+    pub premium: Option<String>,
+    /// Longest query, in words, that can count as simple.
+    #[serde(default = "default_max_simple_words")]
+    pub max_simple_words: usize,
+    /// Top retrieval score a simple query also needs; unset skips the
+    /// retrieval check.
+    #[serde(default)]
+    pub min_retrieval_score: Option<f32>,
+}
+
+fn default_max_simple_words() -> usize {
+    20
+}
+
+/// Model tiers shared by every agent, and per-agent replacements.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ModelRoutingConfig {
+    #[serde(default)]
+    pub default: Option<ModelTiers>,
+    #[serde(default)]
+    pub agents: HashMap<String, ModelTiers>,
+}
+
+impl ModelRoutingConfig {
+    /// The agent's tiers, else the shared ones; `None` disables routing.
+    pub fn for_agent(&self, agent_id: Option<&str>) -> Option<&ModelTiers> {
+        agent_id
+            .and_then(|id| self.agents.get(id))
+            .or(self.default.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelTier {
+    Fast,
+    Premium,
+}
+
+/// The model a query was routed to, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelRoute {
+    pub model: String,
+    pub tier: ModelTier,
+    pub reason: &'static str,
+}
+
+impl ModelTiers {
+    /// Why `message` needs the premium model judging by its text alone, or
+    /// `None` when it reads as a simple question.
+    pub fn complexity(&self, message: &str) -> Option<&'static str> {
+        let lower = message.to_lowercase();
+        if message.split_whitespace().count() > self.max_simple_words {
+            Some("long query")
+        } else if message.matches('?').count() > 1 {
+            Some("several questions")
+        } else if message.contains("```") {
+            Some("contains code")
+        } else if COMPLEX_MARKERS
+            .iter()
+            .any(|marker| contains_phrase(&lower, marker))
+        {
+            Some("asks for reasoning")
+        } else {
+            None
+        }
+    }
+
+    /// Whether routing `message` needs its top retrieval score.
+    pub fn needs_retrieval_check(&self, message: &str) -> bool {
+        self.min_retrieval_score.is_some() && self.complexity(message).is_none()
+    }
+
+    /// Routes `message`; `top_score` is its best retrieval score, if looked up.
+    pub fn route(&self, message: &str, top_score: Option<f32>, llm_model: &str) -> ModelRoute {
+        let premium = |reason| ModelRoute {
+            model: self.premium.as_deref().unwrap_or(llm_model).to_string(),
+            tier: ModelTier::Premium,
+            reason,
+        };
+        if let Some(reason) = self.complexity(message) {
+            return premium(reason);
+        }
+        match self.min_retrieval_score {
+            Some(min) if !top_score.is_some_and(|score| score >= min) => {
+                premium("low retrieval confidence")
+            }
+            _ => ModelRoute {
+                model: self.fast.clone(),
+                tier: ModelTier::Fast,
+                reason: "simple query",
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_simple_queries_to_fast_model() {
+        let tiers = ModelTiers {
+            fast: "flash-lite".to_string(),
+            premium: None,
+            max_simple_words: 8,
+            min_retrieval_score: Some(0.7),
+        };
+
+        let route = tiers.route("How do I reset my password?", Some(0.82), "pro");
+        assert_eq!(
+            (route.model.as_str(), route.tier),
+            ("flash-lite", ModelTier::Fast)
+        );
+
+        let route = tiers.route("How do I reset my password?", Some(0.4), "pro");
+        assert_eq!(route.model, "pro");
+        assert_eq!(route.reason, "low retrieval confidence");
+
+        assert_eq!(
+            tiers
+                .route("Why is sync slower than upload?", Some(0.9), "pro")
+                .reason,
+            "asks for reasoning"
+        );
+        assert!(!tiers.needs_retrieval_check("What is the difference between plans?"));
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::ChunkMetadata;
+
+/// A link to a source the answer drew on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLink {
+    pub title: String,
+    pub url: String,
+}
+
+/// Permalinks for the retrieved sources that have one, deduplicated in retrieval order.
+pub fn source_links(sources: &[ChunkMetadata]) -> Vec<SourceLink> {
+    let mut links: Vec<SourceLink> = Vec::new();
+    for source in sources {
+        let Some(url) = source.permalink() else {
+            continue;
+        };
+        if links.iter().any(|l| l.url == url) {
+            continue;
+        }
+
+        let title = match (&source.source_path, &source.section) {
+            (Some(path), Some(section)) => format!("{path} — {section}"),
+            (Some(path), None) => path.clone(),
+            (None, Some(section)) => section.clone(),
+            (None, None) => source.title.clone().unwrap_or_else(|| url.clone()),
+        };
+        links.push(SourceLink { title, url });
+    }
+    links
+}
+
+/// Appends a Markdown "Sources" list to `response`. Parentheses and spaces
+/// in URLs are percent-encoded so each link parses as one.
+pub fn append_source_links(response: &str, links: &[SourceLink]) -> String {
+    if links.is_empty() {
+        return response.to_string();
+    }
+
+    let list = links
+        .iter()
+        .map(|l| {
+            let url = l
+                .url
+                .replace(' ', "%20")
+                .replace('(', "%28")
+                .replace(')', "%29");
+            format!("- [{}]({url})", l.title)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{}\n\nSources:\n{}", response.trim_end(), list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_links_dedupes_and_appends() {
+        let source = ChunkMetadata {
+            source_url: Some("https://example.com/guide".to_string()),
+            section: Some("Install".to_string()),
+            ..Default::default()
+        };
+        let links = source_links(&[source.clone(), ChunkMetadata::default(), source]);
+
+        assert_eq!(
+            links,
+            vec![SourceLink {
+                title: "Install".to_string(),
+                url: "https://example.com/guide#install".to_string(),
+            }]
+        );
+        assert_eq!(
+            append_source_links("Run the installer.\n", &links),
+            "Run the installer.\n\nSources:\n- [Install](https://example.com/guide#install)"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use deadpool_redis::redis::{self, AsyncCommands};
+use deadpool_redis::{Connection, Pool};
+use std::collections::HashMap;
+
+use crate::application::QueryEmbeddingCache;
+use crate::domain::{ports::QueryLog, DomainError};
+use crate::infrastructure::keys;
+
+/// Longer queries aren't counted; they rarely repeat word for word.
+const MAX_QUERY_CHARS: usize = 500;
+
+/// Query counts in one Redis sorted set per UTC day, kept for `window_days`.
+pub struct RedisQueryLog {
+    pool: Pool,
+    window_days: u32,
+}
+
+impl RedisQueryLog {
+    pub fn new(pool: Pool, window_days: u32) -> Self {
+        Self {
+            pool,
+            window_days: window_days.max(1),
+        }
+    }
+
+    async fn conn(&self) -> Result<Connection, DomainError> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| DomainError::external(format!("Redis pool error: {e}")))
+    }
+
+    fn day_keys(&self) -> Vec<String> {
+        let today = Utc::now().date_naive();
+        (0..self.window_days)
+            .map(|days| keys::query_log(&(today - Duration::days(days.into())).to_string()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl QueryLog for RedisQueryLog {
+    async fn record(&self, query: &str) -> Result<(), DomainError> {
+        let query = QueryEmbeddingCache::key(query);
+        if query.is_empty() || query.chars().count() > MAX_QUERY_CHARS {
+            return Ok(());
+        }
+        let key = keys::query_log(&Utc::now().date_naive().to_string());
+        let ttl = i64::from(self.window_days + 1) * 86_400;
+
+        let mut conn = self.conn().await?;
+        redis::pipe()
+            .zincr(&key, query, 1)
+            .ignore()
+            .expire(&key, ttl)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))
+    }
+
+    async fn frequent(&self, limit: usize) -> Result<Vec<String>, DomainError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn().await?;
+        let mut counts: HashMap<String, f64> = HashMap::new();
+        for key in self.day_keys() {
+            let top: Vec<(String, f64)> = conn
+                .zrevrange_withscores(&key, 0, limit as isize - 1)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+            for (query, count) in top {
+                *counts.entry(query).or_default() += count;
+            }
+        }
+
+        let mut counts: Vec<(String, f64)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(counts
+            .into_iter()
+            .take(limit)
+            .map(|(query, _)| query)
+            .collect())
+    }
+}
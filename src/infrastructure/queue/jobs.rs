@@ -2,10 +2,22 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::domain::{Conversation, ExtractedPage, SearchResult};
+use crate::infrastructure::answer_format::{AnswerFormat, AnswerOptions};
+use crate::infrastructure::llm::GenerationParams;
+
 pub mod queues {
     pub const CHAT_QUEUE: &str = "jobs:chat";
+    /// Chat jobs queued while `jobs:chat` was over its depth limit.
+    pub const CHAT_LOW_QUEUE: &str = "jobs:chat:low";
     pub const EMBED_QUEUE: &str = "jobs:embed";
     pub const INDEX_QUEUE: &str = "jobs:index";
+    pub const REEMBED_QUEUE: &str = "jobs:reembed";
+    pub const GIT_SYNC_QUEUE: &str = "jobs:git_sync";
+    pub const CRAWL_QUEUE: &str = "jobs:crawl";
+    pub const S3_SYNC_QUEUE: &str = "jobs:s3_sync";
+    pub const EXPORT_QUEUE: &str = "jobs:export";
+    pub const IMPORT_QUEUE: &str = "jobs:import";
 }
 
 pub mod keys {
@@ -15,9 +27,167 @@ pub mod keys {
         format!("job:status:{}", job_id)
     }
 
+    /// List of answer chunks streamed by a chat job, in order.
+    pub fn job_stream(job_id: &Uuid) -> String {
+        format!("job:stream:{}", job_id)
+    }
+
+    /// Entry of a [`job_stream`] list marking that the answer restarted (a
+    /// retry or provider fallback); text before it is to be discarded.
+    /// Real chunks are never empty.
+    pub const STREAM_RESET: &str = "";
+
+    pub fn job_cancelled(job_id: &Uuid) -> String {
+        format!("job:cancelled:{}", job_id)
+    }
+
+    /// Capped list of [`super::JobSummary`] entries, newest first.
+    pub const JOB_HISTORY: &str = "job:history";
+
+    /// Name of the vector collection currently serving reads and writes.
+    pub const ACTIVE_COLLECTION: &str = "collection:active";
+    /// `1` while the API rejects writes, `0` once switched back; unset
+    /// defers to `api.read_only`.
+    pub const READ_ONLY: &str = "api:read_only";
+    /// Hash of collection name to the vector store schema version applied.
+    pub const VECTOR_STORE_MIGRATIONS: &str = "migrations:vector_store";
+
+    /// Set once a re-embedded collection has been validated and may be activated.
+    pub fn collection_validated(collection: &str) -> String {
+        format!("collection:validated:{}", collection)
+    }
+
+    /// When a job first reported processing, for its duration in the history.
+    pub fn job_started(job_id: &Uuid) -> String {
+        format!("job:started:{}", job_id)
+    }
+
+    /// Capped list of recent chat job durations in milliseconds, newest first,
+    /// behind the wait estimate returned when a chat job is queued.
+    pub const CHAT_DURATIONS: &str = "stats:chat:durations";
+
+    /// What a finished chat job saw and answered, for replaying it.
+    pub fn chat_snapshot(job_id: &Uuid) -> String {
+        format!("chat:snapshot:{}", job_id)
+    }
+
+    /// Version of a logical model's release currently serving chats.
+    pub fn model_active_release(model: &str) -> String {
+        format!("model_registry:active:{}", model)
+    }
+
+    /// Rollouts of a logical model's releases, newest first.
+    pub fn model_rollouts(model: &str) -> String {
+        format!("model_registry:rollouts:{}", model)
+    }
+
+    /// Finished-job summaries waiting to be written to Postgres, oldest last.
+    pub const JOB_HISTORY_OUTBOX: &str = "job:history:outbox";
+
+    /// Last commit indexed from a git repository.
+    pub fn git_head(repo_url: &str) -> String {
+        format!("git:head:{}", repo_url)
+    }
+
+    /// Hash of object key to the ETag last embedded from a bucket.
+    pub fn s3_etags(bucket: &str) -> String {
+        format!("s3:etags:{}", bucket)
+    }
+
+    /// Held by the worker running the retention sweep.
+    pub const RETENTION_LOCK: &str = "retention:lock";
+    pub const RETENTION_REPORT: &str = "retention:report";
+    /// Held by the worker building the stale-content report.
+    pub const FRESHNESS_LOCK: &str = "freshness:lock";
+    pub const FRESHNESS_REPORT: &str = "freshness:report";
+
+    pub const CONVERSATION_PREFIX: &str = "conversation:";
+    /// Sorted set of conversation ids scored by `updated_at` (Unix millis), for listing newest first.
+    pub const CONVERSATIONS_BY_UPDATED: &str = "conversations:updated";
+    /// Hash of document id to its [`QuarantinedDocument`](crate::infrastructure::QuarantinedDocument).
+    pub const QUARANTINE: &str = "safety:quarantine";
+    /// Hash of attached document id to conversation id, swept once conversations expire.
+    pub const CONVERSATION_ATTACHMENTS: &str = "attachments:conversation";
+    /// Hash of annotation id to the id of the conversation it labels.
+    pub const ANNOTATION_INDEX: &str = "annotations:index";
+
+    /// Hash of annotation id to its [`Annotation`](crate::domain::Annotation), per conversation.
+    pub fn annotations(conversation_id: &Uuid) -> String {
+        format!("annotations:{}", conversation_id)
+    }
+
+    /// Sorted set of normalized query text to how often it was retrieved on `day` (`YYYY-MM-DD`).
+    pub fn query_log(day: &str) -> String {
+        format!("queries:{}", day)
+    }
+
     pub fn conversation(conversation_id: &Uuid) -> String {
-        format!("conversation:{}", conversation_id)
+        format!("{}{}", CONVERSATION_PREFIX, conversation_id)
     }
+
+    /// Hash of the tokens (`prompt_tokens`, `completion_tokens`), `chats`
+    /// and `cost` a conversation's answers used.
+    pub fn conversation_usage(conversation_id: &Uuid) -> String {
+        format!("usage:conversation:{}", conversation_id)
+    }
+
+    /// Like [`conversation_usage`], for the chats sent with an API key.
+    pub fn api_key_usage(api_key_id: &str) -> String {
+        format!("usage:api_key:{}", api_key_id)
+    }
+
+    /// A cached chat answer, by hash of what it depends on.
+    pub fn response_cache(hash: &str) -> String {
+        format!("response_cache:{}", hash)
+    }
+
+    /// Aliases followed at most when resolving a conversation id.
+    pub const CONVERSATION_ALIAS_HOPS: usize = 4;
+
+    /// Id of the conversation a merged conversation's id now resolves to.
+    pub fn conversation_alias(conversation_id: &Uuid) -> String {
+        format!("conversation_alias:{}", conversation_id)
+    }
+}
+
+/// Version of the crate that produced a job payload, stamped on every job.
+pub const PRODUCER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How a payload's producer version relates to [`PRODUCER_VERSION`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionCompatibility {
+    Same,
+    Older,
+    Newer,
+    /// The payload carried no (or an unparseable) version.
+    Unknown,
+}
+
+impl VersionCompatibility {
+    pub fn of(producer_version: Option<&str>) -> Self {
+        let Some(theirs) = producer_version.and_then(parse_version) else {
+            return Self::Unknown;
+        };
+        let Some(ours) = parse_version(PRODUCER_VERSION) else {
+            return Self::Unknown;
+        };
+
+        match theirs.cmp(&ours) {
+            std::cmp::Ordering::Less => Self::Older,
+            std::cmp::Ordering::Equal => Self::Same,
+            std::cmp::Ordering::Greater => Self::Newer,
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+fn producer_version() -> Option<String> {
+    Some(PRODUCER_VERSION.to_string())
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -27,15 +197,60 @@ pub enum QueueJobStatus {
     Processing,
     Completed,
     Failed,
+    Cancelled,
+}
+
+impl QueueJobStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// What a chat job appends to its stream buffer once its answer text has
+/// gone from `previous` to `text`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamUpdate<'a> {
+    Unchanged,
+    /// `text` extends `previous` by this suffix.
+    Append(&'a str),
+    /// `text` no longer starts with `previous`, e.g. the agent retried;
+    /// clients start over with this text.
+    Restart(&'a str),
+}
+
+impl<'a> StreamUpdate<'a> {
+    pub fn between(previous: &str, text: &'a str) -> Self {
+        if text == previous {
+            Self::Unchanged
+        } else if let Some(suffix) = text.strip_prefix(previous) {
+            Self::Append(suffix)
+        } else {
+            Self::Restart(text)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobResult {
     pub job_id: Uuid,
     pub status: QueueJobStatus,
+    /// The job's output once completed, or progress so far while a
+    /// long-running job is processing.
     pub result: Option<serde_json::Value>,
     pub error: Option<String>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Answer text generated so far, while a chat job is still processing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_result: Option<String>,
+    /// Tenant billed for the job, where known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Estimated LLM tokens (prompt plus answer) the job used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<u64>,
+    /// Bytes of document content the job stored, for storage billing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_bytes: Option<u64>,
 }
 
 impl JobResult {
@@ -46,6 +261,10 @@ impl JobResult {
             result: None,
             error: None,
             completed_at: None,
+            partial_result: None,
+            tenant_id: None,
+            tokens: None,
+            stored_bytes: None,
         }
     }
 
@@ -56,6 +275,10 @@ impl JobResult {
             result: None,
             error: None,
             completed_at: None,
+            partial_result: None,
+            tenant_id: None,
+            tokens: None,
+            stored_bytes: None,
         }
     }
 
@@ -66,6 +289,10 @@ impl JobResult {
             result: Some(result),
             error: None,
             completed_at: Some(Utc::now()),
+            partial_result: None,
+            tenant_id: None,
+            tokens: None,
+            stored_bytes: None,
         }
     }
 
@@ -76,8 +303,123 @@ impl JobResult {
             result: None,
             error: Some(error.into()),
             completed_at: Some(Utc::now()),
+            partial_result: None,
+            tenant_id: None,
+            tokens: None,
+            stored_bytes: None,
+        }
+    }
+
+    pub fn cancelled(job_id: Uuid) -> Self {
+        Self {
+            job_id,
+            status: QueueJobStatus::Cancelled,
+            result: None,
+            error: None,
+            completed_at: Some(Utc::now()),
+            partial_result: None,
+            tenant_id: None,
+            tokens: None,
+            stored_bytes: None,
+        }
+    }
+
+    /// A processing status reporting progress, e.g. pages crawled so far.
+    pub fn progress(job_id: Uuid, progress: serde_json::Value) -> Self {
+        Self {
+            result: Some(progress),
+            ..Self::processing(job_id)
+        }
+    }
+
+    /// A processing status carrying the answer streamed so far.
+    pub fn streaming(job_id: Uuid, partial: impl Into<String>) -> Self {
+        Self {
+            partial_result: Some(partial.into()),
+            ..Self::processing(job_id)
         }
     }
+
+    /// Records who the job is billed to and the tokens it used.
+    pub fn with_usage(mut self, tenant_id: Option<String>, tokens: Option<u64>) -> Self {
+        self.tenant_id = tenant_id;
+        self.tokens = tokens;
+        self
+    }
+
+    pub fn with_stored_bytes(mut self, bytes: u64) -> Self {
+        self.stored_bytes = Some(bytes);
+        self
+    }
+}
+
+/// Where a chat request came from; decides e.g. which banners are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    #[default]
+    Api,
+    Widget,
+    Slack,
+    Telegram,
+    Sms,
+}
+
+impl Channel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Api => "api",
+            Self::Widget => "widget",
+            Self::Slack => "slack",
+            Self::Telegram => "telegram",
+            Self::Sms => "sms",
+        }
+    }
+}
+
+/// Compact record of a finished job for the history list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSummary {
+    pub job_id: Uuid,
+    pub queue: String,
+    pub status: QueueJobStatus,
+    pub error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Time from the job starting to process to finishing; absent for jobs
+    /// cancelled before they started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_bytes: Option<u64>,
+}
+
+impl JobSummary {
+    pub fn new(queue: impl Into<String>, result: &JobResult) -> Self {
+        Self {
+            job_id: result.job_id,
+            queue: queue.into(),
+            status: result.status,
+            error: result.error.clone(),
+            completed_at: result.completed_at,
+            duration_ms: None,
+            tenant_id: result.tenant_id.clone(),
+            tokens: result.tokens,
+            stored_bytes: result.stored_bytes,
+        }
+    }
+
+    /// The job type, e.g. `chat` for the `jobs:chat` queue.
+    pub fn job_type(&self) -> &str {
+        self.queue.strip_prefix("jobs:").unwrap_or(&self.queue)
+    }
+}
+
+fn is_first_attempt(attempt: &u32) -> bool {
+    *attempt == 0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +428,36 @@ pub struct ProcessChatJob {
     pub message: String,
     pub conversation_id: Option<Uuid>,
     pub agent_id: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Hash of the API key the chat was sent with, for usage counters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<String>,
+    #[serde(default)]
+    pub channel: Channel,
+    /// Append permalinks to the git/web sources the answer drew on.
+    #[serde(default)]
+    pub include_links: bool,
+    /// Cap on the answer's length, enforced after generation.
+    #[serde(default)]
+    pub max_answer_tokens: Option<usize>,
+    #[serde(default)]
+    pub format: AnswerFormat,
+    /// Sampling settings replacing `llm`'s for this job.
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+    #[serde(default)]
+    pub enqueued_at: Option<DateTime<Utc>>,
+    /// Times the job has been retried after a transient failure.
+    #[serde(default, skip_serializing_if = "is_first_attempt")]
+    pub attempt: u32,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+    /// Set when the job re-runs a past chat instead of continuing a conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ChatReplay>,
 }
 
 impl ProcessChatJob {
@@ -95,6 +467,26 @@ impl ProcessChatJob {
             message: message.into(),
             conversation_id: None,
             agent_id: None,
+            tenant_id: None,
+            user_id: None,
+            api_key_id: None,
+            channel: Channel::default(),
+            include_links: false,
+            max_answer_tokens: None,
+            format: AnswerFormat::default(),
+            generation: GenerationParams::default(),
+            enqueued_at: Some(Utc::now()),
+            attempt: 0,
+            producer_version: producer_version(),
+            replay: None,
+        }
+    }
+
+    /// A copy to requeue after a retryable failure.
+    pub fn next_attempt(&self) -> Self {
+        Self {
+            attempt: self.attempt + 1,
+            ..self.clone()
         }
     }
 
@@ -107,6 +499,96 @@ impl ProcessChatJob {
         self.agent_id = Some(agent_id.into());
         self
     }
+
+    pub fn with_tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    pub fn with_user(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    pub fn with_api_key_id(mut self, api_key_id: impl Into<String>) -> Self {
+        self.api_key_id = Some(api_key_id.into());
+        self
+    }
+
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    pub fn with_links(mut self) -> Self {
+        self.include_links = true;
+        self
+    }
+
+    pub fn with_max_answer_tokens(mut self, max: usize) -> Self {
+        self.max_answer_tokens = Some(max);
+        self
+    }
+
+    pub fn with_format(mut self, format: AnswerFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_generation(mut self, params: GenerationParams) -> Self {
+        self.generation = params;
+        self
+    }
+
+    pub fn answer_options(&self) -> AnswerOptions {
+        AnswerOptions {
+            max_answer_tokens: self.max_answer_tokens,
+            format: self.format,
+        }
+    }
+}
+
+/// What a chat job saw and answered, kept for `worker.replay.ttl_seconds`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSnapshot {
+    pub job: ProcessChatJob,
+    /// The conversation as the agent saw it, ending with the job's message.
+    pub conversation: Conversation,
+    /// Knowledge base results the answer drew on.
+    pub sources: Vec<SearchResult>,
+    pub response: String,
+}
+
+impl ChatSnapshot {
+    /// A new job re-running this one on the same conversation, optionally
+    /// with its knowledge base results and as another agent.
+    pub fn replay_job(&self, pin_sources: bool, agent_id: Option<String>) -> ProcessChatJob {
+        ProcessChatJob {
+            job_id: Uuid::new_v4(),
+            agent_id: agent_id.or_else(|| self.job.agent_id.clone()),
+            enqueued_at: Some(Utc::now()),
+            attempt: 0,
+            producer_version: producer_version(),
+            replay: Some(ChatReplay {
+                of_job_id: self.job.job_id,
+                conversation: self.conversation.clone(),
+                pinned_sources: pin_sources.then(|| self.sources.clone()),
+                original_response: self.response.clone(),
+            }),
+            ..self.job.clone()
+        }
+    }
+}
+
+/// The past chat a replay job re-runs. Replays leave the conversation as is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatReplay {
+    pub of_job_id: Uuid,
+    pub conversation: Conversation,
+    /// Returned by the knowledge base tool instead of searching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_sources: Option<Vec<SearchResult>>,
+    pub original_response: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +597,41 @@ pub struct EmbedDocumentJob {
     pub document_id: Uuid,
     pub content: String,
     pub metadata: serde_json::Value,
+    /// Per-page text for paginated sources; when set, chunks carry page numbers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<ExtractedPage>,
+    /// MIME type recorded on every chunk, for filtered search.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Review and expiry dates recorded on every chunk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_by: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Tags recorded on every chunk, for tag-scoped search.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Conversation the document is attached to instead of the shared knowledge base.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<Uuid>,
+    /// Tenant whose storage the document counts towards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Approved from quarantine, so the safety filter is skipped.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub safety_reviewed: bool,
+    /// Replaces the document's existing chunks instead of adding to them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replace: bool,
+    /// S3 object the document was synced from, recorded as embedded once
+    /// its chunks are indexed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub s3_object: Option<S3ObjectVersion>,
+    /// Times the job has been retried after a transient failure.
+    #[serde(default, skip_serializing_if = "is_first_attempt")]
+    pub attempt: u32,
+    #[serde(default)]
+    pub producer_version: Option<String>,
 }
 
 impl EmbedDocumentJob {
@@ -124,19 +641,92 @@ impl EmbedDocumentJob {
             document_id,
             content: content.into(),
             metadata: serde_json::json!({}),
+            pages: Vec::new(),
+            content_type: None,
+            review_by: None,
+            expires_at: None,
+            tags: Vec::new(),
+            conversation_id: None,
+            tenant_id: None,
+            safety_reviewed: false,
+            replace: false,
+            s3_object: None,
+            attempt: 0,
+            producer_version: producer_version(),
+        }
+    }
+
+    /// A copy to requeue after a retryable failure.
+    pub fn next_attempt(&self) -> Self {
+        Self {
+            attempt: self.attempt + 1,
+            ..self.clone()
         }
     }
 
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_conversation(mut self, conversation_id: Uuid) -> Self {
+        self.conversation_id = Some(conversation_id);
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    pub fn with_freshness(
+        mut self,
+        review_by: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.review_by = review_by;
+        self.expires_at = expires_at;
+        self
+    }
+
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = metadata;
         self
     }
+
+    pub fn with_pages(mut self, pages: Vec<ExtractedPage>) -> Self {
+        self.pages = pages;
+        self
+    }
+
+    /// Re-embeds a synced S3 object in place of its previous chunks.
+    pub fn with_s3_object(mut self, object: S3ObjectVersion) -> Self {
+        self.replace = true;
+        self.s3_object = Some(object);
+        self
+    }
+}
+
+/// An S3 object at the ETag an embed job was queued for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3ObjectVersion {
+    pub bucket: String,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexDocumentJob {
     pub job_id: Uuid,
     pub document_id: Uuid,
+    #[serde(default)]
+    pub producer_version: Option<String>,
 }
 
 impl IndexDocumentJob {
@@ -144,6 +734,400 @@ impl IndexDocumentJob {
         Self {
             job_id: Uuid::new_v4(),
             document_id,
+            producer_version: producer_version(),
+        }
+    }
+}
+
+/// Re-embeds every chunk of the active collection into `target_collection`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReembedCollectionJob {
+    pub job_id: Uuid,
+    pub target_collection: String,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+}
+
+impl ReembedCollectionJob {
+    pub fn new(target_collection: impl Into<String>) -> Self {
+        Self {
+            job_id: Uuid::new_v4(),
+            target_collection: target_collection.into(),
+            producer_version: producer_version(),
+        }
+    }
+}
+
+/// Dumps every point of `collection` (the active one if unset) to `file` in
+/// the worker's dump directory, as JSON lines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportCollectionJob {
+    pub job_id: Uuid,
+    pub collection: Option<String>,
+    pub file: String,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+}
+
+impl ExportCollectionJob {
+    pub fn new(collection: Option<String>, file: impl Into<String>) -> Self {
+        Self {
+            job_id: Uuid::new_v4(),
+            collection,
+            file: file.into(),
+            producer_version: producer_version(),
+        }
+    }
+}
+
+/// Upserts the points of a dump `file` into `collection` (the active one if unset).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportCollectionJob {
+    pub job_id: Uuid,
+    pub collection: Option<String>,
+    pub file: String,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+}
+
+impl ImportCollectionJob {
+    pub fn new(collection: Option<String>, file: impl Into<String>) -> Self {
+        Self {
+            job_id: Uuid::new_v4(),
+            collection,
+            file: file.into(),
+            producer_version: producer_version(),
+        }
+    }
+}
+
+/// Indexes files matching `globs` from a git repository, incrementally since
+/// the last synced commit unless `full` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncGitRepoJob {
+    pub job_id: Uuid,
+    pub url: String,
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub globs: Vec<String>,
+    #[serde(default)]
+    pub full: bool,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+}
+
+impl SyncGitRepoJob {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            job_id: Uuid::new_v4(),
+            url: url.into(),
+            branch: None,
+            globs: Vec::new(),
+            full: false,
+            producer_version: producer_version(),
         }
     }
+
+    pub fn with_branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    pub fn with_globs(mut self, globs: Vec<String>) -> Self {
+        self.globs = globs;
+        self
+    }
+
+    pub fn full(mut self) -> Self {
+        self.full = true;
+        self
+    }
+}
+
+/// Queues embed jobs for supported objects under an S3 bucket prefix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3SyncJob {
+    pub job_id: Uuid,
+    pub bucket: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Re-embed every object, not only those whose ETag changed.
+    #[serde(default)]
+    pub full: bool,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+}
+
+impl S3SyncJob {
+    pub fn new(bucket: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self {
+            job_id: Uuid::new_v4(),
+            bucket: bucket.into(),
+            prefix: prefix.into(),
+            tags: Vec::new(),
+            full: false,
+            producer_version: producer_version(),
+        }
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn full(mut self) -> Self {
+        self.full = true;
+        self
+    }
+}
+
+/// Crawls a website from a page or sitemap and indexes each page as a document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlSiteJob {
+    pub job_id: Uuid,
+    pub url: String,
+    /// Defaults to `crawl.max_depth`.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    /// Defaults to `crawl.max_pages`.
+    #[serde(default)]
+    pub max_pages: Option<usize>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub producer_version: Option<String>,
+}
+
+impl CrawlSiteJob {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            job_id: Uuid::new_v4(),
+            url: url.into(),
+            max_depth: None,
+            max_pages: None,
+            tags: Vec::new(),
+            producer_version: producer_version(),
+        }
+    }
+
+    pub fn with_limits(mut self, max_depth: Option<usize>, max_pages: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self.max_pages = max_pages;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    #[test]
+    fn test_stream_update_restarts_when_the_answer_is_cleared() {
+        let partial = crate::infrastructure::PartialResponse::new();
+        partial.push("Hé");
+        let first = partial.snapshot();
+        assert_eq!(
+            StreamUpdate::between("", &first),
+            StreamUpdate::Append("Hé")
+        );
+        partial.push("llo");
+        let second = partial.snapshot();
+        assert_eq!(
+            StreamUpdate::between(&first, &second),
+            StreamUpdate::Append("llo")
+        );
+        assert_eq!(
+            StreamUpdate::between(&second, &second),
+            StreamUpdate::Unchanged
+        );
+
+        // A retry clears the answer mid-stream: shorter text, then text that
+        // diverges inside a multi-byte character.
+        partial.clear();
+        partial.push("H");
+        let retried = partial.snapshot();
+        assert_eq!(
+            StreamUpdate::between(&second, &retried),
+            StreamUpdate::Restart("H")
+        );
+        partial.push("è");
+        let third = partial.snapshot();
+        assert_eq!(
+            StreamUpdate::between(&retried, &third),
+            StreamUpdate::Append("è")
+        );
+        assert_eq!(
+            StreamUpdate::between(&first, &third),
+            StreamUpdate::Restart("Hè")
+        );
+        assert_eq!(
+            StreamUpdate::between(&second, ""),
+            StreamUpdate::Restart("")
+        );
+    }
+
+    fn fixed_time() -> DateTime<Utc> {
+        "2024-01-01T00:00:00Z".parse().unwrap()
+    }
+
+    /// Serializing, deserializing and serializing again must be lossless.
+    fn assert_round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+        let json = serde_json::to_value(value).unwrap();
+        let decoded: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+    }
+
+    #[test]
+    fn test_chat_job_wire_format() {
+        let job = ProcessChatJob {
+            job_id: Uuid::from_u128(1),
+            message: "Hello".to_string(),
+            conversation_id: Some(Uuid::from_u128(2)),
+            agent_id: Some("support".to_string()),
+            tenant_id: Some("acme".to_string()),
+            user_id: Some("user-1".to_string()),
+            api_key_id: None,
+            channel: Channel::Widget,
+            include_links: true,
+            max_answer_tokens: Some(200),
+            format: AnswerFormat::Plain,
+            generation: GenerationParams::default(),
+            enqueued_at: Some(fixed_time()),
+            attempt: 0,
+            producer_version: Some("0.1.0".to_string()),
+            replay: None,
+        };
+
+        assert_round_trip(&job);
+        insta::assert_json_snapshot!(job);
+    }
+
+    #[test]
+    fn test_chat_job_without_optional_fields() {
+        let job: ProcessChatJob = serde_json::from_value(serde_json::json!({
+            "job_id": Uuid::from_u128(1),
+            "message": "Hello",
+            "conversation_id": null,
+            "agent_id": null,
+        }))
+        .unwrap();
+
+        assert!(job.enqueued_at.is_none());
+        assert!(job.producer_version.is_none());
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        assert_eq!(
+            VersionCompatibility::of(Some(PRODUCER_VERSION)),
+            VersionCompatibility::Same
+        );
+        assert_eq!(
+            VersionCompatibility::of(Some("0.0.1")),
+            VersionCompatibility::Older
+        );
+        assert_eq!(
+            VersionCompatibility::of(Some("999.0.0-beta.1")),
+            VersionCompatibility::Newer
+        );
+        assert_eq!(
+            VersionCompatibility::of(None),
+            VersionCompatibility::Unknown
+        );
+        assert_eq!(
+            VersionCompatibility::of(Some("garbage")),
+            VersionCompatibility::Unknown
+        );
+    }
+
+    #[test]
+    fn test_embed_job_wire_format() {
+        let job = EmbedDocumentJob {
+            job_id: Uuid::from_u128(1),
+            document_id: Uuid::from_u128(2),
+            content: "Some content".to_string(),
+            metadata: serde_json::json!({ "source": "test" }),
+            pages: Vec::new(),
+            content_type: Some("text/plain".to_string()),
+            review_by: None,
+            expires_at: None,
+            tags: Vec::new(),
+            conversation_id: None,
+            tenant_id: None,
+            safety_reviewed: false,
+            replace: false,
+            s3_object: None,
+            attempt: 0,
+            producer_version: Some("0.1.0".to_string()),
+        };
+
+        assert_round_trip(&job);
+        insta::assert_json_snapshot!(job);
+    }
+
+    #[test]
+    fn test_index_job_wire_format() {
+        let job = IndexDocumentJob {
+            job_id: Uuid::from_u128(1),
+            document_id: Uuid::from_u128(2),
+            producer_version: Some("0.1.0".to_string()),
+        };
+
+        assert_round_trip(&job);
+        insta::assert_json_snapshot!(job);
+    }
+
+    #[test]
+    fn test_replay_job_reruns_snapshot() {
+        let job = ProcessChatJob::new("How do refunds work?").with_agent("support");
+        let mut conversation = Conversation::new();
+        conversation.add_message(crate::domain::MessageRole::User, &job.message);
+        let snapshot = ChatSnapshot {
+            job: job.clone(),
+            conversation,
+            sources: Vec::new(),
+            response: "Within 30 days.".to_string(),
+        };
+
+        let replay = snapshot.replay_job(true, None);
+        assert_ne!(replay.job_id, job.job_id);
+        assert_eq!(replay.agent_id.as_deref(), Some("support"));
+        let recorded = replay.replay.as_ref().unwrap();
+        assert_eq!(recorded.of_job_id, job.job_id);
+        assert!(recorded.pinned_sources.is_some());
+
+        let replay = snapshot.replay_job(false, Some("sales".to_string()));
+        assert_eq!(replay.agent_id.as_deref(), Some("sales"));
+        assert!(replay.replay.unwrap().pinned_sources.is_none());
+    }
+
+    #[test]
+    fn test_job_result_wire_format() {
+        let completed = JobResult {
+            completed_at: Some(fixed_time()),
+            ..JobResult::completed(Uuid::from_u128(1), serde_json::json!({ "response": "Hi" }))
+        };
+        let failed = JobResult {
+            completed_at: Some(fixed_time()),
+            ..JobResult::failed(Uuid::from_u128(1), "boom")
+        };
+
+        assert_round_trip(&completed);
+        assert_round_trip(&failed);
+        insta::assert_json_snapshot!("job_result_completed", completed);
+        insta::assert_json_snapshot!("job_result_failed", failed);
+        insta::assert_json_snapshot!("job_result_pending", JobResult::pending(Uuid::from_u128(1)));
+        insta::assert_json_snapshot!(
+            "job_result_streaming",
+            JobResult::streaming(Uuid::from_u128(1), "Partial ans")
+        );
+    }
 }
@@ -1,5 +1,8 @@
 mod jobs;
 
 pub use jobs::{
-    keys, queues, EmbedDocumentJob, IndexDocumentJob, JobResult, ProcessChatJob, QueueJobStatus,
+    keys, queues, Channel, ChatReplay, ChatSnapshot, CrawlSiteJob, EmbedDocumentJob,
+    ExportCollectionJob, ImportCollectionJob, IndexDocumentJob, JobResult, JobSummary,
+    ProcessChatJob, QueueJobStatus, ReembedCollectionJob, S3ObjectVersion, S3SyncJob, StreamUpdate,
+    SyncGitRepoJob, VersionCompatibility, PRODUCER_VERSION,
 };
@@ -0,0 +1,99 @@
+//! Spans around queue and conversation Redis calls, so slow chats can be
+//! traced to Redis or to the provider.
+
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::Instrument;
+
+/// `redis_tracing.slow_op_ms`, set once at startup.
+static SLOW_OP_MS: AtomicU64 = AtomicU64::new(100);
+
+/// Commands that wait for data by design and are never reported as slow.
+const BLOCKING_COMMANDS: &[&str] = &["BRPOP", "BLPOP", "BLMOVE"];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RedisTracingConfig {
+    /// Operations taking at least this long are logged as warnings; 0 never warns.
+    pub slow_op_ms: u64,
+}
+
+impl Default for RedisTracingConfig {
+    fn default() -> Self {
+        Self { slow_op_ms: 100 }
+    }
+}
+
+impl RedisTracingConfig {
+    /// Makes this the threshold of every [`traced`] call in the process.
+    pub fn install(&self) {
+        SLOW_OP_MS.store(self.slow_op_ms, Ordering::Relaxed);
+    }
+}
+
+/// Runs `op` in a `redis` span recording `command`, the pattern of `key` and
+/// the latency, and warns when it is slower than `redis_tracing.slow_op_ms`.
+/// Pipelines are labelled `PIPELINE` (or `MULTI` when atomic) with their
+/// first key.
+pub async fn traced<T, E>(
+    command: &'static str,
+    key: &str,
+    op: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let key = key_pattern(key);
+    let span = tracing::debug_span!(
+        "redis",
+        command,
+        key = %key,
+        latency_ms = tracing::field::Empty
+    );
+    let started = Instant::now();
+    let result = op.instrument(span.clone()).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.record("latency_ms", latency_ms);
+
+    let threshold = SLOW_OP_MS.load(Ordering::Relaxed);
+    if threshold > 0 && latency_ms >= threshold && !BLOCKING_COMMANDS.contains(&command) {
+        tracing::warn!(command, key = %key, latency_ms, "slow redis operation");
+    }
+    result
+}
+
+/// `key` with ids, hashes, dates and counters replaced by `*`, e.g.
+/// `job:status:*` for every job's status.
+pub fn key_pattern(key: &str) -> String {
+    key.split(':')
+        .map(|segment| {
+            let variable = segment.bytes().any(|b| b.is_ascii_digit())
+                && segment.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-');
+            if variable {
+                "*"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pattern_hides_variable_segments() {
+        assert_eq!(
+            key_pattern("job:status:6f1c2b9e-3a4d-4e5f-8a7b-1c2d3e4f5a6b"),
+            "job:status:*"
+        );
+        assert_eq!(
+            key_pattern("usage:api_key:3fa85f6457174562"),
+            "usage:api_key:*"
+        );
+        assert_eq!(key_pattern("queries:2026-10-16"), "queries:*");
+        assert_eq!(key_pattern("jobs:chat:low"), "jobs:chat:low");
+        assert_eq!(key_pattern("collection:active"), "collection:active");
+    }
+}
@@ -0,0 +1,202 @@
+//! Answers to first messages, reused while the same question retrieves the
+//! same knowledge base chunks.
+
+use deadpool_redis::redis::AsyncCommands;
+use deadpool_redis::{Connection, Pool};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::domain::{ports::TokenUsage, DomainError, SearchResult};
+use crate::infrastructure::agent::AgentReply;
+use crate::infrastructure::config::LlmProvider;
+use crate::infrastructure::keys;
+use crate::infrastructure::latency::AgentTimings;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    pub ttl_seconds: u64,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_seconds: 3600,
+        }
+    }
+}
+
+/// What answered a chat, without its timings or usage.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedReply {
+    response: String,
+    sources: Vec<SearchResult>,
+    provider: LlmProvider,
+    model: String,
+}
+
+/// Everything an answer depends on besides the knowledge base contents.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseKey<'a> {
+    pub agent_id: Option<&'a str>,
+    pub tenant_id: Option<&'a str>,
+    pub model: &'a str,
+    /// The agent's system prompt, which a model release may pin to a version.
+    pub system_prompt: &'a str,
+    /// Added to the system prompt for this chat.
+    pub instructions: Option<&'a str>,
+    pub message: &'a str,
+}
+
+impl ResponseKey<'_> {
+    /// Redis key of the answer when retrieval returns `chunk_ids`. The
+    /// message is compared case-insensitively, ignoring extra whitespace.
+    pub fn with_chunks(&self, chunk_ids: &[Uuid]) -> String {
+        let message = self
+            .message
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let mut chunk_ids = chunk_ids.to_vec();
+        chunk_ids.sort();
+
+        let mut hasher = Sha256::new();
+        for part in [
+            self.agent_id.unwrap_or_default(),
+            self.tenant_id.unwrap_or_default(),
+            self.model,
+            self.system_prompt,
+            self.instructions.unwrap_or_default(),
+            message.as_str(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for id in chunk_ids {
+            hasher.update(id.as_bytes());
+        }
+        keys::response_cache(&format!("{:x}", hasher.finalize()))
+    }
+}
+
+/// Chat answers in Redis for `ttl_seconds`.
+pub struct RedisResponseCache {
+    pool: Pool,
+    ttl_seconds: u64,
+}
+
+impl RedisResponseCache {
+    pub fn new(pool: Pool, ttl_seconds: u64) -> Self {
+        Self { pool, ttl_seconds }
+    }
+
+    async fn conn(&self) -> Result<Connection, DomainError> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| DomainError::external(format!("Redis pool error: {e}")))
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Option<AgentReply>, DomainError> {
+        let data: Option<String> = self
+            .conn()
+            .await?
+            .get(key)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        let Some(data) = data else {
+            return Ok(None);
+        };
+        let cached: CachedReply = serde_json::from_str(&data)
+            .map_err(|e| DomainError::internal(format!("Corrupt cached response: {e}")))?;
+        Ok(Some(AgentReply {
+            response: cached.response,
+            timings: AgentTimings::default(),
+            sources: cached.sources,
+            degraded: false,
+            provider: cached.provider,
+            model: cached.model,
+            fallback: false,
+            usage: TokenUsage::default(),
+            cached: true,
+        }))
+    }
+
+    pub async fn put(&self, key: &str, reply: &AgentReply) -> Result<(), DomainError> {
+        let data = serde_json::to_string(&CachedReply {
+            response: reply.response.clone(),
+            sources: reply.sources.clone(),
+            provider: reply.provider,
+            model: reply.model.clone(),
+        })
+        .map_err(|e| DomainError::internal(e.to_string()))?;
+        self.conn()
+            .await?
+            .set_ex::<_, _, ()>(key, data, self.ttl_seconds)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_ignores_case_spacing_and_chunk_order() {
+        let (a, b) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let key = |message: &'static str| ResponseKey {
+            agent_id: Some("support"),
+            tenant_id: None,
+            model: "gemini-3-flash-preview",
+            system_prompt: "You are a support agent.",
+            instructions: None,
+            message,
+        };
+
+        assert_eq!(
+            key("How do I reset my password?").with_chunks(&[a, b]),
+            key("  how do I  reset my PASSWORD? ").with_chunks(&[b, a])
+        );
+        assert_ne!(
+            key("How do I reset my password?").with_chunks(&[a]),
+            key("How do I reset my password?").with_chunks(&[a, b])
+        );
+        assert_ne!(
+            key("How do I reset my password?").with_chunks(&[a]),
+            ResponseKey {
+                model: "claude-sonnet-4-20250514",
+                ..key("How do I reset my password?")
+            }
+            .with_chunks(&[a])
+        );
+    }
+
+    #[test]
+    fn test_key_changes_with_prompt_version_and_tenant() {
+        let chunks = [Uuid::from_u128(1)];
+        let v1 = ResponseKey {
+            agent_id: Some("support"),
+            tenant_id: Some("acme"),
+            model: "gemini-3-flash-preview",
+            system_prompt: "You are a support agent.",
+            instructions: None,
+            message: "How do I reset my password?",
+        };
+        let v2 = ResponseKey {
+            system_prompt: "You are a concise support agent. Answer in one paragraph.",
+            ..v1
+        };
+        let other_tenant = ResponseKey {
+            tenant_id: Some("globex"),
+            ..v1
+        };
+
+        assert_ne!(v1.with_chunks(&chunks), v2.with_chunks(&chunks));
+        assert_ne!(v1.with_chunks(&chunks), other_tenant.with_chunks(&chunks));
+    }
+}
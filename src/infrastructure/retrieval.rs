@@ -0,0 +1,91 @@
+//! Vector stores and retrieval built from config, shared by the worker and
+//! the API's document search.
+
+use deadpool_redis::Pool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::application::{QueryTransform, RagService};
+use crate::domain::ports::{LlmService, VectorStore};
+use crate::domain::DomainError;
+#[cfg(feature = "chaos")]
+use crate::infrastructure::chaos::{FaultInjector, FaultyVectorStore};
+use crate::infrastructure::config::{Config, VectorStoreBackend};
+use crate::infrastructure::{
+    CircuitBreaker, GuardedVectorStore, QdrantVectorStore, RedisVectorStore,
+};
+
+/// Opens (creating if needed) a collection on the configured vector store backend.
+pub async fn vector_store_from_config(
+    qdrant_url: &str,
+    redis_pool: &Pool,
+    collection: &str,
+    config: &Config,
+    breaker: Option<&Arc<CircuitBreaker>>,
+    #[cfg(feature = "chaos")] faults: &Arc<FaultInjector>,
+) -> Result<Arc<dyn VectorStore>, DomainError> {
+    let dimension = config.embedding.dimension;
+    let store: Arc<dyn VectorStore> = match config.vector_store.backend {
+        VectorStoreBackend::Qdrant => Arc::new(
+            QdrantVectorStore::new(qdrant_url, collection, dimension, &config.vector_store)
+                .await?
+                .with_strict_payloads(config.vector_store.strict_payloads)
+                .with_read_replicas(
+                    &config.vector_store.read_replicas.urls,
+                    Duration::from_secs(config.vector_store.read_replicas.cooldown_seconds),
+                    Duration::from_millis(config.vector_store.read_replicas.timeout_ms),
+                )?,
+        ),
+        VectorStoreBackend::Redis => {
+            Arc::new(RedisVectorStore::new(redis_pool.clone(), collection, dimension).await?)
+        }
+    };
+    #[cfg(feature = "chaos")]
+    let store: Arc<dyn VectorStore> = Arc::new(FaultyVectorStore::new(store, faults.clone()));
+    Ok(match breaker {
+        Some(breaker) => Arc::new(GuardedVectorStore::new(store, breaker.clone())),
+        None => store,
+    })
+}
+
+/// Applies the `rag` settings that decide what a search returns, so the
+/// worker's retrieval and the API's search endpoint agree. `llm` reranks and
+/// transforms queries when those are enabled.
+pub fn configure_retrieval(
+    rag: RagService,
+    config: &Config,
+    llm: Option<&Arc<dyn LlmService>>,
+) -> RagService {
+    let rag_config = &config.rag;
+    let rag = rag
+        .with_stale_policy(rag_config.freshness.stale_policy)
+        .with_score_normalization(rag_config.score_normalization);
+    let fallback = &rag_config.embedding_fallback;
+    let rag = if fallback.enabled {
+        rag.with_embedding_fallback(fallback.cache_size, fallback.precision)
+    } else {
+        rag
+    };
+    let rerank = &rag_config.rerank;
+    let rag = match llm {
+        Some(llm) if rerank.enabled => rag.with_reranker(llm.clone(), rerank.candidates),
+        _ => rag,
+    };
+    let rag = match llm {
+        Some(llm) if rag_config.query_transform != QueryTransform::None => {
+            rag.with_query_transform(rag_config.query_transform, llm.clone())
+        }
+        _ => rag,
+    };
+    let rag = if rag_config.collapse.enabled {
+        rag.with_document_collapse(rag_config.collapse.candidates)
+    } else {
+        rag
+    };
+    let mmr = &rag_config.mmr;
+    if mmr.enabled {
+        rag.with_mmr(mmr.lambda, mmr.candidates)
+    } else {
+        rag
+    }
+}
@@ -0,0 +1,269 @@
+use rig::completion::CompletionError;
+use rig::embeddings::EmbeddingError;
+use rig::http_client;
+use serde::Deserialize;
+use std::future::Future;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::domain::DomainError;
+
+const RETRY_COUNTER: &str = "provider_call_retries_total";
+
+/// In-call retries of LLM and embedding provider requests, so a single 429,
+/// 5xx or timeout doesn't fail the whole job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts including the first; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further one.
+    pub initial_backoff_ms: u64,
+    /// Cap on a single delay.
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Upper bound of the delay after a failed `attempt` (0 for the first).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ms = self
+            .initial_backoff_ms
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_backoff_ms);
+        Duration::from_millis(ms)
+    }
+
+    /// [`backoff`](Self::backoff) with jitter: a random delay between half
+    /// and all of it, so callers that failed together don't retry together.
+    fn jittered_backoff(&self, attempt: u32) -> Duration {
+        let max = self.backoff(attempt).as_millis() as u64;
+        let half = max / 2;
+        let spread = (Uuid::new_v4().as_u128() as u64) % (max - half + 1);
+        Duration::from_millis(half + spread)
+    }
+}
+
+/// Whether a failed provider call is worth repeating: rate limits, timeouts
+/// and server or connection errors, but not rejections of the request
+/// itself such as bad credentials or an unknown model.
+pub fn should_retry(error: &DomainError) -> bool {
+    error.is_retryable()
+}
+
+/// Messages of rejections a provider reports without a status rig passes
+/// on, such as Ollama's for models without tool calling.
+const REJECTION_MARKERS: &[&str] = &["does not support tools", "doesn't support tool use"];
+
+/// Converts an error of a rig provider call, classified by the HTTP status
+/// found in its source chain or in the provider's error body.
+pub fn provider_error(
+    error: &(dyn std::error::Error + 'static),
+    msg: impl Into<String>,
+) -> DomainError {
+    let msg = msg.into();
+    match provider_status(error) {
+        Some(status) => DomainError::provider_status(status, msg),
+        None if REJECTION_MARKERS
+            .iter()
+            .any(|marker| msg.to_lowercase().contains(marker)) =>
+        {
+            DomainError::Rejected(msg)
+        }
+        None => DomainError::provider(msg),
+    }
+}
+
+/// The HTTP status behind a rig error: the status rig's HTTP client
+/// reports, or the one the provider's JSON error body names.
+fn provider_status(error: &(dyn std::error::Error + 'static)) -> Option<u16> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(
+            http_client::Error::InvalidStatusCode(status)
+            | http_client::Error::InvalidStatusCodeWithMessage(status, _),
+        ) = error.downcast_ref()
+        {
+            return Some(status.as_u16());
+        }
+        if let Some(CompletionError::ProviderError(body)) = error.downcast_ref() {
+            return status_in_body(body);
+        }
+        if let Some(EmbeddingError::ProviderError(body)) = error.downcast_ref() {
+            return status_in_body(body);
+        }
+        current = error.source();
+    }
+    None
+}
+
+/// Status named by a provider's error body: Gemini's numeric `error.code`,
+/// or the error code (OpenAI) or type (OpenAI, Anthropic) it stands for.
+fn status_in_body(body: &str) -> Option<u16> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error")?;
+    if let Some(code) = error.get("code").and_then(serde_json::Value::as_u64) {
+        return u16::try_from(code).ok();
+    }
+    let status = [error.get("code"), error.get("type")]
+        .into_iter()
+        .flatten()
+        .filter_map(serde_json::Value::as_str)
+        .find_map(status_for_error_type);
+    status
+}
+
+fn status_for_error_type(kind: &str) -> Option<u16> {
+    Some(match kind {
+        "invalid_request_error" => 400,
+        "authentication_error" | "invalid_api_key" => 401,
+        "permission_error" => 403,
+        "not_found_error" | "model_not_found" => 404,
+        "rate_limit_error" | "rate_limit_exceeded" | "insufficient_quota" => 429,
+        "api_error" | "server_error" => 500,
+        "overloaded_error" => 529,
+        _ => return None,
+    })
+}
+
+/// Runs `call` until it succeeds, fails with an error [`should_retry`]
+/// rejects, or `policy.max_attempts` is used up, sleeping a jittered
+/// exponential backoff between attempts. `operation` labels logs and the
+/// `provider_call_retries_total` counter.
+pub async fn retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    operation: &'static str,
+    mut call: F,
+) -> Result<T, DomainError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DomainError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt + 1 < policy.max_attempts && should_retry(&e) => {
+                let delay = policy.jittered_backoff(attempt);
+                tracing::warn!(
+                    error = %e,
+                    operation,
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    "provider call failed, retrying"
+                );
+                ::metrics::counter!(RETRY_COUNTER, "operation" => operation).increment(1);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures_until_success() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&fast_policy(3), "test", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(DomainError::provider_status(429, "Too Many Requests")),
+                1 => Err(DomainError::provider_status(503, "Service Unavailable")),
+                _ => Ok("done"),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_on_client_errors_and_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&fast_policy(3), "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(DomainError::provider_status(401, "Unauthorized"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&fast_policy(2), "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(DomainError::timeout("embedding"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_provider_errors_are_classified_by_status() {
+        let unauthorized = CompletionError::HttpError(http_client::Error::InvalidStatusCode(
+            reqwest::StatusCode::UNAUTHORIZED,
+        ));
+        assert!(!should_retry(&provider_error(
+            &unauthorized,
+            unauthorized.to_string()
+        )));
+
+        // Anthropic's body on a 529, which mentions a 4xx code.
+        let overloaded = CompletionError::ProviderError(
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded, see 401 docs"}}"#
+                .to_string(),
+        );
+        assert!(should_retry(&provider_error(
+            &overloaded,
+            overloaded.to_string()
+        )));
+
+        let bad_model = EmbeddingError::ProviderError(
+            r#"{"error":{"code":404,"message":"models/x is not found","status":"NOT_FOUND"}}"#
+                .to_string(),
+        );
+        assert!(!should_retry(&provider_error(
+            &bad_model,
+            bad_model.to_string()
+        )));
+
+        let reset = CompletionError::ProviderError("connection reset by peer".to_string());
+        assert!(should_retry(&provider_error(&reset, reset.to_string())));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 300,
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(300));
+        let jittered = policy.jittered_backoff(1);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::infrastructure::glossary::contains_phrase;
+use crate::infrastructure::EmbedDocumentJob;
+
+/// What happens to a document in a disallowed category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyAction {
+    /// Fail the embed job; nothing is indexed.
+    Reject,
+    /// Hold the document for review at `/admin/quarantine`.
+    #[default]
+    Quarantine,
+}
+
+/// Ingestion-time screening of documents against disallowed content categories.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SafetyConfig {
+    pub enabled: bool,
+    pub action: SafetyAction,
+    /// Category name to the terms that flag it, matched case-insensitively on whole words.
+    pub categories: BTreeMap<String, Vec<String>>,
+}
+
+impl SafetyConfig {
+    /// Categories whose terms appear in `text`, empty when the filter is off.
+    pub fn flagged_categories(&self, text: &str) -> Vec<String> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let text = text.to_lowercase();
+        self.categories
+            .iter()
+            .filter(|(_, terms)| {
+                terms
+                    .iter()
+                    .any(|term| contains_phrase(&text, &term.to_lowercase()))
+            })
+            .map(|(category, _)| category.clone())
+            .collect()
+    }
+}
+
+/// A document held back from indexing until an operator approves it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedDocument {
+    pub document_id: Uuid,
+    pub categories: Vec<String>,
+    pub quarantined_at: DateTime<Utc>,
+    /// Queued again, marked as reviewed, on approval.
+    pub job: EmbedDocumentJob,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_categories_by_whole_word() {
+        let config = SafetyConfig {
+            enabled: true,
+            action: SafetyAction::Reject,
+            categories: BTreeMap::from([
+                ("weapons".to_string(), vec!["pipe bomb".to_string()]),
+                ("self_harm".to_string(), vec!["overdose".to_string()]),
+            ]),
+        };
+
+        assert_eq!(
+            config.flagged_categories("How to build a Pipe Bomb"),
+            vec!["weapons".to_string()]
+        );
+        assert!(config.flagged_categories("overdoses of caution").is_empty());
+        assert!(SafetyConfig::default()
+            .flagged_categories("pipe bomb")
+            .is_empty());
+    }
+}
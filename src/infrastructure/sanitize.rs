@@ -0,0 +1,410 @@
+//! Cleanup of markup in final answers.
+//!
+//! Answers quote retrieved documents and crawled pages, which may carry
+//! scripts, `javascript:` links or tracking images into chat UIs that render
+//! Markdown or HTML. Fenced and inline code is left as is.
+
+use serde::Deserialize;
+
+use crate::infrastructure::answer_format::parse_link;
+
+/// Elements dropped together with their content.
+const DROPPED_ELEMENTS: [&str; 8] = [
+    "script", "style", "iframe", "object", "embed", "noscript", "template", "svg",
+];
+const SAFE_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+/// Bytes at the end of a partial answer searched for an unclosed tag or link.
+const PARTIAL_HOLD_BACK: usize = 256;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SanitizeConfig {
+    pub enabled: bool,
+    /// Hosts (and their subdomains) images may load from over HTTPS; other
+    /// images are replaced by their alt text.
+    pub image_hosts: Vec<String>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            image_hosts: Vec::new(),
+        }
+    }
+}
+
+impl SanitizeConfig {
+    /// Strips raw HTML (dropping script-like elements with their content),
+    /// links with schemes other than http, https, mailto and tel, and
+    /// images from hosts not in `image_hosts` from a Markdown `answer`.
+    pub fn sanitize(&self, answer: &str) -> String {
+        if !self.enabled {
+            return answer.to_string();
+        }
+
+        let mut out: Vec<String> = Vec::new();
+        let mut prose: Vec<&str> = Vec::new();
+        let mut in_code = false;
+        for line in answer.split('\n') {
+            let fence = line.trim_start().starts_with("```");
+            if in_code || fence {
+                if !prose.is_empty() {
+                    out.push(self.sanitize_prose(&prose.join("\n")));
+                    prose.clear();
+                }
+                out.push(line.to_string());
+                if fence {
+                    in_code = !in_code;
+                }
+            } else if reference_definition(line).is_some_and(|url| !is_safe_url(url)) {
+                // `[x]: javascript:...` would arm every `[text][x]` link.
+                continue;
+            } else {
+                prose.push(line);
+            }
+        }
+        if !prose.is_empty() {
+            out.push(self.sanitize_prose(&prose.join("\n")));
+        }
+        out.join("\n")
+    }
+
+    /// [`sanitize`](Self::sanitize) for an answer still being generated.
+    /// A trailing tag or link that isn't closed yet is held back, since its
+    /// end decides whether it is kept.
+    pub fn sanitize_partial(&self, partial: &str) -> String {
+        if !self.enabled {
+            return partial.to_string();
+        }
+        let tail_start = partial.len().saturating_sub(PARTIAL_HOLD_BACK);
+        let open = partial
+            .char_indices()
+            .skip_while(|&(i, _)| i < tail_start)
+            .filter(|&(i, c)| {
+                let rest = &partial[i + 1..];
+                (c == '<' && !rest.contains('>')) || (c == '[' && !rest.contains(')'))
+            })
+            .map(|(i, _)| i)
+            .next();
+        self.sanitize(&partial[..open.unwrap_or(partial.len())])
+    }
+
+    fn sanitize_prose(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '`' {
+                let ticks = rest.len() - rest.trim_start_matches('`').len();
+                let fence = &rest[..ticks];
+                if let Some(end) = rest[ticks..].find(fence) {
+                    let len = ticks + end + ticks;
+                    out.push_str(&rest[..len]);
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+            if c == '!' && rest[1..].starts_with('[') {
+                if let Some((alt, url, len)) = parse_link(&rest[1..]) {
+                    let alt = self.sanitize_prose(alt);
+                    if self.allows_image(url) {
+                        out.push_str(&format!("![{alt}]({url})"));
+                    } else {
+                        out.push_str(&alt);
+                    }
+                    rest = &rest[len + 1..];
+                    continue;
+                }
+            }
+            if c == '[' {
+                if let Some((label, url, len)) = parse_link(rest) {
+                    // Labels may carry markup of their own, e.g. document titles.
+                    let label = self.sanitize_prose(label);
+                    if is_safe_url(url) {
+                        out.push_str(&format!("[{label}]({url})"));
+                    } else {
+                        out.push_str(&label);
+                    }
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+            if c == '<' {
+                if let Some(len) = markup_len(rest) {
+                    let tag = &rest[..len];
+                    // Autolinks (`<https://...>`) are kept when their scheme is safe.
+                    if is_autolink(tag) && is_safe_url(&tag[1..len - 1]) {
+                        out.push_str(tag);
+                    }
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        out
+    }
+
+    fn allows_image(&self, url: &str) -> bool {
+        let Some(after_scheme) = url.trim().strip_prefix("https://") else {
+            return false;
+        };
+        let authority = after_scheme
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default().to_lowercase();
+        self.image_hosts.iter().any(|allowed| {
+            let allowed = allowed.to_lowercase();
+            host == allowed || host.ends_with(&format!(".{allowed}"))
+        })
+    }
+}
+
+/// Length of the HTML tag, comment or autolink at the start of `text`,
+/// including the content of [`DROPPED_ELEMENTS`] up to their closing tag
+/// (or the end of `text` when it is never closed).
+fn markup_len(text: &str) -> Option<usize> {
+    let lower = text.to_ascii_lowercase();
+    if lower.starts_with("<!--") {
+        return Some(lower.find("-->").map_or(text.len(), |end| end + 3));
+    }
+
+    let name: String = lower[1..]
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let tag_end = lower.find('>')? + 1;
+
+    if DROPPED_ELEMENTS.contains(&name.as_str()) && !lower.starts_with("</") {
+        let closing = format!("</{name}");
+        return Some(match lower[tag_end..].find(&closing) {
+            Some(start) => {
+                let close_start = tag_end + start;
+                lower[close_start..]
+                    .find('>')
+                    .map_or(text.len(), |end| close_start + end + 1)
+            }
+            None => text.len(),
+        });
+    }
+    Some(tag_end)
+}
+
+fn is_autolink(tag: &str) -> bool {
+    tag.ends_with('>')
+        && !tag.contains(char::is_whitespace)
+        && tag[1..].split_once(':').is_some_and(|(scheme, _)| {
+            !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// The target of a link reference definition line (`[label]: url "title"`).
+fn reference_definition(line: &str) -> Option<&str> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = line.trim_start().strip_prefix('[')?;
+    let (label, target) = rest.split_once("]:")?;
+    if label.trim().is_empty() {
+        return None;
+    }
+    let url = target.split_whitespace().next()?;
+    Some(url.trim_start_matches('<').trim_end_matches('>'))
+}
+
+/// Whether a link target is relative or uses a [`SAFE_SCHEMES`] scheme.
+/// HTML entities and percent-escapes are decoded and whitespace and control
+/// characters ignored first, as Markdown renderers and browsers do.
+fn is_safe_url(url: &str) -> bool {
+    let mut decoded = url.to_string();
+    // Nested escapes (`&amp;#58;`) decode one layer per pass.
+    for _ in 0..3 {
+        let next = percent_decode(&decode_entities(&decoded));
+        if next == decoded {
+            break;
+        }
+        decoded = next;
+    }
+    let url: String = decoded
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => SAFE_SCHEMES.contains(&&url[..i]),
+        _ => true,
+    }
+}
+
+/// Decodes numeric character references and the named ones that can spell
+/// a scheme or its separators.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 12).and_then(|end| {
+            let name = &rest[1..end];
+            let c = match name.strip_prefix('#') {
+                Some(num) => match num.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => num.parse().ok(),
+                }
+                .and_then(char::from_u32),
+                None => match name.to_ascii_lowercase().as_str() {
+                    "colon" => Some(':'),
+                    "sol" => Some('/'),
+                    "quest" => Some('?'),
+                    "num" => Some('#'),
+                    "amp" => Some('&'),
+                    "tab" => Some('\t'),
+                    "newline" => Some('\n'),
+                    _ => None,
+                },
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decodes `%XX` escapes; invalid ones and non-UTF-8 results are kept as is.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).unwrap_or_else(|_| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_scripts_unsafe_links_and_untrusted_images() {
+        let config = SanitizeConfig {
+            enabled: true,
+            image_hosts: vec!["docs.example.com".to_string()],
+        };
+        let answer = "See <b>this</b><script>alert(1)</script> and \
+                      [docs](https://docs.example.com) or [click]( JavaScript:steal).\n\
+                      ![logo](https://cdn.docs.example.com/logo.png) \
+                      ![pixel](https://tracker.io/p.gif) <https://x.io> <javascript:alert(1)>\n\
+                      Use `<script>` tags.\n\
+                      ```html\n<script src=\"app.js\"></script>\n```";
+
+        assert_eq!(
+            config.sanitize(answer),
+            "See this and [docs](https://docs.example.com) or click.\n\
+             ![logo](https://cdn.docs.example.com/logo.png) pixel <https://x.io> \n\
+             Use `<script>` tags.\n\
+             ```html\n<script src=\"app.js\"></script>\n```"
+        );
+    }
+
+    #[test]
+    fn test_encoded_schemes_and_reference_definitions_are_checked() {
+        let config = SanitizeConfig::default();
+        assert_eq!(config.sanitize("[x](javascript&#58;alert(1))"), "x)");
+        assert_eq!(config.sanitize("[x](javascript&colon;alert(1))"), "x)");
+        assert_eq!(config.sanitize("[x](java&#x09;script:alert(1))"), "x)");
+        assert_eq!(config.sanitize("[x](javascript%3Aalert(1))"), "x)");
+        assert_eq!(config.sanitize("[x](javascript&amp;#58;x)"), "x");
+        assert_eq!(
+            config.sanitize("[docs](https://x.io/a?b=1&amp;c=%20)"),
+            "[docs](https://x.io/a?b=1&amp;c=%20)"
+        );
+
+        assert_eq!(
+            config
+                .sanitize("See [here][1].\n\n[1]: javascript:alert(1)\n  [2]: <data:text/html,x>"),
+            "See [here][1].\n"
+        );
+        assert_eq!(
+            config.sanitize("See [here][1].\n\n[1]: https://x.io \"Docs\""),
+            "See [here][1].\n\n[1]: https://x.io \"Docs\""
+        );
+    }
+
+    #[test]
+    fn test_partial_answers_hold_back_unfinished_markup() {
+        let config = SanitizeConfig::default();
+        assert_eq!(config.sanitize_partial("Hi <scr"), "Hi ");
+        assert_eq!(
+            config.sanitize_partial("Hi <script>x</script> there"),
+            "Hi  there"
+        );
+        assert_eq!(config.sanitize_partial("See [x](javascr"), "See ");
+        assert_eq!(
+            config.sanitize_partial("See [x](javascript:y) and"),
+            "See x and"
+        );
+        assert_eq!(config.sanitize_partial("a [b] c"), "a ");
+    }
+
+    #[test]
+    fn test_unclosed_script_drops_the_rest() {
+        let config = SanitizeConfig::default();
+        assert_eq!(config.sanitize("Hi <SCRIPT>steal()"), "Hi ");
+        assert_eq!(config.sanitize("1 < 2 and a<b"), "1 < 2 and a<b");
+    }
+
+    #[test]
+    fn test_cleans_source_links_from_metadata() {
+        use crate::infrastructure::permalinks::{append_source_links, SourceLink};
+
+        let links = [
+            SourceLink {
+                title: "Guide<img src=x onerror=alert(1)>".to_string(),
+                url: "https://example.com/guide".to_string(),
+            },
+            SourceLink {
+                title: "Notes".to_string(),
+                url: "javascript:alert(1)".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            SanitizeConfig::default().sanitize(&append_source_links("Done.", &links)),
+            "Done.\n\nSources:\n- [Guide](https://example.com/guide)\n- Notes"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::domain::{ChunkMetadata, Freshness, SearchResult};
+use crate::infrastructure::config::{ContextFormat, ContextFormatConfig};
+
+/// Renders retrieved chunks into the text the model sees.
+#[derive(Debug, Clone)]
+pub struct ContextFormatter {
+    config: ContextFormatConfig,
+}
+
+impl ContextFormatter {
+    pub fn new(config: ContextFormatConfig) -> Self {
+        Self { config }
+    }
+
+    /// Formats `results` in order, stopping before the block that would pass
+    /// `max_chars`. A first block that alone is too long is cut to fit.
+    pub fn format(&self, results: &[SearchResult]) -> String {
+        let max_chars = self.config.max_chars.unwrap_or(usize::MAX);
+        let mut output = String::new();
+        let mut used = 0;
+
+        for (i, result) in results.iter().enumerate() {
+            let block = self.block(i + 1, result);
+            let separator = if output.is_empty() { 0 } else { 2 };
+            let size = block.chars().count();
+
+            if used + separator + size > max_chars {
+                if output.is_empty() {
+                    output = block.chars().take(max_chars).collect();
+                }
+                break;
+            }
+            if separator > 0 {
+                output.push_str("\n\n");
+            }
+            output.push_str(&block);
+            used += separator + size;
+        }
+
+        output
+    }
+
+    fn block(&self, index: usize, result: &SearchResult) -> String {
+        let source = self
+            .config
+            .include_sources
+            .then(|| result.chunk.metadata.citation())
+            .flatten();
+        let score = self
+            .config
+            .include_scores
+            .then(|| format!("{:.2}", result.score));
+        let status = freshness_note(&result.chunk.metadata);
+        let title = self
+            .config
+            .include_sources
+            .then_some(result.chunk.metadata.title.as_deref())
+            .flatten();
+        let content = &result.chunk.content;
+
+        match self.config.format {
+            ContextFormat::Numbered => {
+                let details: Vec<String> = title
+                    .map(|t| format!("title: {t}"))
+                    .into_iter()
+                    .chain(source.map(|s| format!("source: {s}")))
+                    .chain(score.map(|s| format!("score: {s}")))
+                    .chain(status)
+                    .collect();
+                if details.is_empty() {
+                    format!("[{index}] {content}")
+                } else {
+                    format!("[{index}] ({}) {content}", details.join(", "))
+                }
+            }
+            ContextFormat::Xml => {
+                let mut attributes = format!("index=\"{index}\"");
+                if let Some(title) = title {
+                    attributes.push_str(&format!(" title=\"{}\"", escape_xml(title)));
+                }
+                if let Some(source) = source {
+                    attributes.push_str(&format!(" source=\"{}\"", escape_xml(&source)));
+                }
+                if let Some(score) = score {
+                    attributes.push_str(&format!(" score=\"{score}\""));
+                }
+                if let Some(status) = status {
+                    attributes.push_str(&format!(" status=\"{status}\""));
+                }
+                format!(
+                    "<document {attributes}>\n{}\n</document>",
+                    escape_xml(content)
+                )
+            }
+            ContextFormat::Markdown => {
+                let label: Vec<&str> = title.into_iter().chain(source.as_deref()).collect();
+                let mut header = format!("**[{index}]");
+                if !label.is_empty() {
+                    header.push_str(&format!(" {}", label.join(" — ")));
+                }
+                header.push_str("**");
+                if let Some(score) = score {
+                    header.push_str(&format!(" (score {score})"));
+                }
+                if let Some(status) = status {
+                    header.push_str(&format!(" ({status})"));
+                }
+                let quoted: Vec<String> = content.lines().map(|l| format!("> {l}")).collect();
+                format!("{header}\n{}", quoted.join("\n"))
+            }
+        }
+    }
+}
+
+/// Warns the model off quoting expired or overdue content as current.
+fn freshness_note(metadata: &ChunkMetadata) -> Option<String> {
+    match metadata.freshness(chrono::Utc::now()) {
+        Freshness::Current => None,
+        Freshness::ReviewDue => metadata
+            .review_by
+            .map(|at| format!("review overdue since {}", at.format("%Y-%m-%d"))),
+        Freshness::Expired => metadata
+            .expires_at
+            .map(|at| format!("expired {}", at.format("%Y-%m-%d"))),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ChunkMetadata, DocumentChunk};
+    use uuid::Uuid;
+
+    fn results() -> Vec<SearchResult> {
+        let metadata = ChunkMetadata {
+            source_path: Some("docs/a.md".to_string()),
+            title: Some("Alpha".to_string()),
+            ..Default::default()
+        };
+        vec![
+            SearchResult {
+                chunk: DocumentChunk::new(Uuid::nil(), "Alpha <1>", 0).with_metadata(metadata),
+                score: 0.912,
+            },
+            SearchResult {
+                chunk: DocumentChunk::new(Uuid::nil(), "Beta\nGamma", 1),
+                score: 0.5,
+            },
+        ]
+    }
+
+    fn formatter(format: ContextFormat, include_scores: bool) -> ContextFormatter {
+        ContextFormatter::new(ContextFormatConfig {
+            format,
+            include_scores,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(
+            formatter(ContextFormat::Numbered, false).format(&results()),
+            "[1] (title: Alpha, source: docs/a.md) Alpha <1>\n\n[2] Beta\nGamma"
+        );
+        assert_eq!(
+            formatter(ContextFormat::Xml, true).format(&results()),
+            "<document index=\"1\" title=\"Alpha\" source=\"docs/a.md\" score=\"0.91\">\nAlpha &lt;1&gt;\n</document>\n\n\
+             <document index=\"2\" score=\"0.50\">\nBeta\nGamma\n</document>"
+        );
+        assert_eq!(
+            formatter(ContextFormat::Markdown, false).format(&results()),
+            "**[1] Alpha — docs/a.md**\n> Alpha <1>\n\n**[2]**\n> Beta\n> Gamma"
+        );
+    }
+
+    #[test]
+    fn test_flags_expired_content() {
+        let expires_at = "2020-01-31T00:00:00Z".parse().unwrap();
+        let metadata = ChunkMetadata {
+            expires_at: Some(expires_at),
+            ..Default::default()
+        };
+        let results = vec![SearchResult {
+            chunk: DocumentChunk::new(Uuid::nil(), "Old policy", 0).with_metadata(metadata),
+            score: 0.8,
+        }];
+
+        assert_eq!(
+            formatter(ContextFormat::Numbered, false).format(&results),
+            "[1] (expired 2020-01-31) Old policy"
+        );
+    }
+
+    #[test]
+    fn test_max_chars_drops_later_chunks() {
+        let config = |max_chars| ContextFormatConfig {
+            include_sources: false,
+            max_chars: Some(max_chars),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ContextFormatter::new(config(20)).format(&results()),
+            "[1] Alpha <1>"
+        );
+        assert_eq!(ContextFormatter::new(config(5)).format(&results()), "[1] A");
+    }
+}
@@ -2,10 +2,15 @@ use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-use crate::application::RagService;
-use crate::infrastructure::config::KnowledgeBaseToolConfig;
+use crate::application::{RagService, RetrievalOptions, RetrievalStrategy};
+use crate::domain::{SearchFilter, SearchResult};
+use crate::infrastructure::config::{
+    ContextFormatConfig, KnowledgeBaseToolConfig, ToolLimitOverrides,
+};
+use crate::infrastructure::latency::StageTimer;
+use crate::infrastructure::tools::ContextFormatter;
 
 #[derive(Debug, thiserror::Error)]
 #[error("Knowledge base error: {0}")]
@@ -16,15 +21,78 @@ pub struct KnowledgeBaseArgs {
     pub query: String,
 }
 
+/// Every chunk the tool returned during one agent run, with its score.
+#[derive(Debug, Default)]
+pub struct RetrievedSources {
+    sources: Mutex<Vec<SearchResult>>,
+}
+
+impl RetrievedSources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, results: &[SearchResult]) {
+        let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        sources.extend_from_slice(results);
+    }
+
+    pub fn take(&self) -> Vec<SearchResult> {
+        std::mem::take(&mut *self.sources.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
 pub struct KnowledgeBaseTool {
     rag: Arc<RagService>,
     top_k: usize,
+    min_score: f32,
     config: KnowledgeBaseToolConfig,
+    timer: Option<Arc<StageTimer>>,
+    sources: Option<Arc<RetrievedSources>>,
+    filter: SearchFilter,
+    /// Returned for every query instead of searching, to replay a past chat.
+    pinned: Option<Arc<Vec<SearchResult>>>,
 }
 
 impl KnowledgeBaseTool {
     pub fn new(rag: Arc<RagService>, top_k: usize, config: KnowledgeBaseToolConfig) -> Self {
-        Self { rag, top_k, config }
+        Self {
+            rag,
+            top_k,
+            min_score: 0.0,
+            config,
+            timer: None,
+            sources: None,
+            filter: SearchFilter::default(),
+            pinned: None,
+        }
+    }
+
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    pub fn with_timer(mut self, timer: Arc<StageTimer>) -> Self {
+        self.timer = Some(timer);
+        self
+    }
+
+    pub fn with_sources(mut self, sources: Arc<RetrievedSources>) -> Self {
+        self.sources = Some(sources);
+        self
+    }
+
+    /// Restricts every search, e.g. to include one conversation's attachments.
+    pub fn with_filter(mut self, filter: SearchFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Answers every query with `results` instead of searching.
+    pub fn with_pinned_results(mut self, results: Arc<Vec<SearchResult>>) -> Self {
+        self.pinned = Some(results);
+        self
     }
 
     pub fn with_defaults(rag: Arc<RagService>) -> Self {
@@ -35,6 +103,11 @@ impl KnowledgeBaseTool {
                 name: "knowledge_base".to_string(),
                 description: "Search the knowledge base for relevant information.".to_string(),
                 no_results_message: "No relevant documents found.".to_string(),
+                collection: None,
+                strategy: RetrievalStrategy::default(),
+                limits: ToolLimitOverrides::default(),
+                context: ContextFormatConfig::default(),
+                tags: Vec::new(),
             },
         )
     }
@@ -65,18 +138,32 @@ impl Tool for KnowledgeBaseTool {
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
-        let results = self
-            .rag
-            .retrieve_top_k(&args.query, self.top_k)
-            .await
-            .map_err(|e| KnowledgeBaseError(e.to_string()))?;
-
-        let output = results
-            .iter()
-            .enumerate()
-            .map(|(i, r)| format!("[{}] {}", i + 1, r.chunk.content))
-            .collect::<Vec<_>>()
-            .join("\n\n");
+        let options = RetrievalOptions {
+            top_k: self.top_k,
+            strategy: self.config.strategy,
+            min_score: self.min_score,
+            filter: self.filter.clone().with_default_tags(&self.config.tags),
+        };
+
+        let results = match &self.pinned {
+            Some(pinned) => pinned.as_ref().clone(),
+            None => {
+                let (results, timings) = self
+                    .rag
+                    .retrieve_timed(&args.query, &options)
+                    .await
+                    .map_err(|e| KnowledgeBaseError(e.to_string()))?;
+                if let Some(timer) = &self.timer {
+                    timer.add_retrieval(&timings);
+                }
+                results
+            }
+        };
+        if let Some(sources) = &self.sources {
+            sources.record(&results);
+        }
+
+        let output = ContextFormatter::new(self.config.context.clone()).format(&results);
 
         Ok(if output.is_empty() {
             self.config.no_results_message.clone()
@@ -0,0 +1,168 @@
+use rig::completion::ToolDefinition;
+use rig::tool::Tool;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::infrastructure::config::ToolLimits;
+
+/// Enforces [`ToolLimits`] around another tool.
+///
+/// Limit breaches are reported to the model as the tool's output rather than
+/// as errors, so the agent can still answer with what it already has. Build a
+/// fresh wrapper per chat turn: the call count is never reset.
+pub struct LimitedTool<T> {
+    inner: T,
+    limits: ToolLimits,
+    calls: AtomicUsize,
+}
+
+impl<T: Tool> LimitedTool<T> {
+    pub fn new(inner: T, limits: ToolLimits) -> Self {
+        Self {
+            inner,
+            limits,
+            calls: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Cuts `output` to at most `max_bytes` on a char boundary, appending a marker.
+fn truncate_output(mut output: String, max_bytes: usize) -> String {
+    if output.len() <= max_bytes {
+        return output;
+    }
+
+    let total = output.len();
+    let mut end = max_bytes;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    output.truncate(end);
+    output.push_str(&format!(
+        "\n[output truncated: {end} of {total} bytes shown]"
+    ));
+    output
+}
+
+/// Tool output as the model would see it, unquoted if it is a plain string.
+fn render<O: Serialize>(output: &O) -> String {
+    match serde_json::to_value(output) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(e) => format!("[unserializable tool output: {e}]"),
+    }
+}
+
+impl<T: Tool> Tool for LimitedTool<T> {
+    const NAME: &'static str = T::NAME;
+
+    type Error = T::Error;
+    type Args = T::Args;
+    type Output = String;
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    async fn definition(&self, prompt: String) -> ToolDefinition {
+        self.inner.definition(prompt).await
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let name = self.inner.name();
+        let call = self.calls.fetch_add(1, Ordering::Relaxed) + 1;
+        if call > self.limits.max_calls_per_turn {
+            tracing::warn!(tool = %name, calls = call, "tool call limit reached");
+            return Ok(format!(
+                "[{name} call limit of {} per turn reached; answer with the information already gathered]",
+                self.limits.max_calls_per_turn
+            ));
+        }
+
+        let timeout = Duration::from_secs(self.limits.timeout_seconds);
+        match tokio::time::timeout(timeout, self.inner.call(args)).await {
+            Ok(output) => Ok(truncate_output(
+                render(&output?),
+                self.limits.max_output_bytes,
+            )),
+            Err(_) => {
+                tracing::warn!(tool = %name, ?timeout, "tool call timed out");
+                Ok(format!(
+                    "[{name} timed out after {}s; answer with the information already gathered]",
+                    self.limits.timeout_seconds
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("echo failed")]
+    struct EchoError;
+
+    struct Echo;
+
+    impl Tool for Echo {
+        const NAME: &'static str = "echo";
+
+        type Error = EchoError;
+        type Args = String;
+        type Output = String;
+
+        async fn definition(&self, _prompt: String) -> ToolDefinition {
+            ToolDefinition {
+                name: Self::NAME.to_string(),
+                description: "Echoes its input.".to_string(),
+                parameters: serde_json::json!({}),
+            }
+        }
+
+        async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+            if args == "slow" {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            Ok(args)
+        }
+    }
+
+    fn limits(timeout_seconds: u64) -> ToolLimits {
+        ToolLimits {
+            timeout_seconds,
+            max_output_bytes: 8,
+            max_calls_per_turn: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limits_are_enforced() {
+        let tool = LimitedTool::new(Echo, limits(5));
+        assert_eq!(
+            tool.call("0123456789".to_string()).await.unwrap(),
+            "01234567\n[output truncated: 8 of 10 bytes shown]"
+        );
+        assert_eq!(tool.call("hi".to_string()).await.unwrap(), "hi");
+        assert!(tool
+            .call("hi".to_string())
+            .await
+            .unwrap()
+            .contains("call limit of 2 per turn"));
+
+        let slow = LimitedTool::new(Echo, limits(0));
+        assert!(slow
+            .call("slow".to_string())
+            .await
+            .unwrap()
+            .contains("timed out after 0s"));
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        let output = truncate_output("ข้อความ".to_string(), 4);
+        assert_eq!(output, "ข\n[output truncated: 3 of 21 bytes shown]");
+    }
+}
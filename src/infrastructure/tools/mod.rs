@@ -1,3 +1,7 @@
+mod context;
 mod knowledge_base;
+mod limits;
 
-pub use knowledge_base::KnowledgeBaseTool;
+pub use context::ContextFormatter;
+pub use knowledge_base::{KnowledgeBaseArgs, KnowledgeBaseTool, RetrievedSources};
+pub use limits::LimitedTool;
@@ -0,0 +1,88 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::DocumentChunk;
+
+/// Term frequency saturation.
+const K1: f32 = 1.2;
+/// Document length normalization.
+const B: f32 = 0.75;
+
+/// Lowercased runs of letters, digits and `_`, so identifiers like
+/// `ERR_4012` or `v2` survive as single terms.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// BM25 scores of `chunks` against `query`, best first, omitting chunks that
+/// share no term with it. Corpus statistics come from `chunks` alone.
+pub fn rank<'a>(query: &str, chunks: &[&'a DocumentChunk]) -> Vec<(&'a DocumentChunk, f32)> {
+    let terms: HashSet<String> = tokenize(query).into_iter().collect();
+    if terms.is_empty() || chunks.is_empty() {
+        return Vec::new();
+    }
+
+    let documents: Vec<HashMap<String, usize>> = chunks
+        .iter()
+        .map(|chunk| {
+            let mut frequencies = HashMap::new();
+            for token in tokenize(&chunk.content) {
+                *frequencies.entry(token).or_insert(0) += 1;
+            }
+            frequencies
+        })
+        .collect();
+    let lengths: Vec<usize> = documents.iter().map(|d| d.values().sum()).collect();
+    let count = chunks.len() as f32;
+    let average_length = (lengths.iter().sum::<usize>() as f32 / count).max(1.0);
+
+    let idf: HashMap<&str, f32> = terms
+        .iter()
+        .map(|term| {
+            let containing = documents.iter().filter(|d| d.contains_key(term)).count() as f32;
+            let idf = ((count - containing + 0.5) / (containing + 0.5) + 1.0).ln();
+            (term.as_str(), idf)
+        })
+        .collect();
+
+    let mut scored: Vec<(&DocumentChunk, f32)> = chunks
+        .iter()
+        .zip(documents.iter().zip(&lengths))
+        .filter_map(|(chunk, (frequencies, &length))| {
+            let norm = K1 * (1.0 - B + B * length as f32 / average_length);
+            let score: f32 = idf
+                .iter()
+                .filter_map(|(term, idf)| {
+                    let tf = *frequencies.get(*term)? as f32;
+                    Some(idf * tf * (K1 + 1.0) / (tf + norm))
+                })
+                .sum();
+            (score > 0.0).then_some((*chunk, score))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_rank_prefers_rare_exact_terms() {
+        let chunks = [
+            DocumentChunk::new(Uuid::nil(), "The printer shows an error.", 0),
+            DocumentChunk::new(Uuid::nil(), "Error ERR_4012 means the tray is empty.", 1),
+            DocumentChunk::new(Uuid::nil(), "Unrelated text.", 2),
+        ];
+        let refs: Vec<&DocumentChunk> = chunks.iter().collect();
+
+        let ranked = rank("what is err_4012 error", &refs);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0.chunk_index, 1);
+    }
+}
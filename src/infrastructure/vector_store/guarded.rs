@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::{
+    ports::{MalformedPoint, PointPage, VectorStore},
+    DocumentChunk, DomainError, Embedding, ErrorKind, SearchFilter, SearchResult,
+};
+use crate::infrastructure::circuit_breaker::CircuitBreaker;
+
+/// A vector store behind a [`CircuitBreaker`]: transient errors (connection
+/// failures, timeouts) count as failures, and while the breaker is open every call
+/// fails immediately.
+pub struct GuardedVectorStore {
+    inner: Arc<dyn VectorStore>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl GuardedVectorStore {
+    pub fn new(inner: Arc<dyn VectorStore>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+
+    async fn guard<T>(
+        &self,
+        call: impl Future<Output = Result<T, DomainError>>,
+    ) -> Result<T, DomainError> {
+        if self.breaker.is_open() {
+            return Err(DomainError::external(
+                "vector store unavailable (circuit open)",
+            ));
+        }
+        let result = call.await;
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(e) if e.kind() == ErrorKind::Transient => self.breaker.record_failure(),
+            Err(_) => {}
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl VectorStore for GuardedVectorStore {
+    async fn upsert(
+        &self,
+        chunk: &DocumentChunk,
+        embedding: &Embedding,
+    ) -> Result<(), DomainError> {
+        self.guard(self.inner.upsert(chunk, embedding)).await
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.inner.dimension()
+    }
+
+    async fn search(
+        &self,
+        query: &Embedding,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.guard(self.inner.search(query, top_k, filter)).await
+    }
+
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.guard(self.inner.keyword_search(query, top_k, filter))
+            .await
+    }
+
+    async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError> {
+        self.guard(self.inner.delete_by_document(document_id)).await
+    }
+
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.guard(self.inner.list_chunks()).await
+    }
+
+    async fn document_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.guard(self.inner.document_chunks(document_id)).await
+    }
+
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        self.guard(self.inner.scroll_points(offset, limit)).await
+    }
+
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        self.guard(self.inner.vectors(ids)).await
+    }
+
+    async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+        self.guard(self.inner.malformed_points()).await
+    }
+
+    async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+        self.guard(self.inner.rewrite_payload(chunk)).await
+    }
+}
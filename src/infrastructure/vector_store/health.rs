@@ -0,0 +1,50 @@
+use deadpool_redis::{redis, Pool};
+use qdrant_client::Qdrant;
+use std::sync::Arc;
+
+use super::redis::index_name;
+use crate::domain::DomainError;
+
+/// Checks that the configured vector store backend is reachable and holds a
+/// collection, without opening a full store.
+#[derive(Clone)]
+pub enum VectorStoreHealth {
+    Qdrant(Arc<Qdrant>),
+    /// RediSearch on the queue's Redis.
+    Redis(Pool),
+}
+
+impl VectorStoreHealth {
+    pub fn qdrant(url: &str) -> Result<Self, DomainError> {
+        let client = Qdrant::from_url(url)
+            .build()
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        Ok(Self::Qdrant(Arc::new(client)))
+    }
+
+    /// Fails when the backend is unreachable or `collection` doesn't exist.
+    pub async fn check(&self, collection: &str) -> Result<(), DomainError> {
+        let exists = match self {
+            Self::Qdrant(client) => client
+                .collection_exists(collection)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?,
+            Self::Redis(pool) => {
+                let mut conn = pool
+                    .get()
+                    .await
+                    .map_err(|e| DomainError::external(format!("Redis pool error: {e}")))?;
+                redis::cmd("FT.INFO")
+                    .arg(index_name(collection))
+                    .query_async::<redis::Value>(&mut conn)
+                    .await
+                    .is_ok()
+            }
+        };
+        if exists {
+            Ok(())
+        } else {
+            Err(DomainError::not_found(format!("collection {collection}")))
+        }
+    }
+}
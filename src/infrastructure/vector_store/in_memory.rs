@@ -1,19 +1,79 @@
 use async_trait::async_trait;
-use std::sync::RwLock;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
-use crate::domain::{ports::VectorStore, DocumentChunk, DomainError, Embedding, SearchResult};
+use super::bm25;
+use crate::domain::{
+    ports::{PointPage, VectorStore},
+    DocumentChunk, DomainError, Embedding, EmbeddingPrecision, QuantizedEmbedding, SearchFilter,
+    SearchResult,
+};
 
+/// Chunks are shared behind `Arc` so reads never deep-copy the store; only
+/// the results a caller keeps are cloned out.
 pub struct InMemoryVectorStore {
-    chunks: RwLock<Vec<(DocumentChunk, Embedding)>>,
+    chunks: RwLock<Points>,
+    precision: EmbeddingPrecision,
+}
+
+/// Points in insertion order, indexed by chunk id.
+#[derive(Default)]
+struct Points {
+    entries: Vec<(Arc<DocumentChunk>, QuantizedEmbedding)>,
+    positions: HashMap<Uuid, usize>,
+}
+
+impl Points {
+    fn reindex(&mut self) {
+        self.positions = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(position, (chunk, _))| (chunk.id, position))
+            .collect();
+    }
 }
 
 impl InMemoryVectorStore {
     pub fn new() -> Self {
         Self {
-            chunks: RwLock::new(Vec::new()),
+            chunks: RwLock::new(Points::default()),
+            precision: EmbeddingPrecision::default(),
         }
     }
+
+    /// Holds embeddings upserted from now on at `precision`, trading a
+    /// little recall for memory on large datasets.
+    pub fn with_precision(mut self, precision: EmbeddingPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+}
+
+/// A candidate's position in the store and its score. Ordered by score,
+/// with earlier chunks ranking higher on ties.
+#[derive(PartialEq)]
+struct Scored {
+    score: f32,
+    index: usize,
+}
+
+impl Eq for Scored {}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl Default for InMemoryVectorStore {
@@ -34,8 +94,18 @@ impl VectorStore for InMemoryVectorStore {
             .write()
             .map_err(|e| DomainError::internal(e.to_string()))?;
 
-        store.retain(|(c, _)| c.id != chunk.id);
-        store.push((chunk.clone(), embedding.clone()));
+        let entry = (
+            Arc::new(chunk.clone()),
+            QuantizedEmbedding::new(embedding.clone(), self.precision),
+        );
+        match store.positions.get(&chunk.id) {
+            Some(&position) => store.entries[position] = entry,
+            None => {
+                let position = store.entries.len();
+                store.positions.insert(chunk.id, position);
+                store.entries.push(entry);
+            }
+        }
         Ok(())
     }
 
@@ -43,29 +113,65 @@ impl VectorStore for InMemoryVectorStore {
         &self,
         query: &Embedding,
         top_k: usize,
+        filter: &SearchFilter,
     ) -> Result<Vec<SearchResult>, DomainError> {
         let store = self
             .chunks
             .read()
             .map_err(|e| DomainError::internal(e.to_string()))?;
 
-        let mut results: Vec<(SearchResult, f32)> = store
-            .iter()
-            .map(|(chunk, embedding)| {
-                let score = query.cosine_similarity(embedding);
-                (
-                    SearchResult {
-                        chunk: chunk.clone(),
-                        score,
-                    },
-                    score,
-                )
+        // Min-heap of the best `top_k` so far; chunks are cloned only once
+        // they have won a place.
+        let mut best = BinaryHeap::with_capacity(top_k.saturating_add(1).min(store.entries.len()));
+        for (index, (chunk, embedding)) in store.entries.iter().enumerate() {
+            if top_k == 0 || !filter.matches(chunk) {
+                continue;
+            }
+            best.push(Reverse(Scored {
+                score: embedding.cosine_similarity(query),
+                index,
+            }));
+            if best.len() > top_k {
+                best.pop();
+            }
+        }
+
+        Ok(best
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(Scored { score, index })| SearchResult {
+                chunk: store.entries[index].0.as_ref().clone(),
+                score,
             })
-            .collect();
+            .collect())
+    }
 
-        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let store = self
+            .chunks
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+
+        let candidates: Vec<&DocumentChunk> = store
+            .entries
+            .iter()
+            .map(|(chunk, _)| chunk.as_ref())
+            .filter(|chunk| filter.matches(chunk))
+            .collect();
 
-        Ok(results.into_iter().take(top_k).map(|(r, _)| r).collect())
+        Ok(bm25::rank(query, &candidates)
+            .into_iter()
+            .take(top_k)
+            .map(|(chunk, score)| SearchResult {
+                chunk: chunk.clone(),
+                score,
+            })
+            .collect())
     }
 
     async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError> {
@@ -74,14 +180,72 @@ impl VectorStore for InMemoryVectorStore {
             .write()
             .map_err(|e| DomainError::internal(e.to_string()))?;
 
-        store.retain(|(chunk, _)| chunk.document_id != document_id);
+        store
+            .entries
+            .retain(|(chunk, _)| chunk.document_id != document_id);
+        store.reindex();
         Ok(())
     }
+
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+        let store = self
+            .chunks
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+
+        Ok(store
+            .entries
+            .iter()
+            .map(|(chunk, _)| chunk.as_ref().clone())
+            .collect())
+    }
+
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        let store = self
+            .chunks
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| {
+                let (_, embedding) = &store.entries[*store.positions.get(id)?];
+                Some((*id, embedding.dequantize()))
+            })
+            .collect())
+    }
+
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        let store = self
+            .chunks
+            .read()
+            .map_err(|e| DomainError::internal(e.to_string()))?;
+
+        let entries = &store.entries;
+        let start = match offset {
+            Some(id) => store.positions.get(&id).copied().unwrap_or(entries.len()),
+            None => 0,
+        };
+        let end = (start + limit.max(1)).min(entries.len());
+
+        Ok(PointPage {
+            points: entries[start..end]
+                .iter()
+                .map(|(chunk, embedding)| (chunk.as_ref().clone(), embedding.dequantize()))
+                .collect(),
+            next: entries.get(end).map(|(c, _)| c.id),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::ChunkMetadata;
 
     #[tokio::test]
     async fn test_upsert_and_search() {
@@ -94,12 +258,178 @@ mod tests {
         store.upsert(&chunk, &embedding).await.unwrap();
 
         let query = Embedding::new(vec![1.0, 0.0, 0.0]);
-        let results = store.search(&query, 1).await.unwrap();
+        let results = store
+            .search(&query, 1, &SearchFilter::default())
+            .await
+            .unwrap();
 
         assert_eq!(results.len(), 1);
         assert!((results[0].score - 1.0).abs() < 0.001);
     }
 
+    #[tokio::test]
+    async fn test_search_keeps_the_best_top_k_in_order() {
+        let store = InMemoryVectorStore::new();
+        let doc_id = Uuid::new_v4();
+        for (i, y) in [0.9, 0.0, 0.5, 0.1, 0.5].into_iter().enumerate() {
+            let chunk = DocumentChunk::new(doc_id, format!("chunk {i}"), i);
+            store
+                .upsert(&chunk, &Embedding::new(vec![1.0 - y, y, 0.0]))
+                .await
+                .unwrap();
+        }
+
+        let query = Embedding::new(vec![1.0, 0.0, 0.0]);
+        let results = store
+            .search(&query, 3, &SearchFilter::default())
+            .await
+            .unwrap();
+
+        let order: Vec<usize> = results.iter().map(|r| r.chunk.chunk_index).collect();
+        assert_eq!(order, vec![1, 3, 2]);
+        assert!(store
+            .search(&query, 0, &SearchFilter::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quantized_store_ranks_like_f32() {
+        let doc_id = Uuid::new_v4();
+        let chunks: Vec<(DocumentChunk, Embedding)> =
+            [[0.9, 0.1, 0.3], [0.2, 0.8, 0.1], [0.5, 0.5, 0.5]]
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    (
+                        DocumentChunk::new(doc_id, "chunk", i),
+                        Embedding::new(v.to_vec()),
+                    )
+                })
+                .collect();
+        let query = Embedding::new(vec![1.0, 0.2, 0.2]);
+
+        for precision in [EmbeddingPrecision::F16, EmbeddingPrecision::Int8] {
+            let store = InMemoryVectorStore::new().with_precision(precision);
+            for (chunk, embedding) in &chunks {
+                store.upsert(chunk, embedding).await.unwrap();
+            }
+            let results = store
+                .search(&query, 3, &SearchFilter::default())
+                .await
+                .unwrap();
+            let order: Vec<usize> = results.iter().map(|r| r.chunk.chunk_index).collect();
+            assert_eq!(order, vec![0, 2, 1]);
+            let page = store.scroll_points(None, 3).await.unwrap();
+            assert!((page.points[0].1.as_slice()[0] - 0.9).abs() < 0.01);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_applies_filter() {
+        let store = InMemoryVectorStore::new();
+        let embedding = Embedding::new(vec![1.0, 0.0, 0.0]);
+        let pdf = DocumentChunk::new(Uuid::new_v4(), "manual", 0).with_metadata(ChunkMetadata {
+            content_type: Some("application/pdf".to_string()),
+            tags: vec!["emea".to_string()],
+            ..Default::default()
+        });
+        let text = DocumentChunk::new(Uuid::new_v4(), "notes", 0);
+        store.upsert(&pdf, &embedding).await.unwrap();
+        store.upsert(&text, &embedding).await.unwrap();
+
+        let by_tag = SearchFilter {
+            tags: vec!["emea".to_string(), "apac".to_string()],
+            ..Default::default()
+        };
+        let by_document = SearchFilter {
+            document_ids: vec![text.document_id],
+            content_types: vec!["application/pdf".to_string()],
+            ..Default::default()
+        };
+
+        let results = store.search(&embedding, 10, &by_tag).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk.id, pdf.id);
+        assert!(store
+            .search(&embedding, 10, &by_document)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_attachments_only_match_their_conversation() {
+        let store = InMemoryVectorStore::new();
+        let embedding = Embedding::new(vec![1.0, 0.0, 0.0]);
+        let conversation_id = Uuid::new_v4();
+        let shared = DocumentChunk::new(Uuid::new_v4(), "shared", 0);
+        let attached =
+            DocumentChunk::new(Uuid::new_v4(), "upload", 0).with_metadata(ChunkMetadata {
+                conversation_id: Some(conversation_id),
+                ..Default::default()
+            });
+        store.upsert(&shared, &embedding).await.unwrap();
+        store.upsert(&attached, &embedding).await.unwrap();
+
+        let count = |filter: SearchFilter| {
+            let store = &store;
+            let embedding = &embedding;
+            async move { store.search(embedding, 10, &filter).await.unwrap().len() }
+        };
+        assert_eq!(count(SearchFilter::default()).await, 1);
+        assert_eq!(
+            count(SearchFilter {
+                conversation_id: Some(Uuid::new_v4()),
+                ..Default::default()
+            })
+            .await,
+            1
+        );
+        assert_eq!(
+            count(SearchFilter {
+                conversation_id: Some(conversation_id),
+                ..Default::default()
+            })
+            .await,
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_documents_only_match_their_tenant() {
+        let store = InMemoryVectorStore::new();
+        let embedding = Embedding::new(vec![1.0, 0.0, 0.0]);
+        let owned = |tenant: &str| {
+            DocumentChunk::new(Uuid::new_v4(), "private", 0).with_metadata(ChunkMetadata {
+                tenant_id: Some(tenant.to_string()),
+                ..Default::default()
+            })
+        };
+        store
+            .upsert(&DocumentChunk::new(Uuid::new_v4(), "shared", 0), &embedding)
+            .await
+            .unwrap();
+        store.upsert(&owned("acme"), &embedding).await.unwrap();
+        store.upsert(&owned("globex"), &embedding).await.unwrap();
+
+        let acme = SearchFilter {
+            tenant_id: Some("acme".to_string()),
+            ..Default::default()
+        };
+        let results = store.search(&embedding, 10, &acme).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|r| r.chunk.metadata.tenant_id.as_deref() != Some("globex")));
+        let shared = store
+            .keyword_search("private shared", 10, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(shared.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_delete_by_document() {
         let store = InMemoryVectorStore::new();
@@ -112,8 +442,38 @@ mod tests {
         store.delete_by_document(doc_id).await.unwrap();
 
         let query = Embedding::new(vec![1.0, 0.0, 0.0]);
-        let results = store.search(&query, 10).await.unwrap();
+        let results = store
+            .search(&query, 10, &SearchFilter::default())
+            .await
+            .unwrap();
 
         assert!(results.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_upsert_replaces_by_id_and_vectors_follow_deletes() {
+        let store = InMemoryVectorStore::new();
+        let first = DocumentChunk::new(Uuid::new_v4(), "first", 0);
+        let second = DocumentChunk::new(Uuid::new_v4(), "second", 0);
+        store
+            .upsert(&first, &Embedding::new(vec![1.0, 0.0]))
+            .await
+            .unwrap();
+        store
+            .upsert(&second, &Embedding::new(vec![0.0, 1.0]))
+            .await
+            .unwrap();
+        store
+            .upsert(&first, &Embedding::new(vec![0.6, 0.8]))
+            .await
+            .unwrap();
+        assert_eq!(store.list_chunks().await.unwrap().len(), 2);
+
+        store.delete_by_document(first.document_id).await.unwrap();
+        let vectors = store.vectors(&[first.id, second.id]).await.unwrap();
+        assert_eq!(vectors.len(), 1);
+        assert_eq!(vectors[&second.id].as_slice(), &[0.0, 1.0]);
+        let page = store.scroll_points(Some(second.id), 10).await.unwrap();
+        assert_eq!(page.points[0].0.id, second.id);
+    }
 }
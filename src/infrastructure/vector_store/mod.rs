@@ -1,5 +1,35 @@
+mod bm25;
+mod guarded;
+mod health;
 mod in_memory;
 mod qdrant;
+mod redis;
+mod snapshots;
+mod switchable;
 
+pub use guarded::GuardedVectorStore;
+pub use health::VectorStoreHealth;
 pub use in_memory::InMemoryVectorStore;
 pub use qdrant::QdrantVectorStore;
+pub use redis::RedisVectorStore;
+pub use snapshots::{QdrantSnapshots, SnapshotInfo};
+pub use switchable::SwitchableVectorStore;
+
+use crate::domain::{DomainError, Embedding};
+
+/// Refuses an embedding whose length differs from the collection's vectors,
+/// which the backends would otherwise reject with an opaque error.
+fn check_dimension(
+    collection: &str,
+    dimension: usize,
+    embedding: &Embedding,
+) -> Result<(), DomainError> {
+    if embedding.dimension() == dimension {
+        return Ok(());
+    }
+    Err(DomainError::validation(format!(
+        "collection {collection} stores {dimension}-dimensional vectors but the embedding \
+         is {}-dimensional; check embedding.model and embedding.dimension",
+        embedding.dimension()
+    )))
+}
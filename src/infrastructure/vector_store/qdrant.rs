@@ -1,21 +1,131 @@
 use async_trait::async_trait;
 use qdrant_client::qdrant::{
-    Condition, CreateCollectionBuilder, DeletePointsBuilder, Distance, Filter, PointStruct,
-    SearchPointsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+    point_id::PointIdOptions, quantization_config, quantization_config_diff, vector_output,
+    vectors_config, vectors_output::VectorsOptions, CompressionRatio, Condition,
+    CreateCollectionBuilder, CreateFieldIndexCollectionBuilder, DeletePointsBuilder, Distance,
+    FieldType, Filter, GetPointsBuilder, HnswConfigDiff, PointId, PointStruct, PointsIdsList,
+    ProductQuantization, ProductQuantizationBuilder, QuantizationType, ScalarQuantization,
+    ScalarQuantizationBuilder, ScrollPointsBuilder, SearchPointsBuilder, SetPayloadPointsBuilder,
+    TextIndexParamsBuilder, TokenizerType, UpdateCollectionBuilder, UpsertPointsBuilder, Value,
+    VectorParamsBuilder, VectorsOutput,
 };
-use qdrant_client::{Payload, Qdrant};
+use qdrant_client::{Payload, Qdrant, QdrantError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tonic::Code;
 use uuid::Uuid;
 
-use crate::domain::{ports::VectorStore, DocumentChunk, DomainError, Embedding, SearchResult};
+use super::bm25;
+use crate::domain::{
+    ports::{MalformedPoint, PointPage, VectorStore},
+    ChunkMetadata, DocumentChunk, DomainError, Embedding, SearchFilter, SearchResult,
+};
+use crate::infrastructure::config::{
+    HnswConfig, PayloadIndexKind, ProductCompression, QuantizationConfig, VectorStoreConfig,
+};
+
+const SCROLL_PAGE_SIZE: u32 = 256;
+/// Most points fetched from the full-text index per keyword search.
+const KEYWORD_CANDIDATES: u32 = 500;
+/// Counter of points dropped for malformed payloads, recorded in strict mode.
+const MALFORMED_COUNTER: &str = "vector_store_malformed_points_total";
+/// Counter of reads moved off a failing replica.
+const FAILOVER_COUNTER: &str = "vector_store_replica_failovers_total";
+
+/// Payload stored with every point.
+#[derive(Debug, Serialize, Deserialize)]
+struct ChunkPayload {
+    chunk_id: Uuid,
+    document_id: Uuid,
+    content: String,
+    chunk_index: usize,
+    /// Points written before metadata was stored simply have none.
+    #[serde(default)]
+    metadata: ChunkMetadata,
+    /// Unix seconds when the point was written; missing on older points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<i64>,
+}
+
+impl ChunkPayload {
+    fn new(chunk: &DocumentChunk) -> Self {
+        Self {
+            chunk_id: chunk.id,
+            document_id: chunk.document_id,
+            content: chunk.content.clone(),
+            chunk_index: chunk.chunk_index,
+            metadata: chunk.metadata.clone(),
+            created_at: Some(chrono::Utc::now().timestamp()),
+        }
+    }
+
+    fn payload(chunk: &DocumentChunk) -> Result<Payload, DomainError> {
+        serde_json::to_value(Self::new(chunk))
+            .map_err(|e| DomainError::internal(format!("Failed to create payload: {e}")))?
+            .try_into()
+            .map_err(|_| DomainError::internal("Failed to create payload"))
+    }
+
+    fn into_chunk(self) -> DocumentChunk {
+        DocumentChunk {
+            id: self.chunk_id,
+            document_id: self.document_id,
+            content: self.content,
+            chunk_index: self.chunk_index,
+            metadata: self.metadata,
+        }
+    }
+}
 
 pub struct QdrantVectorStore {
     client: Qdrant,
     collection: String,
     dimension: usize,
+    strict_payloads: bool,
+    replicas: Vec<ReadReplica>,
+    replica_cooldown: Duration,
+    replica_timeout: Duration,
+}
+
+/// A Qdrant endpoint serving searches, ranked by its recent latency.
+struct ReadReplica {
+    url: String,
+    client: Qdrant,
+    /// Moving average of successful reads in microseconds; 0 until measured,
+    /// so new replicas are tried first.
+    latency_micros: AtomicU64,
+    /// Unix milliseconds until which the replica is skipped after a failure.
+    down_until_ms: AtomicU64,
+}
+
+impl ReadReplica {
+    fn record_latency(&self, elapsed: Duration) {
+        let sample = (elapsed.as_micros() as u64).max(1);
+        let _ = self
+            .latency_micros
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+                Some(if average == 0 {
+                    sample
+                } else {
+                    (average * 4 + sample) / 5
+                })
+            });
+    }
 }
 
 impl QdrantVectorStore {
-    pub async fn new(url: &str, collection: &str, dimension: usize) -> Result<Self, DomainError> {
+    /// Connects, creating the collection if needed with the configured HNSW
+    /// and quantization settings, and indexing `payload_indexes` on it
+    /// (existing indexes are kept).
+    pub async fn new(
+        url: &str,
+        collection: &str,
+        dimension: usize,
+        config: &VectorStoreConfig,
+    ) -> Result<Self, DomainError> {
         let client = Qdrant::from_url(url)
             .build()
             .map_err(|e| DomainError::external(e.to_string()))?;
@@ -24,14 +134,160 @@ impl QdrantVectorStore {
             client,
             collection: collection.to_string(),
             dimension,
+            strict_payloads: false,
+            replicas: Vec::new(),
+            replica_cooldown: Duration::ZERO,
+            replica_timeout: Duration::ZERO,
         };
 
-        store.ensure_collection().await?;
+        store.ensure_collection(config).await?;
 
         Ok(store)
     }
 
-    async fn ensure_collection(&self) -> Result<(), DomainError> {
+    /// Logs every point dropped for a malformed payload as an error and counts
+    /// it in `vector_store_malformed_points_total`, instead of a warning.
+    pub fn with_strict_payloads(mut self, strict: bool) -> Self {
+        self.strict_payloads = strict;
+        self
+    }
+
+    /// Serves searches, keyword searches and document chunk reads from the
+    /// replicas at `urls`, which must replicate this collection. Writes and
+    /// maintenance scans stay on the primary. A replica read taking longer
+    /// than `timeout` fails over like an unreachable replica.
+    pub fn with_read_replicas(
+        mut self,
+        urls: &[String],
+        cooldown: Duration,
+        timeout: Duration,
+    ) -> Result<Self, DomainError> {
+        self.replicas = urls
+            .iter()
+            .map(|url| {
+                Ok(ReadReplica {
+                    url: url.clone(),
+                    client: Qdrant::from_url(url)
+                        .build()
+                        .map_err(|e| DomainError::external(e.to_string()))?,
+                    latency_micros: AtomicU64::new(0),
+                    down_until_ms: AtomicU64::new(0),
+                })
+            })
+            .collect::<Result<_, DomainError>>()?;
+        self.replica_cooldown = cooldown;
+        self.replica_timeout = timeout;
+        Ok(self)
+    }
+
+    /// Available replicas, fastest first.
+    fn read_order(&self, now_ms: u64) -> Vec<&ReadReplica> {
+        let mut replicas: Vec<&ReadReplica> = self
+            .replicas
+            .iter()
+            .filter(|replica| replica.down_until_ms.load(Ordering::Relaxed) <= now_ms)
+            .collect();
+        replicas.sort_by_key(|replica| replica.latency_micros.load(Ordering::Relaxed));
+        replicas
+    }
+
+    /// Runs `read` on the fastest available replica, failing over to the
+    /// next ones and finally the primary. Only replicas that are unreachable,
+    /// overloaded or time out are failed over and cooled down; an error in
+    /// the request itself, such as a bad filter, is returned as is.
+    async fn read<T, F, Fut>(&self, read: F) -> Result<T, DomainError>
+    where
+        F: Fn(Qdrant) -> Fut,
+        Fut: Future<Output = Result<T, QdrantError>>,
+    {
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        for replica in self.read_order(now_ms) {
+            let started = Instant::now();
+            let error = match tokio::time::timeout(
+                self.replica_timeout,
+                read(replica.client.clone()),
+            )
+            .await
+            {
+                Ok(Ok(value)) => {
+                    replica.record_latency(started.elapsed());
+                    return Ok(value);
+                }
+                Ok(Err(e)) if !is_transport_error(&e) => {
+                    return Err(DomainError::external(e.to_string()));
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => format!("timed out after {:?}", self.replica_timeout),
+            };
+            let until = now_ms + self.replica_cooldown.as_millis() as u64;
+            replica.down_until_ms.store(until, Ordering::Relaxed);
+            tracing::warn!(
+                replica = %replica.url,
+                collection = %self.collection,
+                error = %error,
+                "read replica failed; failing over"
+            );
+            ::metrics::counter!(FAILOVER_COUNTER, "replica" => replica.url.clone()).increment(1);
+        }
+        read(self.client.clone())
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))
+    }
+
+    /// Parses a point's payload, reporting points that don't match [`ChunkPayload`].
+    fn chunk_from_point(
+        &self,
+        id: Option<&PointId>,
+        payload: &HashMap<String, Value>,
+    ) -> Option<DocumentChunk> {
+        chunk_from_payload(payload)
+            .inspect_err(|e| {
+                let point_id = point_id_label(id);
+                if self.strict_payloads {
+                    tracing::error!(
+                        collection = %self.collection,
+                        point_id = %point_id,
+                        error = %e,
+                        "dropping point with malformed payload"
+                    );
+                    ::metrics::counter!(MALFORMED_COUNTER, "collection" => self.collection.clone())
+                        .increment(1);
+                } else {
+                    tracing::warn!(point_id = %point_id, error = %e, "skipping point with malformed payload");
+                }
+            })
+            .ok()
+    }
+
+    /// Refuses an existing collection whose vectors are a different size
+    /// than the embeddings that would be written to it.
+    async fn check_vector_size(&self) -> Result<(), DomainError> {
+        let info = self
+            .client
+            .collection_info(&self.collection)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        let size = info
+            .result
+            .and_then(|info| info.config)
+            .and_then(|config| config.params)
+            .and_then(|params| params.vectors_config)
+            .and_then(|vectors| vectors.config);
+        match size {
+            Some(vectors_config::Config::Params(params))
+                if params.size as usize != self.dimension =>
+            {
+                Err(DomainError::validation(format!(
+                    "collection {} stores {}-dimensional vectors but embeddings are {}-dimensional; \
+                     re-embed into a new collection",
+                    self.collection, params.size, self.dimension
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn ensure_collection(&self, config: &VectorStoreConfig) -> Result<(), DomainError> {
         let collections = self
             .client
             .list_collections()
@@ -43,17 +299,74 @@ impl QdrantVectorStore {
             .iter()
             .any(|c| c.name == self.collection);
 
-        if !exists {
+        let quantized = config.quantization != QuantizationConfig::None;
+        if exists {
+            self.check_vector_size().await?;
+            // Qdrant only rebuilds segments whose index settings actually change.
+            if !config.hnsw.is_default() || quantized {
+                let mut update = UpdateCollectionBuilder::new(&self.collection);
+                if !config.hnsw.is_default() {
+                    update = update.hnsw_config(hnsw_diff(&config.hnsw));
+                }
+                if let Some(quantization) =
+                    quantization::<quantization_config_diff::Quantization>(&config.quantization)
+                {
+                    update = update.quantization_config(quantization);
+                }
+                self.client
+                    .update_collection(update)
+                    .await
+                    .map_err(|e| DomainError::external(e.to_string()))?;
+            }
+        } else {
+            let mut create = CreateCollectionBuilder::new(&self.collection).vectors_config(
+                VectorParamsBuilder::new(self.dimension as u64, Distance::Cosine),
+            );
+            if !config.hnsw.is_default() {
+                create = create.hnsw_config(hnsw_diff(&config.hnsw));
+            }
+            if let Some(quantization) =
+                quantization::<quantization_config::Quantization>(&config.quantization)
+            {
+                create = create.quantization_config(quantization);
+            }
+            self.client
+                .create_collection(create)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+        }
+
+        // Creating an index that already exists is a no-op.
+        for index in &config.payload_indexes {
             self.client
-                .create_collection(
-                    CreateCollectionBuilder::new(&self.collection).vectors_config(
-                        VectorParamsBuilder::new(self.dimension as u64, Distance::Cosine),
-                    ),
+                .create_field_index(
+                    CreateFieldIndexCollectionBuilder::new(
+                        &self.collection,
+                        &index.field,
+                        field_type(index.kind),
+                    )
+                    .wait(true),
                 )
                 .await
                 .map_err(|e| DomainError::external(e.to_string()))?;
         }
 
+        // Full-text index backing `keyword_search`.
+        self.client
+            .create_field_index(
+                CreateFieldIndexCollectionBuilder::new(
+                    &self.collection,
+                    "content",
+                    FieldType::Text,
+                )
+                .field_index_params(
+                    TextIndexParamsBuilder::new(TokenizerType::Word).lowercase(true),
+                )
+                .wait(true),
+            )
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+
         Ok(())
     }
 }
@@ -65,15 +378,8 @@ impl VectorStore for QdrantVectorStore {
         chunk: &DocumentChunk,
         embedding: &Embedding,
     ) -> Result<(), DomainError> {
-        let payload: Payload = serde_json::json!({
-            "chunk_id": chunk.id.to_string(),
-            "document_id": chunk.document_id.to_string(),
-            "content": chunk.content,
-            "chunk_index": chunk.chunk_index,
-        })
-        .try_into()
-        .map_err(|_| DomainError::internal("Failed to create payload"))?;
-
+        super::check_dimension(&self.collection, self.dimension, embedding)?;
+        let payload = ChunkPayload::payload(chunk)?;
         let point = PointStruct::new(chunk.id.to_string(), embedding.as_slice().to_vec(), payload);
 
         self.client
@@ -84,41 +390,34 @@ impl VectorStore for QdrantVectorStore {
         Ok(())
     }
 
+    fn dimension(&self) -> Option<usize> {
+        Some(self.dimension)
+    }
+
     async fn search(
         &self,
         query: &Embedding,
         top_k: usize,
+        filter: &SearchFilter,
     ) -> Result<Vec<SearchResult>, DomainError> {
+        let request =
+            SearchPointsBuilder::new(&self.collection, query.as_slice().to_vec(), top_k as u64)
+                .with_payload(true)
+                .filter(payload_filter(filter));
+
         let results = self
-            .client
-            .search_points(
-                SearchPointsBuilder::new(&self.collection, query.as_slice().to_vec(), top_k as u64)
-                    .with_payload(true),
-            )
-            .await
-            .map_err(|e| DomainError::external(e.to_string()))?;
+            .read(|client| {
+                let request = request.clone();
+                async move { client.search_points(request).await }
+            })
+            .await?;
 
         let search_results: Vec<SearchResult> = results
             .result
             .into_iter()
             .filter_map(|point| {
-                let payload = point.payload;
-
-                let chunk_id: Uuid = payload.get("chunk_id")?.as_str()?.parse().ok()?;
-                let document_id: Uuid = payload.get("document_id")?.as_str()?.parse().ok()?;
-                let content = payload.get("content")?.as_str()?.to_string();
-                let chunk_index = payload.get("chunk_index")?.as_integer()? as usize;
-
-                let chunk = DocumentChunk {
-                    id: chunk_id,
-                    document_id,
-                    content,
-                    chunk_index,
-                    metadata: Default::default(),
-                };
-
                 Some(SearchResult {
-                    chunk,
+                    chunk: self.chunk_from_point(point.id.as_ref(), &point.payload)?,
                     score: point.score,
                 })
             })
@@ -127,6 +426,55 @@ impl VectorStore for QdrantVectorStore {
         Ok(search_results)
     }
 
+    /// Fetches up to [`KEYWORD_CANDIDATES`] points containing any query term
+    /// from the full-text index and ranks them with BM25, using the candidates
+    /// as the corpus for term statistics.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let terms = bm25::tokenize(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut keyword_filter = payload_filter(filter);
+        keyword_filter.should = terms
+            .into_iter()
+            .map(|term| Condition::matches_text("content", term))
+            .collect();
+
+        let request = ScrollPointsBuilder::new(&self.collection)
+            .filter(keyword_filter)
+            .limit(KEYWORD_CANDIDATES)
+            .with_payload(true)
+            .with_vectors(false);
+        let page = self
+            .read(|client| {
+                let request = request.clone();
+                async move { client.scroll(request).await }
+            })
+            .await?;
+
+        let candidates: Vec<DocumentChunk> = page
+            .result
+            .iter()
+            .filter_map(|point| self.chunk_from_point(point.id.as_ref(), &point.payload))
+            .collect();
+        let refs: Vec<&DocumentChunk> = candidates.iter().collect();
+
+        Ok(bm25::rank(query, &refs)
+            .into_iter()
+            .take(top_k)
+            .map(|(chunk, score)| SearchResult {
+                chunk: chunk.clone(),
+                score,
+            })
+            .collect())
+    }
+
     async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError> {
         let filter = Filter::must([Condition::matches("document_id", document_id.to_string())]);
 
@@ -137,4 +485,443 @@ impl VectorStore for QdrantVectorStore {
 
         Ok(())
     }
+
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+        let mut chunks = Vec::new();
+        let mut offset = None;
+
+        loop {
+            let mut request = ScrollPointsBuilder::new(&self.collection)
+                .limit(SCROLL_PAGE_SIZE)
+                .with_payload(true)
+                .with_vectors(false);
+            if let Some(offset) = offset.take() {
+                request = request.offset(offset);
+            }
+
+            let page = self
+                .client
+                .scroll(request)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+
+            chunks.extend(
+                page.result
+                    .iter()
+                    .filter_map(|point| self.chunk_from_point(point.id.as_ref(), &point.payload)),
+            );
+
+            match page.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(chunks)
+    }
+
+    async fn document_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        let filter = Filter::must([Condition::matches("document_id", document_id.to_string())]);
+        let mut chunks = Vec::new();
+        let mut offset = None;
+
+        loop {
+            let mut request = ScrollPointsBuilder::new(&self.collection)
+                .filter(filter.clone())
+                .limit(SCROLL_PAGE_SIZE)
+                .with_payload(true)
+                .with_vectors(false);
+            if let Some(offset) = offset.take() {
+                request = request.offset(offset);
+            }
+
+            let page = self
+                .read(|client| {
+                    let request = request.clone();
+                    async move { client.scroll(request).await }
+                })
+                .await?;
+
+            chunks.extend(
+                page.result
+                    .iter()
+                    .filter_map(|point| self.chunk_from_point(point.id.as_ref(), &point.payload)),
+            );
+
+            match page.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+
+        chunks.sort_by_key(|chunk| chunk.chunk_index);
+        Ok(chunks)
+    }
+
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        let mut request = ScrollPointsBuilder::new(&self.collection)
+            .limit(limit.max(1) as u32)
+            .with_payload(true)
+            .with_vectors(true);
+        if let Some(offset) = offset {
+            request = request.offset(offset.to_string());
+        }
+
+        let page = self
+            .client
+            .scroll(request)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+
+        let points = page
+            .result
+            .into_iter()
+            .filter_map(|point| {
+                let chunk = self.chunk_from_point(point.id.as_ref(), &point.payload)?;
+                Some((chunk, dense_vector(point.vectors)?))
+            })
+            .collect();
+        let next = page
+            .next_page_offset
+            .and_then(|id| match id.point_id_options? {
+                PointIdOptions::Uuid(id) => id.parse().ok(),
+                PointIdOptions::Num(id) => {
+                    // Chunks have always been written under their UUID, so a
+                    // numeric id comes from another writer; it is only logged.
+                    tracing::warn!(
+                        collection = %self.collection,
+                        point_id = id,
+                        "stopping scroll at a numeric point id not written by this service"
+                    );
+                    None
+                }
+            });
+
+        Ok(PointPage { points, next })
+    }
+
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let point_ids: Vec<PointId> = ids.iter().map(|id| id.to_string().into()).collect();
+        let request = GetPointsBuilder::new(&self.collection, point_ids)
+            .with_payload(false)
+            .with_vectors(true);
+        let points = self
+            .read(|client| {
+                let request = request.clone();
+                async move { client.get_points(request).await }
+            })
+            .await?;
+
+        Ok(points
+            .result
+            .into_iter()
+            .filter_map(|point| {
+                let Some(PointIdOptions::Uuid(id)) = point.id?.point_id_options else {
+                    return None;
+                };
+                Some((id.parse().ok()?, dense_vector(point.vectors)?))
+            })
+            .collect())
+    }
+
+    async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+        let mut malformed = Vec::new();
+        let mut offset = None;
+
+        loop {
+            let mut request = ScrollPointsBuilder::new(&self.collection)
+                .limit(SCROLL_PAGE_SIZE)
+                .with_payload(true)
+                .with_vectors(false);
+            if let Some(offset) = offset.take() {
+                request = request.offset(offset);
+            }
+
+            let page = self
+                .client
+                .scroll(request)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+
+            for point in &page.result {
+                let Err(error) = chunk_from_payload(&point.payload) else {
+                    continue;
+                };
+                let field = |key: &str| point.payload.get(key).cloned().map(Value::into_json);
+                malformed.push(MalformedPoint {
+                    id: point_id_label(point.id.as_ref()),
+                    document_id: field("document_id")
+                        .and_then(|v| v.as_str().and_then(|id| id.parse().ok())),
+                    chunk_index: field("chunk_index")
+                        .and_then(|v| v.as_u64())
+                        .map(|i| i as usize),
+                    error,
+                });
+            }
+
+            match page.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(malformed)
+    }
+
+    async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+        let payload = ChunkPayload::payload(chunk)?;
+        self.client
+            .overwrite_payload(
+                SetPayloadPointsBuilder::new(&self.collection, payload)
+                    .points_selector(PointsIdsList {
+                        ids: vec![chunk.id.to_string().into()],
+                    })
+                    .wait(true),
+            )
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Qdrant payload conditions equivalent to [`SearchFilter::matches`].
+fn payload_filter(filter: &SearchFilter) -> Filter {
+    // Conversation attachments are hidden from every other conversation.
+    let mut conditions = vec![match filter.conversation_id {
+        Some(id) => Filter::should([
+            Condition::is_empty("metadata.conversation_id"),
+            Condition::matches("metadata.conversation_id", id.to_string()),
+        ])
+        .into(),
+        None => Condition::is_empty("metadata.conversation_id"),
+    }];
+    // So are other tenants' documents.
+    conditions.push(match &filter.tenant_id {
+        Some(tenant) => Filter::should([
+            Condition::is_empty("metadata.tenant_id"),
+            Condition::matches("metadata.tenant_id", tenant.clone()),
+        ])
+        .into(),
+        None => Condition::is_empty("metadata.tenant_id"),
+    });
+    if !filter.document_ids.is_empty() {
+        let ids: Vec<String> = filter.document_ids.iter().map(Uuid::to_string).collect();
+        conditions.push(Condition::matches("document_id", ids));
+    }
+    if !filter.tags.is_empty() {
+        conditions.push(Condition::matches("metadata.tags", filter.tags.clone()));
+    }
+    if !filter.content_types.is_empty() {
+        conditions.push(Condition::matches(
+            "metadata.content_type",
+            filter.content_types.clone(),
+        ));
+    }
+
+    Filter::must(conditions)
+}
+
+fn chunk_from_payload(payload: &HashMap<String, Value>) -> Result<DocumentChunk, String> {
+    let json: serde_json::Map<String, serde_json::Value> = payload
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone().into_json()))
+        .collect();
+    serde_json::from_value::<ChunkPayload>(json.into())
+        .map(ChunkPayload::into_chunk)
+        .map_err(|e| e.to_string())
+}
+
+fn hnsw_diff(hnsw: &HnswConfig) -> HnswConfigDiff {
+    HnswConfigDiff {
+        m: hnsw.m,
+        ef_construct: hnsw.ef_construct,
+        ..Default::default()
+    }
+}
+
+/// Quantization parameters for a collection create or update request.
+fn quantization<Q>(config: &QuantizationConfig) -> Option<Q>
+where
+    Q: From<ScalarQuantization> + From<ProductQuantization>,
+{
+    match *config {
+        QuantizationConfig::None => None,
+        QuantizationConfig::Scalar {
+            quantile,
+            always_ram,
+        } => {
+            let mut scalar =
+                ScalarQuantizationBuilder::default().r#type(QuantizationType::Int8.into());
+            if let Some(quantile) = quantile {
+                scalar = scalar.quantile(quantile);
+            }
+            if always_ram {
+                scalar = scalar.always_ram(true);
+            }
+            Some(scalar.build().into())
+        }
+        QuantizationConfig::Product {
+            compression,
+            always_ram,
+        } => {
+            let ratio = match compression {
+                ProductCompression::X4 => CompressionRatio::X4,
+                ProductCompression::X8 => CompressionRatio::X8,
+                ProductCompression::X16 => CompressionRatio::X16,
+                ProductCompression::X32 => CompressionRatio::X32,
+                ProductCompression::X64 => CompressionRatio::X64,
+            };
+            let mut product = ProductQuantizationBuilder::new(ratio.into());
+            if always_ram {
+                product = product.always_ram(true);
+            }
+            Some(product.build().into())
+        }
+    }
+}
+
+fn field_type(kind: PayloadIndexKind) -> FieldType {
+    match kind {
+        PayloadIndexKind::Keyword => FieldType::Keyword,
+        PayloadIndexKind::Integer => FieldType::Integer,
+        PayloadIndexKind::Float => FieldType::Float,
+        PayloadIndexKind::Bool => FieldType::Bool,
+        PayloadIndexKind::Datetime => FieldType::Datetime,
+        PayloadIndexKind::Uuid => FieldType::Uuid,
+    }
+}
+
+/// A point's single dense vector, as returned with `with_vectors(true)`.
+fn dense_vector(vectors: Option<VectorsOutput>) -> Option<Embedding> {
+    let VectorsOptions::Vector(vector) = vectors?.vectors_options? else {
+        return None;
+    };
+    let vector_output::Vector::Dense(dense) = vector.vector? else {
+        return None;
+    };
+    Some(Embedding::new(dense.data))
+}
+
+/// Whether a failed read points at the node that served it (unreachable,
+/// overloaded or failing) rather than at the request, which any node would
+/// refuse the same way.
+fn is_transport_error(error: &QdrantError) -> bool {
+    match error {
+        QdrantError::ResponseError { status } => matches!(
+            status.code(),
+            Code::Unavailable
+                | Code::DeadlineExceeded
+                | Code::Unknown
+                | Code::Internal
+                | Code::Cancelled
+                | Code::Aborted
+        ),
+        QdrantError::ResourceExhaustedError { .. } | QdrantError::Io(_) => true,
+        _ => false,
+    }
+}
+
+fn point_id_label(id: Option<&PointId>) -> String {
+    match id.and_then(|id| id.point_id_options.as_ref()) {
+        Some(PointIdOptions::Uuid(uuid)) => uuid.clone(),
+        Some(PointIdOptions::Num(num)) => num.to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip_and_validation() {
+        let chunk = DocumentChunk::new(Uuid::new_v4(), "text", 3);
+        let payload = ChunkPayload::payload(&chunk).unwrap();
+        let payload: HashMap<String, Value> = payload.into();
+
+        let parsed = chunk_from_payload(&payload).unwrap();
+        assert_eq!(parsed.id, chunk.id);
+        assert_eq!(parsed.chunk_index, 3);
+
+        let mut malformed = payload.clone();
+        malformed.insert("chunk_index".to_string(), Value::from("three"));
+        assert!(chunk_from_payload(&malformed).is_err());
+    }
+
+    #[test]
+    fn test_quantization_from_config() {
+        let config: QuantizationConfig =
+            serde_yaml::from_str("{ type: product, compression: x32, always_ram: true }").unwrap();
+        let Some(quantization_config::Quantization::Product(product)) = quantization(&config)
+        else {
+            panic!("expected product quantization");
+        };
+        assert_eq!(product.compression, CompressionRatio::X32 as i32);
+        assert_eq!(product.always_ram, Some(true));
+
+        let config: QuantizationConfig = serde_yaml::from_str("{ type: scalar }").unwrap();
+        let Some(quantization_config_diff::Quantization::Scalar(scalar)) = quantization(&config)
+        else {
+            panic!("expected scalar quantization");
+        };
+        assert_eq!(scalar.r#type, QuantizationType::Int8 as i32);
+        assert_eq!(scalar.always_ram, None);
+        assert!(
+            quantization::<quantization_config::Quantization>(&QuantizationConfig::None).is_none()
+        );
+    }
+
+    #[test]
+    fn test_reads_prefer_the_fastest_available_replica() {
+        let urls = ["http://a:6334", "http://b:6334", "http://c:6334"].map(String::from);
+        let store = QdrantVectorStore {
+            client: Qdrant::from_url("http://primary:6334").build().unwrap(),
+            collection: "kb".to_string(),
+            dimension: 3,
+            strict_payloads: false,
+            replicas: Vec::new(),
+            replica_cooldown: Duration::ZERO,
+            replica_timeout: Duration::ZERO,
+        }
+        .with_read_replicas(&urls, Duration::from_secs(30), Duration::from_secs(2))
+        .unwrap();
+        store.replicas[0].record_latency(Duration::from_millis(80));
+        store.replicas[1].record_latency(Duration::from_millis(20));
+        store.replicas[2]
+            .down_until_ms
+            .store(2_000, Ordering::Relaxed);
+
+        let order = |now_ms| -> Vec<String> {
+            store
+                .read_order(now_ms)
+                .iter()
+                .map(|replica| replica.url.clone())
+                .collect()
+        };
+        assert_eq!(order(1_000), ["http://b:6334", "http://a:6334"]);
+        // Back from its cooldown and not yet measured, c is tried first.
+        assert_eq!(order(2_000)[0], "http://c:6334");
+    }
+
+    #[test]
+    fn test_only_transport_errors_fail_over() {
+        let unavailable = QdrantError::ResponseError {
+            status: tonic::Status::unavailable("connection refused"),
+        };
+        assert!(is_transport_error(&unavailable));
+        let bad_filter = QdrantError::ResponseError {
+            status: tonic::Status::invalid_argument("Bad request: unknown field"),
+        };
+        assert!(!is_transport_error(&bad_filter));
+        assert!(!is_transport_error(&QdrantError::ConversionError(
+            "sparse vector".to_string()
+        )));
+    }
 }
@@ -0,0 +1,720 @@
+use async_trait::async_trait;
+use deadpool_redis::redis::{self, Value};
+use deadpool_redis::{Connection, Pool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::bm25;
+use crate::domain::{
+    ports::{MalformedPoint, PointPage, VectorStore},
+    DocumentChunk, DomainError, Embedding, SearchFilter, SearchResult, UNTENANTED,
+};
+
+const PAGE_SIZE: usize = 256;
+/// Most chunks fetched from the full-text index per keyword search.
+const KEYWORD_CANDIDATES: usize = 500;
+/// `conversation_id` and `tenant_id` tag of chunks in the shared knowledge
+/// base, since RediSearch can't match a missing field. No tenant may use it.
+const SHARED: &str = UNTENANTED;
+
+/// RediSearch index of a collection's hashes.
+pub(super) fn index_name(collection: &str) -> String {
+    format!("idx:vec:{collection}")
+}
+
+/// The `DIM` of the vector field in an `FT.INFO` reply, which nests it in
+/// the attribute list as a `dim` key followed by its value.
+fn index_dimension(info: &Value) -> Option<usize> {
+    let is_dim =
+        |key: &Value| string_value(key.clone()).is_some_and(|k| k.eq_ignore_ascii_case("dim"));
+    match info {
+        Value::Array(items) | Value::Set(items) => items
+            .windows(2)
+            .find(|pair| is_dim(&pair[0]))
+            .and_then(|pair| string_value(pair[1].clone())?.parse().ok())
+            .or_else(|| items.iter().find_map(index_dimension)),
+        Value::Map(pairs) => pairs.iter().find_map(|(key, value)| {
+            if is_dim(key) {
+                string_value(value.clone())?.parse().ok()
+            } else {
+                index_dimension(value)
+            }
+        }),
+        _ => None,
+    }
+}
+
+/// Vector store on Redis with the RediSearch module (Redis Stack, or Redis 8).
+///
+/// Each chunk is a hash `vec:{collection}:{chunk_id}` holding the chunk as
+/// JSON, its FLOAT32 embedding and the fields searches filter on, indexed
+/// by `idx:vec:{collection}`. A sorted set of chunk ids gives listing and
+/// exports a stable order.
+pub struct RedisVectorStore {
+    pool: Pool,
+    collection: String,
+    dimension: usize,
+}
+
+impl RedisVectorStore {
+    /// Creates the collection's search index if it doesn't exist.
+    pub async fn new(pool: Pool, collection: &str, dimension: usize) -> Result<Self, DomainError> {
+        let store = Self {
+            pool,
+            collection: collection.to_string(),
+            dimension,
+        };
+        store.ensure_index().await?;
+        Ok(store)
+    }
+
+    async fn conn(&self) -> Result<Connection, DomainError> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| DomainError::external(format!("Redis pool error: {e}")))
+    }
+
+    fn index(&self) -> String {
+        index_name(&self.collection)
+    }
+
+    fn key(&self, id: Uuid) -> String {
+        format!("vec:{}:{id}", self.collection)
+    }
+
+    fn ids_key(&self) -> String {
+        format!("vec:ids:{}", self.collection)
+    }
+
+    async fn ensure_index(&self) -> Result<(), DomainError> {
+        let mut conn = self.conn().await?;
+        let info = redis::cmd("FT.INFO")
+            .arg(self.index())
+            .query_async::<Value>(&mut conn)
+            .await;
+        if let Ok(info) = info {
+            match index_dimension(&info) {
+                Some(dim) if dim != self.dimension => {
+                    return Err(DomainError::validation(format!(
+                        "index {} stores {dim}-dimensional vectors but embeddings are {}-dimensional; \
+                         re-embed into a new collection",
+                        self.index(),
+                        self.dimension
+                    )));
+                }
+                _ => {}
+            }
+            // Indexes created before tenants existed; adding a field twice fails harmlessly.
+            let _ = redis::cmd("FT.ALTER")
+                .arg(self.index())
+                .arg(&["SCHEMA", "ADD", "tenant_id", "TAG"])
+                .query_async::<()>(&mut conn)
+                .await;
+            return Ok(());
+        }
+
+        redis::cmd("FT.CREATE")
+            .arg(self.index())
+            .arg(&["ON", "HASH", "PREFIX", "1"])
+            .arg(format!("vec:{}:", self.collection))
+            .arg(&[
+                "SCHEMA",
+                "embedding",
+                "VECTOR",
+                "HNSW",
+                "6",
+                "TYPE",
+                "FLOAT32",
+            ])
+            .arg("DIM")
+            .arg(self.dimension)
+            .arg(&["DISTANCE_METRIC", "COSINE"])
+            .arg(&["document_id", "TAG"])
+            .arg(&["tags", "TAG", "SEPARATOR", "\u{1f}"])
+            .arg(&["content_type", "TAG", "SEPARATOR", "\u{1f}"])
+            .arg(&["conversation_id", "TAG"])
+            .arg(&["tenant_id", "TAG"])
+            .arg(&["content", "TEXT"])
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DomainError::external(format!("Failed to create index: {e}")))
+    }
+
+    /// Runs `FT.SEARCH` and returns the hits' `chunk` fields, skipping (and
+    /// logging) any that can't be read.
+    async fn search_chunks(
+        &self,
+        conn: &mut Connection,
+        cmd: &redis::Cmd,
+    ) -> Result<Vec<(DocumentChunk, HashMap<String, String>)>, DomainError> {
+        let reply: Value = cmd
+            .query_async(conn)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+
+        Ok(search_hits(reply)
+            .into_iter()
+            .filter_map(|(key, mut fields)| {
+                let chunk = fields.remove("chunk").unwrap_or_default();
+                serde_json::from_str(&chunk)
+                    .inspect_err(|e| {
+                        tracing::warn!(key = %key, error = %e, "skipping point with malformed payload")
+                    })
+                    .ok()
+                    .map(|chunk| (chunk, fields))
+            })
+            .collect())
+    }
+
+    /// Chunk ids after `offset` (inclusive) in id order, up to `limit`.
+    async fn ids_from(
+        &self,
+        conn: &mut Connection,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<String>, DomainError> {
+        let start = offset.map_or_else(|| "-".to_string(), |id| format!("[{id}"));
+        redis::cmd("ZRANGE")
+            .arg(self.ids_key())
+            .arg(start)
+            .arg("+")
+            .arg(&["BYLEX", "LIMIT", "0"])
+            .arg(limit)
+            .query_async(conn)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))
+    }
+
+    /// The stored `chunk` JSON and embedding of each id; missing hashes are `None`.
+    async fn load(
+        &self,
+        conn: &mut Connection,
+        ids: &[String],
+        with_embedding: bool,
+    ) -> Result<Vec<(String, Option<String>, Option<Vec<u8>>)>, DomainError> {
+        let mut pipe = redis::pipe();
+        for id in ids {
+            let key = format!("vec:{}:{id}", self.collection);
+            if with_embedding {
+                pipe.cmd("HMGET").arg(key).arg(&["chunk", "embedding"]);
+            } else {
+                pipe.cmd("HMGET").arg(key).arg(&["chunk"]);
+            }
+        }
+        let rows: Vec<Vec<Option<Vec<u8>>>> = pipe
+            .query_async(conn)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+
+        Ok(ids
+            .iter()
+            .zip(rows)
+            .map(|(id, mut row)| {
+                let embedding = if with_embedding {
+                    row.pop().flatten()
+                } else {
+                    None
+                };
+                let chunk = row
+                    .pop()
+                    .flatten()
+                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+                (id.clone(), chunk, embedding)
+            })
+            .collect())
+    }
+
+    /// Every stored chunk JSON, by id.
+    async fn all_chunks(&self) -> Result<Vec<(String, Option<String>)>, DomainError> {
+        let mut conn = self.conn().await?;
+        let mut all = Vec::new();
+        let mut offset = None;
+        loop {
+            let mut ids = self.ids_from(&mut conn, offset, PAGE_SIZE + 1).await?;
+            offset = (ids.len() > PAGE_SIZE)
+                .then(|| ids.pop())
+                .flatten()
+                .and_then(|id| id.parse().ok());
+            for (id, chunk, _) in self.load(&mut conn, &ids, false).await? {
+                all.push((id, chunk));
+            }
+            if offset.is_none() {
+                return Ok(all);
+            }
+        }
+    }
+
+    fn filter_query(filter: &SearchFilter) -> String {
+        let conversation = match filter.conversation_id {
+            Some(id) => format!(
+                "@conversation_id:{{{SHARED} | {}}}",
+                escape_query(&id.to_string())
+            ),
+            None => format!("@conversation_id:{{{SHARED}}}"),
+        };
+        let tenant = match &filter.tenant_id {
+            Some(tenant) => format!("@tenant_id:{{{SHARED} | {}}}", escape_query(tenant)),
+            None => format!("@tenant_id:{{{SHARED}}}"),
+        };
+        let mut clauses = vec![conversation, tenant];
+        let any_of = |field: &str, values: &[String]| {
+            let values: Vec<String> = values.iter().map(|v| escape_query(v)).collect();
+            format!("@{field}:{{{}}}", values.join(" | "))
+        };
+        if !filter.document_ids.is_empty() {
+            let ids: Vec<String> = filter.document_ids.iter().map(Uuid::to_string).collect();
+            clauses.push(any_of("document_id", &ids));
+        }
+        if !filter.tags.is_empty() {
+            clauses.push(any_of("tags", &filter.tags));
+        }
+        if !filter.content_types.is_empty() {
+            clauses.push(any_of("content_type", &filter.content_types));
+        }
+        clauses.join(" ")
+    }
+}
+
+#[async_trait]
+impl VectorStore for RedisVectorStore {
+    async fn upsert(
+        &self,
+        chunk: &DocumentChunk,
+        embedding: &Embedding,
+    ) -> Result<(), DomainError> {
+        super::check_dimension(&self.collection, self.dimension, embedding)?;
+        let json = serde_json::to_string(chunk)
+            .map_err(|e| DomainError::internal(format!("Failed to create payload: {e}")))?;
+        let mut conn = self.conn().await?;
+
+        redis::pipe()
+            .atomic()
+            .cmd("DEL")
+            .arg(self.key(chunk.id))
+            .ignore()
+            .cmd("HSET")
+            .arg(self.key(chunk.id))
+            .arg("embedding")
+            .arg(vector_bytes(embedding))
+            .arg(chunk_fields(chunk, &json))
+            .ignore()
+            .cmd("ZADD")
+            .arg(self.ids_key())
+            .arg(0)
+            .arg(chunk.id.to_string())
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        Some(self.dimension)
+    }
+
+    async fn search(
+        &self,
+        query: &Embedding,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let mut cmd = redis::cmd("FT.SEARCH");
+        cmd.arg(self.index())
+            .arg(format!(
+                "({})=>[KNN $k @embedding $vector AS distance]",
+                Self::filter_query(filter)
+            ))
+            .arg(&["PARAMS", "4", "k"])
+            .arg(top_k)
+            .arg("vector")
+            .arg(vector_bytes(query))
+            .arg(&["SORTBY", "distance", "RETURN", "2", "chunk", "distance"])
+            .arg(&["LIMIT", "0"])
+            .arg(top_k)
+            .arg(&["DIALECT", "2"]);
+
+        let mut conn = self.conn().await?;
+        Ok(self
+            .search_chunks(&mut conn, &cmd)
+            .await?
+            .into_iter()
+            .map(|(chunk, fields)| {
+                let distance: f32 = fields
+                    .get("distance")
+                    .and_then(|d| d.parse().ok())
+                    .unwrap_or(1.0);
+                SearchResult {
+                    chunk,
+                    score: 1.0 - distance,
+                }
+            })
+            .collect())
+    }
+
+    /// Fetches up to [`KEYWORD_CANDIDATES`] chunks containing any query term
+    /// from the full-text index and ranks them with BM25, like the Qdrant store.
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        let terms: Vec<String> = bm25::tokenize(query)
+            .iter()
+            .map(|term| escape_query(term))
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut cmd = redis::cmd("FT.SEARCH");
+        cmd.arg(self.index())
+            .arg(format!(
+                "{} @content:({})",
+                Self::filter_query(filter),
+                terms.join(" | ")
+            ))
+            .arg(&["RETURN", "1", "chunk", "LIMIT", "0"])
+            .arg(KEYWORD_CANDIDATES)
+            .arg(&["DIALECT", "2"]);
+
+        let mut conn = self.conn().await?;
+        let candidates: Vec<DocumentChunk> = self
+            .search_chunks(&mut conn, &cmd)
+            .await?
+            .into_iter()
+            .map(|(chunk, _)| chunk)
+            .collect();
+        let refs: Vec<&DocumentChunk> = candidates.iter().collect();
+
+        Ok(bm25::rank(query, &refs)
+            .into_iter()
+            .take(top_k)
+            .map(|(chunk, score)| SearchResult {
+                chunk: chunk.clone(),
+                score,
+            })
+            .collect())
+    }
+
+    async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError> {
+        let mut conn = self.conn().await?;
+        loop {
+            let reply: Value = redis::cmd("FT.SEARCH")
+                .arg(self.index())
+                .arg(format!(
+                    "@document_id:{{{}}}",
+                    escape_query(&document_id.to_string())
+                ))
+                .arg(&["NOCONTENT", "LIMIT", "0"])
+                .arg(PAGE_SIZE)
+                .arg(&["DIALECT", "2"])
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+
+            let keys = search_keys(reply);
+            if keys.is_empty() {
+                return Ok(());
+            }
+            let prefix = format!("vec:{}:", self.collection);
+            let ids: Vec<&str> = keys
+                .iter()
+                .filter_map(|key| key.strip_prefix(&prefix))
+                .collect();
+            redis::pipe()
+                .atomic()
+                .cmd("DEL")
+                .arg(&keys)
+                .ignore()
+                .cmd("ZREM")
+                .arg(self.ids_key())
+                .arg(&ids)
+                .ignore()
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(|e| DomainError::external(e.to_string()))?;
+        }
+    }
+
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+        Ok(self
+            .all_chunks()
+            .await?
+            .into_iter()
+            .filter_map(|(id, chunk)| {
+                serde_json::from_str(&chunk?)
+                    .inspect_err(|e| {
+                        tracing::warn!(point_id = %id, error = %e, "skipping point with malformed payload")
+                    })
+                    .ok()
+            })
+            .collect())
+    }
+
+    async fn document_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        let mut conn = self.conn().await?;
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut cmd = redis::cmd("FT.SEARCH");
+            cmd.arg(self.index())
+                .arg(format!(
+                    "@document_id:{{{}}}",
+                    escape_query(&document_id.to_string())
+                ))
+                .arg(&["RETURN", "1", "chunk", "LIMIT"])
+                .arg(offset)
+                .arg(PAGE_SIZE)
+                .arg(&["DIALECT", "2"]);
+            let page = self.search_chunks(&mut conn, &cmd).await?;
+            if page.is_empty() {
+                break;
+            }
+            chunks.extend(page.into_iter().map(|(chunk, _)| chunk));
+            offset += PAGE_SIZE;
+        }
+        chunks.sort_by_key(|chunk| chunk.chunk_index);
+        Ok(chunks)
+    }
+
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        let limit = limit.max(1);
+        let mut conn = self.conn().await?;
+        let mut ids = self.ids_from(&mut conn, offset, limit + 1).await?;
+        let next = (ids.len() > limit)
+            .then(|| ids.pop())
+            .flatten()
+            .and_then(|id| id.parse().ok());
+
+        let points = self
+            .load(&mut conn, &ids, true)
+            .await?
+            .into_iter()
+            .filter_map(|(_, chunk, embedding)| {
+                let chunk = serde_json::from_str(&chunk?).ok()?;
+                Some((chunk, Embedding::new(vector_from_bytes(&embedding?))))
+            })
+            .collect();
+        Ok(PointPage { points, next })
+    }
+
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let mut conn = self.conn().await?;
+        Ok(self
+            .load(&mut conn, &ids, true)
+            .await?
+            .into_iter()
+            .filter_map(|(id, _, embedding)| {
+                Some((
+                    id.parse().ok()?,
+                    Embedding::new(vector_from_bytes(&embedding?)),
+                ))
+            })
+            .collect())
+    }
+
+    async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+        Ok(self
+            .all_chunks()
+            .await?
+            .into_iter()
+            .filter_map(|(id, chunk)| {
+                let chunk = chunk.unwrap_or_default();
+                let error = serde_json::from_str::<DocumentChunk>(&chunk).err()?;
+                let json: Option<serde_json::Value> = serde_json::from_str(&chunk).ok();
+                let field = |key: &str| json.as_ref().and_then(|v| v.get(key).cloned());
+                Some(MalformedPoint {
+                    id,
+                    document_id: field("document_id")
+                        .and_then(|v| v.as_str().and_then(|id| id.parse().ok())),
+                    chunk_index: field("chunk_index")
+                        .and_then(|v| v.as_u64())
+                        .map(|i| i as usize),
+                    error: error.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+        let json = serde_json::to_string(chunk)
+            .map_err(|e| DomainError::internal(format!("Failed to create payload: {e}")))?;
+        let mut conn = self.conn().await?;
+        redis::cmd("HSET")
+            .arg(self.key(chunk.id))
+            .arg(chunk_fields(chunk, &json))
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))
+    }
+}
+
+/// Hash fields stored next to the embedding: the chunk and what filters match on.
+fn chunk_fields(chunk: &DocumentChunk, json: &str) -> Vec<(&'static str, String)> {
+    let metadata = &chunk.metadata;
+    vec![
+        ("chunk", json.to_string()),
+        ("document_id", chunk.document_id.to_string()),
+        ("content", chunk.content.clone()),
+        ("tags", metadata.tags.join("\u{1f}")),
+        (
+            "content_type",
+            metadata.content_type.clone().unwrap_or_default(),
+        ),
+        (
+            "conversation_id",
+            metadata
+                .conversation_id
+                .map_or_else(|| SHARED.to_string(), |id| id.to_string()),
+        ),
+        (
+            "tenant_id",
+            metadata
+                .tenant_id
+                .clone()
+                .unwrap_or_else(|| SHARED.to_string()),
+        ),
+    ]
+}
+
+fn vector_bytes(embedding: &Embedding) -> Vec<u8> {
+    embedding
+        .as_slice()
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+fn vector_from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Escapes RediSearch query syntax in a tag value or search term.
+fn escape_query(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if !(c.is_alphanumeric() || c == '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn string_value(value: Value) -> Option<String> {
+    match value {
+        Value::BulkString(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+        Value::SimpleString(s) => Some(s),
+        Value::VerbatimString { text, .. } => Some(text),
+        Value::Int(n) => Some(n.to_string()),
+        Value::Double(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Keys and returned fields of an `FT.SEARCH` reply
+/// (`[total, key, [field, value, ...], ...]`).
+fn search_hits(reply: Value) -> Vec<(String, HashMap<String, String>)> {
+    let Value::Array(items) = reply else {
+        return Vec::new();
+    };
+    let mut items = items.into_iter().skip(1);
+    let mut hits = Vec::new();
+    while let (Some(key), Some(fields)) = (items.next(), items.next()) {
+        let Some(key) = string_value(key) else {
+            continue;
+        };
+        let fields = match fields {
+            Value::Array(fields) => {
+                let mut fields = fields.into_iter();
+                let mut map = HashMap::new();
+                while let (Some(name), Some(value)) = (fields.next(), fields.next()) {
+                    if let (Some(name), Some(value)) = (string_value(name), string_value(value)) {
+                        map.insert(name, value);
+                    }
+                }
+                map
+            }
+            _ => HashMap::new(),
+        };
+        hits.push((key, fields));
+    }
+    hits
+}
+
+/// Keys of an `FT.SEARCH ... NOCONTENT` reply (`[total, key, ...]`).
+fn search_keys(reply: Value) -> Vec<String> {
+    match reply {
+        Value::Array(items) => items.into_iter().skip(1).filter_map(string_value).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_query_and_reply_parsing() {
+        let conversation = Uuid::from_u128(7);
+        let filter = SearchFilter {
+            tags: vec!["emea".to_string(), "q3-plan".to_string()],
+            content_types: vec!["text/plain".to_string()],
+            conversation_id: Some(conversation),
+            tenant_id: Some("acme-eu".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            RedisVectorStore::filter_query(&filter),
+            format!(
+                "@conversation_id:{{shared | {}}} @tenant_id:{{shared | acme\\-eu}} @tags:{{emea | q3\\-plan}} @content_type:{{text\\/plain}}",
+                escape_query(&conversation.to_string())
+            )
+        );
+
+        let chunk = DocumentChunk::new(Uuid::new_v4(), "text", 0);
+        let reply = Value::Array(vec![
+            Value::Int(1),
+            Value::BulkString(b"vec:kb:1".to_vec()),
+            Value::Array(vec![
+                Value::BulkString(b"chunk".to_vec()),
+                Value::BulkString(serde_json::to_vec(&chunk).unwrap()),
+                Value::BulkString(b"distance".to_vec()),
+                Value::BulkString(b"0.25".to_vec()),
+            ]),
+        ]);
+        let hits = search_hits(reply);
+        assert_eq!(hits[0].0, "vec:kb:1");
+        assert_eq!(hits[0].1["distance"], "0.25");
+
+        let embedding = Embedding::new(vec![0.5, -1.25]);
+        assert_eq!(
+            vector_from_bytes(&vector_bytes(&embedding)),
+            vec![0.5, -1.25]
+        );
+
+        let info = Value::Array(vec![
+            Value::SimpleString("attributes".to_string()),
+            Value::Array(vec![Value::Array(vec![
+                Value::SimpleString("identifier".to_string()),
+                Value::BulkString(b"embedding".to_vec()),
+                Value::SimpleString("dim".to_string()),
+                Value::Int(768),
+            ])]),
+        ]);
+        assert_eq!(index_dimension(&info), Some(768));
+    }
+}
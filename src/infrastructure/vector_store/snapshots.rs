@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use qdrant_client::qdrant::SnapshotDescription;
+use qdrant_client::Qdrant;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::domain::DomainError;
+
+/// A Qdrant snapshot of one collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl From<SnapshotDescription> for SnapshotInfo {
+    fn from(snapshot: SnapshotDescription) -> Self {
+        Self {
+            name: snapshot.name,
+            created_at: snapshot
+                .creation_time
+                .and_then(|t| DateTime::from_timestamp(t.seconds, t.nanos.max(0) as u32)),
+            size_bytes: snapshot.size.max(0) as u64,
+            checksum: snapshot.checksum,
+        }
+    }
+}
+
+/// Creates, lists and downloads collection snapshots. Snapshots are taken
+/// over gRPC; downloads go through Qdrant's REST API, which serves the files.
+#[derive(Clone)]
+pub struct QdrantSnapshots {
+    client: Arc<Qdrant>,
+    http: reqwest::Client,
+    rest_url: String,
+}
+
+impl QdrantSnapshots {
+    pub fn new(grpc_url: &str, rest_url: &str) -> Result<Self, DomainError> {
+        let client = Qdrant::from_url(grpc_url)
+            .build()
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        Ok(Self {
+            client: Arc::new(client),
+            http: reqwest::Client::new(),
+            rest_url: rest_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    pub async fn create(&self, collection: &str) -> Result<SnapshotInfo, DomainError> {
+        let response = self
+            .client
+            .create_snapshot(collection)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        response
+            .snapshot_description
+            .map(SnapshotInfo::from)
+            .ok_or_else(|| DomainError::external("Qdrant returned no snapshot description"))
+    }
+
+    /// Snapshots of `collection`, newest first.
+    pub async fn list(&self, collection: &str) -> Result<Vec<SnapshotInfo>, DomainError> {
+        let response = self
+            .client
+            .list_snapshots(collection)
+            .await
+            .map_err(|e| DomainError::external(e.to_string()))?;
+        let mut snapshots: Vec<SnapshotInfo> = response
+            .snapshot_descriptions
+            .into_iter()
+            .map(SnapshotInfo::from)
+            .collect();
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        Ok(snapshots)
+    }
+
+    /// Starts downloading a snapshot listed for `collection`; the body is
+    /// read by the caller so large files are never buffered whole.
+    pub async fn download(
+        &self,
+        collection: &str,
+        name: &str,
+    ) -> Result<reqwest::Response, DomainError> {
+        // Only names Qdrant lists are requested, so `name` can't address another path.
+        if !self.list(collection).await?.iter().any(|s| s.name == name) {
+            return Err(DomainError::not_found(format!("snapshot {name}")));
+        }
+
+        let url = format!(
+            "{}/collections/{collection}/snapshots/{name}",
+            self.rest_url
+        );
+        self.http
+            .get(&url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| DomainError::external(format!("Snapshot download failed: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_info_from_description() {
+        let info = SnapshotInfo::from(SnapshotDescription {
+            name: "knowledge_base-1.snapshot".to_string(),
+            creation_time: None,
+            size: -1,
+            checksum: Some("abc".to_string()),
+        });
+
+        assert_eq!(info.size_bytes, 0);
+        assert_eq!(info.created_at, None);
+        assert_eq!(
+            serde_json::to_value(&info).unwrap()["name"],
+            "knowledge_base-1.snapshot"
+        );
+    }
+}
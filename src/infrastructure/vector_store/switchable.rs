@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+use crate::domain::{
+    ports::{MalformedPoint, PointPage, VectorStore},
+    DocumentChunk, DomainError, Embedding, SearchFilter, SearchResult,
+};
+
+/// A vector store whose backing collection can be swapped at runtime.
+///
+/// Used for blue/green re-embeds: callers keep one handle while the active
+/// collection behind it is switched once a new one has been validated.
+pub struct SwitchableVectorStore {
+    active: RwLock<(String, Arc<dyn VectorStore>)>,
+}
+
+impl SwitchableVectorStore {
+    pub fn new(collection: impl Into<String>, store: Arc<dyn VectorStore>) -> Self {
+        Self {
+            active: RwLock::new((collection.into(), store)),
+        }
+    }
+
+    pub fn active_collection(&self) -> String {
+        self.active
+            .read()
+            .map(|active| active.0.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn switch(&self, collection: impl Into<String>, store: Arc<dyn VectorStore>) {
+        let collection = collection.into();
+        let mut active = self.active.write().unwrap_or_else(|e| e.into_inner());
+        tracing::info!(from = %active.0, to = %collection, "switching active collection");
+        *active = (collection, store);
+    }
+
+    fn current(&self) -> Result<Arc<dyn VectorStore>, DomainError> {
+        self.active
+            .read()
+            .map(|active| active.1.clone())
+            .map_err(|e| DomainError::internal(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl VectorStore for SwitchableVectorStore {
+    async fn upsert(
+        &self,
+        chunk: &DocumentChunk,
+        embedding: &Embedding,
+    ) -> Result<(), DomainError> {
+        self.current()?.upsert(chunk, embedding).await
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.current().ok()?.dimension()
+    }
+
+    async fn search(
+        &self,
+        query: &Embedding,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.current()?.search(query, top_k, filter).await
+    }
+
+    async fn keyword_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<SearchResult>, DomainError> {
+        self.current()?.keyword_search(query, top_k, filter).await
+    }
+
+    async fn delete_by_document(&self, document_id: Uuid) -> Result<(), DomainError> {
+        self.current()?.delete_by_document(document_id).await
+    }
+
+    async fn list_chunks(&self) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.current()?.list_chunks().await
+    }
+
+    async fn document_chunks(&self, document_id: Uuid) -> Result<Vec<DocumentChunk>, DomainError> {
+        self.current()?.document_chunks(document_id).await
+    }
+
+    async fn scroll_points(
+        &self,
+        offset: Option<Uuid>,
+        limit: usize,
+    ) -> Result<PointPage, DomainError> {
+        self.current()?.scroll_points(offset, limit).await
+    }
+
+    async fn vectors(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Embedding>, DomainError> {
+        self.current()?.vectors(ids).await
+    }
+
+    async fn malformed_points(&self) -> Result<Vec<MalformedPoint>, DomainError> {
+        self.current()?.malformed_points().await
+    }
+
+    async fn rewrite_payload(&self, chunk: &DocumentChunk) -> Result<(), DomainError> {
+        self.current()?.rewrite_payload(chunk).await
+    }
+}
@@ -1,4 +1,5 @@
 pub mod api;
 pub mod application;
+pub mod consumer;
 pub mod domain;
 pub mod infrastructure;
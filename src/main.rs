@@ -1,6 +1,16 @@
 use ai_agent::api::{create_router, queue, AppState};
-use ai_agent::infrastructure::AppConfig;
+use ai_agent::application::{search_rag_services, QueryTransform, RagService};
+use ai_agent::domain::ports::LlmService;
+#[cfg(feature = "chaos")]
+use ai_agent::infrastructure::chaos::FaultInjector;
+use ai_agent::infrastructure::config::VectorStoreBackend;
+use ai_agent::infrastructure::{
+    configure_retrieval, embedding_from_config, llm_from_config, vector_store_from_config,
+    AppConfig, PostgresJobHistory, QdrantSnapshots, VectorStoreHealth,
+};
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -24,9 +34,39 @@ async fn main() -> anyhow::Result<()> {
 
     let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".into());
     let redis_pool = queue::create_pool(&redis_url)?;
+    config.config.redis_tracing.install();
     info!("Redis pool initialized");
 
-    let state = AppState::new(redis_pool, config);
+    let mut state = AppState::new(redis_pool.clone(), config);
+    // Billing exports read the job history the worker writes.
+    if state.config.config.worker.history.postgres {
+        let database_url = std::env::var("DATABASE_URL")
+            .map_err(|_| anyhow::anyhow!("DATABASE_URL must be set for worker.history.postgres"))?;
+        let history = PostgresJobHistory::connect(&database_url).await?;
+        if state.config.config.migrations.on_startup {
+            history.migrate().await?;
+        }
+        state = state.with_job_history(history);
+        info!("Postgres job history connected");
+    }
+    match state.config.config.vector_store.backend {
+        VectorStoreBackend::Qdrant => {
+            let (health, snapshots) = qdrant_clients()?;
+            state = state
+                .with_vector_store_health(health)
+                .with_snapshots(snapshots);
+        }
+        VectorStoreBackend::Redis => {
+            state = state.with_vector_store_health(VectorStoreHealth::Redis(redis_pool.clone()));
+        }
+    }
+    // /documents/search reads the collections the worker retrieves from.
+    let config = state.config.clone();
+    let qdrant_url = std::env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".into());
+    match search_services(&redis_pool, &qdrant_url, &config).await {
+        Ok(services) => state = state.with_search_services(services),
+        Err(e) => tracing::warn!(error = %e, "document search unavailable"),
+    }
     let app = create_router(state);
 
     let host = std::env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".into());
@@ -41,3 +81,59 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Retrieval for document search, configured like the worker's.
+async fn search_services(
+    redis_pool: &queue::RedisPool,
+    qdrant_url: &str,
+    config: &AppConfig,
+) -> anyhow::Result<HashMap<String, Arc<RagService>>> {
+    let config = &config.config;
+    let embedding = embedding_from_config(&config.embedding)?;
+    let needs_llm = config.rag.rerank.enabled || config.rag.query_transform != QueryTransform::None;
+    let llm: Option<Arc<dyn LlmService>> = needs_llm
+        .then(|| llm_from_config(&config.llm, &config.llm.model))
+        .transpose()?;
+    #[cfg(feature = "chaos")]
+    let faults = Arc::new(FaultInjector::new(config.chaos.clone()));
+
+    let tool_collection = config.tools.knowledge_base.collection(&config.vector_store);
+    let services = search_rag_services(
+        &config.vector_store.collection,
+        tool_collection,
+        |collection| {
+            let embedding = embedding.clone();
+            let llm = llm.clone();
+            #[cfg(feature = "chaos")]
+            let faults = faults.clone();
+            async move {
+                let store = vector_store_from_config(
+                    qdrant_url,
+                    redis_pool,
+                    &collection,
+                    config,
+                    None,
+                    #[cfg(feature = "chaos")]
+                    &faults,
+                )
+                .await?;
+                let rag = RagService::new(embedding, store, config.rag.top_k);
+                Ok(Arc::new(configure_retrieval(rag, config, llm.as_ref())))
+            }
+        },
+    )
+    .await?;
+    Ok(services)
+}
+
+/// Readiness checks and snapshots go over gRPC at `QDRANT_URL`; snapshot
+/// downloads use the REST API at `QDRANT_HTTP_URL`.
+fn qdrant_clients() -> anyhow::Result<(VectorStoreHealth, QdrantSnapshots)> {
+    let grpc_url = std::env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".into());
+    let rest_url =
+        std::env::var("QDRANT_HTTP_URL").unwrap_or_else(|_| "http://localhost:6333".into());
+    Ok((
+        VectorStoreHealth::qdrant(&grpc_url)?,
+        QdrantSnapshots::new(&grpc_url, &rest_url)?,
+    ))
+}
@@ -0,0 +1,57 @@
+//! Copies every point of one vector store into another.
+//!
+//! Chunk ids, payloads and embeddings are kept as they are, so nothing is
+//! re-embedded and the target can be swapped in for the source directly.
+
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use ai_agent::application::migrate_points;
+use ai_agent::infrastructure::{AppConfig, QdrantVectorStore};
+
+fn required_env(name: &str) -> anyhow::Result<String> {
+    std::env::var(name).map_err(|_| anyhow::anyhow!("{name} must be set"))
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "migrate=info,ai_agent=info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    dotenvy::dotenv().ok();
+
+    let config = AppConfig::load().unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Failed to load config, using defaults");
+        AppConfig::default()
+    });
+    let dimension = config.config.embedding.dimension;
+
+    let qdrant_url = std::env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".into());
+    let source_url = std::env::var("MIGRATE_SOURCE_URL").unwrap_or(qdrant_url);
+    let target_url = std::env::var("MIGRATE_TARGET_URL").unwrap_or_else(|_| source_url.clone());
+    let source_collection = required_env("MIGRATE_SOURCE_COLLECTION")?;
+    let target_collection = required_env("MIGRATE_TARGET_COLLECTION")?;
+    let page_size = std::env::var("MIGRATE_PAGE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(256);
+
+    let settings = &config.config.vector_store;
+    let source =
+        QdrantVectorStore::new(&source_url, &source_collection, dimension, settings).await?;
+    let target =
+        QdrantVectorStore::new(&target_url, &target_collection, dimension, settings).await?;
+
+    println!("Migrating {source_url}/{source_collection} -> {target_url}/{target_collection}");
+    let report = migrate_points(&source, &target, page_size).await?;
+    println!("migrated={} missing={}", report.migrated, report.missing);
+
+    if report.missing > 0 {
+        anyhow::bail!("{} points are missing from the target", report.missing);
+    }
+    Ok(())
+}